@@ -0,0 +1,130 @@
+//! Paint, layout and formatting benchmarks. `cargo bench` on Windows; the
+//! frames are drawn into the same memory DCs the overlay presents from, with
+//! no window. Paint covers the DirectWrite path; GDI is only the fallback.
+
+use std::hint::black_box;
+
+use chrono::NaiveDate;
+use clockor::config::{ProgressDisplay, TextStyle};
+use clockor::directwrite::{self, Font, Line, Pass, Text};
+use clockor::layered::{self, Layer};
+use clockor::{segments, template};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const FONT_SIZES: [u32; 3] = [16, 32, 60];
+const STYLES: [TextStyle; 3] = [TextStyle::None, TextStyle::Outline, TextStyle::Shadow];
+const WHITE: u32 = 0x00FF_FFFF;
+
+fn font(px: u32) -> Font<'static> {
+    Font {
+        family: "Segoe UI",
+        px,
+        weight: 400,
+        width: 5,
+        subpixel: true,
+    }
+}
+
+fn line(text: &str) -> Line {
+    Line {
+        text: text.encode_utf16().collect(),
+        colors: Vec::new(),
+    }
+}
+
+/// The overlay's passes for `style`: outline or shadow first, then the text.
+fn passes(style: TextStyle) -> Vec<Pass> {
+    let mut passes: Vec<Pass> = style
+        .offsets(false)
+        .into_iter()
+        .map(|offset| Pass {
+            offset,
+            color: 0,
+            line_colors: false,
+        })
+        .collect();
+    passes.push(Pass {
+        offset: (0, 0),
+        color: WHITE,
+        line_colors: true,
+    });
+    passes
+}
+
+fn paint(c: &mut Criterion) {
+    let mut group = c.benchmark_group("paint");
+    let layer = Layer {
+        bg: [0, 0, 0],
+        bg_alpha: 128,
+        image: None,
+        text_alpha: 255,
+        window_alpha: 255,
+    };
+    for style in STYLES {
+        let passes = passes(style);
+        for px in FONT_SIZES {
+            let Some(text) = Text::new(&[line("12:34:56")], &font(px)) else {
+                eprintln!("DirectWrite unavailable; skipping paint benchmarks");
+                return;
+            };
+            let size = (text.width(0) + 8, px as i32 + 8);
+            group.bench_with_input(BenchmarkId::new(format!("{style:?}"), px), &px, |b, _| {
+                b.iter(|| {
+                    layered::render(size, &layer, |hdc| {
+                        directwrite::draw(hdc, size, &text, &[(4, 4)], false, &passes);
+                    })
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn layout(c: &mut Criterion) {
+    let mut group = c.benchmark_group("layout");
+    let lines = [
+        line("12:34:56"),
+        line("Friday, October 16, 2026"),
+        line("🔋 64%  ⏰ 5m Standup"),
+    ];
+    for px in FONT_SIZES {
+        group.bench_with_input(BenchmarkId::from_parameter(px), &px, |b, &px| {
+            b.iter(|| {
+                Text::new(black_box(&lines), &font(px))
+                    .map(|text| (0..lines.len()).map(|i| text.width(i)).sum::<i32>())
+            })
+        });
+    }
+    group.finish();
+}
+
+fn format(c: &mut Criterion) {
+    let mut group = c.benchmark_group("format");
+    group.bench_function("template", |b| {
+        b.iter(|| {
+            template::expand(
+                black_box("{time}  {battery|AC}  {timer}  {cpu}"),
+                |name| match name {
+                    "time" => Some("12:34:56".to_string()),
+                    "cpu" => Some("23%".to_string()),
+                    _ => None,
+                },
+            )
+        })
+    });
+    group.bench_function("segments", |b| {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        b.iter(|| {
+            (
+                segments::hms(black_box(3723)),
+                segments::countdown(black_box(299)),
+                segments::uptime(black_box(22_320_000)),
+                segments::progress(ProgressDisplay::Year, black_box(date)),
+            )
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, paint, layout, format);
+criterion_main!(benches);
@@ -0,0 +1,37 @@
+/*
+ * ClockOR plugin interface.
+ *
+ * A plugin is a DLL in the "plugins" folder next to clockor.exe that exports
+ * clockor_plugin(). ClockOR calls read() on a thread of its own at the
+ * requested interval and shows the text as a segment of the overlay line
+ * (scripts also see it in the `plugins` map, keyed by name).
+ *
+ * All strings are UTF-8. Functions may be called from a thread other than the
+ * one that loaded the DLL, but never concurrently for the same plugin.
+ */
+#ifndef CLOCKOR_PLUGIN_H
+#define CLOCKOR_PLUGIN_H
+
+#include <stdint.h>
+
+#define CLOCKOR_PLUGIN_ABI 1
+
+typedef struct ClockorPlugin {
+    /* Must be CLOCKOR_PLUGIN_ABI. */
+    uint32_t abi_version;
+    /* Shown in settings and used as the script key; must stay valid while loaded. */
+    const char *name;
+    /* Milliseconds between read() calls; 0 means 1000. Clamped to 250–60000. */
+    uint32_t interval_ms;
+    /* Called once before the first read(); may be NULL. Return 0 on success. */
+    int32_t (*init)(void);
+    /* Write up to `len` bytes of text into `buf` (no NUL needed) and return the
+     * number written, or a negative value on error. Return 0 to show nothing. */
+    int32_t (*read)(char *buf, uint32_t len);
+    /* Called once before the DLL is unloaded; may be NULL. */
+    void (*shutdown)(void);
+} ClockorPlugin;
+
+__declspec(dllexport) const ClockorPlugin *clockor_plugin(void);
+
+#endif
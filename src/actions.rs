@@ -0,0 +1,107 @@
+//! "At HH:MM do X" actions. They are polled by the main loop alongside
+//! reminders, so they run on time whether or not the overlay is painting.
+
+use chrono::{Datelike, Duration, NaiveDateTime};
+
+use crate::config::ScheduledAction;
+use crate::reminders::MAX_CATCH_UP_MINUTES;
+use crate::schedule::day_matches;
+
+/// Remembers the last poll so each action runs once when its time passes.
+pub struct ActionTracker {
+    last_check: NaiveDateTime,
+}
+
+impl ActionTracker {
+    pub fn new(now: NaiveDateTime) -> Self {
+        Self { last_check: now }
+    }
+
+    /// The actions whose time fell in `(last_check, now]` on one of their days.
+    /// Like reminders, a long gap (sleep) is skipped rather than replayed.
+    pub fn poll(
+        &mut self,
+        actions: &[ScheduledAction],
+        now: NaiveDateTime,
+    ) -> Vec<ScheduledAction> {
+        let last = std::mem::replace(&mut self.last_check, now);
+        if now <= last || now - last > Duration::minutes(MAX_CATCH_UP_MINUTES) {
+            return Vec::new();
+        }
+        actions
+            .iter()
+            .filter(|action| {
+                let Some(time) = action.parsed_time() else {
+                    return false;
+                };
+                // Check both days so an action at 00:00 runs when polling across midnight
+                [last.date(), now.date()].iter().any(|day| {
+                    let at = day.and_time(time);
+                    last < at && at <= now && day_matches(action.days, day.weekday())
+                })
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ActionKind, ScheduleDays};
+    use chrono::NaiveDate;
+
+    // 2026-03-06 is a Friday
+    fn fri(h: u32, m: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, 6)
+            .unwrap()
+            .and_hms_opt(h, m, s)
+            .unwrap()
+    }
+
+    fn action(time: &str, days: ScheduleDays) -> ScheduledAction {
+        ScheduledAction {
+            time: time.to_string(),
+            days,
+            action: ActionKind::StartTimer,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn runs_once_when_time_passes() {
+        let actions = [action("20:00", ScheduleDays::EveryDay)];
+        let mut tracker = ActionTracker::new(fri(19, 59, 59));
+        assert_eq!(tracker.poll(&actions, fri(20, 0, 0)).len(), 1);
+        assert!(tracker.poll(&actions, fri(20, 0, 1)).is_empty());
+    }
+
+    #[test]
+    fn respects_days() {
+        let weekends = [action("20:00", ScheduleDays::Weekends)];
+        let mut tracker = ActionTracker::new(fri(19, 59, 0));
+        assert!(tracker.poll(&weekends, fri(20, 0, 0)).is_empty());
+        let weekdays = [action("20:00", ScheduleDays::Weekdays)];
+        let mut tracker = ActionTracker::new(fri(19, 59, 0));
+        assert_eq!(tracker.poll(&weekdays, fri(20, 0, 0)).len(), 1);
+    }
+
+    #[test]
+    fn midnight_counts_for_the_new_day() {
+        // Friday 23:59 -> Saturday 00:00 is a weekend action
+        let actions = [action("00:00", ScheduleDays::Weekends)];
+        let mut tracker = ActionTracker::new(fri(23, 59, 59));
+        let saturday = fri(23, 59, 59) + Duration::seconds(1);
+        assert_eq!(tracker.poll(&actions, saturday).len(), 1);
+    }
+
+    #[test]
+    fn skips_long_gaps_and_bad_times() {
+        let actions = [action("20:00", ScheduleDays::EveryDay)];
+        let mut tracker = ActionTracker::new(fri(19, 0, 0));
+        assert!(tracker.poll(&actions, fri(20, 30, 0)).is_empty());
+        let bad = [action("8pm", ScheduleDays::EveryDay)];
+        let mut tracker = ActionTracker::new(fri(19, 59, 0));
+        assert!(tracker.poll(&bad, fri(20, 0, 0)).is_empty());
+    }
+}
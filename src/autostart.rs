@@ -0,0 +1,315 @@
+//! "Start with Windows" registration.
+//!
+//! Three interchangeable mechanisms: a value under the `Run` key, a shortcut in
+//! the user's Startup folder, or a logon task in Task Scheduler. Only the one
+//! selected in the config is kept; applying the config removes the others so
+//! switching methods never leaves ClockOR starting twice.
+//!
+//! With "Run as administrator" the scheduled task runs at the highest
+//! privileges, so ClockOR starts elevated without a UAC prompt at sign-in.
+//! Creating such a task needs one UAC prompt when ClockOR isn't elevated.
+
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use windows::core::{w, Interface, HSTRING, PCWSTR};
+use windows::Win32::Foundation::{CloseHandle, LPARAM, WPARAM};
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoTaskMemFree, CoUninitialize, IPersistFile,
+    CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED,
+};
+use windows::Win32::System::Registry::{
+    RegCloseKey, RegDeleteValueW, RegOpenKeyExW, RegSetValueExW, HKEY, HKEY_CURRENT_USER,
+    KEY_WRITE, REG_SZ,
+};
+use windows::Win32::System::Threading::{GetExitCodeProcess, WaitForSingleObject};
+use windows::Win32::UI::Shell::{
+    FOLDERID_Startup, IShellLinkW, SHGetKnownFolderPath, ShellExecuteExW, ShellLink,
+    KF_FLAG_DEFAULT, SEE_MASK_NOASYNC, SEE_MASK_NOCLOSEPROCESS, SHELLEXECUTEINFOW,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    FindWindowW, SendMessageTimeoutW, SMTO_ABORTIFHUNG, SW_HIDE, WM_NULL,
+};
+
+use crate::config::{AutostartMethod, Config};
+
+/// Run value, shortcut and task name.
+const NAME: &str = "ClockOR";
+
+/// Passed by every autostart mechanism so a sign-in launch can be told apart
+/// from the user starting ClockOR by hand.
+pub const LAUNCH_FLAG: &str = "--autostart";
+
+/// Give up waiting for the taskbar after this long and start anyway.
+const SHELL_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Register or unregister ClockOR to match `config`. `previous` is the config
+/// before the change; an existing scheduled task is only recreated when the
+/// autostart settings differ, since that can take a UAC prompt.
+pub fn apply(config: &Config, previous: &Config) {
+    let Ok(exe) = std::env::current_exe() else {
+        return;
+    };
+    let task_changed = config.start_with_windows != previous.start_with_windows
+        || config.autostart_method != previous.autostart_method
+        || config.run_elevated != previous.run_elevated;
+    for method in AutostartMethod::ALL {
+        let enable = config.start_with_windows && method == config.autostart_method;
+        match method {
+            AutostartMethod::RunKey => set_run_key(&exe, enable),
+            AutostartMethod::StartupFolder => set_startup_shortcut(&exe, enable),
+            AutostartMethod::ScheduledTask => {
+                let task = enable.then_some(config.run_elevated);
+                set_scheduled_task(&exe, task, task_changed);
+            }
+        }
+    }
+}
+
+/// Whether this process was started by one of the autostart mechanisms.
+pub fn launched_at_sign_in() -> bool {
+    std::env::args().any(|a| a == LAUNCH_FLAG)
+}
+
+/// The taskbar exists and answers messages, i.e. Explorer has finished starting.
+fn shell_ready() -> bool {
+    unsafe {
+        let Ok(taskbar) = FindWindowW(w!("Shell_TrayWnd"), PCWSTR::null()) else {
+            return false;
+        };
+        SendMessageTimeoutW(
+            taskbar,
+            WM_NULL,
+            WPARAM(0),
+            LPARAM(0),
+            SMTO_ABORTIFHUNG,
+            1000,
+            None,
+        )
+        .0 != 0
+    }
+}
+
+/// Hold off a sign-in launch per the startup delay settings, so the hotkey isn't
+/// registered while the desktop is still busy starting everything else.
+pub fn wait_before_start(config: &Config) {
+    if !launched_at_sign_in() {
+        return;
+    }
+    std::thread::sleep(Duration::from_secs(config.startup_delay_seconds.into()));
+    if config.wait_for_shell {
+        let started = Instant::now();
+        while !shell_ready() && started.elapsed() < SHELL_TIMEOUT {
+            std::thread::sleep(Duration::from_millis(500));
+        }
+    }
+}
+
+fn set_run_key(exe: &std::path::Path, enable: bool) {
+    let key_path = HSTRING::from("SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Run");
+    let value_name = HSTRING::from(NAME);
+
+    unsafe {
+        let mut hkey = HKEY::default();
+        let result = RegOpenKeyExW(HKEY_CURRENT_USER, &key_path, 0, KEY_WRITE, &mut hkey);
+        if result.is_err() {
+            return;
+        }
+
+        if enable {
+            let path_str = format!("\"{}\" {LAUNCH_FLAG}", exe.display());
+            let wide: Vec<u16> = path_str.encode_utf16().chain(std::iter::once(0)).collect();
+            let byte_len = wide.len() * std::mem::size_of::<u16>();
+            let bytes = std::slice::from_raw_parts(wide.as_ptr().cast::<u8>(), byte_len);
+            let _ = RegSetValueExW(hkey, &value_name, 0, REG_SZ, Some(bytes));
+        } else {
+            let _ = RegDeleteValueW(hkey, &value_name);
+        }
+
+        let _ = RegCloseKey(hkey);
+    }
+}
+
+/// `%APPDATA%\Microsoft\Windows\Start Menu\Programs\Startup\ClockOR.lnk`
+fn startup_shortcut_path() -> Option<PathBuf> {
+    unsafe {
+        let folder = SHGetKnownFolderPath(&FOLDERID_Startup, KF_FLAG_DEFAULT, None).ok()?;
+        let path = folder.to_string();
+        CoTaskMemFree(Some(folder.0 as *const _));
+        Some(PathBuf::from(path.ok()?).join(format!("{NAME}.lnk")))
+    }
+}
+
+fn set_startup_shortcut(exe: &std::path::Path, enable: bool) {
+    let Some(link_path) = startup_shortcut_path() else {
+        return;
+    };
+    if !enable {
+        let _ = std::fs::remove_file(&link_path);
+        return;
+    }
+
+    unsafe {
+        // The settings window may already have initialized COM on this thread;
+        // only balance the call if this one succeeded.
+        let initialized = CoInitializeEx(None, COINIT_APARTMENTTHREADED).is_ok();
+        let _ = (|| -> windows::core::Result<()> {
+            let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+            link.SetPath(&HSTRING::from(exe.as_os_str()))?;
+            link.SetArguments(&HSTRING::from(LAUNCH_FLAG))?;
+            if let Some(dir) = exe.parent() {
+                link.SetWorkingDirectory(&HSTRING::from(dir.as_os_str()))?;
+            }
+            link.cast::<IPersistFile>()?
+                .Save(&HSTRING::from(link_path.as_os_str()), true)
+        })();
+        if initialized {
+            CoUninitialize();
+        }
+    }
+}
+
+/// Task definition with a logon trigger for the current user only, which unlike
+/// `schtasks /SC ONLOGON` doesn't need administrator rights.
+fn task_xml(exe: &std::path::Path, user: &str, elevated: bool) -> String {
+    let escape = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    };
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-16"?>
+<Task version="1.2" xmlns="http://schemas.microsoft.com/windows/2004/02/mit/task">
+  <Triggers>
+    <LogonTrigger><Enabled>true</Enabled><UserId>{user}</UserId></LogonTrigger>
+  </Triggers>
+  <Principals>
+    <Principal id="Author"><UserId>{user}</UserId><LogonType>InteractiveToken</LogonType><RunLevel>{run_level}</RunLevel></Principal>
+  </Principals>
+  <Settings>
+    <DisallowStartIfOnBatteries>false</DisallowStartIfOnBatteries>
+    <StopIfGoingOnBatteries>false</StopIfGoingOnBatteries>
+    <ExecutionTimeLimit>PT0S</ExecutionTimeLimit>
+    <MultipleInstancesPolicy>IgnoreNew</MultipleInstancesPolicy>
+  </Settings>
+  <Actions Context="Author">
+    <Exec><Command>{exe}</Command><Arguments>{LAUNCH_FLAG}</Arguments></Exec>
+  </Actions>
+</Task>
+"#,
+        user = escape(user),
+        exe = escape(&exe.to_string_lossy()),
+        run_level = if elevated {
+            "HighestAvailable"
+        } else {
+            "LeastPrivilege"
+        },
+    )
+}
+
+/// Create the logon task (`Some(elevated)`) or remove it (`None`). An existing
+/// task is left alone unless `changed`.
+fn set_scheduled_task(exe: &std::path::Path, task: Option<bool>, changed: bool) {
+    let exists = schtasks(&["/Query", "/TN", NAME]);
+    let Some(elevated) = task else {
+        // A task created elevated can only be deleted elevated
+        if exists && !schtasks(&["/Delete", "/F", "/TN", NAME]) {
+            schtasks_elevated(&["/Delete", "/F", "/TN", NAME]);
+        }
+        return;
+    };
+    if exists && !changed {
+        return;
+    }
+
+    let user = match (std::env::var("USERDOMAIN"), std::env::var("USERNAME")) {
+        (Ok(domain), Ok(name)) => format!("{domain}\\{name}"),
+        (Err(_), Ok(name)) => name,
+        _ => return,
+    };
+    // schtasks reads the definition from a file, UTF-16 with a BOM
+    let xml_path = std::env::temp_dir().join("clockor_task.xml");
+    let bytes: Vec<u8> = std::iter::once(0xFEFF)
+        .chain(task_xml(exe, &user, elevated).encode_utf16())
+        .flat_map(u16::to_le_bytes)
+        .collect();
+    if std::fs::write(&xml_path, bytes).is_err() {
+        return;
+    }
+    let xml_arg = xml_path.to_string_lossy();
+    let args = ["/Create", "/F", "/TN", NAME, "/XML", &xml_arg];
+    if elevated && !crate::elevation::is_elevated() {
+        schtasks_elevated(&args);
+    } else {
+        schtasks(&args);
+    }
+    let _ = std::fs::remove_file(&xml_path);
+}
+
+/// Run `schtasks.exe` without a console window; true if it succeeded.
+fn schtasks(args: &[&str]) -> bool {
+    let mut command = Command::new("schtasks.exe");
+    command
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command.status().is_ok_and(|status| status.success())
+}
+
+/// Run `schtasks.exe` through the UAC prompt and wait for it; true if it succeeded.
+fn schtasks_elevated(args: &[&str]) -> bool {
+    let params = HSTRING::from(
+        args.iter()
+            .map(|a| format!("\"{a}\""))
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    let verb = HSTRING::from("runas");
+    let file = HSTRING::from("schtasks.exe");
+    let mut info = SHELLEXECUTEINFOW {
+        cbSize: std::mem::size_of::<SHELLEXECUTEINFOW>() as u32,
+        fMask: SEE_MASK_NOCLOSEPROCESS | SEE_MASK_NOASYNC,
+        lpVerb: PCWSTR(verb.as_ptr()),
+        lpFile: PCWSTR(file.as_ptr()),
+        lpParameters: PCWSTR(params.as_ptr()),
+        nShow: SW_HIDE.0,
+        ..Default::default()
+    };
+    unsafe {
+        if ShellExecuteExW(&mut info).is_err() || info.hProcess.is_invalid() {
+            return false;
+        }
+        WaitForSingleObject(info.hProcess, 30_000);
+        let mut code = 1;
+        let _ = GetExitCodeProcess(info.hProcess, &mut code);
+        let _ = CloseHandle(info.hProcess);
+        code == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn task_xml_escapes_paths() {
+        let xml = task_xml(
+            std::path::Path::new("C:\\Games & Tools\\clockor.exe"),
+            "PC\\me",
+            false,
+        );
+        assert!(xml.contains("<Command>C:\\Games &amp; Tools\\clockor.exe</Command>"));
+        assert!(xml.contains("<UserId>PC\\me</UserId>"));
+        assert!(xml.contains("<RunLevel>LeastPrivilege</RunLevel>"));
+        let xml = task_xml(std::path::Path::new("clockor.exe"), "me", true);
+        assert!(xml.contains("<RunLevel>HighestAvailable</RunLevel>"));
+    }
+}
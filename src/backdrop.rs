@@ -0,0 +1,132 @@
+//! Frosted panel behind the clock through DWM's blur-behind and acrylic
+//! accents. `SetWindowCompositionAttribute` is undocumented, so it is looked
+//! up at run time; where it is missing or refused, or Windows' transparency
+//! effects are off, the flat panel is drawn instead.
+
+use std::ffi::c_void;
+use std::sync::{Mutex, OnceLock};
+
+use windows::core::{s, w};
+use windows::Win32::Foundation::{BOOL, HWND};
+use windows::Win32::System::LibraryLoader::{GetModuleHandleW, GetProcAddress};
+
+use crate::config::Backdrop;
+
+const ACCENT_DISABLED: u32 = 0;
+const ACCENT_ENABLE_BLURBEHIND: u32 = 3;
+const ACCENT_ENABLE_ACRYLICBLURBEHIND: u32 = 4;
+/// Tint the effect with `gradient_color`.
+const ACCENT_FLAG_GRADIENT: u32 = 2;
+const WCA_ACCENT_POLICY: u32 = 19;
+
+#[repr(C)]
+struct AccentPolicy {
+    accent_state: u32,
+    accent_flags: u32,
+    gradient_color: u32,
+    animation_id: u32,
+}
+
+#[repr(C)]
+struct CompositionAttributeData {
+    attribute: u32,
+    data: *mut c_void,
+    size: usize,
+}
+
+type SetWindowCompositionAttribute =
+    unsafe extern "system" fn(HWND, *mut CompositionAttributeData) -> BOOL;
+
+fn api() -> Option<SetWindowCompositionAttribute> {
+    static API: OnceLock<Option<SetWindowCompositionAttribute>> = OnceLock::new();
+    *API.get_or_init(|| unsafe {
+        let user32 = GetModuleHandleW(w!("user32.dll")).ok()?;
+        let proc = GetProcAddress(user32, s!("SetWindowCompositionAttribute"))?;
+        Some(std::mem::transmute::<
+            unsafe extern "system" fn() -> isize,
+            SetWindowCompositionAttribute,
+        >(proc))
+    })
+}
+
+/// The tint as the accent policy wants it: 0xAABBGGRR.
+fn gradient_color(color: [u8; 3], alpha: u8) -> u32 {
+    (alpha as u32) << 24 | (color[2] as u32) << 16 | (color[1] as u32) << 8 | color[0] as u32
+}
+
+fn accent_state(backdrop: Backdrop) -> u32 {
+    match backdrop {
+        Backdrop::Flat => ACCENT_DISABLED,
+        Backdrop::Blur => ACCENT_ENABLE_BLURBEHIND,
+        Backdrop::Acrylic => ACCENT_ENABLE_ACRYLICBLURBEHIND,
+    }
+}
+
+fn set_accent(hwnd: HWND, backdrop: Backdrop, gradient: u32) -> bool {
+    let Some(set) = api() else {
+        return false;
+    };
+    let mut policy = AccentPolicy {
+        accent_state: accent_state(backdrop),
+        accent_flags: ACCENT_FLAG_GRADIENT,
+        gradient_color: gradient,
+        animation_id: 0,
+    };
+    let mut data = CompositionAttributeData {
+        attribute: WCA_ACCENT_POLICY,
+        data: &mut policy as *mut _ as *mut c_void,
+        size: std::mem::size_of::<AccentPolicy>(),
+    };
+    unsafe { set(hwnd, &mut data).as_bool() }
+}
+
+/// What was last asked of the window: its handle, effect and tint, and
+/// whether the effect took.
+static APPLIED: Mutex<Option<(isize, Backdrop, u32, bool)>> = Mutex::new(None);
+
+/// Give `hwnd` the `backdrop` effect tinted `color` at `alpha`. Returns whether
+/// a frosted effect is showing; `false` means the caller draws the flat panel.
+pub fn apply(hwnd: HWND, backdrop: Backdrop, color: [u8; 3], alpha: u8) -> bool {
+    let backdrop = if crate::theme::transparency_enabled() {
+        backdrop
+    } else {
+        Backdrop::Flat
+    };
+    // Acrylic with a fully clear tint draws nothing at all on some builds
+    let gradient = gradient_color(color, alpha.max(1));
+    let mut applied = APPLIED.lock().unwrap();
+    let handle = hwnd.0 as isize;
+    match *applied {
+        Some((h, b, g, active)) if h == handle && b == backdrop && g == gradient => return active,
+        None if backdrop == Backdrop::Flat => return false,
+        _ => {}
+    }
+    let active = set_accent(hwnd, backdrop, gradient) && backdrop != Backdrop::Flat;
+    if backdrop != Backdrop::Flat && !active {
+        crate::logging::info(format!(
+            "{backdrop:?} background isn't available here; drawing a flat panel"
+        ));
+    }
+    *applied = Some((handle, backdrop, gradient, active));
+    active
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tint_is_abgr() {
+        assert_eq!(gradient_color([0x11, 0x22, 0x33], 0x80), 0x8033_2211);
+    }
+
+    #[test]
+    fn each_backdrop_has_its_accent() {
+        assert_eq!(accent_state(Backdrop::Flat), ACCENT_DISABLED);
+        assert_eq!(accent_state(Backdrop::Blur), ACCENT_ENABLE_BLURBEHIND);
+        assert_eq!(
+            accent_state(Backdrop::Acrylic),
+            ACCENT_ENABLE_ACRYLICBLURBEHIND
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! A PNG drawn behind the clock text (a team logo, a subtle plate), stretched
+//! to the overlay's size. Its alpha channel is kept, which needs the
+//! per-pixel alpha path in `layered`.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Largest image accepted, per side, so a huge file can't eat memory.
+const MAX_SIDE: u32 = 4096;
+
+/// Decoded RGBA pixels, straight (not premultiplied) alpha.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Image {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+fn decode(bytes: &[u8]) -> Result<Image, String> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let (width, height) = (reader.info().width, reader.info().height);
+    if width > MAX_SIDE || height > MAX_SIDE {
+        return Err(format!(
+            "{width}×{height} is larger than {MAX_SIDE}×{MAX_SIDE}"
+        ));
+    }
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buf).map_err(|e| e.to_string())?;
+    let buf = &buf[..frame.buffer_size()];
+    let rgba = match frame.color_type {
+        png::ColorType::Rgba => buf.to_vec(),
+        png::ColorType::Rgb => buf
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buf
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Grayscale => buf.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        png::ColorType::Indexed => return Err("unsupported palette image".to_string()),
+    };
+    Ok(Image {
+        width,
+        height,
+        rgba,
+    })
+}
+
+/// Stretch `image` to `w`×`h` with bilinear filtering, returning premultiplied
+/// BGRA as `layered` expects.
+fn scale(image: &Image, w: i32, h: i32) -> Vec<u8> {
+    let (iw, ih) = (image.width as usize, image.height as usize);
+    let (w, h) = (w.max(0) as usize, h.max(0) as usize);
+    let mut out = Vec::with_capacity(w * h * 4);
+    let texel = |x: usize, y: usize| -> [f32; 4] {
+        let i = (y * iw + x) * 4;
+        let p = &image.rgba[i..i + 4];
+        // Premultiply before filtering so transparent pixels don't bleed color
+        let a = p[3] as f32 / 255.0;
+        [
+            p[2] as f32 * a,
+            p[1] as f32 * a,
+            p[0] as f32 * a,
+            p[3] as f32,
+        ]
+    };
+    for y in 0..h {
+        let sy = ((y as f32 + 0.5) * ih as f32 / h as f32 - 0.5).clamp(0.0, (ih - 1) as f32);
+        let (y0, fy) = (sy.floor() as usize, sy.fract());
+        let y1 = (y0 + 1).min(ih - 1);
+        for x in 0..w {
+            let sx = ((x as f32 + 0.5) * iw as f32 / w as f32 - 0.5).clamp(0.0, (iw - 1) as f32);
+            let (x0, fx) = (sx.floor() as usize, sx.fract());
+            let x1 = (x0 + 1).min(iw - 1);
+            let (a, b, c, d) = (texel(x0, y0), texel(x1, y0), texel(x0, y1), texel(x1, y1));
+            for i in 0..4 {
+                let top = a[i] + (b[i] - a[i]) * fx;
+                let bottom = c[i] + (d[i] - c[i]) * fx;
+                out.push((top + (bottom - top) * fy).round().clamp(0.0, 255.0) as u8);
+            }
+        }
+    }
+    out
+}
+
+/// Pixels scaled for one overlay size.
+type Scaled = ((i32, i32), Arc<[u8]>);
+
+struct Cached {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    image: Result<Image, String>,
+    /// The last scaled copy, reused while the overlay size stays the same.
+    scaled: Option<Scaled>,
+}
+
+static CACHE: Mutex<Option<Cached>> = Mutex::new(None);
+
+/// The image at `setting`, scaled to `size` as premultiplied BGRA, or `None`
+/// when no image is set or it can't be read (logged once).
+pub fn scaled(setting: &str, size: (i32, i32)) -> Option<Arc<[u8]>> {
+    let mut cache = CACHE.lock().unwrap();
+    if setting.trim().is_empty() {
+        *cache = None;
+        return None;
+    }
+    let path = crate::config::user_path(setting);
+    let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+    if cache
+        .as_ref()
+        .is_none_or(|c| c.path != path || c.modified != modified)
+    {
+        let image = std::fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| decode(&bytes));
+        if let Err(e) = &image {
+            crate::logging::error(format!("Background image {}: {e}", path.display()));
+        }
+        *cache = Some(Cached {
+            path,
+            modified,
+            image,
+            scaled: None,
+        });
+    }
+    let cached = cache.as_mut()?;
+    let image = cached.image.as_ref().ok()?;
+    match &cached.scaled {
+        Some((at, pixels)) if *at == size => Some(Arc::clone(pixels)),
+        _ => {
+            let pixels: Arc<[u8]> = scale(image, size.0, size.1).into();
+            cached.scaled = Some((size, Arc::clone(&pixels)));
+            Some(pixels)
+        }
+    }
+}
+
+/// Why the image isn't showing, for settings.
+pub fn last_error() -> Option<String> {
+    CACHE
+        .lock()
+        .unwrap()
+        .as_ref()?
+        .image
+        .as_ref()
+        .err()
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(width: u32, height: u32, color: png::ColorType, data: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, width, height);
+        encoder.set_color(color);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(data).unwrap();
+        writer.finish().unwrap();
+        bytes
+    }
+
+    #[test]
+    fn decodes_rgba_and_rgb() {
+        let png = encode(1, 1, png::ColorType::Rgba, &[10, 20, 30, 40]);
+        assert_eq!(decode(&png).unwrap().rgba, [10, 20, 30, 40]);
+        let png = encode(1, 1, png::ColorType::Rgb, &[10, 20, 30]);
+        assert_eq!(decode(&png).unwrap().rgba, [10, 20, 30, 255]);
+        assert!(decode(b"not a png").is_err());
+    }
+
+    #[test]
+    fn scaling_premultiplies_into_bgra() {
+        let image = Image {
+            width: 1,
+            height: 1,
+            rgba: vec![200, 100, 0, 128],
+        };
+        let out = scale(&image, 2, 2);
+        assert_eq!(out.len(), 16);
+        assert_eq!(&out[..4], &[0, 50, 100, 128]);
+    }
+
+    #[test]
+    fn scaling_blends_neighbors() {
+        // Black and white, opaque, stretched to four pixels
+        let image = Image {
+            width: 2,
+            height: 1,
+            rgba: vec![0, 0, 0, 255, 255, 255, 255, 255],
+        };
+        let out = scale(&image, 4, 1);
+        let reds: Vec<u8> = out.chunks_exact(4).map(|p| p[2]).collect();
+        assert_eq!(reds[0], 0);
+        assert_eq!(reds[3], 255);
+        assert!(reds[1] > 0 && reds[1] < reds[2] && reds[2] < 255);
+    }
+}
@@ -0,0 +1,166 @@
+//! Command-line config access for scripted setups and support:
+//! `clockor --print-config` and `clockor --set key=value`, plus
+//! `clockor --preview [file]`.
+//!
+//! These run instead of the app. `--set` writes config.toml and asks a running
+//! instance, if any, to reload it, so changes apply without a restart.
+
+use std::path::PathBuf;
+
+use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+use crate::config::Config;
+
+/// Print the effective config (after clamping and migration) as TOML.
+pub const PRINT_CONFIG_FLAG: &str = "--print-config";
+/// Change one setting; may be repeated.
+pub const SET_FLAG: &str = "--set";
+/// Show only the overlay, optionally with another config file; Esc exits.
+pub const PREVIEW_FLAG: &str = "--preview";
+
+/// What the command line asked for.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Command {
+    print: bool,
+    /// `(key, value)` pairs in the order given.
+    sets: Vec<(String, String)>,
+}
+
+/// Parse the arguments after the program name. `Ok(None)` means a normal start.
+fn parse(args: &[String]) -> Result<Option<Command>, String> {
+    let mut command = Command::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == PRINT_CONFIG_FLAG {
+            command.print = true;
+        } else if arg == SET_FLAG {
+            let pair = args.next().ok_or("--set needs key=value")?;
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("expected key=value, got \"{pair}\""))?;
+            command
+                .sets
+                .push((key.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    if command.print || !command.sets.is_empty() {
+        Ok(Some(command))
+    } else {
+        Ok(None)
+    }
+}
+
+/// `Some(file)` when `--preview` was given, with the config file that follows
+/// it, if any.
+fn parse_preview(args: &[String]) -> Option<Option<PathBuf>> {
+    let at = args.iter().position(|a| a == PREVIEW_FLAG)?;
+    Some(
+        args.get(at + 1)
+            .filter(|a| !a.starts_with("--"))
+            .map(PathBuf::from),
+    )
+}
+
+/// The preview request on this command line.
+pub fn preview() -> Option<Option<PathBuf>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    parse_preview(&args)
+}
+
+fn apply_sets(sets: &[(String, String)]) -> Result<(), String> {
+    let mut config = Config::load();
+    let policy = crate::policy::current();
+    for (key, value) in sets {
+        if policy.is_locked(key) {
+            return Err(format!("\"{key}\" is set by your administrator"));
+        }
+        config.set_value(key, value)?;
+    }
+    config
+        .save()
+        .map_err(|e| format!("failed to save config: {e}"))?;
+    crate::overlay::request_reload_running();
+    Ok(())
+}
+
+/// Handle a config command if one was given, returning the exit code.
+pub fn run() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = parse(&args);
+    if matches!(command, Ok(None)) {
+        return None;
+    }
+    // A GUI-subsystem exe has no console of its own; borrow the one we were started from
+    unsafe {
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+    let result = command.and_then(|command| {
+        let command = command.expect("checked above");
+        apply_sets(&command.sets)?;
+        if command.print {
+            let text = toml::to_string_pretty(&Config::load()).map_err(|e| e.to_string())?;
+            print!("{text}");
+        }
+        Ok(())
+    });
+    Some(match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("clockor: {e}");
+            2
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn normal_start_is_not_a_command() {
+        assert_eq!(parse(&[]), Ok(None));
+        assert_eq!(parse(&args(&["--debug"])), Ok(None));
+    }
+
+    #[test]
+    fn parses_print_and_repeated_sets() {
+        let command = parse(&args(&[
+            "--set",
+            "font_size=30",
+            "--set",
+            "position = bottom-left",
+            "--print-config",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert!(command.print);
+        assert_eq!(
+            command.sets,
+            vec![
+                ("font_size".to_string(), "30".to_string()),
+                ("position".to_string(), "bottom-left".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn preview_with_and_without_file() {
+        assert_eq!(parse_preview(&args(&["--debug"])), None);
+        assert_eq!(parse_preview(&args(&["--preview"])), Some(None));
+        assert_eq!(parse_preview(&args(&["--preview", "--debug"])), Some(None));
+        assert_eq!(
+            parse_preview(&args(&["--preview", "theme.toml"])),
+            Some(Some(PathBuf::from("theme.toml")))
+        );
+    }
+
+    #[test]
+    fn set_without_pair_is_an_error() {
+        assert!(parse(&args(&["--set"])).is_err());
+        assert!(parse(&args(&["--set", "font_size"])).is_err());
+    }
+}
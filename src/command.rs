@@ -0,0 +1,180 @@
+//! Output of a shell command as a segment, for data nothing else provides
+//! (a stock price, CI status). `command` runs through `cmd /C` every
+//! `command_interval_seconds` without a console window, and its trimmed
+//! standard output is shown. A run that takes longer than `TIMEOUT` is
+//! killed; a failing command shows nothing and is logged once.
+
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Longest a run may take before it is killed.
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest output shown, in characters.
+const MAX_CHARS: usize = 200;
+/// Output read per run; the rest is ignored.
+const MAX_BYTES: u64 = 64 * 1024;
+
+struct Runner {
+    command: String,
+    /// Bumped when the command changes, so a stale run's result is dropped.
+    generation: u64,
+    last_start: Option<Instant>,
+    running: bool,
+    text: Option<String>,
+    last_error: Option<String>,
+}
+
+static RUNNER: Mutex<Runner> = Mutex::new(Runner {
+    command: String::new(),
+    generation: 0,
+    last_start: None,
+    running: false,
+    text: None,
+    last_error: None,
+});
+
+fn due(last_start: Option<Instant>, now: Instant, interval: Duration) -> bool {
+    last_start.is_none_or(|start| now.duration_since(start) >= interval)
+}
+
+/// Standard output as one line: trimmed, lines joined, cut to `MAX_CHARS`.
+fn output_text(bytes: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(bytes);
+    let text = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("  ");
+    (!text.is_empty()).then(|| text.chars().take(MAX_CHARS).collect())
+}
+
+/// Run the command if it is due. An empty `command` turns the segment off.
+pub fn update(command: &str, interval_seconds: u32) {
+    let mut runner = RUNNER.lock().unwrap();
+    let command = command.trim();
+    if command != runner.command {
+        runner.command = command.to_string();
+        runner.generation += 1;
+        runner.last_start = None;
+        runner.running = false;
+        runner.text = None;
+        runner.last_error = None;
+    }
+    let interval = Duration::from_secs(interval_seconds as u64);
+    if command.is_empty() || runner.running || !due(runner.last_start, Instant::now(), interval) {
+        return;
+    }
+    runner.last_start = Some(Instant::now());
+    runner.running = true;
+    let command = command.to_string();
+    let generation = runner.generation;
+    std::thread::spawn(move || {
+        let result = run(&command);
+        let mut runner = RUNNER.lock().unwrap();
+        if runner.generation != generation {
+            return;
+        }
+        runner.running = false;
+        match result {
+            Ok(text) => {
+                runner.text = text;
+                runner.last_error = None;
+            }
+            Err(e) => {
+                runner.text = None;
+                if runner.last_error.as_deref() != Some(e.as_str()) {
+                    crate::logging::error(format!("Command: {e}"));
+                    runner.last_error = Some(e);
+                }
+            }
+        }
+    });
+}
+
+/// Run once, killing it after `TIMEOUT`.
+fn run(command: &str) -> Result<Option<String>, String> {
+    let mut child = {
+        let mut cmd = Command::new("cmd.exe");
+        cmd.arg("/C")
+            .arg(command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+            cmd.creation_flags(CREATE_NO_WINDOW);
+        }
+        cmd.spawn().map_err(|e| format!("could not start ({e})"))?
+    };
+    // Read on another thread so a chatty command can't fill the pipe and stall
+    let stdout = child.stdout.take().expect("piped above");
+    let (sender, output) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = stdout.take(MAX_BYTES).read_to_end(&mut bytes);
+        let _ = sender.send(bytes);
+    });
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() < TIMEOUT => {
+                std::thread::sleep(Duration::from_millis(50))
+            }
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {}s", TIMEOUT.as_secs()));
+            }
+            Err(e) => return Err(e.to_string()),
+        }
+    };
+    // A process the command started in the background may hold the pipe open
+    let bytes = output
+        .recv_timeout(TIMEOUT.saturating_sub(started.elapsed()))
+        .unwrap_or_default();
+    if !status.success() {
+        return Err(format!("exited with {status}"));
+    }
+    Ok(output_text(&bytes))
+}
+
+/// The command's output from its last successful run.
+pub fn text() -> Option<String> {
+    RUNNER.lock().unwrap().text.clone()
+}
+
+/// Why the command's output isn't showing, for settings.
+pub fn last_error() -> Option<String> {
+    RUNNER.lock().unwrap().last_error.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_first_time_then_every_interval() {
+        let now = Instant::now();
+        let minute = Duration::from_secs(60);
+        assert!(due(None, now, minute));
+        assert!(!due(Some(now), now + Duration::from_secs(59), minute));
+        assert!(due(Some(now), now + minute, minute));
+    }
+
+    #[test]
+    fn output_is_trimmed_to_one_line() {
+        assert_eq!(
+            output_text(b"  AAPL 231.4\r\n\r\n+1.2%\r\n").as_deref(),
+            Some("AAPL 231.4  +1.2%")
+        );
+        assert_eq!(output_text(b"\r\n  \r\n"), None);
+        let long = "x".repeat(MAX_CHARS * 2);
+        assert_eq!(output_text(long.as_bytes()).unwrap().len(), MAX_CHARS);
+    }
+}
@@ -1,3 +1,5 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{NaiveDateTime, NaiveTime};
 use serde::{Deserialize, Deserializer, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -14,6 +16,176 @@ pub enum Position {
     TopLeft,
     BottomRight,
     BottomLeft,
+    /// Dragged by the user; offset stored in `Config::custom_pos`.
+    Custom,
+}
+
+impl Position {
+    /// The diagonally opposite corner. A custom position has no opposite and stays put.
+    pub fn opposite(self) -> Self {
+        match self {
+            Position::TopRight => Position::BottomLeft,
+            Position::TopLeft => Position::BottomRight,
+            Position::BottomRight => Position::TopLeft,
+            Position::BottomLeft => Position::TopRight,
+            Position::Custom => Position::Custom,
+        }
+    }
+}
+
+/// What the overlay does while the mouse pointer is over it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CursorReaction {
+    #[default]
+    Off,
+    /// Drop to a low opacity so whatever is underneath stays visible.
+    Dim,
+    /// Move to the opposite corner until the pointer leaves.
+    Dodge,
+}
+
+/// How hard the overlay holds on to its place above other windows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ZOrder {
+    /// Topmost, placed again on each tick.
+    #[default]
+    Topmost,
+    /// Also check several times a second and jump back above any topmost
+    /// window that covered it.
+    Watchdog,
+    /// Created in the accessibility-tool band above ordinary topmost windows,
+    /// where Windows allows it; otherwise the same as `Watchdog`.
+    Band,
+}
+
+/// Screen edge for the silent-alert band.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScreenEdge {
+    #[default]
+    Off,
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+/// Modifier that toggles the overlay when tapped twice in quick succession.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DoubleTapKey {
+    #[default]
+    Off,
+    Ctrl,
+    Shift,
+    Alt,
+}
+
+/// Screen corner that reveals the clock when the pointer is pushed into it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HotCorner {
+    #[default]
+    Off,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl HotCorner {
+    pub const ALL: [HotCorner; 5] = [
+        HotCorner::Off,
+        HotCorner::TopLeft,
+        HotCorner::TopRight,
+        HotCorner::BottomLeft,
+        HotCorner::BottomRight,
+    ];
+}
+
+/// What clicking the tray icon does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TrayAction {
+    #[default]
+    Toggle,
+    Settings,
+    StreamTimer,
+    Nothing,
+}
+
+impl TrayAction {
+    pub const ALL: [TrayAction; 4] = [
+        TrayAction::Toggle,
+        TrayAction::Settings,
+        TrayAction::StreamTimer,
+        TrayAction::Nothing,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TrayAction::Toggle => "Toggle overlay",
+            TrayAction::Settings => "Open settings",
+            TrayAction::StreamTimer => "Start/stop stream timer",
+            TrayAction::Nothing => "Nothing",
+        }
+    }
+}
+
+/// How "Start with Windows" registers ClockOR to run at sign-in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AutostartMethod {
+    /// `HKCU\...\CurrentVersion\Run` value.
+    #[default]
+    RunKey,
+    /// Shortcut in the user's Startup folder, for machines where registry
+    /// autostart entries are flagged by security software.
+    StartupFolder,
+    /// Logon task in Task Scheduler.
+    ScheduledTask,
+}
+
+impl AutostartMethod {
+    pub const ALL: [AutostartMethod; 3] = [
+        AutostartMethod::RunKey,
+        AutostartMethod::StartupFolder,
+        AutostartMethod::ScheduledTask,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AutostartMethod::RunKey => "Registry (Run key)",
+            AutostartMethod::StartupFolder => "Startup folder shortcut",
+            AutostartMethod::ScheduledTask => "Scheduled task",
+        }
+    }
+}
+
+/// Groups of settings that can be reset independently in the settings window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsSection {
+    Display,
+    Appearance,
+    Reminders,
+    Sound,
+    Automation,
+    Tray,
+    System,
+}
+
+impl SettingsSection {
+    pub const ALL: [SettingsSection; 7] = [
+        SettingsSection::Display,
+        SettingsSection::Appearance,
+        SettingsSection::Reminders,
+        SettingsSection::Sound,
+        SettingsSection::Automation,
+        SettingsSection::Tray,
+        SettingsSection::System,
+    ];
 }
 
 /// Deserialize font_size from either a u32 or a legacy string ("small"/"medium"/"large").
@@ -50,46 +222,828 @@ pub enum TextStyle {
     Shadow,
 }
 
+impl TextStyle {
+    /// Offsets the text is drawn at in the outline color before it is drawn
+    /// itself: a ring for an outline (thicker under high contrast), one for a shadow.
+    pub fn offsets(self, high_contrast: bool) -> Vec<(i32, i32)> {
+        match self {
+            TextStyle::Outline => {
+                let radius = if high_contrast { 2 } else { 1 };
+                (-radius..=radius)
+                    .flat_map(|dy| (-radius..=radius).map(move |dx| (dx, dy)))
+                    .filter(|&offset| offset != (0, 0))
+                    .collect()
+            }
+            TextStyle::Shadow => vec![(2, 2)],
+            TextStyle::None => Vec::new(),
+        }
+    }
+}
+
+/// What draws the clock text. DirectWrite falls back to GDI by itself on
+/// machines where it can't start.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TextRenderer {
+    /// `TextOutW` with a separate emoji font; what older versions drew with.
+    Gdi,
+    /// Subpixel positioning, font fallback and color emoji.
+    #[default]
+    DirectWrite,
+}
+
+/// Typeface of the clock text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FontFamily {
+    #[default]
+    SegoeUi,
+    /// Variable, with condensed widths; ships with Windows 10 and later.
+    Bahnschrift,
+}
+
+impl FontFamily {
+    /// The family name Windows knows it by.
+    pub fn name(self) -> &'static str {
+        match self {
+            FontFamily::SegoeUi => "Segoe UI",
+            FontFamily::Bahnschrift => "Bahnschrift",
+        }
+    }
+
+    /// Whether the family has faces narrower than normal for `font_width`.
+    pub fn has_widths(self) -> bool {
+        self == FontFamily::Bahnschrift
+    }
+}
+
+/// Colors of the warning chips, from a far-off reminder (notice) through a
+/// near one (warning) to an alert such as low battery.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AlertPalette {
+    /// Yellow, orange and red.
+    #[default]
+    Standard,
+    /// Sky blue, orange and vermillion from the Okabe–Ito palette, which
+    /// also differ in lightness for red–green color blindness.
+    ColorblindSafe,
+    /// The colors in `alert_colors`.
+    Custom,
+}
+
+/// Notice, warning and alert colors of the standard palette.
+pub const STANDARD_ALERT_COLORS: [[u8; 3]; 3] = [[255, 210, 0], [255, 140, 0], [255, 64, 64]];
+const COLORBLIND_ALERT_COLORS: [[u8; 3]; 3] = [[86, 180, 233], [230, 159, 0], [213, 94, 0]];
+
+/// How the background panel is drawn. The frosted kinds fall back to a flat
+/// panel where Windows can't provide them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backdrop {
+    #[default]
+    Flat,
+    /// Blur of what's behind the overlay, tinted with the panel color.
+    Blur,
+    /// Windows 10/11 acrylic: blur plus noise, tinted with the panel color.
+    Acrylic,
+}
+
+/// Horizontal placement of a line of text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Corner shape of the overlay window on Windows 11.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CornerStyle {
+    /// Whatever Windows does for a popup (square).
+    #[default]
+    System,
+    Square,
+    Round,
+    RoundSmall,
+}
+
+/// Optional "how far through the year/month" segment shown after the time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProgressDisplay {
+    #[default]
+    Off,
+    Year,
+    Month,
+}
+
+/// Optional line under the clock with today's date.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DateDisplay {
+    #[default]
+    Off,
+    /// 2026-10-16
+    Iso,
+    /// The long date format from Windows' region settings, e.g. "2026年10月16日".
+    Localized,
+}
+
+/// A clock for another time zone, on its own line under the local time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldClock {
+    /// Shown before the time (empty = the zone's abbreviation, e.g. "JST").
+    pub label: String,
+    /// Time zone database name, e.g. "Asia/Tokyo" or "UTC".
+    pub zone: String,
+}
+
+impl Default for WorldClock {
+    fn default() -> Self {
+        Self {
+            label: String::new(),
+            zone: "UTC".to_string(),
+        }
+    }
+}
+
+impl WorldClock {
+    /// The zone, or `None` if the time zone database doesn't know the name.
+    pub fn tz(&self) -> Option<chrono_tz::Tz> {
+        self.zone.trim().parse().ok()
+    }
+}
+
+/// Optional segment naming what has the focus: the program or its window title.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ActiveWindowDisplay {
+    #[default]
+    Off,
+    Process,
+    Title,
+}
+
+/// A named time of day at which the overlay briefly shows a banner.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyEvent {
+    pub name: String,
+    /// Local time of day in "HH:MM" (24-hour) form.
+    pub time: String,
+    pub sound: bool,
+    /// Also ring as an alarm: a Windows notification and a flash of the overlay.
+    pub alarm: bool,
+}
+
+impl Default for DailyEvent {
+    fn default() -> Self {
+        Self {
+            name: "Reminder".to_string(),
+            time: "12:00".to_string(),
+            sound: true,
+            alarm: false,
+        }
+    }
+}
+
+/// Something that happened, for event hooks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookEvent {
+    #[default]
+    Shown,
+    Hidden,
+    StreamStarted,
+    StreamStopped,
+    Reminder,
+    /// The PC has been on (uptime, which includes sleep) for another
+    /// `hours` hours. Older configs call it "session-hours".
+    #[serde(alias = "session-hours")]
+    PcOnHours,
+    /// A scheduled action ran.
+    Scheduled,
+    /// The countdown reached zero.
+    TimerFinished,
+}
+
+impl HookEvent {
+    pub const ALL: [HookEvent; 8] = [
+        HookEvent::Shown,
+        HookEvent::Hidden,
+        HookEvent::StreamStarted,
+        HookEvent::StreamStopped,
+        HookEvent::Reminder,
+        HookEvent::PcOnHours,
+        HookEvent::Scheduled,
+        HookEvent::TimerFinished,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HookEvent::Shown => "Overlay shown",
+            HookEvent::Hidden => "Overlay hidden",
+            HookEvent::StreamStarted => "Stream timer started",
+            HookEvent::StreamStopped => "Stream timer stopped",
+            HookEvent::Reminder => "Reminder",
+            HookEvent::PcOnHours => "PC on for N hours",
+            HookEvent::Scheduled => "Scheduled action",
+            HookEvent::TimerFinished => "Countdown finished",
+        }
+    }
+}
+
+/// Runs `action` when `event` happens: a URL gets a JSON POST, anything else
+/// runs as a command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Hook {
+    pub event: HookEvent,
+    /// Interval for `PcOnHours`.
+    pub hours: u32,
+    pub action: String,
+}
+
+impl Default for Hook {
+    fn default() -> Self {
+        Self {
+            event: HookEvent::Shown,
+            hours: 2,
+            action: String::new(),
+        }
+    }
+}
+
+impl DailyEvent {
+    pub fn parsed_time(&self) -> Option<NaiveTime> {
+        parse_hhmm(&self.time)
+    }
+}
+
+/// Which days a schedule range applies to (the day the range starts on).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScheduleDays {
+    #[default]
+    EveryDay,
+    Weekdays,
+    Weekends,
+}
+
+/// A daily time range during which the overlay is shown automatically.
+/// `end` earlier than `start` means the range runs past midnight.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduleRange {
+    pub start: String,
+    pub end: String,
+    pub days: ScheduleDays,
+}
+
+impl Default for ScheduleRange {
+    fn default() -> Self {
+        Self {
+            start: "21:00".to_string(),
+            end: "01:00".to_string(),
+            days: ScheduleDays::EveryDay,
+        }
+    }
+}
+
+impl ScheduleRange {
+    pub fn parsed(&self) -> Option<(NaiveTime, NaiveTime)> {
+        Some((parse_hhmm(&self.start)?, parse_hhmm(&self.end)?))
+    }
+}
+
+/// Colors the clock takes during a daily time range, e.g. red after midnight.
+/// `end` earlier than `start` means the range runs past midnight.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorRule {
+    pub start: String,
+    pub end: String,
+    pub days: ScheduleDays,
+    pub text_color: [u8; 3],
+    /// Panel color as well, if set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background_color: Option<[u8; 3]>,
+}
+
+impl Default for ColorRule {
+    fn default() -> Self {
+        Self {
+            start: "00:00".to_string(),
+            end: "06:00".to_string(),
+            days: ScheduleDays::EveryDay,
+            text_color: [255, 64, 64],
+            background_color: None,
+        }
+    }
+}
+
+impl ColorRule {
+    /// The time range the rule applies in.
+    pub fn range(&self) -> ScheduleRange {
+        ScheduleRange {
+            start: self.start.clone(),
+            end: self.end.clone(),
+            days: self.days,
+        }
+    }
+}
+
+/// What a scheduled action does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ActionKind {
+    #[default]
+    ShowOverlay,
+    HideOverlay,
+    StartTimer,
+    StopTimer,
+    /// Load the theme file named by `target`.
+    ApplyTheme,
+    /// Post to the URL or run the command in `target`, like an event hook.
+    Webhook,
+}
+
+impl ActionKind {
+    pub const ALL: [ActionKind; 6] = [
+        ActionKind::ShowOverlay,
+        ActionKind::HideOverlay,
+        ActionKind::StartTimer,
+        ActionKind::StopTimer,
+        ActionKind::ApplyTheme,
+        ActionKind::Webhook,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ActionKind::ShowOverlay => "Show overlay",
+            ActionKind::HideOverlay => "Hide overlay",
+            ActionKind::StartTimer => "Start stream timer",
+            ActionKind::StopTimer => "Stop stream timer",
+            ActionKind::ApplyTheme => "Apply theme",
+            ActionKind::Webhook => "Run hook",
+        }
+    }
+
+    /// Whether the action uses `target`.
+    pub fn has_target(self) -> bool {
+        matches!(self, ActionKind::ApplyTheme | ActionKind::Webhook)
+    }
+}
+
+/// Does `action` at `time` on `days`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduledAction {
+    /// Local time of day in "HH:MM" (24-hour) form.
+    pub time: String,
+    pub days: ScheduleDays,
+    pub action: ActionKind,
+    /// Theme name for `ApplyTheme`; URL or command for `Webhook`.
+    pub target: String,
+}
+
+impl Default for ScheduledAction {
+    fn default() -> Self {
+        Self {
+            time: "09:00".to_string(),
+            days: ScheduleDays::EveryDay,
+            action: ActionKind::ShowOverlay,
+            target: String::new(),
+        }
+    }
+}
+
+impl ScheduledAction {
+    pub fn parsed_time(&self) -> Option<NaiveTime> {
+        parse_hhmm(&self.time)
+    }
+}
+
+/// Size multiplier for one monitor, e.g. a TV read from the couch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonitorScale {
+    /// Device name as Windows reports it, e.g. `\\.\DISPLAY2`.
+    pub monitor: String,
+    /// Percent of the normal size.
+    pub scale: u32,
+}
+
+impl Default for MonitorScale {
+    fn default() -> Self {
+        Self {
+            monitor: String::new(),
+            scale: 100,
+        }
+    }
+}
+
+/// Whether a monitor gets a clock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MonitorMode {
+    /// Only when the overlay is shown here, on the foreground window's monitor.
+    #[default]
+    Follow,
+    /// A clock of its own whenever the overlay is visible, wherever it was shown.
+    Always,
+    /// Never; the overlay goes to another monitor instead.
+    Never,
+}
+
+/// Clock settings for one monitor, e.g. a big clock on the TV and none on the
+/// drawing tablet. The size comes from `monitor_scales`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonitorOverlay {
+    /// Device name as Windows reports it, e.g. `\\.\DISPLAY2`.
+    pub monitor: String,
+    pub mode: MonitorMode,
+    /// Replaces `Config::position` on this monitor (`None` = the same as elsewhere).
+    pub position: Option<Position>,
+    /// Top-left offset from the monitor origin, used when `position` is `Custom`.
+    pub custom_pos: [i32; 2],
+}
+
+impl Default for MonitorOverlay {
+    fn default() -> Self {
+        Self {
+            monitor: String::new(),
+            mode: MonitorMode::Follow,
+            position: None,
+            custom_pos: [0, 0],
+        }
+    }
+}
+
+/// Whether chrono can format with `pattern`; formatting with an invalid one panics.
+pub fn valid_time_format(pattern: &str) -> bool {
+    !pattern.trim().is_empty()
+        && StrftimeItems::new(pattern).all(|item| !matches!(item, Item::Error))
+}
+
+/// Parse a 24-hour "HH:MM" time of day.
+pub fn parse_hhmm(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M").ok()
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(default)]
 pub struct Config {
     pub position: Position,
+    /// Top-left offset from the monitor origin, used when `position` is `Custom`.
+    pub custom_pos: [i32; 2],
+    /// Pull a dragged overlay onto nearby screen edges and corners.
+    pub snap_to_edges: bool,
+    /// Round dragged positions to this many pixels (0 = no grid).
+    pub snap_grid: u32,
+    /// Font size and margins are multiplied by these on the named monitors.
+    pub monitor_scales: Vec<MonitorScale>,
+    /// Per-monitor clocks and positions; monitors not listed follow the overlay.
+    pub monitor_overlays: Vec<MonitorOverlay>,
+    pub cursor_reaction: CursorReaction,
+    /// Read once at startup for `Band`, which only applies to a new window.
+    pub z_order: ZOrder,
     pub format_24h: bool,
     pub show_seconds: bool,
+    /// chrono/strftime pattern for the time, e.g. `%H:%M:%S %a`; replaces the two
+    /// settings above when set and valid (empty = off).
+    pub time_format: String,
+    pub progress: ProgressDisplay,
+    /// Today's date on its own line under the clock.
+    pub date_line: DateDisplay,
+    /// Other time zones, each on a line under the local time (and date).
+    pub world_clocks: Vec<WorldClock>,
+    pub show_uptime: bool,
+    /// Show recently pressed keys and mouse buttons, fading out, for streams.
+    pub show_input: bool,
+    pub active_window: ActiveWindowDisplay,
+    /// Show the focused window's input language and IME state ("JP あ").
+    pub show_input_language: bool,
+    /// Rhai script whose result replaces the clock, relative to the config folder (empty = off).
+    pub script: String,
+    /// Composes the whole clock line from tokens like `{time}  {battery}` (empty = the settings above).
+    pub template: String,
+    /// Show the first line of this file, re-read when it changes (empty = off).
+    pub text_file: String,
+    /// Shell command whose output is shown, run through `cmd /C` (empty = off).
+    pub command: String,
+    pub command_interval_seconds: u32,
     #[serde(deserialize_with = "deserialize_font_size")]
     pub font_size: u32,
+    /// Font weight, 100 (thin) to 900 (black). Variable fonts get the nearest
+    /// named instance.
+    pub font_weight: u32,
+    pub font_family: FontFamily,
+    /// Font width as a DirectWrite stretch, 1 (ultra-condensed) to 9
+    /// (ultra-expanded), 5 = normal. Only families with such faces change;
+    /// GDI always draws normal width.
+    pub font_width: u32,
+    /// Longest the text may grow before it is cut with an ellipsis, in pixels (0 = unlimited).
+    pub max_width: u32,
+    /// Where the text sits when the overlay is wider than it.
+    pub text_align: TextAlign,
+    /// Put the clock and each segment on its own line instead of one row.
+    pub multi_line: bool,
+    /// Extra pixels between lines when `multi_line` is on.
+    pub line_spacing: u32,
+    /// Alignment of each line from the top; the last entry applies to the
+    /// lines after it (empty = all left).
+    pub line_alignment: Vec<TextAlign>,
+    /// Turn the text 90° to read top to bottom, for a clock along a side edge.
+    pub vertical: bool,
+    /// Text opacity (%).
     pub opacity: u8,
+    /// Opacity (%) of the panel behind the text; 0 leaves only the text visible.
+    pub background_opacity: u8,
+    pub background_color: [u8; 3],
+    /// PNG stretched behind the text, relative to the config folder (empty = none).
+    pub background_image: String,
+    /// Flat panel, or a frosted one tinted with `background_color` at `background_opacity`.
+    pub backdrop: Backdrop,
+    /// Windows 11 corner rounding of the overlay window.
+    pub corner_style: CornerStyle,
+    /// Thin Windows 11 border around the overlay in `border_color`.
+    pub window_border: bool,
+    pub border_color: [u8; 3],
     pub hotkey: String,
+    pub double_tap: DoubleTapKey,
+    /// Peek at the clock for `peek_seconds` when the pointer hits this corner
+    /// of the monitor it is on.
+    pub hot_corner: HotCorner,
+    /// Pulse the overlay briefly when it is toggled on, as confirmation.
+    pub toggle_flash: bool,
+    /// Toggles click-through so the overlay can be dragged (empty = unbound).
+    pub lock_hotkey: String,
+    /// Flips between 24h and 12h display (empty = unbound).
+    pub format_hotkey: String,
+    pub tray_left_click: TrayAction,
+    pub tray_double_click: TrayAction,
+    pub tray_middle_click: TrayAction,
+    /// Ask before quitting from the tray menu.
+    pub confirm_quit: bool,
     pub start_with_windows: bool,
+    pub autostart_method: AutostartMethod,
+    /// Seconds to wait after a sign-in launch before setting up the hotkey and overlay.
+    pub startup_delay_seconds: u32,
+    /// After a sign-in launch, also wait until the taskbar responds.
+    pub wait_for_shell: bool,
+    /// Show the overlay as soon as ClockOR starts instead of waiting for the hotkey.
+    pub start_visible: bool,
+    /// Start shown or hidden as the overlay was last left, over `start_visible`.
+    pub remember_visibility: bool,
+    /// Restart through UAC at launch so the hotkey reaches games run as administrator.
+    pub run_elevated: bool,
+    /// Look for a newer GitHub release on startup (at most once a day).
+    pub check_updates: bool,
+    /// Send anonymous feature usage (at most once a week). Off unless the user opts in.
+    pub usage_stats: bool,
+    /// Load the DLLs in the plugins folder as extra overlay segments.
+    pub plugins: bool,
+    /// Plugin DLL file names that stay unloaded while `plugins` is on.
+    pub disabled_plugins: Vec<String>,
+    /// Webhooks and commands run on overlay, timer and session events.
+    pub hooks: Vec<Hook>,
     pub text_style: TextStyle,
+    pub text_renderer: TextRenderer,
+    /// Place DirectWrite glyphs at fractions of a pixel for even spacing;
+    /// off snaps them to whole pixels like GDI, for crisper small text.
+    pub subpixel_text: bool,
     pub text_color: [u8; 3],
     pub outline_color: [u8; 3],
+    /// Colors by time of day; the first rule whose range holds wins.
+    pub color_rules: Vec<ColorRule>,
+    pub alert_palette: AlertPalette,
+    /// Notice, warning and alert colors for the custom palette.
+    pub alert_colors: [[u8; 3]; 3],
+    /// How long a reminder banner stays on screen, in seconds.
+    pub banner_seconds: u32,
+    /// Delay before a snoozed reminder or countdown rings again, in minutes.
+    pub snooze_minutes: u32,
+    /// Count down to the next reminder on the overlay from this many minutes
+    /// before it, turning from the notice to the alert color (0 = off).
+    pub reminder_warning_minutes: u32,
+    /// Length of the countdown started from the tray or its hotkey, in minutes.
+    pub countdown_minutes: u32,
+    /// Count down to this "HH:MM" instead, the next time it comes round (empty = off).
+    pub countdown_until: String,
+    /// Show the running countdown in place of the clock rather than beside it.
+    pub countdown_replaces_clock: bool,
+    /// Edge where reminders flash a colored band, for alerts without sound.
+    pub edge_flash: ScreenEdge,
+    pub edge_flash_color: [u8; 3],
+    /// Pulse connected game controllers when a reminder fires.
+    pub controller_rumble: bool,
+    /// Snoozes the showing reminder or finished countdown (empty = unbound;
+    /// the toggle hotkey also snoozes).
+    pub snooze_hotkey: String,
+    /// No flashing or animation, whatever else is configured. Also implied by
+    /// Windows' "Animation effects" being off.
+    pub reduce_motion: bool,
+    /// Draws the clock at double size for `zoom_seconds` (empty = unbound).
+    pub zoom_hotkey: String,
+    pub zoom_seconds: u32,
+    /// Starts and stops the countdown (empty = unbound).
+    pub countdown_hotkey: String,
+    /// Toggles the diagnostics line on the overlay (config file only; empty = unbound).
+    pub debug_hotkey: String,
+    pub daily_events: Vec<DailyEvent>,
+    pub visibility_schedule: Vec<ScheduleRange>,
+    /// "At HH:MM do X" actions, checked by the main loop.
+    pub scheduled_actions: Vec<ScheduledAction>,
+    /// Hide the overlay after this many minutes without input (0 = never).
+    pub idle_hide_minutes: u32,
+    /// Briefly reveal the clock every N minutes past midnight (60 = on the hour, 0 = off).
+    pub peek_interval_minutes: u32,
+    pub peek_seconds: u32,
+    /// Show the overlay once a fullscreen session has run this many minutes (0 = off).
+    pub play_reveal_minutes: u32,
+    /// On battery power at or below this charge (%), show the overlay with a
+    /// red battery segment (0 = off).
+    pub low_battery_percent: u8,
+    /// Show the overlay with a red "shutdown requested" segment while a
+    /// shutdown or restart is pending.
+    pub shutdown_warning: bool,
+    /// How long Windows waits between a shutdown request and the shutdown,
+    /// when the user knows it (e.g. the `/t` an update policy uses). The
+    /// segment then counts down; 0 counts up since the request.
+    pub shutdown_timer_minutes: u32,
+    /// Hide the overlay while the Xbox Game Bar overlay is open.
+    pub hide_under_game_bar: bool,
+    /// No schedule shows, peeks, reminder banners or chimes while Windows
+    /// presentation settings are on.
+    pub quiet_when_presenting: bool,
+    /// Soft tick at the turn of each minute.
+    pub tick_sound: bool,
+    /// Chime on the hour.
+    pub hourly_chime: bool,
+    /// Tick volume, 0–100.
+    pub tick_volume: u8,
+    /// Hourly chime volume, 0–100.
+    pub chime_volume: u8,
+    /// No ClockOR sounds while a fullscreen app or game is in front.
+    pub mute_in_fullscreen: bool,
+    /// WASAPI endpoint ID of the output device for ClockOR's sounds (empty = Windows default).
+    pub audio_device: String,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             position: Position::TopRight,
+            custom_pos: [10, 10],
+            snap_to_edges: true,
+            snap_grid: 0,
+            monitor_scales: Vec::new(),
+            monitor_overlays: Vec::new(),
+            cursor_reaction: CursorReaction::Off,
+            z_order: ZOrder::Topmost,
             format_24h: true,
             show_seconds: false,
+            time_format: String::new(),
+            progress: ProgressDisplay::Off,
+            date_line: DateDisplay::Off,
+            world_clocks: Vec::new(),
+            show_uptime: false,
+            show_input: false,
+            active_window: ActiveWindowDisplay::Off,
+            show_input_language: false,
+            script: String::new(),
+            template: String::new(),
+            text_file: String::new(),
+            command: String::new(),
+            command_interval_seconds: 60,
             font_size: 22,
+            font_weight: 700,
+            font_family: FontFamily::SegoeUi,
+            font_width: 5,
+            max_width: 0,
+            text_align: TextAlign::Left,
+            multi_line: false,
+            line_spacing: 2,
+            line_alignment: Vec::new(),
+            vertical: false,
             opacity: 80,
+            background_opacity: 0,
+            background_color: [0, 0, 0],
+            background_image: String::new(),
+            backdrop: Backdrop::Flat,
+            corner_style: CornerStyle::System,
+            window_border: false,
+            border_color: [96, 96, 96],
             hotkey: "Ctrl+F12".to_string(),
+            double_tap: DoubleTapKey::Off,
+            hot_corner: HotCorner::Off,
+            toggle_flash: false,
+            lock_hotkey: String::new(),
+            format_hotkey: String::new(),
+            tray_left_click: TrayAction::Toggle,
+            tray_double_click: TrayAction::Nothing,
+            tray_middle_click: TrayAction::Nothing,
+            confirm_quit: false,
             start_with_windows: false,
+            autostart_method: AutostartMethod::RunKey,
+            startup_delay_seconds: 0,
+            wait_for_shell: false,
+            start_visible: false,
+            remember_visibility: false,
+            run_elevated: false,
+            check_updates: false,
+            usage_stats: false,
+            plugins: false,
+            disabled_plugins: Vec::new(),
+            hooks: Vec::new(),
             text_style: TextStyle::default(),
+            text_renderer: TextRenderer::default(),
+            subpixel_text: true,
             text_color: [255, 255, 255],
             outline_color: [0, 0, 0],
+            color_rules: Vec::new(),
+            alert_palette: AlertPalette::default(),
+            alert_colors: STANDARD_ALERT_COLORS,
+            banner_seconds: 10,
+            snooze_minutes: 5,
+            reminder_warning_minutes: 0,
+            countdown_minutes: 10,
+            countdown_until: String::new(),
+            countdown_replaces_clock: false,
+            edge_flash: ScreenEdge::Off,
+            edge_flash_color: [255, 160, 0],
+            controller_rumble: false,
+            snooze_hotkey: String::new(),
+            reduce_motion: false,
+            zoom_hotkey: String::new(),
+            zoom_seconds: 5,
+            countdown_hotkey: String::new(),
+            debug_hotkey: String::new(),
+            daily_events: Vec::new(),
+            visibility_schedule: Vec::new(),
+            scheduled_actions: Vec::new(),
+            idle_hide_minutes: 0,
+            peek_interval_minutes: 0,
+            peek_seconds: 10,
+            play_reveal_minutes: 0,
+            low_battery_percent: 0,
+            shutdown_warning: true,
+            shutdown_timer_minutes: 0,
+            hide_under_game_bar: false,
+            quiet_when_presenting: false,
+            tick_sound: false,
+            hourly_chime: false,
+            tick_volume: 30,
+            chime_volume: 60,
+            mute_in_fullscreen: false,
+            audio_device: String::new(),
         }
     }
 }
 
-fn config_path() -> PathBuf {
-    let mut path = std::env::current_exe()
+/// Directory holding the executable, where config and state files live.
+pub fn app_dir() -> PathBuf {
+    std::env::current_exe()
         .ok()
         .and_then(|p| p.parent().map(|p| p.to_path_buf()))
-        .unwrap_or_else(|| PathBuf::from("."));
-    path.push("config.toml");
-    path
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// A path from the config file; a relative one is taken from `app_dir()`.
+pub fn user_path(path: &str) -> PathBuf {
+    let path = PathBuf::from(path.trim());
+    if path.is_absolute() {
+        path
+    } else {
+        app_dir().join(path)
+    }
+}
+
+fn config_path() -> PathBuf {
+    app_dir().join("config.toml")
+}
+
+/// Unapplied edits from the settings window, kept in case it doesn't close normally.
+fn draft_path() -> PathBuf {
+    app_dir().join("config.draft.toml")
+}
+
+/// When config.toml was last written, or `None` if it doesn't exist (yet).
+pub fn file_modified() -> Option<std::time::SystemTime> {
+    fs::metadata(config_path()).and_then(|m| m.modified()).ok()
+}
+
+/// `%ProgramData%\ClockOR\defaults.toml`, for internet cafés and shared PCs.
+fn machine_defaults_path() -> Option<PathBuf> {
+    std::env::var_os("ProgramData")
+        .map(|dir| PathBuf::from(dir).join("ClockOR").join("defaults.toml"))
 }
 
 pub const MODIFIER_OPTIONS: &[(&str, u32)] = &[
@@ -116,6 +1070,32 @@ pub const KEY_OPTIONS: &[(&str, u32)] = &[
     ("F12", VK_F12.0 as u32),
 ];
 
+/// Tried in order when the configured toggle hotkey is already taken by another app.
+pub const FALLBACK_HOTKEYS: &[&str] = &[
+    "Ctrl+F12",
+    "Ctrl+Shift+F12",
+    "Ctrl+Alt+F12",
+    "Alt+Shift+F12",
+    "Ctrl+Shift+F11",
+    "Ctrl+Alt+F11",
+    "Ctrl+Shift+F10",
+];
+
+/// The configured hotkey followed by the fallbacks, without duplicate combinations.
+pub fn hotkey_candidates(hotkey: &str) -> Vec<(String, (u32, u32))> {
+    let mut out: Vec<(String, (u32, u32))> = Vec::new();
+    let first = parse_hotkey(hotkey).map(|b| (hotkey.to_string(), b));
+    let rest = FALLBACK_HOTKEYS
+        .iter()
+        .filter_map(|h| parse_hotkey(h).map(|b| (h.to_string(), b)));
+    for (name, binding) in first.into_iter().chain(rest) {
+        if !out.iter().any(|(_, b)| *b == binding) {
+            out.push((name, binding));
+        }
+    }
+    out
+}
+
 /// Parse hotkey string like "Ctrl+F12" into (modifiers, vk_code).
 pub fn parse_hotkey(hotkey: &str) -> Option<(u32, u32)> {
     let parts: Vec<&str> = hotkey.split('+').map(str::trim).collect();
@@ -146,38 +1126,389 @@ impl Config {
             | ((self.text_color[2] as u32) << 16)
     }
 
-    /// Convert outline_color [R,G,B] to Win32 COLORREF (0x00BBGGRR)
-    pub fn outline_colorref(&self) -> u32 {
-        self.outline_color[0] as u32
-            | ((self.outline_color[1] as u32) << 8)
-            | ((self.outline_color[2] as u32) << 16)
+    /// Notice, warning and alert colors of the chosen palette.
+    pub fn alert_levels(&self) -> [[u8; 3]; 3] {
+        match self.alert_palette {
+            AlertPalette::Standard => STANDARD_ALERT_COLORS,
+            AlertPalette::ColorblindSafe => COLORBLIND_ALERT_COLORS,
+            AlertPalette::Custom => self.alert_colors,
+        }
+    }
+
+    /// Convert outline_color [R,G,B] to Win32 COLORREF (0x00BBGGRR)
+    pub fn outline_colorref(&self) -> u32 {
+        self.outline_color[0] as u32
+            | ((self.outline_color[1] as u32) << 8)
+            | ((self.outline_color[2] as u32) << 16)
+    }
+
+    /// How long a countdown started at `now` runs: until the next
+    /// `countdown_until` when that is a valid time, else `countdown_minutes`.
+    pub fn countdown_length(&self, now: NaiveDateTime) -> chrono::Duration {
+        match parse_hhmm(&self.countdown_until) {
+            Some(until) => {
+                let mut end = now.date().and_time(until);
+                if end <= now {
+                    end += chrono::Duration::days(1);
+                }
+                end - now
+            }
+            None => chrono::Duration::minutes(self.countdown_minutes as i64),
+        }
+    }
+
+    /// Size multiplier (percent) for the monitor with device name `monitor`.
+    pub fn scale_for_monitor(&self, monitor: &str) -> u32 {
+        self.monitor_scales
+            .iter()
+            .find(|m| m.monitor.eq_ignore_ascii_case(monitor))
+            .map_or(100, |m| m.scale)
+    }
+
+    /// Settings for the monitor with device name `monitor`, if it has any.
+    pub fn monitor_overlay(&self, monitor: &str) -> Option<&MonitorOverlay> {
+        self.monitor_overlays
+            .iter()
+            .find(|m| m.monitor.eq_ignore_ascii_case(monitor))
+    }
+
+    /// Whether and when the monitor with device name `monitor` gets a clock.
+    pub fn monitor_mode(&self, monitor: &str) -> MonitorMode {
+        self.monitor_overlay(monitor)
+            .map_or(MonitorMode::Follow, |m| m.mode)
+    }
+
+    /// The config as laid out on `monitor`, with that monitor's position.
+    pub fn on_monitor(&self, monitor: &str) -> Config {
+        match self.monitor_overlay(monitor) {
+            Some(MonitorOverlay {
+                position: Some(position),
+                custom_pos,
+                ..
+            }) => Config {
+                position: *position,
+                custom_pos: *custom_pos,
+                ..self.clone()
+            },
+            _ => self.clone(),
+        }
+    }
+
+    /// Where the overlay goes when shown with the foreground window on
+    /// `foreground`: there, unless that monitor is `Never`, then the first of
+    /// `monitors` (primary first) that allows it.
+    pub fn home_monitor<'a>(&self, foreground: &'a str, monitors: &'a [String]) -> &'a str {
+        if self.monitor_mode(foreground) != MonitorMode::Never {
+            return foreground;
+        }
+        monitors
+            .iter()
+            .find(|m| self.monitor_mode(m) != MonitorMode::Never)
+            .map_or(foreground, |m| m.as_str())
+    }
+
+    /// Keep a dragged spot on `monitor`: in its own settings when it has its
+    /// own position, otherwise as the shared custom position.
+    pub fn set_custom_pos(&mut self, monitor: &str, pos: [i32; 2]) {
+        let own = self
+            .monitor_overlays
+            .iter_mut()
+            .find(|m| m.monitor.eq_ignore_ascii_case(monitor) && m.position.is_some());
+        match own {
+            Some(entry) => {
+                entry.position = Some(Position::Custom);
+                entry.custom_pos = pos;
+            }
+            None => {
+                self.position = Position::Custom;
+                self.custom_pos = pos;
+            }
+        }
+    }
+
+    /// Alignment of line `index` (from 0) in the multi-line layout.
+    pub fn alignment_for_line(&self, index: usize) -> TextAlign {
+        self.line_alignment
+            .get(index)
+            .or(self.line_alignment.last())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Restore one section's fields to their defaults, leaving the others untouched.
+    pub fn reset_section(&mut self, section: SettingsSection) {
+        let d = Config::machine_defaults();
+        match section {
+            SettingsSection::Display => {
+                self.position = d.position;
+                self.custom_pos = d.custom_pos;
+                self.snap_to_edges = d.snap_to_edges;
+                self.snap_grid = d.snap_grid;
+                self.monitor_scales = d.monitor_scales;
+                self.monitor_overlays = d.monitor_overlays;
+                self.cursor_reaction = d.cursor_reaction;
+                self.z_order = d.z_order;
+                self.format_24h = d.format_24h;
+                self.show_seconds = d.show_seconds;
+                self.time_format = d.time_format;
+                self.progress = d.progress;
+                self.date_line = d.date_line;
+                self.world_clocks = d.world_clocks;
+                self.show_uptime = d.show_uptime;
+                self.show_input = d.show_input;
+                self.active_window = d.active_window;
+                self.show_input_language = d.show_input_language;
+                self.script = d.script;
+                self.template = d.template;
+                self.text_file = d.text_file;
+                self.command = d.command;
+                self.command_interval_seconds = d.command_interval_seconds;
+            }
+            SettingsSection::Appearance => {
+                self.font_size = d.font_size;
+                self.font_weight = d.font_weight;
+                self.font_family = d.font_family;
+                self.font_width = d.font_width;
+                self.max_width = d.max_width;
+                self.text_align = d.text_align;
+                self.multi_line = d.multi_line;
+                self.line_spacing = d.line_spacing;
+                self.line_alignment = d.line_alignment;
+                self.vertical = d.vertical;
+                self.text_style = d.text_style;
+                self.text_renderer = d.text_renderer;
+                self.subpixel_text = d.subpixel_text;
+                self.text_color = d.text_color;
+                self.outline_color = d.outline_color;
+                self.color_rules = d.color_rules;
+                self.alert_palette = d.alert_palette;
+                self.alert_colors = d.alert_colors;
+                self.opacity = d.opacity;
+                self.background_opacity = d.background_opacity;
+                self.background_color = d.background_color;
+                self.background_image = d.background_image;
+                self.backdrop = d.backdrop;
+                self.corner_style = d.corner_style;
+                self.window_border = d.window_border;
+                self.border_color = d.border_color;
+            }
+            SettingsSection::Reminders => {
+                self.daily_events = d.daily_events;
+                self.banner_seconds = d.banner_seconds;
+                self.snooze_minutes = d.snooze_minutes;
+                self.reminder_warning_minutes = d.reminder_warning_minutes;
+                self.countdown_minutes = d.countdown_minutes;
+                self.countdown_until = d.countdown_until;
+                self.countdown_replaces_clock = d.countdown_replaces_clock;
+                self.edge_flash = d.edge_flash;
+                self.edge_flash_color = d.edge_flash_color;
+                self.controller_rumble = d.controller_rumble;
+            }
+            SettingsSection::Sound => {
+                self.tick_sound = d.tick_sound;
+                self.hourly_chime = d.hourly_chime;
+                self.tick_volume = d.tick_volume;
+                self.chime_volume = d.chime_volume;
+                self.mute_in_fullscreen = d.mute_in_fullscreen;
+                self.audio_device = d.audio_device;
+            }
+            SettingsSection::Automation => {
+                self.visibility_schedule = d.visibility_schedule;
+                self.scheduled_actions = d.scheduled_actions;
+                self.idle_hide_minutes = d.idle_hide_minutes;
+                self.peek_interval_minutes = d.peek_interval_minutes;
+                self.peek_seconds = d.peek_seconds;
+                self.play_reveal_minutes = d.play_reveal_minutes;
+                self.low_battery_percent = d.low_battery_percent;
+                self.shutdown_warning = d.shutdown_warning;
+                self.shutdown_timer_minutes = d.shutdown_timer_minutes;
+                self.hide_under_game_bar = d.hide_under_game_bar;
+                self.quiet_when_presenting = d.quiet_when_presenting;
+            }
+            SettingsSection::Tray => {
+                self.tray_left_click = d.tray_left_click;
+                self.tray_double_click = d.tray_double_click;
+                self.tray_middle_click = d.tray_middle_click;
+                self.confirm_quit = d.confirm_quit;
+            }
+            SettingsSection::System => {
+                self.hotkey = d.hotkey;
+                self.double_tap = d.double_tap;
+                self.hot_corner = d.hot_corner;
+                self.lock_hotkey = d.lock_hotkey;
+                self.format_hotkey = d.format_hotkey;
+                self.snooze_hotkey = d.snooze_hotkey;
+                self.zoom_hotkey = d.zoom_hotkey;
+                self.zoom_seconds = d.zoom_seconds;
+                self.countdown_hotkey = d.countdown_hotkey;
+                self.debug_hotkey = d.debug_hotkey;
+                self.toggle_flash = d.toggle_flash;
+                self.reduce_motion = d.reduce_motion;
+                self.start_with_windows = d.start_with_windows;
+                self.autostart_method = d.autostart_method;
+                self.startup_delay_seconds = d.startup_delay_seconds;
+                self.wait_for_shell = d.wait_for_shell;
+                self.start_visible = d.start_visible;
+                self.remember_visibility = d.remember_visibility;
+                self.run_elevated = d.run_elevated;
+                self.check_updates = d.check_updates;
+                self.usage_stats = d.usage_stats;
+                self.plugins = d.plugins;
+                self.disabled_plugins = d.disabled_plugins;
+                self.hooks = d.hooks;
+            }
+        }
+    }
+
+    pub fn parsed_hotkey(&self) -> (u32, u32) {
+        parse_hotkey(&self.hotkey).unwrap_or((MOD_CONTROL.0, VK_F12.0 as u32))
+    }
+
+    /// Load config.toml with administrator policies applied on top.
+    pub fn load() -> Self {
+        let mut config = Self::load_from(&config_path());
+        crate::policy::current().apply(&mut config);
+        config
+    }
+
+    pub fn load_from(path: &std::path::Path) -> Self {
+        Self::load_layered(path, Self::machine_defaults())
+    }
+
+    /// Built-in defaults with the machine-wide defaults file, if any, on top.
+    pub fn machine_defaults() -> Self {
+        let text = machine_defaults_path().and_then(|p| fs::read_to_string(p).ok());
+        Self::defaults_from(text.as_deref())
+    }
+
+    /// Apply each key of a defaults file on its own, so one bad value doesn't
+    /// throw away the rest.
+    fn defaults_from(text: Option<&str>) -> Self {
+        let mut config = Config::default();
+        let Some(overrides) = text.and_then(|t| t.parse::<toml::Table>().ok()) else {
+            return config;
+        };
+        let Ok(mut table) = toml::Table::try_from(&config) else {
+            return config;
+        };
+        for (key, value) in overrides {
+            let mut candidate = table.clone();
+            candidate.insert(key, value);
+            if candidate.clone().try_into::<Config>().is_ok() {
+                table = candidate;
+            }
+        }
+        if let Ok(merged) = table.try_into::<Config>() {
+            config = merged;
+        }
+        config.sanitize();
+        config
+    }
+
+    /// Load config.toml layered over `defaults`: keys the file doesn't set come
+    /// from `defaults`, and a file that doesn't parse yields `defaults`. A missing
+    /// file is created from `defaults`, which is how new users get seeded.
+    fn load_layered(path: &std::path::Path, defaults: Config) -> Self {
+        let file_exists = path.exists();
+        let layered = fs::read_to_string(path).ok().and_then(|content| {
+            let user = content.parse::<toml::Table>().ok()?;
+            let mut table = toml::Table::try_from(&defaults).ok()?;
+            table.extend(user);
+            table.try_into::<Config>().ok()
+        });
+        let mut config = layered.unwrap_or(defaults);
+        config.sanitize();
+        if !file_exists {
+            let _ = config.save_to(path);
+        }
+        config
+    }
+
+    /// Change one key from text, as in `--set key=value`. The value is read as
+    /// TOML (`30`, `true`, `[255, 0, 0]`), falling back to a plain string so
+    /// `position=bottom-left` needs no quotes. The result is clamped like a loaded file.
+    pub fn set_value(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let mut table = toml::Table::try_from(&*self).map_err(|e| e.to_string())?;
+        if !table.contains_key(key) {
+            return Err(format!("unknown setting \"{key}\""));
+        }
+        let parsed = format!("v = {value}")
+            .parse::<toml::Table>()
+            .ok()
+            .and_then(|mut t| t.remove("v"))
+            .unwrap_or_else(|| toml::Value::String(value.to_string()));
+        table.insert(key.to_string(), parsed);
+        let mut updated: Config = table
+            .try_into()
+            .map_err(|_| format!("invalid value for \"{key}\": {value}"))?;
+        updated.sanitize();
+        *self = updated;
+        Ok(())
+    }
+
+    /// Clamp numeric settings into their supported ranges.
+    pub fn sanitize(&mut self) {
+        self.opacity = self.opacity.clamp(25, 100);
+        self.background_opacity = self.background_opacity.min(100);
+        self.font_size = self.font_size.clamp(10, 60);
+        self.font_weight = self.font_weight.clamp(100, 900) / 100 * 100;
+        self.font_width = self.font_width.clamp(1, 9);
+        self.line_spacing = self.line_spacing.min(40);
+        self.snap_grid = self.snap_grid.min(200);
+        for monitor in &mut self.monitor_scales {
+            monitor.scale = monitor.scale.clamp(50, 400);
+        }
+        if self.max_width > 0 {
+            self.max_width = self.max_width.clamp(100, 3840);
+        }
+        self.banner_seconds = self.banner_seconds.clamp(3, 60);
+        self.snooze_minutes = self.snooze_minutes.clamp(1, 60);
+        self.reminder_warning_minutes = self.reminder_warning_minutes.min(120);
+        self.countdown_minutes = self.countdown_minutes.clamp(1, 1440);
+        self.idle_hide_minutes = self.idle_hide_minutes.min(120);
+        self.peek_interval_minutes = self.peek_interval_minutes.min(720);
+        self.peek_seconds = self.peek_seconds.clamp(3, 60);
+        self.play_reveal_minutes = self.play_reveal_minutes.min(600);
+        self.low_battery_percent = self.low_battery_percent.min(50);
+        self.shutdown_timer_minutes = self.shutdown_timer_minutes.min(240);
+        self.zoom_seconds = self.zoom_seconds.clamp(2, 30);
+        self.command_interval_seconds = self.command_interval_seconds.clamp(5, 86_400);
+        for hook in &mut self.hooks {
+            hook.hours = hook.hours.clamp(1, 24);
+        }
+        self.startup_delay_seconds = self.startup_delay_seconds.min(300);
+        self.tick_volume = self.tick_volume.min(100);
+        self.chime_volume = self.chime_volume.min(100);
+    }
+
+    /// Save to config.toml. Keys locked by policy keep the values already in
+    /// the file, so the user's own settings survive the policy.
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = config_path();
+        let user = Self::load_from(&path);
+        crate::policy::current().unapply(self, &user).save_to(&path)
     }
 
-    pub fn parsed_hotkey(&self) -> (u32, u32) {
-        parse_hotkey(&self.hotkey).unwrap_or((MOD_CONTROL.0, VK_F12.0 as u32))
+    /// Keep unapplied settings edits so a crash doesn't lose them.
+    pub fn save_draft(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.save_to(&draft_path())
     }
 
-    pub fn load() -> Self {
-        Self::load_from(&config_path())
+    /// Edits left behind by a settings window that didn't close normally, if any.
+    pub fn load_draft() -> Option<Config> {
+        Self::load_draft_from(&draft_path())
     }
 
-    pub fn load_from(path: &std::path::Path) -> Self {
-        let file_exists = path.exists();
-        let mut config = if let Ok(content) = fs::read_to_string(path) {
-            toml::from_str(&content).unwrap_or_default()
-        } else {
-            Config::default()
-        };
-        config.opacity = config.opacity.clamp(25, 100);
-        config.font_size = config.font_size.clamp(10, 60);
-        if !file_exists {
-            let _ = config.save_to(path);
-        }
-        config
+    fn load_draft_from(path: &std::path::Path) -> Option<Config> {
+        let content = fs::read_to_string(path).ok()?;
+        let mut config: Config = toml::from_str(&content).ok()?;
+        config.sanitize();
+        Some(config)
     }
 
-    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        self.save_to(&config_path())
+    /// Forget the draft, once its edits were applied or deliberately dropped.
+    pub fn discard_draft() {
+        let _ = fs::remove_file(draft_path());
     }
 
     pub fn save_to(&self, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
@@ -191,6 +1522,7 @@ impl Config {
 }
 
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
 
@@ -245,15 +1577,108 @@ mod tests {
     fn default_config_values() {
         let cfg = Config::default();
         assert_eq!(cfg.position, Position::TopRight);
+        assert_eq!(cfg.cursor_reaction, CursorReaction::Off);
+        assert_eq!(cfg.z_order, ZOrder::Topmost);
         assert!(cfg.format_24h);
         assert!(!cfg.show_seconds);
+        assert!(cfg.time_format.is_empty());
+        assert_eq!(cfg.progress, ProgressDisplay::Off);
+        assert_eq!(cfg.date_line, DateDisplay::Off);
+        assert!(cfg.world_clocks.is_empty());
+        assert!(!cfg.show_uptime);
+        assert!(!cfg.show_input);
+        assert_eq!(cfg.active_window, ActiveWindowDisplay::Off);
+        assert!(!cfg.show_input_language);
+        assert!(cfg.script.is_empty());
+        assert!(cfg.template.is_empty());
+        assert!(cfg.text_file.is_empty());
+        assert!(cfg.command.is_empty());
+        assert_eq!(cfg.command_interval_seconds, 60);
         assert_eq!(cfg.font_size, 22);
+        assert_eq!(cfg.font_weight, 700);
+        assert_eq!(cfg.font_family, FontFamily::SegoeUi);
+        assert_eq!(cfg.font_width, 5);
         assert_eq!(cfg.opacity, 80);
+        assert_eq!(cfg.max_width, 0);
+        assert_eq!(cfg.text_align, TextAlign::Left);
+        assert!(!cfg.multi_line);
+        assert_eq!(cfg.line_spacing, 2);
+        assert!(cfg.line_alignment.is_empty());
+        assert!(!cfg.vertical);
+        assert_eq!(cfg.background_opacity, 0);
+        assert_eq!(cfg.background_color, [0, 0, 0]);
+        assert!(cfg.background_image.is_empty());
+        assert_eq!(cfg.backdrop, Backdrop::Flat);
+        assert_eq!(cfg.corner_style, CornerStyle::System);
+        assert!(!cfg.window_border);
+        assert_eq!(cfg.border_color, [96, 96, 96]);
         assert_eq!(cfg.hotkey, "Ctrl+F12");
+        assert!(!cfg.toggle_flash);
+        assert!(cfg.lock_hotkey.is_empty());
+        assert!(cfg.format_hotkey.is_empty());
+        assert_eq!(cfg.double_tap, DoubleTapKey::Off);
+        assert_eq!(cfg.hot_corner, HotCorner::Off);
+        assert_eq!(cfg.custom_pos, [10, 10]);
+        assert!(cfg.snap_to_edges);
+        assert_eq!(cfg.snap_grid, 0);
+        assert!(cfg.monitor_scales.is_empty());
+        assert!(cfg.monitor_overlays.is_empty());
+        assert_eq!(cfg.tray_left_click, TrayAction::Toggle);
+        assert_eq!(cfg.tray_double_click, TrayAction::Nothing);
+        assert_eq!(cfg.tray_middle_click, TrayAction::Nothing);
+        assert!(!cfg.confirm_quit);
         assert!(!cfg.start_with_windows);
+        assert_eq!(cfg.autostart_method, AutostartMethod::RunKey);
+        assert_eq!(cfg.startup_delay_seconds, 0);
+        assert!(!cfg.wait_for_shell);
+        assert!(!cfg.start_visible);
+        assert!(!cfg.remember_visibility);
+        assert!(!cfg.run_elevated);
+        assert!(!cfg.check_updates);
+        assert!(!cfg.usage_stats);
+        assert!(!cfg.plugins);
+        assert!(cfg.disabled_plugins.is_empty());
+        assert!(cfg.hooks.is_empty());
         assert_eq!(cfg.text_style, TextStyle::Outline);
+        assert_eq!(cfg.text_renderer, TextRenderer::DirectWrite);
+        assert!(cfg.subpixel_text);
         assert_eq!(cfg.text_color, [255, 255, 255]);
         assert_eq!(cfg.outline_color, [0, 0, 0]);
+        assert!(cfg.color_rules.is_empty());
+        assert_eq!(cfg.alert_palette, AlertPalette::Standard);
+        assert_eq!(cfg.alert_colors, STANDARD_ALERT_COLORS);
+        assert_eq!(cfg.banner_seconds, 10);
+        assert_eq!(cfg.snooze_minutes, 5);
+        assert_eq!(cfg.reminder_warning_minutes, 0);
+        assert_eq!(cfg.countdown_minutes, 10);
+        assert!(cfg.countdown_until.is_empty());
+        assert!(!cfg.countdown_replaces_clock);
+        assert_eq!(cfg.edge_flash, ScreenEdge::Off);
+        assert_eq!(cfg.edge_flash_color, [255, 160, 0]);
+        assert!(!cfg.controller_rumble);
+        assert!(cfg.snooze_hotkey.is_empty());
+        assert!(!cfg.reduce_motion);
+        assert!(cfg.zoom_hotkey.is_empty());
+        assert_eq!(cfg.zoom_seconds, 5);
+        assert!(cfg.countdown_hotkey.is_empty());
+        assert!(cfg.debug_hotkey.is_empty());
+        assert!(cfg.daily_events.is_empty());
+        assert!(cfg.scheduled_actions.is_empty());
+        assert_eq!(cfg.idle_hide_minutes, 0);
+        assert_eq!(cfg.peek_interval_minutes, 0);
+        assert_eq!(cfg.peek_seconds, 10);
+        assert_eq!(cfg.play_reveal_minutes, 0);
+        assert_eq!(cfg.low_battery_percent, 0);
+        assert!(cfg.shutdown_warning);
+        assert_eq!(cfg.shutdown_timer_minutes, 0);
+        assert!(!cfg.hide_under_game_bar);
+        assert!(!cfg.quiet_when_presenting);
+        assert!(!cfg.tick_sound);
+        assert!(!cfg.hourly_chime);
+        assert_eq!(cfg.tick_volume, 30);
+        assert_eq!(cfg.chime_volume, 60);
+        assert!(!cfg.mute_in_fullscreen);
+        assert!(cfg.audio_device.is_empty());
     }
 
     // --- color fields ---
@@ -280,6 +1705,82 @@ mod tests {
         assert_eq!(cfg.text_colorref(), 0x00FF0000);
     }
 
+    #[test]
+    fn monitor_scale_by_device_name() {
+        let mut cfg = Config {
+            monitor_scales: vec![MonitorScale {
+                monitor: r"\\.\DISPLAY2".to_string(),
+                scale: 1000,
+            }],
+            ..Default::default()
+        };
+        cfg.sanitize();
+        assert_eq!(cfg.scale_for_monitor(r"\\.\display2"), 400);
+        assert_eq!(cfg.scale_for_monitor(r"\\.\DISPLAY1"), 100);
+    }
+
+    #[test]
+    fn monitor_overlays_by_device_name() {
+        let cfg = Config {
+            position: Position::TopRight,
+            monitor_overlays: vec![
+                MonitorOverlay {
+                    monitor: r"\\.\DISPLAY1".to_string(),
+                    mode: MonitorMode::Never,
+                    ..Default::default()
+                },
+                MonitorOverlay {
+                    monitor: r"\\.\DISPLAY2".to_string(),
+                    mode: MonitorMode::Always,
+                    position: Some(Position::BottomLeft),
+                    custom_pos: [0, 0],
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(cfg.monitor_mode(r"\\.\display2"), MonitorMode::Always);
+        assert_eq!(cfg.monitor_mode(r"\\.\DISPLAY3"), MonitorMode::Follow);
+        assert_eq!(
+            cfg.on_monitor(r"\\.\DISPLAY2").position,
+            Position::BottomLeft
+        );
+        assert_eq!(cfg.on_monitor(r"\\.\DISPLAY1").position, Position::TopRight);
+
+        let monitors = [r"\\.\DISPLAY1", r"\\.\DISPLAY2", r"\\.\DISPLAY3"].map(String::from);
+        assert_eq!(cfg.home_monitor(&monitors[2], &monitors), r"\\.\DISPLAY3");
+        assert_eq!(cfg.home_monitor(&monitors[0], &monitors), r"\\.\DISPLAY2");
+    }
+
+    #[test]
+    fn drags_are_saved_where_the_position_comes_from() {
+        let mut cfg = Config {
+            monitor_overlays: vec![MonitorOverlay {
+                monitor: r"\\.\DISPLAY2".to_string(),
+                position: Some(Position::TopLeft),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        cfg.set_custom_pos(r"\\.\DISPLAY2", [40, 50]);
+        assert_eq!(cfg.monitor_overlays[0].position, Some(Position::Custom));
+        assert_eq!(cfg.monitor_overlays[0].custom_pos, [40, 50]);
+        assert_eq!(cfg.position, Position::TopRight);
+
+        cfg.set_custom_pos(r"\\.\DISPLAY1", [7, 8]);
+        assert_eq!(cfg.position, Position::Custom);
+        assert_eq!(cfg.custom_pos, [7, 8]);
+    }
+
+    #[test]
+    fn last_line_alignment_carries_over() {
+        let mut cfg = Config::default();
+        assert_eq!(cfg.alignment_for_line(2), TextAlign::Left);
+        cfg.line_alignment = vec![TextAlign::Center, TextAlign::Right];
+        assert_eq!(cfg.alignment_for_line(0), TextAlign::Center);
+        assert_eq!(cfg.alignment_for_line(1), TextAlign::Right);
+        assert_eq!(cfg.alignment_for_line(5), TextAlign::Right);
+    }
+
     #[test]
     fn color_roundtrip() {
         let dir = std::env::temp_dir().join("clockor_test_color_rt");
@@ -322,6 +1823,201 @@ mod tests {
         assert_eq!(k, VK_F12.0 as u32);
     }
 
+    #[test]
+    fn hotkey_candidates_start_with_configured() {
+        let c = hotkey_candidates("Alt+F1");
+        assert_eq!(c[0].0, "Alt+F1");
+        assert_eq!(c[1].0, "Ctrl+F12");
+        assert_eq!(c.len(), FALLBACK_HOTKEYS.len() + 1);
+    }
+
+    #[test]
+    fn hotkey_candidates_skip_duplicates() {
+        let c = hotkey_candidates("ctrl+shift+f12");
+        assert_eq!(c[0].0, "ctrl+shift+f12");
+        assert_eq!(c.len(), FALLBACK_HOTKEYS.len());
+        assert!(!c[1..].iter().any(|(name, _)| name == "Ctrl+Shift+F12"));
+    }
+
+    #[test]
+    fn hotkey_candidates_invalid_uses_fallbacks() {
+        let c = hotkey_candidates("garbage");
+        assert_eq!(c[0].0, "Ctrl+F12");
+        assert_eq!(c.len(), FALLBACK_HOTKEYS.len());
+    }
+
+    // --- per-section reset ---
+
+    /// Every field differs from the default. Deliberately no `..Default::default()`,
+    /// so a new field must be given a value here (and a section in `reset_section`).
+    fn all_changed() -> Config {
+        Config {
+            position: Position::BottomLeft,
+            custom_pos: [1, 2],
+            snap_to_edges: false,
+            snap_grid: 20,
+            monitor_scales: vec![MonitorScale {
+                monitor: r"\\.\DISPLAY2".to_string(),
+                scale: 200,
+            }],
+            monitor_overlays: vec![MonitorOverlay {
+                monitor: r"\\.\DISPLAY2".to_string(),
+                mode: MonitorMode::Always,
+                position: Some(Position::BottomLeft),
+                custom_pos: [5, 5],
+            }],
+            cursor_reaction: CursorReaction::Dodge,
+            z_order: ZOrder::Watchdog,
+            format_24h: false,
+            show_seconds: true,
+            time_format: "%H:%M:%S.%3f %a".to_string(),
+            progress: ProgressDisplay::Month,
+            date_line: DateDisplay::Localized,
+            world_clocks: vec![WorldClock {
+                label: "NY".to_string(),
+                zone: "America/New_York".to_string(),
+            }],
+            show_uptime: true,
+            show_input: true,
+            active_window: ActiveWindowDisplay::Title,
+            show_input_language: true,
+            script: "clock.rhai".to_string(),
+            template: "{time}  {cpu}".to_string(),
+            text_file: "nowplaying.txt".to_string(),
+            command: "echo hi".to_string(),
+            command_interval_seconds: 300,
+            font_size: 40,
+            max_width: 300,
+            text_align: TextAlign::Right,
+            multi_line: true,
+            line_spacing: 6,
+            line_alignment: vec![TextAlign::Center],
+            vertical: true,
+            font_weight: 400,
+            font_family: FontFamily::Bahnschrift,
+            font_width: 3,
+            opacity: 50,
+            background_opacity: 30,
+            background_color: [9, 9, 9],
+            background_image: "logo.png".to_string(),
+            backdrop: Backdrop::Acrylic,
+            corner_style: CornerStyle::Round,
+            window_border: true,
+            border_color: [1, 1, 1],
+            hotkey: "Alt+F1".to_string(),
+            double_tap: DoubleTapKey::Ctrl,
+            hot_corner: HotCorner::BottomLeft,
+            toggle_flash: true,
+            lock_hotkey: "Ctrl+F1".to_string(),
+            format_hotkey: "Ctrl+F2".to_string(),
+            tray_left_click: TrayAction::Settings,
+            tray_double_click: TrayAction::Toggle,
+            tray_middle_click: TrayAction::StreamTimer,
+            confirm_quit: true,
+            start_with_windows: true,
+            autostart_method: AutostartMethod::StartupFolder,
+            startup_delay_seconds: 30,
+            wait_for_shell: true,
+            start_visible: true,
+            remember_visibility: true,
+            run_elevated: true,
+            check_updates: true,
+            usage_stats: true,
+            plugins: true,
+            disabled_plugins: vec!["sensors.dll".to_string()],
+            hooks: vec![Hook {
+                event: HookEvent::PcOnHours,
+                hours: 3,
+                action: "http://localhost/lights".to_string(),
+            }],
+            text_style: TextStyle::Shadow,
+            text_renderer: TextRenderer::Gdi,
+            subpixel_text: false,
+            text_color: [1, 2, 3],
+            outline_color: [4, 5, 6],
+            color_rules: vec![ColorRule {
+                background_color: Some([7, 8, 9]),
+                ..ColorRule::default()
+            }],
+            alert_palette: AlertPalette::Custom,
+            alert_colors: [[1, 1, 1], [2, 2, 2], [3, 3, 3]],
+            banner_seconds: 30,
+            snooze_minutes: 15,
+            reminder_warning_minutes: 10,
+            countdown_minutes: 25,
+            countdown_until: "18:00".to_string(),
+            countdown_replaces_clock: true,
+            edge_flash: ScreenEdge::Left,
+            edge_flash_color: [0, 200, 255],
+            controller_rumble: true,
+            snooze_hotkey: "Ctrl+F3".to_string(),
+            reduce_motion: true,
+            zoom_hotkey: "Ctrl+F4".to_string(),
+            zoom_seconds: 10,
+            countdown_hotkey: "Ctrl+F5".to_string(),
+            debug_hotkey: "Ctrl+Alt+F12".to_string(),
+            daily_events: vec![DailyEvent::default()],
+            visibility_schedule: vec![ScheduleRange::default()],
+            scheduled_actions: vec![ScheduledAction {
+                action: ActionKind::ApplyTheme,
+                target: "Night".to_string(),
+                ..Default::default()
+            }],
+            idle_hide_minutes: 5,
+            peek_interval_minutes: 60,
+            peek_seconds: 20,
+            play_reveal_minutes: 90,
+            low_battery_percent: 15,
+            shutdown_warning: false,
+            shutdown_timer_minutes: 10,
+            hide_under_game_bar: true,
+            quiet_when_presenting: true,
+            tick_sound: true,
+            hourly_chime: true,
+            tick_volume: 10,
+            chime_volume: 90,
+            mute_in_fullscreen: true,
+            audio_device: "{0.0.0.00000000}.{8f1c6c2e-0000-0000-0000-000000000000}".to_string(),
+        }
+    }
+
+    #[test]
+    fn alert_levels_follow_the_palette() {
+        let mut cfg = Config {
+            alert_colors: [[1, 1, 1], [2, 2, 2], [3, 3, 3]],
+            ..Default::default()
+        };
+        assert_eq!(cfg.alert_levels(), STANDARD_ALERT_COLORS);
+        cfg.alert_palette = AlertPalette::ColorblindSafe;
+        assert_eq!(cfg.alert_levels(), COLORBLIND_ALERT_COLORS);
+        cfg.alert_palette = AlertPalette::Custom;
+        assert_eq!(cfg.alert_levels()[2], [3, 3, 3]);
+    }
+
+    #[test]
+    fn reset_section_leaves_others() {
+        let mut cfg = all_changed();
+        cfg.reset_section(SettingsSection::Appearance);
+        assert_eq!(cfg.font_size, 22);
+        assert_eq!(cfg.text_color, [255, 255, 255]);
+        assert_eq!(cfg.position, Position::BottomLeft);
+        assert_eq!(cfg.hotkey, "Alt+F1");
+
+        cfg.reset_section(SettingsSection::System);
+        assert_eq!(cfg.hotkey, "Ctrl+F12");
+        assert!(cfg.lock_hotkey.is_empty());
+        assert!(!cfg.format_24h);
+    }
+
+    #[test]
+    fn resetting_every_section_restores_defaults() {
+        let mut cfg = all_changed();
+        for section in SettingsSection::ALL {
+            cfg.reset_section(section);
+        }
+        assert_eq!(cfg, Config::default());
+    }
+
     // --- legacy font_size string deserialization ---
 
     #[test]
@@ -384,6 +2080,185 @@ mod tests {
         let _ = fs::remove_dir_all(&dir);
     }
 
+    #[test]
+    fn font_weight_clamped_to_hundreds() {
+        let dir = std::env::temp_dir().join("clockor_test_font_weight");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        fs::write(&path, "font_weight = 950\n").unwrap();
+        assert_eq!(Config::load_from(&path).font_weight, 900);
+        fs::write(&path, "font_weight = 450\n").unwrap();
+        assert_eq!(Config::load_from(&path).font_weight, 400);
+        fs::write(&path, "font_weight = 0\n").unwrap();
+        assert_eq!(Config::load_from(&path).font_weight, 100);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn font_width_clamped_to_stretches() {
+        let dir = std::env::temp_dir().join("clockor_test_font_width");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        fs::write(&path, "font_family = \"bahnschrift\"\nfont_width = 0\n").unwrap();
+        let cfg = Config::load_from(&path);
+        assert_eq!(cfg.font_family, FontFamily::Bahnschrift);
+        assert_eq!(cfg.font_width, 1);
+        fs::write(&path, "font_width = 12\n").unwrap();
+        assert_eq!(Config::load_from(&path).font_width, 9);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn max_width_clamped_unless_unlimited() {
+        let dir = std::env::temp_dir().join("clockor_test_max_width");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+
+        fs::write(&path, "max_width = 20\n").unwrap();
+        assert_eq!(Config::load_from(&path).max_width, 100);
+        fs::write(&path, "max_width = 0\n").unwrap();
+        assert_eq!(Config::load_from(&path).max_width, 0);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn background_opacity_roundtrip_and_clamp() {
+        let dir = std::env::temp_dir().join("clockor_test_bg_opacity");
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("config.toml");
+
+        let cfg = Config {
+            opacity: 100,
+            background_opacity: 5,
+            background_color: [20, 30, 40],
+            ..Default::default()
+        };
+        cfg.save_to(&path).unwrap();
+        let loaded = Config::load_from(&path);
+        assert_eq!(loaded.opacity, 100);
+        assert_eq!(loaded.background_opacity, 5);
+        assert_eq!(loaded.background_color, [20, 30, 40]);
+
+        fs::write(&path, "background_opacity = 250\n").unwrap();
+        assert_eq!(Config::load_from(&path).background_opacity, 100);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn double_tap_roundtrip() {
+        let dir = std::env::temp_dir().join("clockor_test_double_tap");
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("config.toml");
+
+        for key in [DoubleTapKey::Ctrl, DoubleTapKey::Shift, DoubleTapKey::Alt] {
+            let cfg = Config {
+                double_tap: key,
+                ..Default::default()
+            };
+            cfg.save_to(&path).unwrap();
+            assert_eq!(Config::load_from(&path).double_tap, key);
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn tray_actions_roundtrip() {
+        let dir = std::env::temp_dir().join("clockor_test_tray_actions");
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("config.toml");
+
+        let cfg = Config {
+            tray_left_click: TrayAction::Nothing,
+            tray_double_click: TrayAction::Settings,
+            tray_middle_click: TrayAction::StreamTimer,
+            ..Default::default()
+        };
+        cfg.save_to(&path).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.contains("tray_middle_click = \"stream-timer\""));
+        assert_eq!(Config::load_from(&path), cfg);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn autostart_method_roundtrip() {
+        let dir = std::env::temp_dir().join("clockor_test_autostart_method");
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("config.toml");
+
+        // Configs from before the method existed keep using the Run key
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "start_with_windows = true\n").unwrap();
+        let cfg = Config::load_from(&path);
+        assert!(cfg.start_with_windows);
+        assert_eq!(cfg.autostart_method, AutostartMethod::RunKey);
+
+        let cfg = Config {
+            autostart_method: AutostartMethod::ScheduledTask,
+            ..cfg
+        };
+        cfg.save_to(&path).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.contains("autostart_method = \"scheduled-task\""));
+        assert_eq!(Config::load_from(&path), cfg);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn machine_defaults_seed_new_configs() {
+        let dir = std::env::temp_dir().join("clockor_test_machine_defaults");
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("config.toml");
+
+        let defaults = Config::defaults_from(Some(
+            "font_size = 40\nposition = \"middle\"\nshow_seconds = true\n",
+        ));
+        // A bad value is skipped without losing the others
+        assert_eq!(defaults.font_size, 40);
+        assert_eq!(defaults.position, Config::default().position);
+        assert!(defaults.show_seconds);
+
+        // Missing config.toml: written out from the machine defaults
+        let cfg = Config::load_layered(&path, defaults.clone());
+        assert_eq!(cfg, defaults);
+        assert_eq!(Config::load_from(&path).font_size, 40);
+
+        // Keys the user's file sets win; the rest come from the machine defaults
+        fs::write(&path, "font_size = 18\n").unwrap();
+        let cfg = Config::load_layered(&path, defaults.clone());
+        assert_eq!(cfg.font_size, 18);
+        assert!(cfg.show_seconds);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unreadable_machine_defaults_ignored() {
+        assert_eq!(Config::defaults_from(None), Config::default());
+        assert_eq!(
+            Config::defaults_from(Some("not = [valid")),
+            Config::default()
+        );
+    }
+
+    #[test]
+    fn outline_rings_the_text() {
+        let ring = TextStyle::Outline.offsets(false);
+        assert_eq!(ring.len(), 8);
+        assert!(!ring.contains(&(0, 0)));
+        assert_eq!(TextStyle::Outline.offsets(true).len(), 24);
+        assert_eq!(TextStyle::Shadow.offsets(false), vec![(2, 2)]);
+        assert!(TextStyle::None.offsets(true).is_empty());
+    }
+
     // --- TextStyle round-trip ---
 
     #[test]
@@ -493,4 +2368,288 @@ mod tests {
 
         let _ = fs::remove_dir_all(&dir);
     }
+
+    // --- Position::opposite ---
+
+    #[test]
+    fn opposite_corners() {
+        assert_eq!(Position::TopRight.opposite(), Position::BottomLeft);
+        assert_eq!(Position::TopLeft.opposite(), Position::BottomRight);
+        assert_eq!(Position::Custom.opposite(), Position::Custom);
+        for pos in [
+            Position::TopRight,
+            Position::TopLeft,
+            Position::BottomRight,
+            Position::BottomLeft,
+        ] {
+            assert_eq!(pos.opposite().opposite(), pos);
+        }
+    }
+
+    #[test]
+    fn draft_roundtrip() {
+        let dir = std::env::temp_dir().join("clockor_test_draft");
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("config.draft.toml");
+        assert_eq!(Config::load_draft_from(&path), None);
+
+        let cfg = Config {
+            font_size: 44,
+            snap_grid: 500,
+            ..Default::default()
+        };
+        cfg.save_to(&path).unwrap();
+        let loaded = Config::load_draft_from(&path).unwrap();
+        assert_eq!(loaded.font_size, 44);
+        assert_eq!(loaded.snap_grid, 200);
+
+        // A draft that doesn't parse is no draft, and isn't replaced by defaults
+        fs::write(&path, "font_size = \"huge\"\n").unwrap();
+        assert_eq!(Config::load_draft_from(&path), None);
+        assert!(path.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn time_format_validation() {
+        assert!(valid_time_format("%H:%M:%S.%3f %a"));
+        assert!(valid_time_format("%-I時%M分"));
+        assert!(!valid_time_format(""));
+        assert!(!valid_time_format("%H:%Q"));
+        assert!(!valid_time_format("%"));
+    }
+
+    #[test]
+    fn countdown_runs_for_its_length_or_to_its_time() {
+        let now = chrono::NaiveDate::from_ymd_opt(2026, 10, 16)
+            .unwrap()
+            .and_hms_opt(17, 30, 0)
+            .unwrap();
+        let mut cfg = Config {
+            countdown_minutes: 25,
+            ..Config::default()
+        };
+        assert_eq!(cfg.countdown_length(now), chrono::Duration::minutes(25));
+        cfg.countdown_until = "18:00".to_string();
+        assert_eq!(cfg.countdown_length(now), chrono::Duration::minutes(30));
+        // Already past today: tomorrow's
+        cfg.countdown_until = "17:00".to_string();
+        assert_eq!(
+            cfg.countdown_length(now),
+            chrono::Duration::minutes(23 * 60 + 30)
+        );
+        cfg.countdown_until = "soon".to_string();
+        assert_eq!(cfg.countdown_length(now), chrono::Duration::minutes(25));
+    }
+
+    #[test]
+    fn world_clock_zones() {
+        let clock = |zone: &str| WorldClock {
+            label: String::new(),
+            zone: zone.to_string(),
+        };
+        assert_eq!(clock(" Asia/Tokyo ").tz(), Some(chrono_tz::Asia::Tokyo));
+        assert_eq!(clock("UTC").tz(), Some(chrono_tz::UTC));
+        assert_eq!(WorldClock::default().tz(), Some(chrono_tz::UTC));
+        // Abbreviations are ambiguous, so only database names count
+        assert_eq!(clock("JST").tz(), None);
+        assert_eq!(clock("").tz(), None);
+    }
+
+    #[test]
+    fn custom_position_roundtrip() {
+        let dir = std::env::temp_dir().join("clockor_test_custom_pos");
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("config.toml");
+
+        let cfg = Config {
+            position: Position::Custom,
+            custom_pos: [640, 32],
+            lock_hotkey: "Ctrl+F11".to_string(),
+            ..Default::default()
+        };
+        cfg.save_to(&path).unwrap();
+        let loaded = Config::load_from(&path);
+        assert_eq!(loaded.position, Position::Custom);
+        assert_eq!(loaded.custom_pos, [640, 32]);
+        assert_eq!(loaded.lock_hotkey, "Ctrl+F11");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    // --- progress display ---
+
+    #[test]
+    fn progress_roundtrip() {
+        let dir = std::env::temp_dir().join("clockor_test_progress");
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("config.toml");
+
+        for progress in [
+            ProgressDisplay::Off,
+            ProgressDisplay::Year,
+            ProgressDisplay::Month,
+        ] {
+            let cfg = Config {
+                progress,
+                ..Default::default()
+            };
+            cfg.save_to(&path).unwrap();
+            assert_eq!(Config::load_from(&path).progress, progress);
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    // --- daily events ---
+
+    #[test]
+    fn daily_event_parsed_time() {
+        let ev = DailyEvent {
+            time: "07:30".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(ev.parsed_time(), NaiveTime::from_hms_opt(7, 30, 0));
+
+        let bad = DailyEvent {
+            time: "25:00".to_string(),
+            ..Default::default()
+        };
+        assert!(bad.parsed_time().is_none());
+    }
+
+    #[test]
+    fn daily_events_roundtrip() {
+        let dir = std::env::temp_dir().join("clockor_test_daily_events");
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("config.toml");
+
+        let cfg = Config {
+            daily_events: vec![
+                DailyEvent {
+                    name: "Medication".to_string(),
+                    time: "08:00".to_string(),
+                    sound: true,
+                    alarm: true,
+                },
+                DailyEvent {
+                    name: "Stand up".to_string(),
+                    time: "15:30".to_string(),
+                    sound: false,
+                    alarm: false,
+                },
+            ],
+            ..Default::default()
+        };
+        cfg.save_to(&path).unwrap();
+        let loaded = Config::load_from(&path);
+        assert_eq!(loaded.daily_events, cfg.daily_events);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    // --- visibility schedule ---
+
+    #[test]
+    fn schedule_range_parsed() {
+        let range = ScheduleRange::default();
+        assert_eq!(
+            range.parsed(),
+            Some((
+                NaiveTime::from_hms_opt(21, 0, 0).unwrap(),
+                NaiveTime::from_hms_opt(1, 0, 0).unwrap()
+            ))
+        );
+        let bad = ScheduleRange {
+            end: "1am".to_string(),
+            ..Default::default()
+        };
+        assert!(bad.parsed().is_none());
+    }
+
+    #[test]
+    fn schedule_roundtrip() {
+        let dir = std::env::temp_dir().join("clockor_test_schedule");
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("config.toml");
+
+        let cfg = Config {
+            visibility_schedule: vec![ScheduleRange {
+                start: "22:30".to_string(),
+                end: "02:00".to_string(),
+                days: ScheduleDays::Weekdays,
+            }],
+            ..Default::default()
+        };
+        cfg.save_to(&path).unwrap();
+        let loaded = Config::load_from(&path);
+        assert_eq!(loaded.visibility_schedule, cfg.visibility_schedule);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn banner_seconds_clamped() {
+        let dir = std::env::temp_dir().join("clockor_test_banner_clamp");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        fs::write(&path, "banner_seconds = 0\n").unwrap();
+        let loaded = Config::load_from(&path);
+        assert_eq!(loaded.banner_seconds, 3);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn snooze_minutes_clamped() {
+        let dir = std::env::temp_dir().join("clockor_test_snooze_clamp");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        fs::write(&path, "snooze_minutes = 0\n").unwrap();
+        assert_eq!(Config::load_from(&path).snooze_minutes, 1);
+        fs::write(&path, "snooze_minutes = 600\n").unwrap();
+        assert_eq!(Config::load_from(&path).snooze_minutes, 60);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn set_value_parses_toml_or_plain_text() {
+        let mut cfg = Config::default();
+        cfg.set_value("font_size", "30").unwrap();
+        assert_eq!(cfg.font_size, 30);
+        cfg.set_value("show_seconds", "true").unwrap();
+        assert!(cfg.show_seconds);
+        cfg.set_value("position", "bottom-left").unwrap();
+        assert_eq!(cfg.position, Position::BottomLeft);
+        cfg.set_value("text_color", "[255, 0, 0]").unwrap();
+        assert_eq!(cfg.text_color, [255, 0, 0]);
+        cfg.set_value("hotkey", "Ctrl+Shift+F11").unwrap();
+        assert_eq!(cfg.hotkey, "Ctrl+Shift+F11");
+        // Clamped like a loaded file
+        cfg.set_value("font_size", "500").unwrap();
+        assert_eq!(cfg.font_size, 60);
+    }
+
+    #[test]
+    fn set_value_rejects_unknown_keys_and_bad_values() {
+        let mut cfg = Config::default();
+        assert!(cfg.set_value("no_such_key", "1").is_err());
+        assert!(cfg.set_value("font_size", "big").is_err());
+        assert!(cfg.set_value("position", "middle").is_err());
+        assert_eq!(cfg, Config::default());
+    }
+
+    #[test]
+    fn sound_volumes_clamped() {
+        let dir = std::env::temp_dir().join("clockor_test_volume_clamp");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        fs::write(&path, "tick_volume = 250\nchime_volume = 101\n").unwrap();
+        let loaded = Config::load_from(&path);
+        assert_eq!(loaded.tick_volume, 100);
+        assert_eq!(loaded.chime_volume, 100);
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,92 @@
+//! Overall CPU usage, sampled once per clock tick.
+
+use std::sync::Mutex;
+
+use windows::Win32::Foundation::FILETIME;
+use windows::Win32::System::Threading::GetSystemTimes;
+
+/// System idle, kernel and user time in 100 ns units; kernel time includes idle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Times {
+    idle: u64,
+    kernel: u64,
+    user: u64,
+}
+
+/// Busy share between two samples, 0–100.
+fn usage(before: Times, after: Times) -> Option<u8> {
+    let idle = after.idle.checked_sub(before.idle)?;
+    let total = after.kernel.checked_sub(before.kernel)? + after.user.checked_sub(before.user)?;
+    if total == 0 {
+        return None;
+    }
+    Some((total.saturating_sub(idle) * 100 / total).min(100) as u8)
+}
+
+fn ticks(time: FILETIME) -> u64 {
+    ((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64
+}
+
+struct Meter {
+    last: Option<Times>,
+    usage: Option<u8>,
+}
+
+static METER: Mutex<Meter> = Mutex::new(Meter {
+    last: None,
+    usage: None,
+});
+
+/// Take a sample; the usage is measured since the previous one.
+pub fn sample() {
+    let (mut idle, mut kernel, mut user) = Default::default();
+    if unsafe { GetSystemTimes(Some(&mut idle), Some(&mut kernel), Some(&mut user)) }.is_err() {
+        return;
+    }
+    let now = Times {
+        idle: ticks(idle),
+        kernel: ticks(kernel),
+        user: ticks(user),
+    };
+    let mut meter = METER.lock().unwrap();
+    if let Some(last) = meter.last {
+        meter.usage = usage(last, now);
+    }
+    meter.last = Some(now);
+}
+
+/// CPU usage over the last tick, once two samples have been taken.
+pub fn usage_percent() -> Option<u8> {
+    METER.lock().unwrap().usage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn times(idle: u64, kernel: u64, user: u64) -> Times {
+        Times { idle, kernel, user }
+    }
+
+    #[test]
+    fn busy_share_of_elapsed_time() {
+        // 1000 ticks elapsed (kernel incl. idle 600 + user 400), 750 idle
+        assert_eq!(usage(times(0, 0, 0), times(750, 600, 400)), Some(25));
+        assert_eq!(usage(times(0, 0, 0), times(0, 0, 500)), Some(100));
+    }
+
+    #[test]
+    fn no_elapsed_time_or_wrapped_counters() {
+        assert_eq!(usage(times(5, 5, 5), times(5, 5, 5)), None);
+        assert_eq!(usage(times(9, 9, 9), times(1, 1, 1)), None);
+    }
+
+    #[test]
+    fn combines_filetime_halves() {
+        let time = FILETIME {
+            dwLowDateTime: 2,
+            dwHighDateTime: 1,
+        };
+        assert_eq!(ticks(time), (1 << 32) + 2);
+    }
+}
@@ -0,0 +1,280 @@
+//! "Collect Diagnostics": a zip for attaching to a GitHub issue.
+//!
+//! It holds the config with personal text removed, the in-memory history,
+//! the log file, the monitor layout with each monitor's DPI, and version
+//! information. The
+//! file is written to the desktop and shown in Explorer; nothing is uploaded.
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use windows::core::{w, PCWSTR};
+use windows::Win32::Foundation::{BOOL, LPARAM, RECT};
+use windows::Win32::Graphics::Gdi::{
+    EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFO, MONITORINFOEXW,
+};
+use windows::Win32::System::Com::CoTaskMemFree;
+use windows::Win32::System::Registry::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ};
+use windows::Win32::UI::HiDpi::{GetDpiForMonitor, GetDpiForSystem, MDT_EFFECTIVE_DPI};
+use windows::Win32::UI::Shell::{FOLDERID_Desktop, SHGetKnownFolderPath, KF_FLAG_DEFAULT};
+use windows::Win32::UI::WindowsAndMessaging::MONITORINFOF_PRIMARY;
+
+use crate::config::Config;
+
+/// The config as TOML with reminder names and the audio device ID replaced,
+/// since those can say more about the user than the bug.
+fn sanitized_config(config: &Config) -> String {
+    let mut config = config.clone();
+    for (i, event) in config.daily_events.iter_mut().enumerate() {
+        event.name = format!("Reminder {}", i + 1);
+    }
+    if !config.audio_device.is_empty() {
+        config.audio_device = "(chosen device)".to_string();
+    }
+    toml::to_string_pretty(&config).unwrap_or_default()
+}
+
+/// Table of CRC-32 (IEEE) remainders, built at compile time.
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+};
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &b| {
+        CRC_TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// A zip archive with `files` stored uncompressed. The text files involved are
+/// small, so compression isn't worth a dependency.
+fn zip(files: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    // 1980-01-01 00:00, the earliest DOS date; the file name carries the real time
+    const DOS_DATE: u16 = (1 << 5) | 1;
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for (name, data) in files {
+        let offset = out.len() as u32;
+        let crc = crc32(data);
+        let size = data.len() as u32;
+        let header = |sig: u32, central: bool| {
+            let mut h = Vec::new();
+            h.extend_from_slice(&sig.to_le_bytes());
+            if central {
+                h.extend_from_slice(&20u16.to_le_bytes()); // made by
+            }
+            h.extend_from_slice(&20u16.to_le_bytes()); // needed to extract
+            h.extend_from_slice(&(1u16 << 11).to_le_bytes()); // UTF-8 names
+            h.extend_from_slice(&0u16.to_le_bytes()); // stored
+            h.extend_from_slice(&0u16.to_le_bytes()); // time
+            h.extend_from_slice(&DOS_DATE.to_le_bytes());
+            h.extend_from_slice(&crc.to_le_bytes());
+            h.extend_from_slice(&size.to_le_bytes());
+            h.extend_from_slice(&size.to_le_bytes());
+            h.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            h.extend_from_slice(&0u16.to_le_bytes()); // extra
+            if central {
+                h.extend_from_slice(&[0; 8]); // comment, disk, attributes
+                h.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+                h.extend_from_slice(&offset.to_le_bytes());
+            }
+            h.extend_from_slice(name.as_bytes());
+            h
+        };
+        out.extend(header(0x0403_4b50, false));
+        out.extend_from_slice(data);
+        central.extend(header(0x0201_4b50, true));
+    }
+    let central_offset = out.len() as u32;
+    let central_len = central.len() as u32;
+    out.extend(central);
+    out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    out.extend_from_slice(&[0; 4]); // disk numbers
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&central_len.to_le_bytes());
+    out.extend_from_slice(&central_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // comment
+    out
+}
+
+fn windows_version_value(name: PCWSTR) -> String {
+    let mut buf = [0u16; 128];
+    let mut size = std::mem::size_of_val(&buf) as u32;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            w!("SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion"),
+            name,
+            RRF_RT_REG_SZ,
+            None,
+            Some(buf.as_mut_ptr().cast()),
+            Some(&mut size),
+        )
+    };
+    if status.is_err() {
+        return "?".to_string();
+    }
+    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    String::from_utf16_lossy(&buf[..len])
+}
+
+/// The Windows build number, e.g. "22631", or "?" if it can't be read.
+pub fn windows_build() -> String {
+    windows_version_value(w!("CurrentBuild"))
+}
+
+fn version_info() -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "ClockOR {}", crate::update::CURRENT_VERSION);
+    let _ = writeln!(
+        out,
+        "Windows: {} {} (build {})",
+        windows_version_value(w!("ProductName")),
+        windows_version_value(w!("DisplayVersion")),
+        windows_build(),
+    );
+    let _ = writeln!(out, "Elevated: {}", crate::elevation::is_elevated());
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let _ = writeln!(out, "Arguments: {}", args.join(" "));
+    let locked = crate::policy::current().locked_keys();
+    let _ = writeln!(out, "Policies: {}", locked.join(", "));
+    out
+}
+
+unsafe extern "system" fn collect_monitor(
+    monitor: HMONITOR,
+    _hdc: HDC,
+    _rect: *mut RECT,
+    data: LPARAM,
+) -> BOOL {
+    let out = &mut *(data.0 as *mut String);
+    let mut info = MONITORINFOEXW::default();
+    info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+    if GetMonitorInfoW(monitor, &mut info as *mut _ as *mut MONITORINFO).as_bool() {
+        let name_len = info.szDevice.iter().position(|&c| c == 0).unwrap_or(0);
+        let (mut dpi_x, mut dpi_y) = (0, 0);
+        let _ = GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y);
+        let rc = info.monitorInfo.rcMonitor;
+        let work = info.monitorInfo.rcWork;
+        let primary = info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0;
+        let _ = writeln!(
+            out,
+            "{}{}: {},{} {}x{}, work area {},{} {}x{}, {}dpi ({}%)",
+            String::from_utf16_lossy(&info.szDevice[..name_len]),
+            if primary { " (primary)" } else { "" },
+            rc.left,
+            rc.top,
+            rc.right - rc.left,
+            rc.bottom - rc.top,
+            work.left,
+            work.top,
+            work.right - work.left,
+            work.bottom - work.top,
+            dpi_x,
+            dpi_x * 100 / 96,
+        );
+    }
+    true.into()
+}
+
+fn display_info() -> String {
+    let mut out = format!("System DPI: {}\n", unsafe { GetDpiForSystem() });
+    unsafe {
+        let _ = EnumDisplayMonitors(
+            HDC::default(),
+            None,
+            Some(collect_monitor),
+            LPARAM(&mut out as *mut String as isize),
+        );
+    }
+    out
+}
+
+fn desktop_dir() -> Option<PathBuf> {
+    unsafe {
+        let folder = SHGetKnownFolderPath(&FOLDERID_Desktop, KF_FLAG_DEFAULT, None).ok()?;
+        let path = folder.to_string();
+        CoTaskMemFree(Some(folder.0 as *const _));
+        Some(PathBuf::from(path.ok()?))
+    }
+}
+
+/// Write the diagnostics zip to the desktop and select it in Explorer.
+pub fn collect(config: &Config) -> std::io::Result<PathBuf> {
+    let files = [
+        ("version.txt", version_info().into_bytes()),
+        ("config.toml", sanitized_config(config).into_bytes()),
+        ("history.txt", crate::history::export().into_bytes()),
+        ("display.txt", display_info().into_bytes()),
+        (
+            "clockor.log",
+            std::fs::read(crate::logging::path()).unwrap_or_default(),
+        ),
+    ];
+    let dir = desktop_dir().unwrap_or_else(crate::config::app_dir);
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let path = dir.join(format!("ClockOR-diagnostics-{stamp}.zip"));
+    std::fs::write(&path, zip(&files))?;
+    let _ = std::process::Command::new("explorer")
+        .arg(format!("/select,{}", path.display()))
+        .spawn();
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DailyEvent;
+
+    #[test]
+    fn crc32_matches_reference() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn zip_layout() {
+        let bytes = zip(&[("a.txt", b"hello".to_vec()), ("b.txt", Vec::new())]);
+        assert_eq!(&bytes[..4], &0x0403_4b50u32.to_le_bytes());
+        // Local header (30 + name) then the data
+        assert_eq!(&bytes[35..40], b"hello");
+        let eocd = bytes.len() - 22;
+        assert_eq!(&bytes[eocd..eocd + 4], &0x0605_4b50u32.to_le_bytes());
+        assert_eq!(u16::from_le_bytes([bytes[eocd + 10], bytes[eocd + 11]]), 2);
+        let central = u32::from_le_bytes(bytes[eocd + 16..eocd + 20].try_into().unwrap()) as usize;
+        assert_eq!(&bytes[central..central + 4], &0x0201_4b50u32.to_le_bytes());
+    }
+
+    #[test]
+    fn sanitized_config_hides_personal_text() {
+        let config = Config {
+            daily_events: vec![DailyEvent {
+                name: "Call Mum".to_string(),
+                ..Default::default()
+            }],
+            audio_device: "{0.0.0.00000000}.{guid}".to_string(),
+            ..Default::default()
+        };
+        let text = sanitized_config(&config);
+        assert!(!text.contains("Call Mum"));
+        assert!(text.contains("Reminder 1"));
+        assert!(!text.contains("guid"));
+        assert!(toml::from_str::<Config>(&text).is_ok());
+    }
+}
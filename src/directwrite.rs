@@ -0,0 +1,413 @@
+//! Text drawn with DirectWrite through a Direct2D DC render target, so it
+//! lands on the same device contexts as GDI text: the black and the white
+//! pass of a per-pixel frame (see `layered`). Over GDI it adds font
+//! fallback (emoji and CJK without picking a second font), color emoji,
+//! font widths and subpixel glyph positions. When any of it fails the caller
+//! draws with GDI.
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use windows::core::{w, IUnknown, HSTRING};
+use windows::Foundation::Numerics::Matrix3x2;
+use windows::Win32::Foundation::{BOOL, RECT};
+use windows::Win32::Graphics::Direct2D::Common::{
+    D2D1_ALPHA_MODE_IGNORE, D2D1_COLOR_F, D2D1_PIXEL_FORMAT, D2D_POINT_2F,
+};
+use windows::Win32::Graphics::Direct2D::{
+    D2D1CreateFactory, ID2D1DCRenderTarget, ID2D1Factory, D2D1_DRAW_TEXT_OPTIONS_ENABLE_COLOR_FONT,
+    D2D1_DRAW_TEXT_OPTIONS_NONE, D2D1_FACTORY_TYPE_SINGLE_THREADED, D2D1_FEATURE_LEVEL_DEFAULT,
+    D2D1_RENDER_TARGET_PROPERTIES, D2D1_RENDER_TARGET_TYPE_DEFAULT, D2D1_RENDER_TARGET_USAGE_NONE,
+    D2D1_TEXT_ANTIALIAS_MODE_GRAYSCALE,
+};
+use windows::Win32::Graphics::DirectWrite::{
+    DWriteCreateFactory, IDWriteFactory, IDWriteRenderingParams, IDWriteTextLayout,
+    DWRITE_FACTORY_TYPE_SHARED, DWRITE_FONT_METRICS, DWRITE_FONT_STRETCH, DWRITE_FONT_STYLE_NORMAL,
+    DWRITE_FONT_WEIGHT, DWRITE_PIXEL_GEOMETRY_FLAT, DWRITE_RENDERING_MODE_GDI_CLASSIC,
+    DWRITE_RENDERING_MODE_NATURAL_SYMMETRIC, DWRITE_TEXT_METRICS, DWRITE_TEXT_RANGE,
+};
+use windows::Win32::Graphics::Dxgi::Common::DXGI_FORMAT_B8G8R8A8_UNORM;
+use windows::Win32::Graphics::Gdi::HDC;
+
+/// Segoe UI's ascent plus descent in ems. GDI sizes a font by its cell
+/// height and DirectWrite by its em, so this keeps both the same size when
+/// the family's own metrics can't be read.
+const CELL_PER_EM: f32 = (2210.0 + 514.0) / 2048.0;
+
+/// Logged once, so a machine without Direct2D doesn't fill the log.
+static FAILED: AtomicBool = AtomicBool::new(false);
+
+struct Factories {
+    d2d: ID2D1Factory,
+    dwrite: IDWriteFactory,
+    /// Bound to each DC in turn; dropped and recreated after a failed frame.
+    target: Option<ID2D1DCRenderTarget>,
+}
+
+thread_local! {
+    static FACTORIES: RefCell<Option<Factories>> = const { RefCell::new(None) };
+}
+
+fn failed(what: &str, e: windows::core::Error) {
+    if !FAILED.swap(true, Ordering::Relaxed) {
+        crate::logging::info(format!(
+            "DirectWrite unavailable ({what}: {e}); drawing with GDI"
+        ));
+    }
+}
+
+/// Run `f` with the factories, creating them on first use.
+fn with_factories<T>(f: impl FnOnce(&mut Factories) -> Option<T>) -> Option<T> {
+    FACTORIES.with(|cell| {
+        let mut factories = cell.borrow_mut();
+        if factories.is_none() {
+            let created = unsafe {
+                D2D1CreateFactory::<ID2D1Factory>(D2D1_FACTORY_TYPE_SINGLE_THREADED, None).and_then(
+                    |d2d| {
+                        DWriteCreateFactory::<IDWriteFactory>(DWRITE_FACTORY_TYPE_SHARED)
+                            .map(|dwrite| (d2d, dwrite))
+                    },
+                )
+            };
+            match created {
+                Ok((d2d, dwrite)) => {
+                    *factories = Some(Factories {
+                        d2d,
+                        dwrite,
+                        target: None,
+                    })
+                }
+                Err(e) => {
+                    failed("factories", e);
+                    return None;
+                }
+            }
+        }
+        f(factories.as_mut()?)
+    })
+}
+
+/// One line of text. `colors` are (UTF-16 start, length, COLORREF) ranges
+/// drawn in their own color instead of the pass color.
+pub struct Line {
+    pub text: Vec<u16>,
+    pub colors: Vec<(u32, u32, u32)>,
+}
+
+/// The face the lines are set in.
+pub struct Font<'a> {
+    pub family: &'a str,
+    /// Cell height in pixels, like GDI's.
+    pub px: u32,
+    pub weight: u32,
+    /// `DWRITE_FONT_STRETCH`, 1–9.
+    pub width: u32,
+    /// Natural glyph advances at fractional positions, or whole-pixel GDI
+    /// advances.
+    pub subpixel: bool,
+}
+
+/// Lines laid out and ready to measure and draw.
+pub struct Text {
+    layouts: Vec<IDWriteTextLayout>,
+    colors: Vec<Vec<(u32, u32, u32)>>,
+    rendering: Option<IDWriteRenderingParams>,
+}
+
+/// One drawing of every line: the outline and shadow passes, then the text.
+pub struct Pass {
+    pub offset: (i32, i32),
+    /// COLORREF.
+    pub color: u32,
+    /// Whether the lines' own color ranges apply.
+    pub line_colors: bool,
+}
+
+impl Text {
+    /// Lay out `lines` in `font`.
+    pub fn new(lines: &[Line], font: &Font) -> Option<Self> {
+        with_factories(|factories| unsafe {
+            let dwrite = &factories.dwrite;
+            let family = HSTRING::from(font.family);
+            let weight = DWRITE_FONT_WEIGHT(font.weight as i32);
+            let stretch = DWRITE_FONT_STRETCH(font.width as i32);
+            let cell_per_em = cell_per_em(dwrite, &family, weight, stretch).unwrap_or(CELL_PER_EM);
+            let format = dwrite
+                .CreateTextFormat(
+                    &family,
+                    None,
+                    weight,
+                    DWRITE_FONT_STYLE_NORMAL,
+                    stretch,
+                    font.px as f32 / cell_per_em,
+                    w!(""),
+                )
+                .map_err(|e| failed("text format", e))
+                .ok()?;
+            let layouts = lines
+                .iter()
+                .map(|line| {
+                    if font.subpixel {
+                        dwrite.CreateTextLayout(&line.text, &format, f32::MAX, f32::MAX)
+                    } else {
+                        // One DIP per pixel, as in the render target
+                        dwrite.CreateGdiCompatibleTextLayout(
+                            &line.text,
+                            &format,
+                            f32::MAX,
+                            f32::MAX,
+                            1.0,
+                            None,
+                            false,
+                        )
+                    }
+                })
+                .collect::<windows::core::Result<Vec<_>>>()
+                .map_err(|e| failed("text layout", e))
+                .ok()?;
+            Some(Self {
+                layouts,
+                colors: lines.iter().map(|line| line.colors.clone()).collect(),
+                rendering: rendering_params(dwrite, font.subpixel),
+            })
+        })
+    }
+
+    /// Drawn width of line `index`, rounded up to whole pixels.
+    pub fn width(&self, index: usize) -> i32 {
+        let mut metrics = DWRITE_TEXT_METRICS::default();
+        let _ = unsafe { self.layouts[index].GetMetrics(&mut metrics) };
+        metrics.widthIncludingTrailingWhitespace.ceil() as i32
+    }
+}
+
+/// The family's ascent plus descent in ems, from the face `weight` and
+/// `stretch` pick, or `None` if it isn't installed.
+unsafe fn cell_per_em(
+    dwrite: &IDWriteFactory,
+    family: &HSTRING,
+    weight: DWRITE_FONT_WEIGHT,
+    stretch: DWRITE_FONT_STRETCH,
+) -> Option<f32> {
+    let mut collection = None;
+    dwrite
+        .GetSystemFontCollection(&mut collection, false)
+        .ok()?;
+    let collection = collection?;
+    let (mut index, mut exists) = (0, BOOL(0));
+    collection
+        .FindFamilyName(family, &mut index, &mut exists)
+        .ok()?;
+    if !exists.as_bool() {
+        return None;
+    }
+    let font = collection
+        .GetFontFamily(index)
+        .ok()?
+        .GetFirstMatchingFont(weight, stretch, DWRITE_FONT_STYLE_NORMAL)
+        .ok()?;
+    let mut metrics = DWRITE_FONT_METRICS::default();
+    font.GetMetrics(&mut metrics);
+    em_fraction(metrics)
+}
+
+fn em_fraction(metrics: DWRITE_FONT_METRICS) -> Option<f32> {
+    (metrics.designUnitsPerEm > 0)
+        .then(|| (metrics.ascent as f32 + metrics.descent as f32) / metrics.designUnitsPerEm as f32)
+}
+
+/// Symmetric natural rendering for subpixel positions, or GDI's
+/// whole-pixel rendering to go with GDI-compatible layouts. Grayscale
+/// either way; `None` leaves the target's default.
+unsafe fn rendering_params(
+    dwrite: &IDWriteFactory,
+    subpixel: bool,
+) -> Option<IDWriteRenderingParams> {
+    let defaults = dwrite.CreateRenderingParams().ok()?;
+    let mode = if subpixel {
+        DWRITE_RENDERING_MODE_NATURAL_SYMMETRIC
+    } else {
+        DWRITE_RENDERING_MODE_GDI_CLASSIC
+    };
+    dwrite
+        .CreateCustomRenderingParams(
+            defaults.GetGamma(),
+            defaults.GetEnhancedContrast(),
+            0.0,
+            DWRITE_PIXEL_GEOMETRY_FLAT,
+            mode,
+        )
+        .ok()
+}
+
+/// A COLORREF as an opaque Direct2D color.
+fn color_f(cr: u32) -> D2D1_COLOR_F {
+    let channel = |shift: u32| ((cr >> shift) & 0xFF) as f32 / 255.0;
+    D2D1_COLOR_F {
+        r: channel(0),
+        g: channel(8),
+        b: channel(16),
+        a: 1.0,
+    }
+}
+
+/// The transform that puts a line's top-left at `(x, y)`, turned a quarter
+/// clockwise for vertical text so it runs down with its top to the right.
+fn line_transform((x, y): (i32, i32), vertical: bool) -> Matrix3x2 {
+    let (x, y) = (x as f32, y as f32);
+    if vertical {
+        Matrix3x2 {
+            M11: 0.0,
+            M12: 1.0,
+            M21: -1.0,
+            M22: 0.0,
+            M31: x,
+            M32: y,
+        }
+    } else {
+        Matrix3x2 {
+            M11: 1.0,
+            M12: 0.0,
+            M21: 0.0,
+            M22: 1.0,
+            M31: x,
+            M32: y,
+        }
+    }
+}
+
+/// Draw `text` onto `hdc`, whose drawable area is `size`, once per pass,
+/// with line `i` at `origins[i]`. Returns false if nothing could be drawn,
+/// so the caller can fall back to GDI.
+pub fn draw(
+    hdc: HDC,
+    size: (i32, i32),
+    text: &Text,
+    origins: &[(i32, i32)],
+    vertical: bool,
+    passes: &[Pass],
+) -> bool {
+    with_factories(|factories| unsafe {
+        let target = match &factories.target {
+            Some(target) => target.clone(),
+            None => {
+                let properties = D2D1_RENDER_TARGET_PROPERTIES {
+                    r#type: D2D1_RENDER_TARGET_TYPE_DEFAULT,
+                    pixelFormat: D2D1_PIXEL_FORMAT {
+                        format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                        alphaMode: D2D1_ALPHA_MODE_IGNORE,
+                    },
+                    // One DIP per pixel, as the layout is in pixels
+                    dpiX: 96.0,
+                    dpiY: 96.0,
+                    usage: D2D1_RENDER_TARGET_USAGE_NONE,
+                    minLevel: D2D1_FEATURE_LEVEL_DEFAULT,
+                };
+                let target = factories
+                    .d2d
+                    .CreateDCRenderTarget(&properties)
+                    .map_err(|e| failed("render target", e))
+                    .ok()?;
+                factories.target = Some(target.clone());
+                target
+            }
+        };
+        let rect = RECT {
+            left: 0,
+            top: 0,
+            right: size.0,
+            bottom: size.1,
+        };
+        if let Err(e) = target.BindDC(hdc, &rect) {
+            factories.target = None;
+            failed("bind", e);
+            return None;
+        }
+        // ClearType's colored edges would read as partial coverage in the frame
+        target.SetTextAntialiasMode(D2D1_TEXT_ANTIALIAS_MODE_GRAYSCALE);
+        if let Some(rendering) = &text.rendering {
+            target.SetTextRenderingParams(rendering);
+        }
+        target.BeginDraw();
+        for pass in passes {
+            let Ok(brush) = target.CreateSolidColorBrush(&color_f(pass.color), None) else {
+                continue;
+            };
+            for (index, layout) in text.layouts.iter().enumerate() {
+                // Ranges keep their brush until changed, so clear them for plain passes
+                for &(start, length, color) in &text.colors[index] {
+                    let range = DWRITE_TEXT_RANGE {
+                        startPosition: start,
+                        length,
+                    };
+                    let effect = if pass.line_colors {
+                        target
+                            .CreateSolidColorBrush(&color_f(color), None)
+                            .ok()
+                            .map(IUnknown::from)
+                    } else {
+                        None
+                    };
+                    let _ = layout.SetDrawingEffect(effect.as_ref(), range);
+                }
+                let origin = (
+                    origins[index].0 + pass.offset.0,
+                    origins[index].1 + pass.offset.1,
+                );
+                target.SetTransform(&line_transform(origin, vertical));
+                // Color emoji only in the text itself; outlines stay one color
+                let options = if pass.line_colors {
+                    D2D1_DRAW_TEXT_OPTIONS_ENABLE_COLOR_FONT
+                } else {
+                    D2D1_DRAW_TEXT_OPTIONS_NONE
+                };
+                target.DrawTextLayout(D2D_POINT_2F { x: 0.0, y: 0.0 }, layout, &brush, options);
+            }
+        }
+        target.SetTransform(&line_transform((0, 0), false));
+        if let Err(e) = target.EndDraw(None, None) {
+            // The target may be lost (e.g. after a driver reset); make a new one next time
+            factories.target = None;
+            failed("draw", e);
+            return None;
+        }
+        Some(())
+    })
+    .is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn em_fraction_matches_segoe_ui() {
+        let metrics = DWRITE_FONT_METRICS {
+            designUnitsPerEm: 2048,
+            ascent: 2210,
+            descent: 514,
+            ..Default::default()
+        };
+        assert_eq!(em_fraction(metrics), Some(CELL_PER_EM));
+        assert_eq!(em_fraction(DWRITE_FONT_METRICS::default()), None);
+    }
+
+    #[test]
+    fn colorref_channels() {
+        let c = color_f(0x00FF8000);
+        assert_eq!((c.r, c.g, c.b, c.a), (0.0, 128.0 / 255.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn vertical_lines_turn_clockwise() {
+        // The line's direction (1, 0) points down; its top (0, -1) points right
+        let m = line_transform((100, 20), true);
+        let apply =
+            |(x, y): (f32, f32)| (x * m.M11 + y * m.M21 + m.M31, x * m.M12 + y * m.M22 + m.M32);
+        assert_eq!(apply((0.0, 0.0)), (100.0, 20.0));
+        assert_eq!(apply((10.0, 0.0)), (100.0, 30.0));
+        assert_eq!(apply((0.0, -10.0)), (110.0, 20.0));
+        let m = line_transform((5, 6), false);
+        assert_eq!((m.M11, m.M22, m.M31, m.M32), (1.0, 1.0, 5.0, 6.0));
+    }
+}
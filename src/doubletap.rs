@@ -0,0 +1,201 @@
+use std::sync::atomic::{AtomicBool, AtomicIsize, AtomicU8, Ordering};
+use std::sync::Mutex;
+
+use windows::Win32::Foundation::{HINSTANCE, LPARAM, LRESULT, WPARAM};
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    VK_LCONTROL, VK_LMENU, VK_LSHIFT, VK_RCONTROL, VK_RMENU, VK_RSHIFT,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    CallNextHookEx, SetWindowsHookExW, UnhookWindowsHookEx, HC_ACTION, HHOOK, KBDLLHOOKSTRUCT,
+    WH_KEYBOARD_LL, WM_KEYDOWN, WM_SYSKEYDOWN,
+};
+
+use crate::config::DoubleTapKey;
+
+/// Longest press that still counts as a tap, and the longest gap between two taps.
+const TAP_MS: u32 = 300;
+
+/// Detects "press and release the modifier twice" with nothing else pressed in between.
+#[derive(Default)]
+pub struct Detector {
+    down_at: Option<u32>,
+    last_tap_up: Option<u32>,
+}
+
+impl Detector {
+    /// Feed one key event (timestamps in ms, may wrap). Returns true on a double tap.
+    pub fn on_key(&mut self, is_target: bool, down: bool, time: u32) -> bool {
+        if !is_target {
+            // Any other key (e.g. Ctrl+C) cancels the gesture
+            if down {
+                self.down_at = None;
+                self.last_tap_up = None;
+            }
+            return false;
+        }
+        if down {
+            // Ignore auto-repeat while held
+            if self.down_at.is_none() {
+                if self
+                    .last_tap_up
+                    .is_some_and(|up| time.wrapping_sub(up) > TAP_MS)
+                {
+                    self.last_tap_up = None;
+                }
+                self.down_at = Some(time);
+            }
+            return false;
+        }
+        let Some(down_at) = self.down_at.take() else {
+            return false;
+        };
+        if time.wrapping_sub(down_at) > TAP_MS {
+            // Held too long: a modifier used for something else
+            self.last_tap_up = None;
+            return false;
+        }
+        if self.last_tap_up.take().is_some() {
+            return true;
+        }
+        self.last_tap_up = Some(time);
+        false
+    }
+}
+
+fn matches(key: DoubleTapKey, vk: u32) -> bool {
+    let (left, right) = match key {
+        DoubleTapKey::Off => return false,
+        DoubleTapKey::Ctrl => (VK_LCONTROL, VK_RCONTROL),
+        DoubleTapKey::Shift => (VK_LSHIFT, VK_RSHIFT),
+        DoubleTapKey::Alt => (VK_LMENU, VK_RMENU),
+    };
+    vk == left.0 as u32 || vk == right.0 as u32
+}
+
+fn key_from_u8(v: u8) -> DoubleTapKey {
+    match v {
+        1 => DoubleTapKey::Ctrl,
+        2 => DoubleTapKey::Shift,
+        3 => DoubleTapKey::Alt,
+        _ => DoubleTapKey::Off,
+    }
+}
+
+static HOOK: AtomicIsize = AtomicIsize::new(0);
+static TARGET: AtomicU8 = AtomicU8::new(0);
+static DETECTOR: Mutex<Option<Detector>> = Mutex::new(None);
+static TRIGGERED: AtomicBool = AtomicBool::new(false);
+
+unsafe extern "system" fn hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION as i32 {
+        let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        let down = matches!(wparam.0 as u32, WM_KEYDOWN | WM_SYSKEYDOWN);
+        let is_target = matches(key_from_u8(TARGET.load(Ordering::Relaxed)), info.vkCode);
+        if let Some(detector) = DETECTOR.lock().unwrap().as_mut() {
+            if detector.on_key(is_target, down, info.time) {
+                TRIGGERED.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+    // Observe only; never swallow keys
+    CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+}
+
+/// Install or remove the keyboard hook for `key`. Must run on the thread that pumps messages.
+pub fn apply(key: DoubleTapKey) -> bool {
+    TARGET.store(key as u8, Ordering::Relaxed);
+    *DETECTOR.lock().unwrap() = Some(Detector::default());
+
+    let installed = HOOK.load(Ordering::Relaxed) != 0;
+    if key == DoubleTapKey::Off {
+        if installed {
+            let hook = HHOOK(HOOK.swap(0, Ordering::Relaxed) as *mut _);
+            unsafe {
+                let _ = UnhookWindowsHookEx(hook);
+            }
+        }
+        return true;
+    }
+    if installed {
+        return true;
+    }
+    match unsafe { SetWindowsHookExW(WH_KEYBOARD_LL, Some(hook_proc), HINSTANCE::default(), 0) } {
+        Ok(hook) => {
+            HOOK.store(hook.0 as isize, Ordering::Relaxed);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// True once per detected double tap.
+pub fn take_triggered() -> bool {
+    TRIGGERED.swap(false, Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tap(d: &mut Detector, at: u32) -> bool {
+        let a = d.on_key(true, true, at);
+        let b = d.on_key(true, false, at + 50);
+        a || b
+    }
+
+    #[test]
+    fn double_tap_fires() {
+        let mut d = Detector::default();
+        assert!(!tap(&mut d, 1000));
+        assert!(tap(&mut d, 1200));
+    }
+
+    #[test]
+    fn slow_taps_do_not_fire() {
+        let mut d = Detector::default();
+        assert!(!tap(&mut d, 1000));
+        assert!(!tap(&mut d, 1500));
+        // The slow second tap starts a new gesture
+        assert!(tap(&mut d, 1700));
+    }
+
+    #[test]
+    fn long_press_is_not_a_tap() {
+        let mut d = Detector::default();
+        assert!(!tap(&mut d, 1000));
+        assert!(!d.on_key(true, true, 1100));
+        assert!(!d.on_key(true, false, 1600));
+    }
+
+    #[test]
+    fn other_key_cancels() {
+        let mut d = Detector::default();
+        assert!(!tap(&mut d, 1000));
+        assert!(!d.on_key(false, true, 1060));
+        assert!(!tap(&mut d, 1100));
+    }
+
+    #[test]
+    fn auto_repeat_ignored() {
+        let mut d = Detector::default();
+        assert!(!tap(&mut d, 1000));
+        assert!(!d.on_key(true, true, 1100));
+        assert!(!d.on_key(true, true, 1130));
+        assert!(d.on_key(true, false, 1160));
+    }
+
+    #[test]
+    fn survives_tick_wraparound() {
+        let mut d = Detector::default();
+        assert!(!tap(&mut d, u32::MAX - 120));
+        assert!(tap(&mut d, 40));
+    }
+
+    #[test]
+    fn matches_both_sides() {
+        assert!(matches(DoubleTapKey::Ctrl, VK_LCONTROL.0 as u32));
+        assert!(matches(DoubleTapKey::Ctrl, VK_RCONTROL.0 as u32));
+        assert!(!matches(DoubleTapKey::Ctrl, VK_LSHIFT.0 as u32));
+        assert!(!matches(DoubleTapKey::Off, VK_LCONTROL.0 as u32));
+    }
+}
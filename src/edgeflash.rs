@@ -0,0 +1,190 @@
+//! Silent alerts: a thin colored band that flashes along one edge of the screen.
+//!
+//! For users who can't hear the reminder sound (or have game audio up), the band
+//! sits in its own click-through layered window so it shows even while the
+//! clock itself is hidden, on the monitor of the foreground app.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use windows::core::{w, PCWSTR};
+use windows::Win32::Foundation::{COLORREF, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{CreateSolidBrush, FillRect, HBRUSH};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::WindowsAndMessaging::{
+    CreateWindowExW, DefWindowProcW, GetForegroundWindow, KillTimer, RegisterClassW,
+    SetLayeredWindowAttributes, SetTimer, SetWindowPos, ShowWindow, HWND_TOPMOST, LWA_ALPHA,
+    SWP_NOACTIVATE, SW_HIDE, SW_SHOWNOACTIVATE, WM_DESTROY, WM_PAINT, WM_TIMER, WNDCLASSW,
+    WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW, WS_EX_TOPMOST, WS_EX_TRANSPARENT, WS_POPUP,
+};
+
+use crate::config::{Config, ScreenEdge};
+use crate::gdi::{self, OwnedBrush, PaintGuard};
+
+const CLASS_NAME: PCWSTR = w!("ClockOR_EdgeFlash");
+const TIMER_ID: usize = 1;
+/// Band thickness in pixels.
+const THICKNESS: i32 = 8;
+/// Alternating lit/dark steps of a flash, each `STEP_MS` long (even, so it
+/// starts lit).
+const FLASH_STEPS: u32 = 10;
+const STEP_MS: u32 = 300;
+
+/// Band color as a COLORREF, read when painting.
+static COLOR: AtomicU32 = AtomicU32::new(0);
+/// Remaining steps; 0 when no flash is in progress.
+static REMAINING: AtomicU32 = AtomicU32::new(0);
+
+/// The band's rect (x, y, width, height) along `edge` of `monitor`.
+fn band_rect(edge: ScreenEdge, monitor: (i32, i32, i32, i32)) -> Option<(i32, i32, i32, i32)> {
+    let (x, y, w, h) = monitor;
+    match edge {
+        ScreenEdge::Off => None,
+        ScreenEdge::Top => Some((x, y, w, THICKNESS)),
+        ScreenEdge::Bottom => Some((x, y + h - THICKNESS, w, THICKNESS)),
+        ScreenEdge::Left => Some((x, y, THICKNESS, h)),
+        ScreenEdge::Right => Some((x + w - THICKNESS, y, THICKNESS, h)),
+    }
+}
+
+/// Whether the band is lit while `remaining` steps are left.
+fn step_visible(remaining: u32) -> bool {
+    remaining > 0 && remaining.is_multiple_of(2)
+}
+
+unsafe extern "system" fn wnd_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_PAINT => {
+            {
+                let paint = PaintGuard::begin(hwnd);
+                let brush =
+                    OwnedBrush::new(CreateSolidBrush(COLORREF(COLOR.load(Ordering::Relaxed))));
+                let _ = FillRect(paint.hdc(), &paint.paint_struct().rcPaint, brush.handle());
+            }
+            gdi::assert_released();
+            LRESULT(0)
+        }
+        WM_TIMER => {
+            let remaining = REMAINING.load(Ordering::Relaxed).saturating_sub(1);
+            REMAINING.store(remaining, Ordering::Relaxed);
+            let alpha = if step_visible(remaining) { 255 } else { 0 };
+            let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0), alpha, LWA_ALPHA);
+            if remaining == 0 {
+                let _ = KillTimer(hwnd, TIMER_ID);
+                let _ = ShowWindow(hwnd, SW_HIDE);
+            }
+            LRESULT(0)
+        }
+        WM_DESTROY => {
+            let _ = KillTimer(hwnd, TIMER_ID);
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+pub struct EdgeFlash {
+    hwnd: HWND,
+}
+
+impl EdgeFlash {
+    pub fn new() -> Self {
+        unsafe {
+            let hinstance = GetModuleHandleW(None).unwrap();
+            let wc = WNDCLASSW {
+                lpfnWndProc: Some(wnd_proc),
+                hInstance: hinstance.into(),
+                lpszClassName: CLASS_NAME,
+                hbrBackground: HBRUSH(std::ptr::null_mut()),
+                ..Default::default()
+            };
+            RegisterClassW(&wc);
+            let hwnd = CreateWindowExW(
+                WS_EX_TOPMOST
+                    | WS_EX_TRANSPARENT
+                    | WS_EX_LAYERED
+                    | WS_EX_TOOLWINDOW
+                    | WS_EX_NOACTIVATE,
+                CLASS_NAME,
+                w!("ClockOR alert"),
+                WS_POPUP,
+                0,
+                0,
+                0,
+                0,
+                None,
+                None,
+                hinstance,
+                None,
+            )
+            .unwrap();
+            EdgeFlash { hwnd }
+        }
+    }
+
+    /// Flash the band on the configured edge; a steady band with reduced motion.
+    pub fn alert(&self, config: &Config) {
+        let monitor = crate::overlay::monitor_rect_for(unsafe { GetForegroundWindow() });
+        let Some((x, y, w, h)) = band_rect(config.edge_flash, monitor) else {
+            return;
+        };
+        let [r, g, b] = config.edge_flash_color;
+        COLOR.store(
+            r as u32 | ((g as u32) << 8) | ((b as u32) << 16),
+            Ordering::Relaxed,
+        );
+        // A steady band is one step lasting as long as the whole flash
+        let (steps, step_ms) = if crate::theme::reduce_motion(config) {
+            (1, STEP_MS * FLASH_STEPS)
+        } else {
+            (FLASH_STEPS, STEP_MS)
+        };
+        REMAINING.store(steps, Ordering::Relaxed);
+        unsafe {
+            let _ = SetLayeredWindowAttributes(self.hwnd, COLORREF(0), 255, LWA_ALPHA);
+            let _ = SetWindowPos(self.hwnd, HWND_TOPMOST, x, y, w, h, SWP_NOACTIVATE);
+            let _ = ShowWindow(self.hwnd, SW_SHOWNOACTIVATE);
+            SetTimer(self.hwnd, TIMER_ID, step_ms, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn band_hugs_the_chosen_edge() {
+        let monitor = (1920, 0, 2560, 1440);
+        assert_eq!(band_rect(ScreenEdge::Off, monitor), None);
+        assert_eq!(
+            band_rect(ScreenEdge::Top, monitor),
+            Some((1920, 0, 2560, 8))
+        );
+        assert_eq!(
+            band_rect(ScreenEdge::Bottom, monitor),
+            Some((1920, 1432, 2560, 8))
+        );
+        assert_eq!(
+            band_rect(ScreenEdge::Left, monitor),
+            Some((1920, 0, 8, 1440))
+        );
+        assert_eq!(
+            band_rect(ScreenEdge::Right, monitor),
+            Some((4472, 0, 8, 1440))
+        );
+    }
+
+    #[test]
+    fn flash_starts_lit_and_ends_hidden() {
+        assert!(step_visible(FLASH_STEPS));
+        assert!(!step_visible(FLASH_STEPS - 1));
+        assert!(step_visible(2));
+        assert!(!step_visible(1));
+        assert!(!step_visible(0));
+    }
+}
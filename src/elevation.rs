@@ -0,0 +1,138 @@
+//! Elevation checks for games that run as administrator.
+//!
+//! Windows' UIPI keeps hotkeys registered by a normal process from reaching an
+//! elevated foreground window, so ClockOR looks like it stopped working. When
+//! that situation is first seen, the user gets a one-time explanation with the
+//! option to restart ClockOR elevated. The "Run as administrator" setting (or
+//! `--elevated` on the command line) does that on every launch.
+
+use std::path::PathBuf;
+
+use windows::core::HSTRING;
+use windows::Win32::Foundation::{CloseHandle, ERROR_ACCESS_DENIED, HANDLE, HWND};
+use windows::Win32::Security::{GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY};
+use windows::Win32::System::Threading::{
+    GetCurrentProcess, OpenProcess, OpenProcessToken, PROCESS_QUERY_LIMITED_INFORMATION,
+};
+use windows::Win32::UI::Shell::ShellExecuteW;
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetForegroundWindow, GetWindowThreadProcessId, SW_SHOWNORMAL,
+};
+
+/// Asks for an elevated run regardless of the setting.
+pub const ELEVATED_FLAG: &str = "--elevated";
+
+/// Passed to the elevated copy so it waits for this process to exit, and so it
+/// never relaunches again if elevation didn't take (UAC turned off).
+pub const RELAUNCHED_FLAG: &str = "--relaunched-elevated";
+
+/// Whether `token` is elevated. `None` if it can't be read.
+fn token_elevated(token: HANDLE) -> Option<bool> {
+    let mut elevation = TOKEN_ELEVATION::default();
+    let mut len = 0;
+    unsafe {
+        GetTokenInformation(
+            token,
+            TokenElevation,
+            Some(&mut elevation as *mut _ as *mut _),
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut len,
+        )
+        .ok()?;
+    }
+    Some(elevation.TokenIsElevated != 0)
+}
+
+/// Open the token of `process` for querying.
+fn process_token(process: HANDLE) -> windows::core::Result<HANDLE> {
+    let mut token = HANDLE::default();
+    unsafe { OpenProcessToken(process, TOKEN_QUERY, &mut token)? };
+    Ok(token)
+}
+
+/// Whether ClockOR itself runs as administrator.
+pub fn is_elevated() -> bool {
+    let Ok(token) = process_token(unsafe { GetCurrentProcess() }) else {
+        return false;
+    };
+    let elevated = token_elevated(token).unwrap_or(false);
+    unsafe {
+        let _ = CloseHandle(token);
+    }
+    elevated
+}
+
+/// Whether the window in front belongs to an elevated process. A process whose
+/// token we aren't allowed to query counts as elevated: that's exactly the case
+/// of an administrator process seen from a normal one.
+pub fn foreground_is_elevated() -> bool {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd == HWND::default() {
+            return false;
+        }
+        let mut pid = 0;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        if pid == 0 || pid == std::process::id() {
+            return false;
+        }
+        let Ok(process) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) else {
+            return false;
+        };
+        let elevated = match process_token(process) {
+            Ok(token) => {
+                let elevated = token_elevated(token).unwrap_or(false);
+                let _ = CloseHandle(token);
+                elevated
+            }
+            Err(e) => e.code() == ERROR_ACCESS_DENIED.to_hresult(),
+        };
+        let _ = CloseHandle(process);
+        elevated
+    }
+}
+
+/// Whether this launch should hand over to an elevated copy.
+pub fn wants_elevation(config: &crate::config::Config) -> bool {
+    let mut args = std::env::args().skip(1);
+    let requested = config.run_elevated || args.any(|a| a == ELEVATED_FLAG);
+    requested && !std::env::args().any(|a| a == RELAUNCHED_FLAG) && !is_elevated()
+}
+
+fn notice_path() -> PathBuf {
+    crate::config::app_dir().join("elevation_notice")
+}
+
+/// The explanation has been shown before (on any earlier run).
+pub fn notice_seen() -> bool {
+    notice_path().exists()
+}
+
+pub fn mark_notice_seen() {
+    let _ = std::fs::write(notice_path(), crate::update::CURRENT_VERSION);
+}
+
+/// Start ClockOR again through the UAC prompt, keeping the command line; the
+/// caller should exit right after. False if the user declined the prompt.
+pub fn relaunch_elevated() -> bool {
+    let Ok(exe) = std::env::current_exe() else {
+        return false;
+    };
+    let args: Vec<String> = std::env::args()
+        .skip(1)
+        .chain(std::iter::once(RELAUNCHED_FLAG.to_string()))
+        .map(|a| format!("\"{a}\""))
+        .collect();
+    unsafe {
+        let result = ShellExecuteW(
+            HWND::default(),
+            &HSTRING::from("runas"),
+            &HSTRING::from(exe.as_os_str()),
+            &HSTRING::from(args.join(" ")),
+            None,
+            SW_SHOWNORMAL,
+        );
+        // Values above 32 mean success
+        result.0 as usize > 32
+    }
+}
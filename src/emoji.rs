@@ -0,0 +1,79 @@
+//! Emoji in the overlay text. GDI doesn't fall back to another font for
+//! characters Segoe UI lacks, so emoji are split into their own runs and drawn
+//! with Segoe UI Emoji instead of showing as boxes.
+
+/// Characters that join or modify the emoji before them and take no space of
+/// their own: zero-width joiner, variation selectors, tag characters.
+pub fn is_joiner(c: char) -> bool {
+    matches!(c as u32, 0x200D | 0xFE00..=0xFE0F | 0xE0020..=0xE007F)
+}
+
+/// Whether `c` needs the emoji font.
+pub fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        // Watch, hourglass, alarm clock and other technical symbols
+        0x231A..=0x231B
+            | 0x23E9..=0x23FA
+            // Miscellaneous symbols and dingbats (☀, ☕, ✅, ❤)
+            | 0x2600..=0x27BF
+            // Arrows and stars (⬆, ⭐, ⭕)
+            | 0x2B05..=0x2B55
+            // Mahjong through symbols and pictographs extended-A, incl. flags and skin tones
+            | 0x1F000..=0x1FAFF
+    ) || is_joiner(c)
+}
+
+/// `text` split into runs, each with whether it is drawn with the emoji font.
+/// Joiners stay with the emoji they follow.
+pub fn runs(text: &str) -> Vec<(bool, String)> {
+    let mut runs: Vec<(bool, String)> = Vec::new();
+    for c in text.chars() {
+        let emoji = if is_joiner(c) {
+            runs.last().is_some_and(|(emoji, _)| *emoji)
+        } else {
+            is_emoji(c)
+        };
+        match runs.last_mut() {
+            Some((last, run)) if *last == emoji => run.push(c),
+            _ => runs.push((emoji, c.to_string())),
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_emoji_from_text() {
+        assert_eq!(
+            runs("🍕 18:30"),
+            [(true, "🍕".to_string()), (false, " 18:30".to_string())]
+        );
+        assert_eq!(runs("12:00"), [(false, "12:00".to_string())]);
+        assert!(runs("").is_empty());
+    }
+
+    #[test]
+    fn sequences_stay_together() {
+        // Woman technologist (ZWJ sequence), then a heart with a variation selector
+        let text = "👩\u{200D}💻❤\u{FE0F}!";
+        assert_eq!(
+            runs(text),
+            [
+                (true, "👩\u{200D}💻❤\u{FE0F}".to_string()),
+                (false, "!".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn japanese_is_not_emoji() {
+        assert!(!is_emoji('日'));
+        assert!(!is_emoji('：'));
+        assert!(is_emoji('⏰'));
+        assert!(is_emoji('☕'));
+    }
+}
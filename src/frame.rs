@@ -0,0 +1,88 @@
+//! Windows 11 window styling from DWM: rounded corners and a thin border, so
+//! the panel matches the OS's own flyouts. Earlier versions of Windows don't
+//! know these attributes and keep the square, borderless window.
+
+use std::sync::Mutex;
+
+use windows::Win32::Foundation::{COLORREF, HWND};
+use windows::Win32::Graphics::Dwm::{
+    DwmSetWindowAttribute, DWMWA_BORDER_COLOR, DWMWA_WINDOW_CORNER_PREFERENCE, DWMWCP_DEFAULT,
+    DWMWCP_DONOTROUND, DWMWCP_ROUND, DWMWCP_ROUNDSMALL, DWM_WINDOW_CORNER_PREFERENCE,
+};
+
+use crate::config::CornerStyle;
+
+/// `DWMWA_BORDER_COLOR` value for no border at all.
+const DWMWA_COLOR_NONE: u32 = 0xFFFF_FFFE;
+
+fn corner_preference(style: CornerStyle) -> DWM_WINDOW_CORNER_PREFERENCE {
+    match style {
+        CornerStyle::System => DWMWCP_DEFAULT,
+        CornerStyle::Square => DWMWCP_DONOTROUND,
+        CornerStyle::Round => DWMWCP_ROUND,
+        CornerStyle::RoundSmall => DWMWCP_ROUNDSMALL,
+    }
+}
+
+/// The border as a COLORREF, or "none".
+fn border_value(border: Option<[u8; 3]>) -> u32 {
+    match border {
+        Some([r, g, b]) => r as u32 | (g as u32) << 8 | (b as u32) << 16,
+        None => DWMWA_COLOR_NONE,
+    }
+}
+
+/// What was last set on the window, so the attributes are only sent on change.
+static APPLIED: Mutex<Option<(isize, CornerStyle, u32)>> = Mutex::new(None);
+
+/// Give `hwnd` the corner shape and border (`None` = no border). Failures
+/// (Windows 10 and earlier) are ignored.
+pub fn apply(hwnd: HWND, corners: CornerStyle, border: Option<[u8; 3]>) {
+    let border = border_value(border);
+    let key = (hwnd.0 as isize, corners, border);
+    let mut applied = APPLIED.lock().unwrap();
+    let untouched = applied.is_none_or(|(h, _, _)| h != key.0);
+    if *applied == Some(key)
+        || (untouched && corners == CornerStyle::System && border == DWMWA_COLOR_NONE)
+    {
+        return;
+    }
+    let preference = corner_preference(corners);
+    let color = COLORREF(border);
+    unsafe {
+        let _ = DwmSetWindowAttribute(
+            hwnd,
+            DWMWA_WINDOW_CORNER_PREFERENCE,
+            &preference as *const _ as *const _,
+            std::mem::size_of_val(&preference) as u32,
+        );
+        let _ = DwmSetWindowAttribute(
+            hwnd,
+            DWMWA_BORDER_COLOR,
+            &color as *const _ as *const _,
+            std::mem::size_of_val(&color) as u32,
+        );
+    }
+    *applied = Some(key);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn border_is_colorref_or_none() {
+        assert_eq!(border_value(Some([0x11, 0x22, 0x33])), 0x0033_2211);
+        assert_eq!(border_value(None), DWMWA_COLOR_NONE);
+    }
+
+    #[test]
+    fn corner_styles_map_to_dwm() {
+        assert_eq!(corner_preference(CornerStyle::System), DWMWCP_DEFAULT);
+        assert_eq!(corner_preference(CornerStyle::Square), DWMWCP_DONOTROUND);
+        assert_eq!(
+            corner_preference(CornerStyle::RoundSmall),
+            DWMWCP_ROUNDSMALL
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! Paint-time budget: how long each overlay frame takes to draw.
+//!
+//! The overlay sits on top of games, so a rendering regression costs the game
+//! frames. Recent paint times are kept for the debug line, and a frame over
+//! `BUDGET` is written to the log with its text style and font size.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// A frame slower than this is logged.
+pub const BUDGET: Duration = Duration::from_millis(2);
+/// Paint times kept for the percentiles.
+const SAMPLES: usize = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Summary {
+    pub average: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+}
+
+/// The most recent paint times, oldest first.
+#[derive(Debug, Default)]
+pub struct FrameTimes {
+    samples: VecDeque<Duration>,
+}
+
+impl FrameTimes {
+    pub const fn new() -> Self {
+        Self {
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Add a paint time, returning whether it went over budget.
+    pub fn record(&mut self, elapsed: Duration) -> bool {
+        if self.samples.len() == SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(elapsed);
+        elapsed > BUDGET
+    }
+
+    pub fn summary(&self) -> Option<Summary> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort();
+        let total: Duration = sorted.iter().sum();
+        // Nearest-rank percentile
+        let rank = (sorted.len() * 95).div_ceil(100);
+        Some(Summary {
+            average: total / sorted.len() as u32,
+            p95: sorted[rank - 1],
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// Milliseconds with one decimal, e.g. "0.4ms".
+pub fn format_ms(d: Duration) -> String {
+    format!("{:.1}ms", d.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn empty_has_no_summary() {
+        assert_eq!(FrameTimes::new().summary(), None);
+    }
+
+    #[test]
+    fn summary_percentiles() {
+        let mut times = FrameTimes::new();
+        for i in 1..=100 {
+            times.record(Duration::from_micros(i * 10));
+        }
+        let summary = times.summary().unwrap();
+        assert_eq!(summary.p95, Duration::from_micros(950));
+        assert_eq!(summary.max, Duration::from_micros(1000));
+        assert_eq!(summary.average, Duration::from_micros(505));
+    }
+
+    #[test]
+    fn keeps_recent_samples_and_flags_slow_frames() {
+        let mut times = FrameTimes::new();
+        assert!(times.record(ms(50)));
+        for _ in 0..SAMPLES {
+            assert!(!times.record(ms(1)));
+        }
+        // The slow frame has aged out
+        assert_eq!(times.summary().unwrap().max, ms(1));
+    }
+
+    #[test]
+    fn formats_milliseconds() {
+        assert_eq!(format_ms(Duration::from_micros(420)), "0.4ms");
+        assert_eq!(format_ms(ms(12)), "12.0ms");
+    }
+}
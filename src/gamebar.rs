@@ -0,0 +1,54 @@
+//! Xbox Game Bar detection.
+//!
+//! Game Bar (Win+G) draws its own overlay, with a clock in its top bar, even
+//! over exclusive-fullscreen games. While it is open ClockOR can step aside so
+//! the two overlays don't cover each other. A widget inside Game Bar itself
+//! would need a packaged UWP app and is not provided.
+
+use windows::Win32::Foundation::HWND;
+use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+use crate::foreground::window_image;
+
+/// Executable of the Game Bar overlay.
+const GAME_BAR_EXE: &str = "GameBar.exe";
+
+fn is_game_bar_image(path: &str) -> bool {
+    path.rsplit(['\\', '/'])
+        .next()
+        .is_some_and(|name| name.eq_ignore_ascii_case(GAME_BAR_EXE))
+}
+
+/// Tracks whether Game Bar is the foreground app, looking up the owning
+/// process only when the foreground window changes.
+#[derive(Default)]
+pub struct GameBarWatch {
+    last_foreground: HWND,
+    open: bool,
+}
+
+impl GameBarWatch {
+    pub fn is_open(&mut self) -> bool {
+        let foreground = unsafe { GetForegroundWindow() };
+        if foreground != self.last_foreground {
+            self.last_foreground = foreground;
+            self.open = window_image(foreground).is_some_and(|path| is_game_bar_image(&path));
+        }
+        self.open
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_game_bar_executable() {
+        assert!(is_game_bar_image(
+            "C:\\Program Files\\WindowsApps\\Microsoft.XboxGamingOverlay_7.1\\GameBar.exe"
+        ));
+        assert!(is_game_bar_image("gamebar.exe"));
+        assert!(!is_game_bar_image("C:\\Games\\GameBarFriend.exe"));
+        assert!(!is_game_bar_image(""));
+    }
+}
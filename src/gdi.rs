@@ -0,0 +1,148 @@
+//! Owned GDI handles that are released on drop, so an early return can't leak
+//! a font, brush or DC.
+//!
+//! GDI has a per-process object limit and leaked handles only show up after
+//! hours of repainting, so debug builds count live handles and
+//! `assert_released` checks that a message handler left none behind.
+
+use std::marker::PhantomData;
+
+use windows::Win32::Foundation::HWND;
+use windows::Win32::Graphics::Gdi::{
+    BeginPaint, CreateCompatibleDC, DeleteDC, DeleteObject, EndPaint, SelectObject, HBITMAP,
+    HBRUSH, HDC, HFONT, HGDIOBJ, PAINTSTRUCT,
+};
+
+#[cfg(debug_assertions)]
+static LIVE: std::sync::atomic::AtomicIsize = std::sync::atomic::AtomicIsize::new(0);
+
+fn track(delta: isize) {
+    #[cfg(debug_assertions)]
+    LIVE.fetch_add(delta, std::sync::atomic::Ordering::Relaxed);
+    #[cfg(not(debug_assertions))]
+    let _ = delta;
+}
+
+/// In debug builds, panic if any handle from this module is still alive. Call
+/// at the end of a handler whose handles should all be gone by then.
+pub fn assert_released() {
+    #[cfg(debug_assertions)]
+    assert_eq!(
+        LIVE.load(std::sync::atomic::Ordering::Relaxed),
+        0,
+        "GDI handles leaked"
+    );
+}
+
+/// A font, brush or bitmap, deleted on drop.
+pub struct Owned<T: Copy + Into<HGDIOBJ>>(T);
+
+pub type OwnedFont = Owned<HFONT>;
+pub type OwnedBrush = Owned<HBRUSH>;
+pub type OwnedBitmap = Owned<HBITMAP>;
+
+impl<T: Copy + Into<HGDIOBJ>> Owned<T> {
+    /// Take ownership of a handle just returned by a `Create*` call.
+    pub fn new(handle: T) -> Self {
+        track(1);
+        Self(handle)
+    }
+
+    pub fn handle(&self) -> T {
+        self.0
+    }
+}
+
+impl<T: Copy + Into<HGDIOBJ>> Drop for Owned<T> {
+    fn drop(&mut self) {
+        track(-1);
+        unsafe {
+            let _ = DeleteObject(self.0.into());
+        }
+    }
+}
+
+/// A memory DC from `CreateCompatibleDC`, deleted on drop.
+pub struct MemoryDc(HDC);
+
+impl MemoryDc {
+    /// A DC compatible with the screen.
+    pub fn compatible() -> Self {
+        track(1);
+        Self(unsafe { CreateCompatibleDC(HDC::default()) })
+    }
+
+    pub fn hdc(&self) -> HDC {
+        self.0
+    }
+}
+
+impl Drop for MemoryDc {
+    fn drop(&mut self) {
+        track(-1);
+        unsafe {
+            let _ = DeleteDC(self.0);
+        }
+    }
+}
+
+/// `BeginPaint` … `EndPaint` for one `WM_PAINT`.
+pub struct PaintGuard {
+    hwnd: HWND,
+    ps: PAINTSTRUCT,
+}
+
+impl PaintGuard {
+    pub fn begin(hwnd: HWND) -> Self {
+        track(1);
+        let mut ps = PAINTSTRUCT::default();
+        unsafe { BeginPaint(hwnd, &mut ps) };
+        Self { hwnd, ps }
+    }
+
+    pub fn hdc(&self) -> HDC {
+        self.ps.hdc
+    }
+
+    pub fn paint_struct(&self) -> &PAINTSTRUCT {
+        &self.ps
+    }
+}
+
+impl Drop for PaintGuard {
+    fn drop(&mut self) {
+        track(-1);
+        unsafe {
+            let _ = EndPaint(self.hwnd, &self.ps);
+        }
+    }
+}
+
+/// An object selected into a DC; the previous one is restored on drop. It
+/// borrows the object, so the object can't be deleted while selected.
+pub struct SelectedObjectGuard<'a> {
+    hdc: HDC,
+    previous: HGDIOBJ,
+    _object: PhantomData<&'a ()>,
+}
+
+impl<'a> SelectedObjectGuard<'a> {
+    pub fn select<T: Copy + Into<HGDIOBJ>>(hdc: HDC, object: &'a Owned<T>) -> Self {
+        track(1);
+        let previous = unsafe { SelectObject(hdc, object.handle().into()) };
+        Self {
+            hdc,
+            previous,
+            _object: PhantomData,
+        }
+    }
+}
+
+impl Drop for SelectedObjectGuard<'_> {
+    fn drop(&mut self) {
+        track(-1);
+        unsafe {
+            SelectObject(self.hdc, self.previous);
+        }
+    }
+}
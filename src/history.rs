@@ -0,0 +1,171 @@
+//! Rolling in-memory log of what the overlay announced, for users who were away.
+//!
+//! Nothing is written to disk; the log starts empty on every launch.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use chrono::NaiveDateTime;
+use eframe::egui;
+
+/// Oldest entries are dropped beyond this many.
+const CAPACITY: usize = 200;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub at: NaiveDateTime,
+    pub text: String,
+}
+
+/// Fixed-size log, newest last.
+#[derive(Debug, Default)]
+pub struct History {
+    entries: VecDeque<Entry>,
+}
+
+impl History {
+    pub const fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, at: NaiveDateTime, text: String) {
+        if self.entries.len() == CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Entry { at, text });
+    }
+
+    /// Entries newest first.
+    pub fn newest_first(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter().rev()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+static LOG: Mutex<History> = Mutex::new(History::new());
+
+/// The log as text, oldest first, for the diagnostics bundle.
+pub fn export() -> String {
+    let log = LOG.lock().unwrap();
+    let mut lines: Vec<String> = log
+        .newest_first()
+        .map(|e| format!("{}  {}", e.at.format("%m-%d %H:%M:%S"), e.text))
+        .collect();
+    lines.reverse();
+    lines.join("\n")
+}
+
+/// Add an entry stamped with the current local time.
+pub fn record(text: impl Into<String>) {
+    let now = chrono::Local::now().naive_local();
+    LOG.lock().unwrap().push(now, text.into());
+}
+
+struct HistoryApp;
+
+impl eframe::App for HistoryApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::TopBottomPanel::bottom("actions").show(ctx, |ui| {
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                if ui.button("Clear").clicked() {
+                    LOG.lock().unwrap().clear();
+                }
+                if ui.button("Close").clicked() {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+            });
+            ui.add_space(6.0);
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("History");
+            ui.add_space(4.0);
+            let log = LOG.lock().unwrap();
+            if log.is_empty() {
+                ui.label("Nothing has happened yet.");
+                return;
+            }
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("history")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for entry in log.newest_first() {
+                            ui.monospace(entry.at.format("%m-%d %H:%M").to_string());
+                            ui.label(&entry.text);
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+    }
+}
+
+/// Open the history window. Blocks until it is closed, like the settings window.
+pub fn open_history() {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([380.0, 360.0])
+            .with_always_on_top(),
+        ..Default::default()
+    };
+    let _ = eframe::run_native(
+        "ClockOR History",
+        options,
+        Box::new(|_cc| Ok(Box::new(HistoryApp))),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, minute % 60, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn newest_first() {
+        let mut history = History::new();
+        history.push(at(0), "first".into());
+        history.push(at(1), "second".into());
+        let texts: Vec<_> = history.newest_first().map(|e| e.text.as_str()).collect();
+        assert_eq!(texts, ["second", "first"]);
+    }
+
+    #[test]
+    fn drops_oldest_beyond_capacity() {
+        let mut history = History::new();
+        for i in 0..CAPACITY + 5 {
+            history.push(at(i as u32), format!("#{i}"));
+        }
+        assert_eq!(history.newest_first().count(), CAPACITY);
+        assert_eq!(history.newest_first().last().unwrap().text, "#5");
+        assert_eq!(
+            history.newest_first().next().unwrap().text,
+            format!("#{}", CAPACITY + 4)
+        );
+    }
+
+    #[test]
+    fn clear_empties() {
+        let mut history = History::new();
+        history.push(at(0), "x".into());
+        history.clear();
+        assert!(history.is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+//! Event hooks for automations such as smart lights or logging.
+//!
+//! A hook whose action is an http(s) URL gets a JSON POST
+//! (`{"event":"shown","detail":"","time":"…","version":"…"}`); any other action
+//! runs through `cmd /C` without a window, with `CLOCKOR_EVENT` and
+//! `CLOCKOR_DETAIL` set. Hooks run in the background and never hold up the
+//! overlay; failures go to the log. URL hooks are skipped while network
+//! features are disabled by policy.
+
+use std::process::{Command, Stdio};
+
+use serde::Serialize;
+
+use crate::config::{Config, Hook, HookEvent};
+
+#[derive(Debug, Serialize)]
+struct Payload<'a> {
+    event: HookEvent,
+    detail: &'a str,
+    /// Local time, RFC 3339.
+    time: String,
+    version: &'static str,
+}
+
+fn is_url(action: &str) -> bool {
+    let action = action.trim_start().to_ascii_lowercase();
+    action.starts_with("http://") || action.starts_with("https://")
+}
+
+/// Hooks listening for `event`, skipping ones with no action.
+fn matching(hooks: &[Hook], event: HookEvent) -> impl Iterator<Item = &Hook> {
+    hooks
+        .iter()
+        .filter(move |hook| hook.event == event && !hook.action.trim().is_empty())
+}
+
+/// The event's name as hooks see it, e.g. "stream-started".
+fn event_name(event: HookEvent) -> String {
+    serde_json::to_value(event)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Run every hook for `event`. `detail` is e.g. the reminder's name.
+pub fn fire(config: &Config, event: HookEvent, detail: &str) {
+    for hook in matching(&config.hooks, event) {
+        run(hook.action.trim(), event, detail);
+    }
+}
+
+/// Post to or run one `action` as `event`.
+pub fn run(action: &str, event: HookEvent, detail: &str) {
+    let name = event_name(event);
+    crate::logging::debug(format!("Hook for {name}: {action}"));
+    if is_url(action) {
+        if !crate::policy::current().network_allowed() {
+            crate::logging::info(format!(
+                "Hook for {name} skipped: network disabled by policy"
+            ));
+            return;
+        }
+        let payload = Payload {
+            event,
+            detail,
+            time: chrono::Local::now().to_rfc3339(),
+            version: crate::update::CURRENT_VERSION,
+        };
+        let json = serde_json::to_string(&payload).unwrap_or_default();
+        let url = action.to_string();
+        std::thread::spawn(move || {
+            if let Err(e) = crate::update::post_json(&url, &json) {
+                crate::logging::error(format!("Hook for {name} failed: {e}"));
+            }
+        });
+        return;
+    }
+    let mut command = Command::new("cmd.exe");
+    command
+        .arg("/C")
+        .arg(action)
+        .env("CLOCKOR_EVENT", &name)
+        .env("CLOCKOR_DETAIL", detail)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    if let Err(e) = command.spawn() {
+        crate::logging::error(format!("Hook for {name} failed: {e}"));
+    }
+}
+
+/// Whether the PC's uptime passed a multiple of `hours` between two readings
+/// of `GetTickCount64`.
+fn hours_crossed(before_ms: u64, after_ms: u64, hours: u32) -> bool {
+    let period = hours as u64 * 3_600_000;
+    period > 0 && before_ms / period != after_ms / period
+}
+
+/// Fire "PC on for N hours" hooks whose interval the uptime just passed.
+pub fn poll_uptime(config: &Config, before_ms: u64, after_ms: u64) {
+    for hook in matching(&config.hooks, HookEvent::PcOnHours) {
+        if hours_crossed(before_ms, after_ms, hook.hours) {
+            let hours = after_ms / 3_600_000;
+            run(
+                hook.action.trim(),
+                HookEvent::PcOnHours,
+                &format!("{hours}h"),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 3_600_000;
+
+    #[test]
+    fn urls_are_posted_and_the_rest_run() {
+        assert!(is_url("https://example.com/hook"));
+        assert!(is_url(" HTTP://192.168.1.5/lights"));
+        assert!(!is_url("curl https://example.com"));
+        assert!(!is_url("C:\\scripts\\log.bat"));
+    }
+
+    #[test]
+    fn matches_event_and_skips_empty_actions() {
+        let hooks = vec![
+            Hook {
+                event: HookEvent::Shown,
+                action: "a".to_string(),
+                ..Default::default()
+            },
+            Hook {
+                event: HookEvent::Shown,
+                action: "  ".to_string(),
+                ..Default::default()
+            },
+            Hook {
+                event: HookEvent::Hidden,
+                action: "b".to_string(),
+                ..Default::default()
+            },
+        ];
+        let actions: Vec<&str> = matching(&hooks, HookEvent::Shown)
+            .map(|h| h.action.as_str())
+            .collect();
+        assert_eq!(actions, ["a"]);
+    }
+
+    #[test]
+    fn event_names_are_kebab_case() {
+        assert_eq!(event_name(HookEvent::StreamStarted), "stream-started");
+        assert_eq!(event_name(HookEvent::PcOnHours), "pc-on-hours");
+        assert_eq!(event_name(HookEvent::TimerFinished), "timer-finished");
+    }
+
+    #[test]
+    fn old_session_hours_name_still_loads() {
+        let hook: Hook = toml::from_str("event = \"session-hours\"\nhours = 3\n").unwrap();
+        assert_eq!(hook.event, HookEvent::PcOnHours);
+        assert_eq!(hook.hours, 3);
+    }
+
+    #[test]
+    fn uptime_hours_cross_once_per_interval() {
+        assert!(hours_crossed(2 * HOUR - 1, 2 * HOUR, 2));
+        assert!(!hours_crossed(2 * HOUR, 3 * HOUR, 2));
+        assert!(hours_crossed(3 * HOUR + 5, 4 * HOUR, 2));
+        assert!(!hours_crossed(0, 10 * HOUR, 0));
+    }
+}
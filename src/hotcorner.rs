@@ -0,0 +1,110 @@
+//! Revealing the clock by pushing the pointer into a screen corner, for games
+//! that leave no key free. The main loop polls the cursor; the corner fires
+//! once per visit, so resting the pointer there doesn't keep re-triggering it.
+
+use windows::Win32::Foundation::POINT;
+use windows::Win32::Graphics::Gdi::{
+    GetMonitorInfoW, MonitorFromPoint, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+};
+use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+use crate::config::HotCorner;
+
+/// How close to the corner, in pixels, the pointer has to get.
+const CORNER_SIZE: i32 = 3;
+
+/// Left, top, width, height.
+type Rect = (i32, i32, i32, i32);
+
+/// Whether `point` is in `corner` of `monitor`.
+fn in_corner(corner: HotCorner, (x, y): (i32, i32), monitor: Rect) -> bool {
+    let (left, top, width, height) = monitor;
+    let near_left = x < left + CORNER_SIZE;
+    let near_right = x >= left + width - CORNER_SIZE;
+    let near_top = y < top + CORNER_SIZE;
+    let near_bottom = y >= top + height - CORNER_SIZE;
+    match corner {
+        HotCorner::Off => false,
+        HotCorner::TopLeft => near_left && near_top,
+        HotCorner::TopRight => near_right && near_top,
+        HotCorner::BottomLeft => near_left && near_bottom,
+        HotCorner::BottomRight => near_right && near_bottom,
+    }
+}
+
+/// The pointer and the monitor it is on.
+fn cursor() -> Option<((i32, i32), Rect)> {
+    unsafe {
+        let mut point = POINT::default();
+        GetCursorPos(&mut point).ok()?;
+        let monitor = MonitorFromPoint(point, MONITOR_DEFAULTTONEAREST);
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if !GetMonitorInfoW(monitor, &mut info).as_bool() {
+            return None;
+        }
+        let rc = info.rcMonitor;
+        Some((
+            (point.x, point.y),
+            (rc.left, rc.top, rc.right - rc.left, rc.bottom - rc.top),
+        ))
+    }
+}
+
+#[derive(Default)]
+pub struct HotCornerTracker {
+    inside: bool,
+}
+
+impl HotCornerTracker {
+    /// Check the pointer. Returns true when it has just arrived in `corner`.
+    pub fn poll(&mut self, corner: HotCorner) -> bool {
+        let inside = corner != HotCorner::Off
+            && cursor().is_some_and(|(point, monitor)| in_corner(corner, point, monitor));
+        self.entered(inside)
+    }
+
+    fn entered(&mut self, inside: bool) -> bool {
+        let arrived = inside && !self.inside;
+        self.inside = inside;
+        arrived
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONITOR: Rect = (0, 0, 1920, 1080);
+
+    #[test]
+    fn corners_match_only_their_own() {
+        assert!(in_corner(HotCorner::TopLeft, (0, 0), MONITOR));
+        assert!(in_corner(HotCorner::BottomRight, (1919, 1079), MONITOR));
+        assert!(!in_corner(HotCorner::TopRight, (0, 0), MONITOR));
+        assert!(!in_corner(HotCorner::TopLeft, (0, 540), MONITOR));
+        assert!(!in_corner(HotCorner::Off, (0, 0), MONITOR));
+    }
+
+    #[test]
+    fn corners_of_a_secondary_monitor() {
+        let left_of_primary = (-1280, 0, 1280, 1024);
+        assert!(in_corner(HotCorner::TopRight, (-1, 0), left_of_primary));
+        assert!(in_corner(
+            HotCorner::BottomLeft,
+            (-1280, 1023),
+            left_of_primary
+        ));
+    }
+
+    #[test]
+    fn fires_once_per_visit() {
+        let mut tracker = HotCornerTracker::default();
+        assert!(tracker.entered(true));
+        assert!(!tracker.entered(true));
+        assert!(!tracker.entered(false));
+        assert!(tracker.entered(true));
+    }
+}
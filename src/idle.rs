@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use windows::Win32::System::SystemInformation::GetTickCount;
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+/// Milliseconds between two 32-bit tick counts, tolerating the ~49.7-day wraparound.
+fn ticks_between(earlier: u32, later: u32) -> u32 {
+    later.wrapping_sub(earlier)
+}
+
+/// Time since the last keyboard or mouse input in this session.
+pub fn idle_duration() -> Duration {
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    unsafe {
+        if !GetLastInputInfo(&mut info).as_bool() {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(ticks_between(info.dwTime, GetTickCount()) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks_between_simple() {
+        assert_eq!(ticks_between(1_000, 61_000), 60_000);
+    }
+
+    #[test]
+    fn ticks_between_wraparound() {
+        assert_eq!(ticks_between(u32::MAX - 999, 1_000), 2_000);
+    }
+}
@@ -0,0 +1,339 @@
+//! Recent key presses and mouse clicks for streamers, read with low-level
+//! hooks and shown at the end of the overlay until they fade out. Keys are
+//! observed, never swallowed; like any input overlay this also shows what is
+//! typed into password fields.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicIsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use windows::Win32::Foundation::{HINSTANCE, LPARAM, LRESULT, WPARAM};
+use windows::Win32::UI::Input::KeyboardAndMouse::GetKeyNameTextW;
+use windows::Win32::UI::WindowsAndMessaging::{
+    CallNextHookEx, SetWindowsHookExW, UnhookWindowsHookEx, HC_ACTION, HHOOK, KBDLLHOOKSTRUCT,
+    MSLLHOOKSTRUCT, WH_KEYBOARD_LL, WH_MOUSE_LL, WM_KEYDOWN, WM_KEYUP, WM_LBUTTONDOWN,
+    WM_MBUTTONDOWN, WM_MOUSEWHEEL, WM_RBUTTONDOWN, WM_SYSKEYDOWN, WM_SYSKEYUP, WM_XBUTTONDOWN,
+};
+
+/// How long an input stays at full strength, then how long it takes to fade.
+const HOLD: Duration = Duration::from_millis(1500);
+const FADE: Duration = Duration::from_millis(1000);
+/// Most inputs shown at once; older ones drop off the front.
+const MAX_SHOWN: usize = 6;
+
+/// Modifier keys (left and right) and the prefix they add, in display order.
+const MODIFIERS: [(u32, u32, &str); 4] = [
+    (0xA2, 0xA3, "Ctrl"),
+    (0xA0, 0xA1, "Shift"),
+    (0xA4, 0xA5, "Alt"),
+    (0x5B, 0x5C, "Win"),
+];
+
+fn is_modifier(vk: u32) -> bool {
+    MODIFIERS.iter().any(|&(l, r, _)| vk == l || vk == r)
+}
+
+/// Short names for the keys streamers press most; others ask Windows.
+fn key_name(vk: u32) -> Option<String> {
+    let name = match vk {
+        0x30..=0x39 | 0x41..=0x5A => return char::from_u32(vk).map(String::from),
+        0x70..=0x87 => return Some(format!("F{}", vk - 0x6F)),
+        0x08 => "Back",
+        0x09 => "Tab",
+        0x0D => "Enter",
+        0x14 => "Caps",
+        0x1B => "Esc",
+        0x20 => "Space",
+        0x25 => "←",
+        0x26 => "↑",
+        0x27 => "→",
+        0x28 => "↓",
+        0x2E => "Del",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+/// One shown input; repeats of the same input count up instead of adding more.
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    label: String,
+    count: u32,
+    at: Instant,
+}
+
+/// The inputs still on screen, oldest first.
+#[derive(Default)]
+pub struct Recent {
+    entries: VecDeque<Entry>,
+    /// Keys held down, to tell presses from auto-repeat and to prefix modifiers.
+    held: Vec<u32>,
+}
+
+impl Recent {
+    /// A key went down. Auto-repeat and modifiers on their own aren't shown.
+    pub fn key_down(&mut self, vk: u32, name: impl FnOnce() -> String, now: Instant) {
+        if self.held.contains(&vk) {
+            return;
+        }
+        self.held.push(vk);
+        if !is_modifier(vk) {
+            let label = key_name(vk).unwrap_or_else(name);
+            self.push(label, now);
+        }
+    }
+
+    pub fn key_up(&mut self, vk: u32) {
+        self.held.retain(|&held| held != vk);
+    }
+
+    /// A mouse button or wheel notch, e.g. "LMB".
+    pub fn click(&mut self, label: &str, now: Instant) {
+        self.push(label.to_string(), now);
+    }
+
+    /// `label` with the held modifiers in front, e.g. "Ctrl+Shift+S".
+    fn push(&mut self, label: String, now: Instant) {
+        let mut full = String::new();
+        for (left, right, prefix) in MODIFIERS {
+            if self.held.contains(&left) || self.held.contains(&right) {
+                full.push_str(prefix);
+                full.push('+');
+            }
+        }
+        full.push_str(&label);
+        if let Some(last) = self.entries.back_mut().filter(|last| last.label == full) {
+            last.count += 1;
+            last.at = now;
+            return;
+        }
+        self.entries.push_back(Entry {
+            label: full,
+            count: 1,
+            at: now,
+        });
+        if self.entries.len() > MAX_SHOWN {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Drop inputs that have faded out; true if any were dropped.
+    pub fn prune(&mut self, now: Instant) -> bool {
+        let before = self.entries.len();
+        self.entries
+            .retain(|entry| now.duration_since(entry.at) < HOLD + FADE);
+        self.entries.len() != before
+    }
+
+    /// Each input's text and how far it has faded, 0.0 (full) to 1.0 (gone).
+    pub fn shown(&self, now: Instant) -> Vec<(String, f32)> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let text = if entry.count > 1 {
+                    format!("{}×{}", entry.label, entry.count)
+                } else {
+                    entry.label.clone()
+                };
+                let age = now.duration_since(entry.at).saturating_sub(HOLD);
+                (text, (age.as_secs_f32() / FADE.as_secs_f32()).min(1.0))
+            })
+            .collect()
+    }
+}
+
+static KEYBOARD_HOOK: AtomicIsize = AtomicIsize::new(0);
+static MOUSE_HOOK: AtomicIsize = AtomicIsize::new(0);
+static RECENT: Mutex<Option<Recent>> = Mutex::new(None);
+/// Something was pressed since the overlay last redrew.
+static CHANGED: AtomicBool = AtomicBool::new(false);
+
+fn record(f: impl FnOnce(&mut Recent)) {
+    if let Some(recent) = RECENT.lock().unwrap().as_mut() {
+        f(recent);
+        CHANGED.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The key's name in the keyboard layout, from its scan code.
+fn system_name(info: &KBDLLHOOKSTRUCT) -> String {
+    let mut lparam = (info.scanCode as i32) << 16;
+    // LLKHF_EXTENDED: tell e.g. the numpad Enter from the main one
+    if info.flags.0 & 1 != 0 {
+        lparam |= 1 << 24;
+    }
+    let mut buffer = [0u16; 32];
+    let len = unsafe { GetKeyNameTextW(lparam, &mut buffer) }.max(0) as usize;
+    if len == 0 {
+        return format!("0x{:02X}", info.vkCode);
+    }
+    String::from_utf16_lossy(&buffer[..len])
+}
+
+unsafe extern "system" fn keyboard_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION as i32 {
+        let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        match wparam.0 as u32 {
+            WM_KEYDOWN | WM_SYSKEYDOWN => {
+                record(|recent| recent.key_down(info.vkCode, || system_name(info), Instant::now()))
+            }
+            WM_KEYUP | WM_SYSKEYUP => record(|recent| recent.key_up(info.vkCode)),
+            _ => {}
+        }
+    }
+    CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+}
+
+unsafe extern "system" fn mouse_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION as i32 {
+        let info = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+        let high = (info.mouseData >> 16) as i16;
+        let label = match wparam.0 as u32 {
+            WM_LBUTTONDOWN => Some("LMB"),
+            WM_RBUTTONDOWN => Some("RMB"),
+            WM_MBUTTONDOWN => Some("MMB"),
+            WM_XBUTTONDOWN if high == 1 => Some("X1"),
+            WM_XBUTTONDOWN => Some("X2"),
+            WM_MOUSEWHEEL if high > 0 => Some("Wheel↑"),
+            WM_MOUSEWHEEL => Some("Wheel↓"),
+            // Moves are by far the most common; they're passed straight on
+            _ => None,
+        };
+        if let Some(label) = label {
+            record(|recent| recent.click(label, Instant::now()));
+        }
+    }
+    CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+}
+
+/// Install or remove the hooks. Must run on the thread that pumps messages.
+pub fn apply(enabled: bool) {
+    let installed = KEYBOARD_HOOK.load(Ordering::Relaxed) != 0;
+    if enabled == installed {
+        return;
+    }
+    if !enabled {
+        for hook in [&KEYBOARD_HOOK, &MOUSE_HOOK] {
+            let handle = hook.swap(0, Ordering::Relaxed);
+            if handle != 0 {
+                unsafe {
+                    let _ = UnhookWindowsHookEx(HHOOK(handle as *mut _));
+                }
+            }
+        }
+        *RECENT.lock().unwrap() = None;
+        CHANGED.store(true, Ordering::Relaxed);
+        return;
+    }
+    *RECENT.lock().unwrap() = Some(Recent::default());
+    unsafe {
+        match SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_proc), HINSTANCE::default(), 0) {
+            Ok(hook) => KEYBOARD_HOOK.store(hook.0 as isize, Ordering::Relaxed),
+            Err(e) => {
+                crate::logging::error(format!("Input display: keyboard hook failed: {e}"));
+                return;
+            }
+        }
+        match SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_proc), HINSTANCE::default(), 0) {
+            Ok(hook) => MOUSE_HOOK.store(hook.0 as isize, Ordering::Relaxed),
+            Err(e) => crate::logging::error(format!("Input display: mouse hook failed: {e}")),
+        }
+    }
+}
+
+/// The inputs to show now, with how far each has faded.
+pub fn shown() -> Vec<(String, f32)> {
+    RECENT
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|recent| recent.shown(Instant::now()))
+        .unwrap_or_default()
+}
+
+/// Whether the overlay should redraw: something was pressed, something is
+/// fading, or the last input just went away.
+pub fn needs_redraw() -> bool {
+    let changed = CHANGED.swap(false, Ordering::Relaxed);
+    let mut guard = RECENT.lock().unwrap();
+    let Some(recent) = guard.as_mut() else {
+        return changed;
+    };
+    let dropped = recent.prune(Instant::now());
+    changed || dropped || !recent.entries.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(recent: &Recent, now: Instant) -> Vec<String> {
+        recent
+            .shown(now)
+            .into_iter()
+            .map(|(text, _)| text)
+            .collect()
+    }
+
+    #[test]
+    fn modifiers_prefix_keys() {
+        let now = Instant::now();
+        let mut recent = Recent::default();
+        recent.key_down(0xA2, String::new, now);
+        recent.key_down(0xA0, String::new, now);
+        recent.key_down(0x53, String::new, now);
+        recent.key_up(0x53);
+        recent.key_up(0xA0);
+        recent.key_up(0xA2);
+        recent.click("LMB", now);
+        assert_eq!(labels(&recent, now), ["Ctrl+Shift+S", "LMB"]);
+    }
+
+    #[test]
+    fn repeats_count_up_and_auto_repeat_is_ignored() {
+        let now = Instant::now();
+        let mut recent = Recent::default();
+        for _ in 0..3 {
+            recent.key_down(0x57, String::new, now);
+            recent.key_down(0x57, String::new, now);
+            recent.key_up(0x57);
+        }
+        recent.key_down(0x70, String::new, now);
+        assert_eq!(labels(&recent, now), ["W×3", "F1"]);
+    }
+
+    #[test]
+    fn unknown_keys_use_the_layout_name() {
+        let now = Instant::now();
+        let mut recent = Recent::default();
+        recent.key_down(0xBA, || "Ü".to_string(), now);
+        assert_eq!(labels(&recent, now), ["Ü"]);
+    }
+
+    #[test]
+    fn inputs_fade_then_drop() {
+        let start = Instant::now();
+        let mut recent = Recent::default();
+        recent.click("RMB", start);
+        assert_eq!(recent.shown(start)[0].1, 0.0);
+        let halfway = start + HOLD + FADE / 2;
+        assert!((recent.shown(halfway)[0].1 - 0.5).abs() < 0.01);
+        assert!(!recent.prune(halfway));
+        assert!(recent.prune(start + HOLD + FADE));
+        assert!(recent.shown(start + HOLD + FADE).is_empty());
+    }
+
+    #[test]
+    fn keeps_the_newest_few() {
+        let now = Instant::now();
+        let mut recent = Recent::default();
+        for vk in 0x41..0x41 + MAX_SHOWN as u32 + 2 {
+            recent.key_down(vk, String::new, now);
+            recent.key_up(vk);
+        }
+        let shown = labels(&recent, now);
+        assert_eq!(shown.len(), MAX_SHOWN);
+        assert_eq!(shown[0], "C");
+    }
+}
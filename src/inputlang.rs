@@ -0,0 +1,138 @@
+//! The input language of the focused window ("EN", "JP") and, for Japanese,
+//! Korean and Chinese, whether the IME is composing native text. Each window
+//! thread has its own layout, so this follows whatever has the focus.
+//! Sampled once per clock tick, since the IME may take a while to answer.
+
+use std::sync::Mutex;
+
+use windows::Win32::Foundation::{LPARAM, WPARAM};
+use windows::Win32::UI::Input::Ime::ImmGetDefaultIMEWnd;
+use windows::Win32::UI::Input::KeyboardAndMouse::GetKeyboardLayout;
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetForegroundWindow, GetWindowThreadProcessId, SendMessageTimeoutW, SMTO_ABORTIFHUNG,
+    WM_IME_CONTROL,
+};
+
+/// `WM_IME_CONTROL` command asking whether the IME is on.
+const IMC_GETOPENSTATUS: usize = 0x0005;
+/// How long a busy window may take to answer, in milliseconds.
+const IME_TIMEOUT_MS: u32 = 50;
+
+/// Short names by primary language ID; others show the language ID in hex.
+const LANGUAGES: &[(u16, &str)] = &[
+    (0x01, "AR"),
+    (0x04, "ZH"),
+    (0x05, "CS"),
+    (0x06, "DA"),
+    (0x07, "DE"),
+    (0x08, "EL"),
+    (0x09, "EN"),
+    (0x0A, "ES"),
+    (0x0B, "FI"),
+    (0x0C, "FR"),
+    (0x0D, "HE"),
+    (0x0E, "HU"),
+    (0x10, "IT"),
+    (0x11, "JP"),
+    (0x12, "KO"),
+    (0x13, "NL"),
+    (0x14, "NO"),
+    (0x15, "PL"),
+    (0x16, "PT"),
+    (0x19, "RU"),
+    (0x1D, "SV"),
+    (0x1E, "TH"),
+    (0x1F, "TR"),
+    (0x22, "UK"),
+    (0x2A, "VI"),
+];
+
+/// What the IME shows for native and for Latin input, for languages that use one.
+fn ime_marks(primary: u16) -> Option<(&'static str, &'static str)> {
+    match primary {
+        0x11 => Some(("あ", "A")),
+        0x12 => Some(("한", "A")),
+        0x04 => Some(("中", "英")),
+        _ => None,
+    }
+}
+
+/// "JP あ", "EN" or "0456" for a language ID and the IME's open state.
+pub fn label(langid: u16, ime_open: Option<bool>) -> String {
+    let primary = langid & 0x3FF;
+    let name = LANGUAGES
+        .iter()
+        .find(|(id, _)| *id == primary)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| format!("{langid:04X}"));
+    match (ime_marks(primary), ime_open) {
+        (Some((native, _)), Some(true)) => format!("{name} {native}"),
+        (Some((_, latin)), Some(false)) => format!("{name} {latin}"),
+        _ => name,
+    }
+}
+
+/// The label as of the last sample.
+static LAST: Mutex<Option<String>> = Mutex::new(None);
+
+/// Read the focused window's input language for `current`.
+pub fn sample() {
+    *LAST.lock().unwrap() = query();
+}
+
+/// The focused window's input language as of the last sample, or `None`
+/// with nothing focused.
+pub fn current() -> Option<String> {
+    LAST.lock().unwrap().clone()
+}
+
+/// Ask the focused window for its input language; may wait up to
+/// `IME_TIMEOUT_MS` on a busy IME.
+fn query() -> Option<String> {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_invalid() {
+            return None;
+        }
+        let thread = GetWindowThreadProcessId(hwnd, None);
+        // The low word of the layout handle is the language ID
+        let langid = (GetKeyboardLayout(thread).0 as usize & 0xFFFF) as u16;
+        let ime = ImmGetDefaultIMEWnd(hwnd);
+        let ime_open = (!ime.is_invalid()).then(|| {
+            let mut open = 0usize;
+            let answered = SendMessageTimeoutW(
+                ime,
+                WM_IME_CONTROL,
+                WPARAM(IMC_GETOPENSTATUS),
+                LPARAM(0),
+                SMTO_ABORTIFHUNG,
+                IME_TIMEOUT_MS,
+                Some(&mut open),
+            );
+            (answered.0 != 0).then_some(open != 0)
+        });
+        Some(label(langid, ime_open.flatten()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_common_languages() {
+        assert_eq!(label(0x0409, None), "EN");
+        assert_eq!(label(0x0809, None), "EN");
+        assert_eq!(label(0x0407, Some(false)), "DE");
+        assert_eq!(label(0x0456, None), "0456");
+    }
+
+    #[test]
+    fn shows_ime_state_for_east_asian_languages() {
+        assert_eq!(label(0x0411, Some(true)), "JP あ");
+        assert_eq!(label(0x0411, Some(false)), "JP A");
+        assert_eq!(label(0x0411, None), "JP");
+        assert_eq!(label(0x0412, Some(true)), "KO 한");
+        assert_eq!(label(0x0804, Some(false)), "ZH 英");
+    }
+}
@@ -0,0 +1,58 @@
+//! Whether the overlay was showing when ClockOR last ran, kept next to the
+//! exe so the clock can come back the way it was left.
+
+use std::path::PathBuf;
+
+fn path() -> PathBuf {
+    crate::config::app_dir().join("last_visible")
+}
+
+fn parse(text: &str) -> Option<bool> {
+    match text.trim() {
+        "1" => Some(true),
+        "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// What was last saved; `None` before the first save or if the file is unreadable.
+pub fn load() -> Option<bool> {
+    parse(&std::fs::read_to_string(path()).ok()?)
+}
+
+pub fn save(visible: bool) {
+    if let Err(e) = std::fs::write(path(), if visible { "1" } else { "0" }) {
+        crate::logging::error(format!("Couldn't save the overlay visibility: {e}"));
+    }
+}
+
+/// Whether to show the overlay at launch: the saved state when remembering
+/// it (once there is one), otherwise `start_visible`.
+pub fn initial(start_visible: bool, remember: bool, saved: Option<bool>) -> bool {
+    match saved {
+        Some(visible) if remember => visible,
+        _ => start_visible,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_only_what_save_writes() {
+        assert_eq!(parse("1"), Some(true));
+        assert_eq!(parse("0\r\n"), Some(false));
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("yes"), None);
+    }
+
+    #[test]
+    fn remembered_state_wins_once_saved() {
+        assert!(!initial(false, false, Some(true)));
+        assert!(initial(true, false, Some(false)));
+        assert!(initial(false, true, Some(true)));
+        assert!(!initial(true, true, Some(false)));
+        assert!(initial(true, true, None));
+    }
+}
@@ -0,0 +1,297 @@
+//! Per-pixel alpha presentation for the overlay via `UpdateLayeredWindow`.
+//!
+//! GDI text doesn't write an alpha channel, so the text is drawn twice — once on
+//! black and once on white — and each pixel's coverage is recovered from the
+//! difference. That lets the background panel and the text carry independent opacity.
+
+use windows::Win32::Foundation::{COLORREF, HANDLE, HWND, POINT, SIZE};
+use windows::Win32::Graphics::Gdi::{
+    CreateDIBSection, GdiFlush, SelectObject, AC_SRC_ALPHA, AC_SRC_OVER, BITMAPINFO,
+    BITMAPINFOHEADER, BI_RGB, BLENDFUNCTION, DIB_RGB_COLORS, HDC, HGDIOBJ,
+};
+use windows::Win32::UI::WindowsAndMessaging::{UpdateLayeredWindow, ULW_ALPHA};
+
+use crate::gdi::{MemoryDc, OwnedBitmap};
+
+/// Colors and opacities for one presented frame.
+pub struct Layer<'a> {
+    /// Panel color as RGB.
+    pub bg: [u8; 3],
+    pub bg_alpha: u8,
+    /// Premultiplied BGRA drawn over the panel and under the text, one pixel
+    /// per window pixel.
+    pub image: Option<&'a [u8]>,
+    pub text_alpha: u8,
+    /// Applied to the whole window on top of the per-pixel values (dim, flash).
+    pub window_alpha: u8,
+}
+
+/// Blend one pixel of text over the panel, returning premultiplied color + alpha.
+///
+/// `on_black`/`on_white` are the same pixel rendered on each background; `bg` must
+/// use the same channel order. The result keeps that order with alpha appended.
+#[cfg(test)]
+pub fn compose_pixel(
+    on_black: [u8; 3],
+    on_white: [u8; 3],
+    text_alpha: u8,
+    bg: [u8; 3],
+    bg_alpha: u8,
+) -> [u8; 4] {
+    compose_over(
+        on_black,
+        on_white,
+        text_alpha,
+        panel_pixel(bg, bg_alpha, None),
+    )
+}
+
+/// The panel with an optional premultiplied image pixel over it, premultiplied.
+pub fn panel_pixel(bg: [u8; 3], bg_alpha: u8, image: Option<[u8; 4]>) -> [u8; 4] {
+    let mut out = [0u8; 4];
+    for i in 0..3 {
+        out[i] = (bg[i] as u32 * bg_alpha as u32 / 255) as u8;
+    }
+    out[3] = bg_alpha;
+    let Some(image) = image else {
+        return out;
+    };
+    let keep = 255 - image[3] as u32;
+    for i in 0..4 {
+        out[i] = (image[i] as u32 + out[i] as u32 * keep / 255).min(255) as u8;
+    }
+    out
+}
+
+/// Blend one pixel of text over a premultiplied `under` pixel.
+pub fn compose_over(
+    on_black: [u8; 3],
+    on_white: [u8; 3],
+    text_alpha: u8,
+    under: [u8; 4],
+) -> [u8; 4] {
+    // On black a covered pixel is color * coverage (already premultiplied);
+    // on white it gains 255 * (1 - coverage), so the gap gives the coverage.
+    let gap = (0..3)
+        .map(|i| on_white[i].saturating_sub(on_black[i]))
+        .min()
+        .unwrap_or(255) as u32;
+    let coverage = 255 - gap;
+
+    let ta = text_alpha as u32;
+    let src_alpha = coverage * ta / 255;
+    let keep = 255 - src_alpha;
+    let mut out = [0u8; 4];
+    for i in 0..3 {
+        let src = on_black[i] as u32 * ta / 255;
+        out[i] = (src + under[i] as u32 * keep / 255).min(255) as u8;
+    }
+    out[3] = (src_alpha + under[3] as u32 * keep / 255) as u8;
+    out
+}
+
+/// A 32-bit top-down DIB selected into its own memory DC.
+struct Dib {
+    old: HGDIOBJ,
+    // Dropped in this order after the selection is undone: bitmap, then DC
+    _bitmap: OwnedBitmap,
+    dc: MemoryDc,
+    bits: *mut u8,
+    len: usize,
+}
+
+impl Dib {
+    unsafe fn new(w: i32, h: i32) -> Option<Self> {
+        let dc = MemoryDc::compatible();
+        let bmi = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: w,
+                biHeight: -h,
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut bits = std::ptr::null_mut();
+        let bitmap = OwnedBitmap::new(
+            CreateDIBSection(
+                dc.hdc(),
+                &bmi,
+                DIB_RGB_COLORS,
+                &mut bits,
+                HANDLE::default(),
+                0,
+            )
+            .ok()?,
+        );
+        let old = SelectObject(dc.hdc(), bitmap.handle());
+        Some(Self {
+            old,
+            _bitmap: bitmap,
+            dc,
+            bits: bits as *mut u8,
+            len: (w * h * 4) as usize,
+        })
+    }
+
+    unsafe fn pixels(&mut self) -> &mut [u8] {
+        std::slice::from_raw_parts_mut(self.bits, self.len)
+    }
+}
+
+impl Drop for Dib {
+    fn drop(&mut self) {
+        unsafe {
+            SelectObject(self.dc.hdc(), self.old);
+        }
+    }
+}
+
+/// A composed frame in a memory DC: premultiplied BGRA, ready for
+/// `UpdateLayeredWindow`.
+pub struct Frame {
+    dib: Dib,
+}
+
+impl Frame {
+    pub fn hdc(&self) -> HDC {
+        self.dib.dc.hdc()
+    }
+}
+
+/// Draw a frame with `draw` on black and on white and compose the two over
+/// the panel. Needs no window, so benchmarks run it on its own.
+pub fn render(size: (i32, i32), layer: &Layer, draw: impl Fn(HDC)) -> Option<Frame> {
+    let (w, h) = size;
+    if w <= 0 || h <= 0 {
+        return None;
+    }
+    unsafe {
+        let (mut black, mut white) = (Dib::new(w, h)?, Dib::new(w, h)?);
+        black.pixels().fill(0);
+        white.pixels().fill(255);
+        draw(black.dc.hdc());
+        draw(white.dc.hdc());
+        let _ = GdiFlush();
+
+        // DIB pixels are stored BGRA
+        let bg = [layer.bg[2], layer.bg[1], layer.bg[0]];
+        let white_px = white.pixels();
+        let image = layer.image.filter(|image| image.len() == white_px.len());
+        for (i, (px, w_px)) in black
+            .pixels()
+            .chunks_exact_mut(4)
+            .zip(white_px.chunks_exact(4))
+            .enumerate()
+        {
+            let image_px = image.map(|image| {
+                let p = &image[i * 4..i * 4 + 4];
+                [p[0], p[1], p[2], p[3]]
+            });
+            let composed = compose_over(
+                [px[0], px[1], px[2]],
+                [w_px[0], w_px[1], w_px[2]],
+                layer.text_alpha,
+                panel_pixel(bg, layer.bg_alpha, image_px),
+            );
+            px.copy_from_slice(&composed);
+        }
+        Some(Frame { dib: black })
+    }
+}
+
+/// Render a frame with `draw` and hand it to the window as its per-pixel alpha content.
+/// The window keeps its current position; `size` must match its current size.
+pub fn present(hwnd: HWND, size: (i32, i32), layer: &Layer, draw: impl Fn(HDC)) {
+    let Some(frame) = render(size, layer, draw) else {
+        return;
+    };
+    unsafe {
+        let blend = BLENDFUNCTION {
+            BlendOp: AC_SRC_OVER as u8,
+            BlendFlags: 0,
+            SourceConstantAlpha: layer.window_alpha,
+            AlphaFormat: AC_SRC_ALPHA as u8,
+        };
+        let _ = UpdateLayeredWindow(
+            hwnd,
+            HDC::default(),
+            None,
+            Some(&SIZE {
+                cx: size.0,
+                cy: size.1,
+            }),
+            frame.hdc(),
+            Some(&POINT::default()),
+            COLORREF(0),
+            Some(&blend),
+            ULW_ALPHA,
+        );
+    }
+    drop(frame);
+    crate::gdi::assert_released();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_pixel_shows_panel() {
+        let px = compose_pixel([0; 3], [255; 3], 255, [20, 40, 60], 128);
+        assert_eq!(px, [10, 20, 30, 128]);
+    }
+
+    #[test]
+    fn empty_pixel_without_panel_is_transparent() {
+        assert_eq!(
+            compose_pixel([0; 3], [255; 3], 255, [0; 3], 0),
+            [0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn solid_text_hides_panel() {
+        let px = compose_pixel([200, 100, 50], [200, 100, 50], 255, [255; 3], 255);
+        assert_eq!(px, [200, 100, 50, 255]);
+    }
+
+    #[test]
+    fn text_opacity_independent_of_panel() {
+        // Solid digits on a nearly invisible panel
+        let text = compose_pixel([255; 3], [255; 3], 255, [0; 3], 10);
+        assert_eq!(text[3], 255);
+        let panel = compose_pixel([0; 3], [255; 3], 255, [0; 3], 10);
+        assert_eq!(panel[3], 10);
+
+        // Faint digits on a solid panel
+        let text = compose_pixel([255; 3], [255; 3], 51, [0; 3], 255);
+        assert_eq!(text, [51, 51, 51, 255]);
+    }
+
+    #[test]
+    fn image_sits_between_panel_and_text() {
+        // Half-transparent red (premultiplied BGRA) over a solid black panel
+        let under = panel_pixel([0; 3], 255, Some([0, 0, 128, 128]));
+        assert_eq!(under, [0, 0, 128, 255]);
+        // Without a panel the image keeps its own alpha
+        assert_eq!(
+            panel_pixel([0; 3], 0, Some([0, 0, 128, 128])),
+            [0, 0, 128, 128]
+        );
+        // Solid text still covers it
+        let px = compose_over([255; 3], [255; 3], 255, under);
+        assert_eq!(px, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn antialiased_edge_is_partial() {
+        // White text at ~50% coverage
+        let px = compose_pixel([128; 3], [255; 3], 255, [0; 3], 0);
+        assert_eq!(px[3], 128);
+        assert_eq!(px[0], 128);
+    }
+}
@@ -0,0 +1,12 @@
+//! The config, formatting and rendering modules as a library, so the
+//! benchmarks in `benches/` can drive them without the tray app. The app
+//! itself is main.rs, which uses these modules from here.
+
+pub mod config;
+pub mod directwrite;
+pub mod gdi;
+pub mod layered;
+pub mod logging;
+pub mod policy;
+pub mod segments;
+pub mod template;
@@ -0,0 +1,258 @@
+//! Troubleshooting log: `clockor.log` next to the executable, and the Event
+//! Log window.
+//!
+//! ClockOR has no console, so errors that used to go to stderr are written
+//! here. Debug lines (hotkey presses, show/hide, config reloads, hotkey
+//! registration) are only written to the file while "Debug logging" is
+//! checked in the tray; the level changes immediately, without a restart.
+//! The most recent events of every level are also kept in memory for the
+//! Event Log window.
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use chrono::NaiveDateTime;
+use eframe::egui;
+
+/// Past this size the log is moved to `clockor.log.old` and started over.
+const MAX_BYTES: u64 = 512 * 1024;
+/// Events kept for the Event Log window; oldest are dropped beyond this.
+const RECENT_CAPACITY: usize = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Error,
+    Info,
+    Debug,
+}
+
+impl Level {
+    fn label(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub at: NaiveDateTime,
+    pub level: Level,
+    pub message: String,
+}
+
+/// Fixed-size list of recent events, newest last.
+#[derive(Debug, Default)]
+struct Recent {
+    events: VecDeque<Event>,
+}
+
+impl Recent {
+    const fn new() -> Self {
+        Self {
+            events: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, event: Event) {
+        if self.events.len() == RECENT_CAPACITY {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+}
+
+static DEBUG: AtomicBool = AtomicBool::new(false);
+static RECENT: Mutex<Recent> = Mutex::new(Recent::new());
+/// Serializes writers so lines from different threads don't interleave.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+pub fn path() -> PathBuf {
+    crate::config::app_dir().join("clockor.log")
+}
+
+/// Raise the filter to debug, or drop it back to errors and info.
+pub fn set_debug(enabled: bool) {
+    DEBUG.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether `level` passes the current filter.
+fn enabled(level: Level) -> bool {
+    level != Level::Debug || DEBUG.load(Ordering::Relaxed)
+}
+
+fn format_line(at: NaiveDateTime, level: Level, message: &str) -> String {
+    format!(
+        "{} {:<5} {message}\n",
+        at.format("%Y-%m-%d %H:%M:%S%.3f"),
+        level.label()
+    )
+}
+
+fn write(level: Level, message: String) {
+    let at = chrono::Local::now().naive_local();
+    let line = enabled(level).then(|| format_line(at, level, &message));
+    RECENT.lock().unwrap().push(Event { at, level, message });
+    let Some(line) = line else {
+        return;
+    };
+    let _guard = FILE_LOCK.lock().unwrap();
+    let path = path();
+    if std::fs::metadata(&path).is_ok_and(|m| m.len() > MAX_BYTES) {
+        let _ = std::fs::rename(&path, path.with_extension("log.old"));
+    }
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+    {
+        let _ = file.write_all(line.as_bytes());
+    }
+}
+
+pub fn error(message: impl Display) {
+    write(Level::Error, message.to_string());
+}
+
+pub fn info(message: impl Display) {
+    write(Level::Info, message.to_string());
+}
+
+/// Detail for troubleshooting: always shown in the Event Log window, written
+/// to the file only while debug logging is on.
+pub fn debug(message: impl Display) {
+    write(Level::Debug, message.to_string());
+}
+
+struct EventLogApp;
+
+impl eframe::App for EventLogApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Background threads (update check, sounds) can log while this is open
+        ctx.request_repaint_after(std::time::Duration::from_secs(1));
+        egui::TopBottomPanel::bottom("actions").show(ctx, |ui| {
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                if ui.button("Clear").clicked() {
+                    RECENT.lock().unwrap().events.clear();
+                }
+                if ui.button("Open Log File").clicked() {
+                    let _ = std::process::Command::new("explorer")
+                        .arg(format!("/select,{}", path().display()))
+                        .spawn();
+                }
+                if ui.button("Close").clicked() {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+            });
+            ui.add_space(6.0);
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("Event Log");
+            ui.label(
+                egui::RichText::new(
+                    "Hotkeys can't be received while a ClockOR window is open: \
+                     close this, press the hotkey, then reopen it.",
+                )
+                .small()
+                .weak(),
+            );
+            ui.add_space(4.0);
+            let recent = RECENT.lock().unwrap();
+            if recent.events.is_empty() {
+                ui.label("Nothing has happened yet.");
+                return;
+            }
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("events")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for event in recent.events.iter().rev() {
+                            ui.monospace(event.at.format("%H:%M:%S").to_string());
+                            let label = egui::RichText::new(event.level.label()).monospace();
+                            match event.level {
+                                Level::Error => ui.label(label.color(ui.visuals().error_fg_color)),
+                                Level::Info => ui.label(label),
+                                Level::Debug => ui.label(label.weak()),
+                            };
+                            ui.label(&event.message);
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+    }
+}
+
+/// Open the Event Log window. Blocks until it is closed, like the history window.
+pub fn open_event_log() {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([480.0, 380.0])
+            .with_always_on_top(),
+        ..Default::default()
+    };
+    let _ = eframe::run_native(
+        "ClockOR Event Log",
+        options,
+        Box::new(|_cc| Ok(Box::new(EventLogApp))),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn line_has_timestamp_and_padded_level() {
+        let at = NaiveDate::from_ymd_opt(2026, 3, 1)
+            .unwrap()
+            .and_hms_milli_opt(9, 5, 0, 42)
+            .unwrap();
+        assert_eq!(
+            format_line(at, Level::Info, "Hotkey 1"),
+            "2026-03-01 09:05:00.042 INFO  Hotkey 1\n"
+        );
+        assert_eq!(
+            format_line(at, Level::Error, "x"),
+            "2026-03-01 09:05:00.042 ERROR x\n"
+        );
+    }
+
+    #[test]
+    fn recent_drops_oldest_beyond_capacity() {
+        let mut recent = Recent::new();
+        let at = NaiveDate::from_ymd_opt(2026, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        for i in 0..RECENT_CAPACITY + 3 {
+            recent.push(Event {
+                at,
+                level: Level::Debug,
+                message: format!("#{i}"),
+            });
+        }
+        assert_eq!(recent.events.len(), RECENT_CAPACITY);
+        assert_eq!(recent.events.front().unwrap().message, "#3");
+    }
+
+    #[test]
+    fn debug_lines_follow_the_toggle() {
+        assert!(enabled(Level::Error));
+        set_debug(true);
+        assert!(enabled(Level::Debug));
+        set_debug(false);
+        assert!(!enabled(Level::Debug));
+        assert!(enabled(Level::Info));
+    }
+}
@@ -1,72 +1,306 @@
 #![windows_subsystem = "windows"]
 
-mod config;
+mod actions;
+mod autostart;
+mod backdrop;
+mod bgimage;
+mod cli;
+mod command;
+mod cpu;
+mod diagnostics;
+mod doubletap;
+mod edgeflash;
+mod elevation;
+mod emoji;
+mod foreground;
+mod frame;
+mod frametime;
+mod gamebar;
+mod history;
+mod hooks;
+mod hotcorner;
+mod idle;
+mod inputdisplay;
+mod inputlang;
+mod laststate;
+mod monitors;
+mod notify;
 mod overlay;
+mod playtime;
+mod plugins;
+mod power;
+mod reminders;
+mod rumble;
+mod schedule;
+mod script;
 mod settings;
+mod shellstate;
+mod shutdown;
+mod sound;
+mod telemetry;
+mod textfile;
+mod theme;
+mod themefile;
+mod uia;
+mod update;
+mod whatsnew;
+mod zorder;
 
-use config::Config;
+use actions::ActionTracker;
+use clockor::{config, directwrite, gdi, layered, logging, policy, segments, template};
+use config::{
+    hotkey_candidates, parse_hotkey, ActionKind, Config, DoubleTapKey, HookEvent, SettingsSection,
+    TrayAction,
+};
+use hotcorner::HotCornerTracker;
 use overlay::Overlay;
+use playtime::PlayTracker;
+use reminders::ReminderTracker;
 
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
 
-use muda::{Menu, MenuEvent, MenuItem};
-use tray_icon::{Icon, MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use muda::{CheckMenuItem, Menu, MenuEvent, MenuItem};
+use tray_icon::{Icon, MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
 
-use windows::Win32::Foundation::{ERROR_ALREADY_EXISTS, HWND};
+use windows::Win32::Foundation::{CloseHandle, ERROR_ALREADY_EXISTS, HWND};
+use windows::Win32::System::SystemInformation::{GetTickCount, GetTickCount64};
 use windows::Win32::System::Threading::CreateMutexW;
 use windows::Win32::UI::HiDpi::{
     SetProcessDpiAwarenessContext, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
 };
 use windows::Win32::UI::Input::KeyboardAndMouse::{
-    RegisterHotKey, UnregisterHotKey, HOT_KEY_MODIFIERS,
+    GetDoubleClickTime, RegisterHotKey, UnregisterHotKey, HOT_KEY_MODIFIERS, VK_ESCAPE,
 };
 use windows::Win32::UI::WindowsAndMessaging::{
-    DispatchMessageW, MessageBoxW, MsgWaitForMultipleObjects, PeekMessageW, TranslateMessage,
-    MB_ICONWARNING, MB_OK, MSG, PM_REMOVE, QS_ALLINPUT, WM_HOTKEY, WM_QUIT,
+    DispatchMessageW, GetMessageW, MessageBoxW, MsgWaitForMultipleObjects, PeekMessageW,
+    TranslateMessage, IDYES, MB_DEFBUTTON2, MB_ICONINFORMATION, MB_ICONQUESTION, MB_ICONWARNING,
+    MB_OK, MB_YESNO, MSG, PM_REMOVE, QS_ALLINPUT, WM_HOTKEY, WM_QUIT,
 };
 
 const HOTKEY_ID: i32 = 1;
+const LOCK_HOTKEY_ID: i32 = 2;
+const FORMAT_HOTKEY_ID: i32 = 3;
+const SNOOZE_HOTKEY_ID: i32 = 4;
+const ZOOM_HOTKEY_ID: i32 = 5;
+const DEBUG_HOTKEY_ID: i32 = 6;
+/// Esc, registered only while previewing.
+const PREVIEW_EXIT_HOTKEY_ID: i32 = 7;
+const COUNTDOWN_HOTKEY_ID: i32 = 8;
+
+/// How often to look for an elevated foreground window while the notice is pending.
+const ELEVATION_CHECK_INTERVAL: Duration = Duration::from_secs(2);
 
 static OVERLAY_VISIBLE: AtomicBool = AtomicBool::new(false);
 static HOTKEY_REREGISTER: AtomicBool = AtomicBool::new(false);
+static HIDE_REQUESTED: AtomicBool = AtomicBool::new(false);
+static THEME_CHANGED: AtomicBool = AtomicBool::new(false);
+/// config.toml's timestamp when it was last loaded, seeded at startup.
+static CONFIG_SEEN: Mutex<Option<SystemTime>> = Mutex::new(None);
+/// The exclusive-fullscreen explanation is shown at most once per run.
+static EXCLUSIVE_WARNED: AtomicBool = AtomicBool::new(false);
 
 pub fn request_hotkey_reregister() {
     HOTKEY_REREGISTER.store(true, Ordering::Relaxed);
 }
 
-fn register_hotkey(config: &Config) -> bool {
-    let (modifiers, vk) = config.parsed_hotkey();
-    unsafe { RegisterHotKey(HWND::default(), HOTKEY_ID, HOT_KEY_MODIFIERS(modifiers), vk).is_ok() }
+/// Ask the main loop to hide the overlay (from the overlay's own context menu).
+pub fn request_overlay_hide() {
+    HIDE_REQUESTED.store(true, Ordering::Relaxed);
 }
 
-fn unregister_hotkey() {
-    unsafe {
-        let _ = UnregisterHotKey(HWND::default(), HOTKEY_ID);
+/// Record whether the overlay is showing for the next launch, when that is
+/// remembered. Runs on every change and again on exit or sign-out, which also
+/// covers "Remember last state" being turned on mid-session.
+pub fn save_visibility() {
+    if overlay::get_config().remember_visibility {
+        laststate::save(OVERLAY_VISIBLE.load(Ordering::Relaxed));
     }
 }
 
-fn show_hotkey_error(hotkey: &str) {
-    let msg: Vec<u16> = format!(
-        "Failed to register hotkey: {hotkey}\n\
-         Another application may already be using this key combination."
-    )
-    .encode_utf16()
-    .chain(std::iter::once(0))
-    .collect();
-    let title: Vec<u16> = "ClockOR".encode_utf16().chain(std::iter::once(0)).collect();
-    unsafe {
-        let _ = MessageBoxW(
-            HWND::default(),
-            windows::core::PCWSTR(msg.as_ptr()),
-            windows::core::PCWSTR(title.as_ptr()),
-            MB_OK | MB_ICONWARNING,
+/// Ask the main loop to re-read the system theme (after `WM_SETTINGCHANGE` and
+/// other theme or color broadcasts).
+pub fn request_theme_refresh() {
+    THEME_CHANGED.store(true, Ordering::Relaxed);
+}
+
+fn register_hotkey(id: i32, (modifiers, vk): (u32, u32)) -> bool {
+    let result = unsafe { RegisterHotKey(HWND::default(), id, HOT_KEY_MODIFIERS(modifiers), vk) };
+    if let Err(e) = &result {
+        logging::error(format!("RegisterHotKey ({}) failed: {e}", hotkey_name(id)));
+    }
+    result.is_ok()
+}
+
+/// What a hotkey ID does, for the log.
+fn hotkey_name(id: i32) -> &'static str {
+    match id {
+        HOTKEY_ID => "toggle",
+        LOCK_HOTKEY_ID => "lock",
+        FORMAT_HOTKEY_ID => "format",
+        SNOOZE_HOTKEY_ID => "snooze",
+        ZOOM_HOTKEY_ID => "zoom",
+        DEBUG_HOTKEY_ID => "debug",
+        COUNTDOWN_HOTKEY_ID => "countdown",
+        _ => "unknown",
+    }
+}
+
+/// An alert the snooze hotkey (or the toggle hotkey) can snooze.
+enum Ringing {
+    /// A reminder whose banner is showing.
+    Reminder(config::DailyEvent),
+    /// The countdown at zero, until it is stopped or snoozed.
+    Countdown,
+}
+
+/// What a reminder or the finished countdown does to get attention.
+struct Alert<'a> {
+    /// Notification center title and text, for alarms and the countdown.
+    notice: Option<(String, &'a str)>,
+    sound: bool,
+    hook: HookEvent,
+    detail: &'a str,
+}
+
+/// Edge flash, notification and overlay flash, controller rumble, hooks and
+/// the chime, so a reminder or timer gets through with or without sound.
+/// The caller checks `presenting()`; `muted` only silences the chime.
+fn raise_alert(
+    overlay: &Overlay,
+    edge_flash: &edgeflash::EdgeFlash,
+    cfg: &Config,
+    alert: Alert,
+    muted: bool,
+) {
+    edge_flash.alert(cfg);
+    // Alarms also reach the notification center, in case the screen is unattended
+    if let Some((title, text)) = &alert.notice {
+        notify::show(overlay.hwnd, notify::Notice::Alarm, title, text);
+        if !theme::reduce_motion(cfg) {
+            overlay.flash();
+        }
+    }
+    if cfg.controller_rumble {
+        rumble::pulse();
+    }
+    hooks::fire(cfg, alert.hook, alert.detail);
+    if alert.sound && !muted {
+        sound::play_chime(&cfg.audio_device, cfg.chime_volume);
+    }
+}
+
+/// Outcome of registering the hotkeys, reported once the overlay can show it.
+#[derive(Default)]
+struct Registration {
+    /// Fallback that replaced a taken toggle hotkey.
+    fallback: Option<String>,
+    /// Bindings that could not be registered.
+    failed: Vec<String>,
+}
+
+/// Register the toggle hotkey and any optional bindings.
+/// If the toggle hotkey is taken, the first free fallback is registered and saved.
+fn register_hotkeys(config: &Config) -> Registration {
+    let mut result = Registration::default();
+    let registered = hotkey_candidates(&config.hotkey)
+        .into_iter()
+        .find(|(_, binding)| register_hotkey(HOTKEY_ID, *binding));
+    match registered {
+        Some((name, _)) if name != config.hotkey => {
+            let mut fresh = Config::load();
+            fresh.hotkey = name.clone();
+            if let Err(e) = fresh.save() {
+                logging::error(format!("Failed to save config: {e}"));
+            }
+            overlay::update_config(&fresh);
+            result.fallback = Some(name);
+        }
+        Some(_) => {}
+        None => result.failed.push(config.hotkey.clone()),
+    }
+    // Optional bindings are skipped when empty or unparsable
+    for (id, hotkey) in optional_bindings(config) {
+        if let Some(binding) = parse_hotkey(hotkey) {
+            if !register_hotkey(id, binding) {
+                result.failed.push(hotkey.to_string());
+            }
+        }
+    }
+    if !doubletap::apply(config.double_tap) {
+        result
+            .failed
+            .push(format!("double-tap {:?}", config.double_tap));
+    }
+    logging::debug(format!(
+        "Hotkeys registered (toggle {}); failed: {:?}",
+        result.fallback.as_deref().unwrap_or(&config.hotkey),
+        result.failed
+    ));
+    result
+}
+
+/// Tell the user about substituted or failed hotkeys without blocking:
+/// a banner for a fallback, a tray balloon (click to fix) for failures.
+fn announce_registration(overlay: &Overlay, registration: Registration) {
+    if let Some(hotkey) = registration.fallback {
+        let cfg = overlay::get_config();
+        overlay.show_banner(&format!("Hotkey: {hotkey}"), cfg.banner_seconds);
+    }
+    if !registration.failed.is_empty() {
+        notify::show(
+            overlay.hwnd,
+            notify::Notice::HotkeyFailed,
+            "ClockOR: hotkey unavailable",
+            &format!(
+                "Failed to register: {}\n\
+                 Another application may be using it. Click to fix…",
+                registration.failed.join(", ")
+            ),
         );
     }
 }
 
+fn optional_bindings(config: &Config) -> [(i32, &str); 6] {
+    [
+        (LOCK_HOTKEY_ID, &config.lock_hotkey),
+        (FORMAT_HOTKEY_ID, &config.format_hotkey),
+        (SNOOZE_HOTKEY_ID, &config.snooze_hotkey),
+        (ZOOM_HOTKEY_ID, &config.zoom_hotkey),
+        (DEBUG_HOTKEY_ID, &config.debug_hotkey),
+        (COUNTDOWN_HOTKEY_ID, &config.countdown_hotkey),
+    ]
+}
+
+fn unregister_hotkeys() {
+    unsafe {
+        for id in [
+            HOTKEY_ID,
+            LOCK_HOTKEY_ID,
+            FORMAT_HOTKEY_ID,
+            SNOOZE_HOTKEY_ID,
+            ZOOM_HOTKEY_ID,
+            DEBUG_HOTKEY_ID,
+            COUNTDOWN_HOTKEY_ID,
+        ] {
+            let _ = UnregisterHotKey(HWND::default(), id);
+        }
+    }
+    doubletap::apply(DoubleTapKey::Off);
+}
+
+/// Circle color of the icon; the light-taskbar variant is darker for contrast.
+const ICON_BLUE: [u8; 3] = [100, 180, 255];
+const ICON_BLUE_ON_LIGHT: [u8; 3] = [20, 90, 200];
+
 /// Generate RGBA pixel data for the app icon at the given size.
 /// Blue circle with white clock hands.
 pub fn generate_icon_rgba(size: u32) -> Vec<u8> {
+    icon_rgba(size, ICON_BLUE)
+}
+
+fn icon_rgba(size: u32, circle: [u8; 3]) -> Vec<u8> {
     let mut rgba = vec![0u8; (size * size * 4) as usize];
     let center = (size / 2) as f32;
     let radius = center - 1.0;
@@ -80,9 +314,7 @@ pub fn generate_icon_rgba(size: u32) -> Vec<u8> {
             let dist = (dx * dx + dy * dy).sqrt();
 
             if dist <= radius {
-                rgba[idx] = 100;
-                rgba[idx + 1] = 180;
-                rgba[idx + 2] = 255;
+                rgba[idx..idx + 3].copy_from_slice(&circle);
                 rgba[idx + 3] = 255;
             }
         }
@@ -119,73 +351,466 @@ pub fn generate_icon_rgba(size: u32) -> Vec<u8> {
     rgba
 }
 
-fn create_default_icon() -> Icon {
+/// Paint a red dot in the bottom-right corner to signal a running timer.
+fn add_badge(rgba: &mut [u8], size: u32) {
+    let radius = size as f32 * 0.28;
+    let center = size as f32 - radius - 0.5;
+    for y in 0..size {
+        for x in 0..size {
+            let dx = x as f32 - center;
+            let dy = y as f32 - center;
+            if dx * dx + dy * dy <= radius * radius {
+                let idx = ((y * size + x) * 4) as usize;
+                rgba[idx..idx + 4].copy_from_slice(&[230, 40, 40, 255]);
+            }
+        }
+    }
+}
+
+/// What the tray icon shows: the badge for a running stream timer or countdown
+/// and the taskbar theme it is drawn for.
+#[derive(Clone, Copy, PartialEq)]
+struct TrayLook {
+    stream_minutes: Option<u64>,
+    countdown_minutes: Option<u64>,
+    light_taskbar: bool,
+}
+
+impl TrayLook {
+    fn badged(&self) -> bool {
+        self.stream_minutes.is_some() || self.countdown_minutes.is_some()
+    }
+
+    fn tooltip(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(m) = self.stream_minutes {
+            parts.push(format!("Stream timer: {m} min"));
+        }
+        if let Some(m) = self.countdown_minutes {
+            parts.push(format!("Countdown: {m} min left"));
+        }
+        if parts.is_empty() {
+            TRAY_TOOLTIP.to_string()
+        } else {
+            format!("ClockOR - {}", parts.join(", "))
+        }
+    }
+}
+
+fn create_icon(look: TrayLook) -> Icon {
     let size = 16u32;
-    let rgba = generate_icon_rgba(size);
+    let circle = if look.light_taskbar {
+        ICON_BLUE_ON_LIGHT
+    } else {
+        ICON_BLUE
+    };
+    let mut rgba = icon_rgba(size, circle);
+    if look.badged() {
+        add_badge(&mut rgba, size);
+    }
     Icon::from_rgba(rgba, size, size).expect("Failed to create icon")
 }
 
-pub fn apply_autostart(config: &Config) {
-    use std::env;
-    use windows::core::HSTRING;
-    use windows::Win32::System::Registry::{
-        RegCloseKey, RegDeleteValueW, RegOpenKeyExW, RegSetValueExW, HKEY_CURRENT_USER, KEY_WRITE,
-        REG_SZ,
-    };
+const TRAY_TOOLTIP: &str = "ClockOR - Press hotkey to toggle";
+
+/// Bring the tray icon and tooltip in line with `look`.
+/// `shown` is the look last applied, so the tray is only touched when it changes.
+fn update_tray(tray: &TrayIcon, look: TrayLook, shown: &mut TrayLook) {
+    if look == *shown {
+        return;
+    }
+    if look.badged() != shown.badged() || look.light_taskbar != shown.light_taskbar {
+        let _ = tray.set_icon(Some(create_icon(look)));
+    }
+    if look.stream_minutes != shown.stream_minutes
+        || look.countdown_minutes != shown.countdown_minutes
+    {
+        let _ = tray.set_tooltip(Some(look.tooltip()));
+    }
+    *shown = look;
+}
+
+fn set_overlay_visible(overlay: &Overlay, visible: bool) {
+    logging::debug(if visible {
+        "Overlay shown"
+    } else {
+        "Overlay hidden"
+    });
+    if visible {
+        reload_if_edited();
+        overlay.show();
+    } else {
+        overlay.hide();
+    }
+    let was_visible = OVERLAY_VISIBLE.swap(visible, Ordering::Relaxed);
+    if visible != was_visible {
+        save_visibility();
+        let event = if visible {
+            HookEvent::Shown
+        } else {
+            HookEvent::Hidden
+        };
+        hooks::fire(&overlay::get_config(), event, "");
+    }
+}
 
-    let key_path = HSTRING::from("SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Run");
-    let value_name = HSTRING::from("ClockOR");
+/// Pick up hand edits to config.toml before showing. The settings window and
+/// `--set` push their changes already, so the file is only parsed again when
+/// its timestamp has moved instead of on every show. A missing file keeps the
+/// defaults already loaded until one appears.
+fn reload_if_edited() {
+    let modified = config::file_modified();
+    let mut seen = CONFIG_SEEN.lock().unwrap();
+    if modified != *seen {
+        *seen = modified;
+        overlay::update_config(&Config::load());
+    }
+}
 
+fn toggle_overlay(overlay: &Overlay) {
+    let was_visible = OVERLAY_VISIBLE.load(Ordering::Relaxed);
+    set_overlay_visible(overlay, !was_visible);
+    let config = overlay::get_config();
+    if !was_visible && config.toggle_flash && !theme::reduce_motion(&config) {
+        overlay.flash();
+    }
+    if !was_visible {
+        warn_if_exclusive_fullscreen(overlay);
+    }
+}
+
+/// The overlay was just shown but can't be seen over an exclusive-fullscreen
+/// game; say why instead of letting the hotkey look broken.
+fn warn_if_exclusive_fullscreen(overlay: &Overlay) {
+    if shellstate::current() != shellstate::ShellState::ExclusiveFullscreen
+        || EXCLUSIVE_WARNED.swap(true, Ordering::Relaxed)
+    {
+        return;
+    }
+    history::record("Clock hidden behind an exclusive fullscreen game");
+    notify::show(
+        overlay.hwnd,
+        notify::Notice::ExclusiveFullscreen,
+        "ClockOR can't draw over this game",
+        "The game is in exclusive fullscreen. Switch it to borderless \
+         (windowed fullscreen) in its display settings to see the clock, \
+         or press Win+G: Game Bar shows the time over any game.",
+    );
+}
+
+/// Ask "Quit ClockOR?" when the user opted into a confirmation; true means quit.
+fn confirm_quit() -> bool {
+    if !overlay::get_config().confirm_quit {
+        return true;
+    }
+    let msg: Vec<u16> = "Quit ClockOR? The hotkey will stop working."
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    let title: Vec<u16> = "ClockOR".encode_utf16().chain(std::iter::once(0)).collect();
     unsafe {
-        let mut hkey = windows::Win32::System::Registry::HKEY::default();
-        let result = RegOpenKeyExW(HKEY_CURRENT_USER, &key_path, 0, KEY_WRITE, &mut hkey);
-        if result.is_err() {
-            return;
+        MessageBoxW(
+            HWND::default(),
+            windows::core::PCWSTR(msg.as_ptr()),
+            windows::core::PCWSTR(title.as_ptr()),
+            MB_YESNO | MB_ICONQUESTION | MB_DEFBUTTON2,
+        ) == IDYES
+    }
+}
+
+/// Explain why the hotkey doesn't reach an elevated game; true to run elevated from now on.
+fn explain_elevation() -> bool {
+    let msg: Vec<u16> = "The program in front is running as administrator. Windows doesn't \
+                         let hotkeys from normal programs reach it, so ClockOR's hotkey won't \
+                         work while it has focus.\n\n\
+                         Run ClockOR as administrator from now on? You can change this later \
+                         under Settings > System > Run as administrator."
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    let title: Vec<u16> = "ClockOR".encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe {
+        MessageBoxW(
+            HWND::default(),
+            windows::core::PCWSTR(msg.as_ptr()),
+            windows::core::PCWSTR(title.as_ptr()),
+            MB_YESNO | MB_ICONINFORMATION,
+        ) == IDYES
+    }
+}
+
+/// Carry out a tray click action.
+fn run_tray_action(overlay: &Overlay, item_stream: &MenuItem, action: TrayAction) {
+    match action {
+        TrayAction::Toggle => toggle_overlay(overlay),
+        TrayAction::Settings => open_settings(overlay, None),
+        TrayAction::StreamTimer => toggle_stream_timer(overlay, item_stream),
+        TrayAction::Nothing => {}
+    }
+}
+
+/// Start or stop the stream timer and keep the tray menu label in step.
+fn toggle_stream_timer(overlay: &Overlay, item: &MenuItem) {
+    let elapsed = overlay.stream_minutes();
+    if overlay.toggle_stream_timer() {
+        item.set_text("Stop Stream Timer");
+        history::record("Stream timer started");
+        hooks::fire(&overlay::get_config(), HookEvent::StreamStarted, "");
+    } else {
+        item.set_text("Start Stream Timer");
+        history::record(format!(
+            "Stream timer stopped after {} min",
+            elapsed.unwrap_or(0)
+        ));
+        hooks::fire(
+            &overlay::get_config(),
+            HookEvent::StreamStopped,
+            &format!("{}m", elapsed.unwrap_or(0)),
+        );
+    }
+}
+
+/// Start or stop the countdown and keep the tray menu label in step.
+fn toggle_countdown(overlay: &Overlay, item: &MenuItem) {
+    if overlay.toggle_countdown() {
+        item.set_text("Stop Countdown");
+        history::record("Countdown started");
+    } else {
+        item.set_text("Start Countdown");
+        history::record("Countdown stopped");
+    }
+}
+
+/// Do what a scheduled action says. Showing is held back during a presentation.
+fn run_scheduled_action(
+    overlay: &Overlay,
+    stream_item: &MenuItem,
+    action: &config::ScheduledAction,
+    presenting: bool,
+) {
+    let label = action.action.label();
+    let visible = OVERLAY_VISIBLE.load(Ordering::Relaxed);
+    let timing = overlay.stream_minutes().is_some();
+    match action.action {
+        ActionKind::ShowOverlay if !visible && !presenting => {
+            set_overlay_visible(overlay, true);
+        }
+        ActionKind::HideOverlay if visible => set_overlay_visible(overlay, false),
+        ActionKind::StartTimer if !timing => toggle_stream_timer(overlay, stream_item),
+        ActionKind::StopTimer if timing => toggle_stream_timer(overlay, stream_item),
+        ActionKind::ApplyTheme => {
+            let path = themefile::resolve(&action.target);
+            match themefile::Theme::load(&path) {
+                Ok(theme) => {
+                    let mut config = overlay::get_config();
+                    theme.apply(&mut config);
+                    policy::current().apply(&mut config);
+                    overlay::update_config(&config);
+                    if let Err(e) = config.save() {
+                        logging::error(format!("Failed to save config: {e}"));
+                    }
+                    overlay.refresh();
+                }
+                Err(e) => {
+                    logging::error(format!("Scheduled theme {}: {e}", path.display()));
+                    return;
+                }
+            }
+        }
+        ActionKind::Webhook if !action.target.trim().is_empty() => {
+            hooks::run(action.target.trim(), HookEvent::Scheduled, label);
         }
+        _ => return,
+    }
+    history::record(format!("Scheduled at {}: {label}", action.time.trim()));
+    hooks::fire(&overlay::get_config(), HookEvent::Scheduled, label);
+}
+
+/// Flip between 24h and 12h display and persist the choice.
+fn toggle_time_format(overlay: &Overlay) {
+    let mut config = overlay::get_config();
+    config.format_24h = !config.format_24h;
+    overlay::update_config(&config);
+    if let Err(e) = config.save() {
+        logging::error(format!("Failed to save config: {e}"));
+    }
+    overlay.refresh();
+}
 
-        if config.start_with_windows {
-            if let Ok(exe_path) = env::current_exe() {
-                let path_str = exe_path.to_string_lossy();
-                let wide: Vec<u16> = path_str.encode_utf16().chain(std::iter::once(0)).collect();
-                let byte_len = wide.len() * std::mem::size_of::<u16>();
-                let bytes = std::slice::from_raw_parts(wide.as_ptr().cast::<u8>(), byte_len);
-                let _ = RegSetValueExW(hkey, &value_name, 0, REG_SZ, Some(bytes));
+/// Write the diagnostics zip, or explain why it couldn't be written.
+fn collect_diagnostics() {
+    match diagnostics::collect(&overlay::get_config()) {
+        Ok(path) => history::record(format!("Diagnostics saved: {}", path.display())),
+        Err(e) => {
+            let msg: Vec<u16> = format!("Could not save the diagnostics file: {e}")
+                .encode_utf16()
+                .chain(std::iter::once(0))
+                .collect();
+            let title: Vec<u16> = "ClockOR".encode_utf16().chain(std::iter::once(0)).collect();
+            unsafe {
+                MessageBoxW(
+                    HWND::default(),
+                    windows::core::PCWSTR(msg.as_ptr()),
+                    windows::core::PCWSTR(title.as_ptr()),
+                    MB_OK | MB_ICONWARNING,
+                );
             }
-        } else {
-            let _ = RegDeleteValueW(hkey, &value_name);
         }
+    }
+}
 
-        let _ = RegCloseKey(hkey);
+/// Switch debug lines on or off and, when turning them on, show where they go.
+fn set_debug_logging(enabled: bool) {
+    logging::set_debug(enabled);
+    logging::info(format!(
+        "Debug logging {}",
+        if enabled { "on" } else { "off" }
+    ));
+    if enabled {
+        let _ = std::process::Command::new("explorer")
+            .arg(format!("/select,{}", logging::path().display()))
+            .spawn();
     }
 }
 
-fn toggle_overlay(overlay: &Overlay) {
-    let was_visible = OVERLAY_VISIBLE.load(Ordering::Relaxed);
-    if was_visible {
-        overlay.hide();
-        OVERLAY_VISIBLE.store(false, Ordering::Relaxed);
+/// Tray menu label for a found release: installs in place when it can.
+fn update_menu_text(release: &update::Release) -> String {
+    if release.install.is_some() {
+        format!("Update to v{}", release.version)
     } else {
-        let fresh = Config::load();
-        overlay::update_config(&fresh);
-        overlay.show();
-        OVERLAY_VISIBLE.store(true, Ordering::Relaxed);
+        format!("Update available: v{}", release.version)
+    }
+}
+
+/// Open a web page in the default browser.
+fn open_url(url: &str) {
+    use windows::core::HSTRING;
+    use windows::Win32::UI::Shell::ShellExecuteW;
+    use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+    unsafe {
+        ShellExecuteW(
+            HWND::default(),
+            &HSTRING::from("open"),
+            &HSTRING::from(url),
+            None,
+            None,
+            SW_SHOWNORMAL,
+        );
+    }
+}
+
+/// Open the settings window, optionally scrolled to `section`, then apply hotkey changes.
+fn open_settings(overlay: &Overlay, section: Option<SettingsSection>) {
+    // eframe/winit requires main thread on Windows — blocks until window closes
+    settings::open_settings(Config::load(), section);
+    // After settings closed, apply any hotkey changes
+    if HOTKEY_REREGISTER.swap(false, Ordering::Relaxed) {
+        unregister_hotkeys();
+        announce_registration(overlay, register_hotkeys(&Config::load()));
+    }
+}
+
+/// `--preview`: show the overlay right away with the current config or `file`,
+/// with no tray icon or hotkeys, until Esc is pressed. For screenshots, theme
+/// design and checking a config file; it runs alongside a normal instance.
+fn run_preview(file: Option<std::path::PathBuf>) {
+    let config = match &file {
+        Some(path) if !path.exists() => {
+            let msg: Vec<u16> = format!("Config file not found: {}", path.display())
+                .encode_utf16()
+                .chain(std::iter::once(0))
+                .collect();
+            let title: Vec<u16> = "ClockOR".encode_utf16().chain(std::iter::once(0)).collect();
+            unsafe {
+                MessageBoxW(
+                    HWND::default(),
+                    windows::core::PCWSTR(msg.as_ptr()),
+                    windows::core::PCWSTR(title.as_ptr()),
+                    MB_OK | MB_ICONWARNING,
+                );
+            }
+            return;
+        }
+        // A theme is previewed on top of the current settings
+        Some(path) if themefile::is_theme_file(path) => {
+            let mut config = Config::load();
+            if let Ok(theme) = themefile::Theme::load(path) {
+                theme.apply(&mut config);
+            }
+            config
+        }
+        Some(path) => Config::load_from(path),
+        None => Config::load(),
+    };
+    let overlay = Overlay::new(&config);
+    if std::env::args().any(|a| a == "--debug") {
+        overlay.toggle_debug();
+    }
+    overlay.show();
+    if !register_hotkey(PREVIEW_EXIT_HOTKEY_ID, (0, VK_ESCAPE.0 as u32)) {
+        // Esc is taken; the overlay's Hide menu item (when unlocked) still ends it
+        logging::error("Preview: Esc could not be registered");
+    }
+    let mut msg = MSG::default();
+    unsafe {
+        while GetMessageW(&mut msg, HWND::default(), 0, 0).as_bool() {
+            if msg.message == WM_HOTKEY && msg.wParam.0 as i32 == PREVIEW_EXIT_HOTKEY_ID {
+                break;
+            }
+            let _ = TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+            if HIDE_REQUESTED.swap(false, Ordering::Relaxed) {
+                break;
+            }
+        }
+        let _ = UnregisterHotKey(HWND::default(), PREVIEW_EXIT_HOTKEY_ID);
     }
+    overlay.destroy();
 }
 
 fn main() {
+    // Config commands run instead of the app, even while it is already running
+    if let Some(code) = cli::run() {
+        std::process::exit(code);
+    }
+
     // High-DPI awareness (ignore failure on older Windows)
     unsafe {
         let _ = SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2);
     }
 
+    if let Some(file) = cli::preview() {
+        run_preview(file);
+        return;
+    }
+
     // Single-instance check
     unsafe {
         let mutex_name: Vec<u16> = "Global\\ClockOR_SingleInstance"
             .encode_utf16()
             .chain(std::iter::once(0))
             .collect();
-        let _mutex = CreateMutexW(None, false, windows::core::PCWSTR(mutex_name.as_ptr()));
-        if windows::Win32::Foundation::GetLastError() == ERROR_ALREADY_EXISTS {
+        // Right after a self-update or elevated restart the previous process may still be exiting
+        let attempts = if std::env::args()
+            .any(|a| a == update::RELAUNCH_FLAG || a == elevation::RELAUNCHED_FLAG)
+        {
+            50
+        } else {
+            1
+        };
+        let mut running = true;
+        for _ in 0..attempts {
+            let mutex = CreateMutexW(None, false, windows::core::PCWSTR(mutex_name.as_ptr()));
+            running = windows::Win32::Foundation::GetLastError() == ERROR_ALREADY_EXISTS;
+            if !running {
+                break;
+            }
+            if let Ok(mutex) = mutex {
+                let _ = CloseHandle(mutex);
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        if running {
             let msg: Vec<u16> = "ClockOR is already running."
                 .encode_utf16()
                 .chain(std::iter::once(0))
@@ -201,77 +826,513 @@ fn main() {
         }
     }
 
+    update::remove_old_executable();
+
     let config = Config::load();
+    *CONFIG_SEEN.lock().unwrap() = config::file_modified();
+    // Run as administrator: hand over to an elevated copy unless UAC is declined
+    if elevation::wants_elevation(&config) && elevation::relaunch_elevated() {
+        return;
+    }
+    autostart::wait_before_start(&config);
 
     // Create overlay (hidden initially)
     let overlay = Overlay::new(&config);
-
-    // Register hotkey from config
-    if !register_hotkey(&config) {
-        show_hotkey_error(&config.hotkey);
+    let edge_flash = edgeflash::EdgeFlash::new();
+    if std::env::args().any(|a| a == "--debug") {
+        overlay.toggle_debug();
     }
 
+    // Register hotkeys from config
+    let registration = register_hotkeys(&config);
+
     // Build tray menu
     let menu = Menu::new();
+    let item_stream = MenuItem::new("Start Stream Timer", true, None);
+    let item_countdown = MenuItem::new("Start Countdown", true, None);
+    let item_history = MenuItem::new("History", true, None);
     let item_settings = MenuItem::new("Settings", true, None);
+    let item_diagnostics = MenuItem::new("Collect Diagnostics", true, None);
+    let item_event_log = MenuItem::new("Event Log", true, None);
+    let item_debug_log = CheckMenuItem::new("Debug logging", true, false, None);
     let item_quit = MenuItem::new("Quit", true, None);
+    let _ = menu.append(&item_stream);
+    let _ = menu.append(&item_countdown);
+    let _ = menu.append(&item_history);
     let _ = menu.append(&item_settings);
+    let _ = menu.append(&item_diagnostics);
+    let _ = menu.append(&item_event_log);
+    let _ = menu.append(&item_debug_log);
     let _ = menu.append(&item_quit);
 
+    // Shown at the top of the menu once a newer release is found
+    let item_update = MenuItem::new("Update available", true, None);
+    let mut pending_update: Option<update::Release> = None;
+
+    let stream_id = item_stream.id().clone();
+    let countdown_id = item_countdown.id().clone();
+    let update_id = item_update.id().clone();
+    let history_id = item_history.id().clone();
     let settings_id = item_settings.id().clone();
+    let diagnostics_id = item_diagnostics.id().clone();
+    let event_log_id = item_event_log.id().clone();
+    let debug_log_id = item_debug_log.id().clone();
     let quit_id = item_quit.id().clone();
 
     // Build tray icon
-    let icon = create_default_icon();
-    let _tray = TrayIconBuilder::new()
-        .with_tooltip("ClockOR - Press hotkey to toggle")
+    let mut tray_look = TrayLook {
+        stream_minutes: None,
+        countdown_minutes: None,
+        light_taskbar: theme::taskbar_is_light(),
+    };
+    let icon = create_icon(tray_look);
+    let tray = TrayIconBuilder::new()
+        .with_tooltip(TRAY_TOOLTIP)
         .with_icon(icon)
-        .with_menu(Box::new(menu))
+        .with_menu(Box::new(menu.clone()))
         .build()
         .expect("Failed to create tray icon");
 
+    // Report substituted or failed hotkeys once the tray is up
+    announce_registration(&overlay, registration);
+
+    if laststate::initial(
+        config.start_visible,
+        config.remember_visibility,
+        laststate::load(),
+    ) {
+        set_overlay_visible(&overlay, true);
+    }
+
+    if config.check_updates {
+        update::spawn_check();
+    }
+    telemetry::spawn_send(&config);
+
+    // First start after a version change: show what's new
+    whatsnew::show_if_updated();
+
+    let mut reminders = ReminderTracker::new(chrono::Local::now().naive_local());
+    let mut scheduled = ActionTracker::new(chrono::Local::now().naive_local());
+    // Edge-triggered so the hotkey can still hide the overlay inside a scheduled range
+    let mut schedule_active = false;
+    let mut schedule_shown = false;
+    // Overlay is logically visible but hidden because the user walked away
+    let mut idle_hidden = false;
+    // Same, while the Game Bar overlay is open
+    let mut game_bar_hidden = false;
+    let mut game_bar = gamebar::GameBarWatch::default();
+    let mut last_poll = chrono::Local::now().naive_local();
+    let mut last_uptime_ms = unsafe { GetTickCount64() };
+    // Reminder banner or finished countdown showing, so a hotkey can snooze it
+    let mut ringing: Option<Ringing> = None;
+    // One-time explanation of why the hotkey can't reach a game run as administrator
+    let mut elevation_notice_due = !elevation::notice_seen()
+        && !elevation::is_elevated()
+        && !policy::current().is_locked("run_elevated");
+    let mut last_elevation_check = Instant::now();
+
+    let mut hot_corner = HotCornerTracker::default();
+    let mut play = PlayTracker::default();
+    // The overlay was brought up for the current low-battery spell
+    let mut battery_alerted = false;
+    // Shutdown requests older than this session don't count
+    let session_start = chrono::Local::now();
+    let mut last_shutdown_check: Option<Instant> = None;
+    let mut shutdown_alerted = false;
+    // The current countdown's zero has been announced
+    let mut countdown_alerted = false;
+    // A left click on the tray icon waits out the double-click time when a
+    // double-click does something, so a double-click doesn't run both
+    let mut pending_left_click: Option<Instant> = None;
+    // The button-up that ends a double-click isn't a click of its own
+    let mut swallow_left_up = false;
+
     // Message loop
     let mut msg = MSG::default();
     'main_loop: loop {
         // Check if hotkey needs re-registration (from settings thread)
         if HOTKEY_REREGISTER.swap(false, Ordering::Relaxed) {
-            unregister_hotkey();
-            let fresh = Config::load();
-            if !register_hotkey(&fresh) {
-                show_hotkey_error(&fresh.hotkey);
-            }
+            unregister_hotkeys();
+            announce_registration(&overlay, register_hotkeys(&Config::load()));
         }
 
-        // Drain tray icon events (left-click toggle)
+        // Drain tray icon events and run the configured click actions
         while let Ok(event) = TrayIconEvent::receiver().try_recv() {
-            if let TrayIconEvent::Click {
-                button: MouseButton::Left,
-                button_state: MouseButtonState::Up,
-                ..
-            } = event
-            {
-                toggle_overlay(&overlay);
-            }
+            let cfg = overlay::get_config();
+            let action = match event {
+                TrayIconEvent::Click {
+                    button,
+                    button_state: MouseButtonState::Up,
+                    ..
+                } => match button {
+                    MouseButton::Left if std::mem::take(&mut swallow_left_up) => {
+                        TrayAction::Nothing
+                    }
+                    MouseButton::Left if cfg.tray_double_click != TrayAction::Nothing => {
+                        pending_left_click = Some(Instant::now());
+                        TrayAction::Nothing
+                    }
+                    MouseButton::Left => cfg.tray_left_click,
+                    MouseButton::Middle => cfg.tray_middle_click,
+                    MouseButton::Right => TrayAction::Nothing,
+                },
+                TrayIconEvent::DoubleClick {
+                    button: MouseButton::Left,
+                    ..
+                } => {
+                    pending_left_click = None;
+                    swallow_left_up = true;
+                    cfg.tray_double_click
+                }
+                _ => TrayAction::Nothing,
+            };
+            run_tray_action(&overlay, &item_stream, action);
+        }
+        let double_click_time = Duration::from_millis(unsafe { GetDoubleClickTime() } as u64);
+        if pending_left_click.is_some_and(|at| at.elapsed() >= double_click_time) {
+            pending_left_click = None;
+            run_tray_action(
+                &overlay,
+                &item_stream,
+                overlay::get_config().tray_left_click,
+            );
         }
 
         // Drain tray menu events
         while let Ok(event) = MenuEvent::receiver().try_recv() {
-            if event.id == settings_id {
-                // eframe/winit requires main thread on Windows — blocks until window closes
-                let cfg = Config::load();
-                settings::open_settings(cfg);
-                // After settings closed, apply any hotkey changes
-                if HOTKEY_REREGISTER.swap(false, Ordering::Relaxed) {
-                    unregister_hotkey();
-                    let fresh = Config::load();
-                    if !register_hotkey(&fresh) {
-                        show_hotkey_error(&fresh.hotkey);
+            if event.id == stream_id {
+                toggle_stream_timer(&overlay, &item_stream);
+            } else if event.id == countdown_id {
+                toggle_countdown(&overlay, &item_countdown);
+            } else if event.id == update_id {
+                match pending_update.as_ref().map(|r| (r, &r.install)) {
+                    Some((release, Some(install))) => {
+                        item_update.set_text(format!("Installing v{}…", release.version));
+                        item_update.set_enabled(false);
+                        update::spawn_install(install.clone());
                     }
+                    Some((release, None)) => open_url(&release.url),
+                    None => {}
                 }
-            } else if event.id == quit_id {
+            } else if event.id == history_id {
+                history::open_history();
+            } else if event.id == settings_id {
+                open_settings(&overlay, None);
+            } else if event.id == diagnostics_id {
+                collect_diagnostics();
+            } else if event.id == event_log_id {
+                logging::open_event_log();
+            } else if event.id == debug_log_id {
+                set_debug_logging(item_debug_log.is_checked());
+            } else if event.id == quit_id && confirm_quit() {
+                overlay.destroy();
+                break 'main_loop;
+            }
+        }
+
+        if let Some(release) = update::take_available() {
+            item_update.set_text(update_menu_text(&release));
+            if pending_update.is_none() {
+                let _ = menu.insert(&item_update, 0);
+            }
+            notify::show(
+                overlay.hwnd,
+                notify::Notice::UpdateAvailable,
+                "ClockOR update available",
+                &format!(
+                    "Version {} is available (you have {}). Click to open the download page.",
+                    release.version,
+                    update::CURRENT_VERSION
+                ),
+            );
+            pending_update = Some(release);
+        }
+
+        // A verified update is in place: hand over to the new executable
+        match update::take_install_result() {
+            Some(Ok(())) if update::relaunch() => {
                 overlay.destroy();
                 break 'main_loop;
             }
+            Some(result) => {
+                let reason = result.err().unwrap_or_else(|| "could not restart".into());
+                notify::show(
+                    overlay.hwnd,
+                    notify::Notice::UpdateAvailable,
+                    "ClockOR update failed",
+                    &format!("{reason}. Click to open the download page."),
+                );
+                if let Some(release) = &pending_update {
+                    item_update.set_text(update_menu_text(release));
+                }
+                item_update.set_enabled(true);
+            }
+            None => {}
+        }
+
+        match notify::take_clicked() {
+            // Jump straight to the hotkey settings
+            Some(notify::Notice::HotkeyFailed) => {
+                open_settings(&overlay, Some(SettingsSection::System));
+            }
+            Some(notify::Notice::UpdateAvailable) => {
+                if let Some(release) = &pending_update {
+                    open_url(&release.url);
+                }
+            }
+            Some(notify::Notice::ElevatedForeground) if explain_elevation() => {
+                let mut config = overlay::get_config();
+                config.run_elevated = true;
+                if let Err(e) = config.save() {
+                    logging::error(format!("Failed to save config: {e}"));
+                }
+                autostart::apply(&config, &overlay::get_config());
+                overlay::update_config(&config);
+                if elevation::relaunch_elevated() {
+                    overlay.destroy();
+                    break 'main_loop;
+                }
+            }
+            Some(
+                notify::Notice::ElevatedForeground
+                | notify::Notice::ExclusiveFullscreen
+                | notify::Notice::Alarm,
+            )
+            | None => {}
+        }
+
+        if elevation_notice_due && last_elevation_check.elapsed() >= ELEVATION_CHECK_INTERVAL {
+            last_elevation_check = Instant::now();
+            if elevation::foreground_is_elevated() {
+                elevation_notice_due = false;
+                elevation::mark_notice_seen();
+                history::record("Hotkey blocked by a program running as administrator");
+                notify::show(
+                    overlay.hwnd,
+                    notify::Notice::ElevatedForeground,
+                    "Program running as administrator",
+                    "ClockOR's hotkey can't reach it. Click for details.",
+                );
+            }
+        }
+
+        if HIDE_REQUESTED.swap(false, Ordering::Relaxed) && OVERLAY_VISIBLE.load(Ordering::Relaxed)
+        {
+            set_overlay_visible(&overlay, false);
+        }
+
+        if doubletap::take_triggered() {
+            toggle_overlay(&overlay);
+        }
+        let light_taskbar = if THEME_CHANGED.swap(false, Ordering::Relaxed) {
+            theme::taskbar_is_light()
+        } else {
+            tray_look.light_taskbar
+        };
+        let look = TrayLook {
+            stream_minutes: overlay.stream_minutes(),
+            countdown_minutes: overlay.countdown_minutes_left(),
+            light_taskbar,
+        };
+        update_tray(&tray, look, &mut tray_look);
+
+        let cfg = overlay::get_config();
+        let now = chrono::Local::now().naive_local();
+        // Only asked when something would pop up on its own
+        let presenting = || {
+            cfg.quiet_when_presenting && shellstate::current() == shellstate::ShellState::Presenting
+        };
+        let muted = || {
+            cfg.mute_in_fullscreen
+                && matches!(
+                    shellstate::current(),
+                    shellstate::ShellState::Busy | shellstate::ShellState::ExclusiveFullscreen
+                )
+        };
+
+        // Auto-show on entering a scheduled range, auto-hide on leaving it.
+        // Entering is held back during a presentation and picked up afterwards.
+        let active = schedule::is_active(&cfg.visibility_schedule, now);
+        if active != schedule_active && !(active && presenting()) {
+            schedule_active = active;
+            let visible = OVERLAY_VISIBLE.load(Ordering::Relaxed);
+            if active && !visible {
+                set_overlay_visible(&overlay, true);
+                history::record("Shown by schedule");
+                schedule_shown = true;
+            } else if !active && schedule_shown {
+                if visible {
+                    set_overlay_visible(&overlay, false);
+                    history::record("Hidden by schedule");
+                }
+                schedule_shown = false;
+            }
+        }
+
+        // Hide while the user is away, restore on the next input
+        let visible = OVERLAY_VISIBLE.load(Ordering::Relaxed);
+        let idle_limit = Duration::from_secs(cfg.idle_hide_minutes as u64 * 60);
+        let away = cfg.idle_hide_minutes > 0 && idle::idle_duration() >= idle_limit;
+        if visible && away && !idle_hidden {
+            overlay.hide();
+            idle_hidden = true;
+        } else if idle_hidden && (!away || !visible) {
+            if visible && !game_bar_hidden {
+                overlay.show();
+            }
+            idle_hidden = false;
+        }
+
+        // Step aside while the Game Bar overlay is open
+        let bar_open = cfg.hide_under_game_bar && game_bar.is_open();
+        if visible && bar_open && !game_bar_hidden {
+            overlay.hide();
+            game_bar_hidden = true;
+        } else if game_bar_hidden && (!bar_open || !visible) {
+            if visible && !idle_hidden {
+                overlay.show();
+            }
+            game_bar_hidden = false;
+        }
+
+        // Periodic auto-peek (e.g. on the hour)
+        if schedule::interval_crossed(last_poll, now, cfg.peek_interval_minutes) && !presenting() {
+            overlay.peek(cfg.peek_seconds);
+        }
+
+        // "You've been playing a while": show the clock until the hotkey hides it.
+        // Skipped during a presentation, like an auto-peek
+        if cfg.play_reveal_minutes > 0 {
+            let fullscreen = matches!(
+                shellstate::current(),
+                shellstate::ShellState::Busy | shellstate::ShellState::ExclusiveFullscreen
+            );
+            let limit = Duration::from_secs(cfg.play_reveal_minutes as u64 * 60);
+            if play.poll(fullscreen, Instant::now(), limit)
+                && !presenting()
+                && !OVERLAY_VISIBLE.load(Ordering::Relaxed)
+            {
+                set_overlay_visible(&overlay, true);
+                history::record(format!(
+                    "Shown after {} minutes of fullscreen play",
+                    cfg.play_reveal_minutes
+                ));
+            }
+        }
+
+        // Low battery: bring the clock up once; its battery segment turns red.
+        // Held back during a presentation and picked up afterwards
+        let battery_low = cfg.low_battery_percent > 0
+            && power::battery().is_some_and(|b| power::is_low(b, cfg.low_battery_percent));
+        if battery_low && !battery_alerted && !presenting() {
+            battery_alerted = true;
+            if !OVERLAY_VISIBLE.load(Ordering::Relaxed) {
+                set_overlay_visible(&overlay, true);
+            }
+            history::record("Shown for low battery");
+        } else if !battery_low {
+            battery_alerted = false;
+        }
+
+        // Pending shutdown or restart: read the System log now and then, and
+        // bring the clock up once per request, after any presentation
+        let shutdown_due = last_shutdown_check
+            .is_none_or(|at| at.elapsed() >= Duration::from_secs(shutdown::POLL_SECONDS));
+        if cfg.shutdown_warning && shutdown_due {
+            last_shutdown_check = Some(Instant::now());
+            let pending = shutdown::poll(session_start);
+            if pending.is_none() {
+                shutdown_alerted = false;
+            } else if !shutdown_alerted && !presenting() {
+                shutdown_alerted = true;
+                if !OVERLAY_VISIBLE.load(Ordering::Relaxed) {
+                    set_overlay_visible(&overlay, true);
+                }
+                history::record("Shown for a pending shutdown");
+            }
+        }
+
+        // Countdown at zero: bring the clock up and flash it once; its chip
+        // keeps flashing until the countdown is stopped. Held back during a
+        // presentation and picked up afterwards
+        let countdown_done = overlay.countdown_done();
+        if countdown_done && !countdown_alerted && !presenting() {
+            countdown_alerted = true;
+            if !OVERLAY_VISIBLE.load(Ordering::Relaxed) {
+                set_overlay_visible(&overlay, true);
+            }
+            history::record("Countdown finished");
+            let alert = Alert {
+                notice: Some(("ClockOR timer".to_string(), "Countdown finished")),
+                sound: true,
+                hook: HookEvent::TimerFinished,
+                detail: "",
+            };
+            raise_alert(&overlay, &edge_flash, &cfg, alert, muted());
+            ringing = Some(Ringing::Countdown);
+        } else if !countdown_done {
+            countdown_alerted = false;
+            if matches!(ringing, Some(Ringing::Countdown)) {
+                ringing = None;
+            }
+        }
+
+        // Peek when the pointer is pushed into the hot corner
+        if hot_corner.poll(cfg.hot_corner) {
+            overlay.peek(cfg.peek_seconds);
+        }
+
+        // Minute tick and hourly chime; the chime replaces the tick on the hour
+        let cue = if cfg.hourly_chime && schedule::interval_crossed(last_poll, now, 60) {
+            Some((sound::Cue::Chime, cfg.chime_volume))
+        } else if cfg.tick_sound && schedule::interval_crossed(last_poll, now, 1) {
+            Some((sound::Cue::Tick, cfg.tick_volume))
+        } else {
+            None
+        };
+        if let Some((cue, volume)) = cue {
+            if !presenting() && !muted() {
+                sound::play_cue(cue, volume, &cfg.audio_device);
+            }
+        }
+        last_poll = now;
+        let uptime_ms = unsafe { GetTickCount64() };
+        hooks::poll_uptime(&cfg, last_uptime_ms, uptime_ms);
+        last_uptime_ms = uptime_ms;
+
+        for action in scheduled.poll(&cfg.scheduled_actions, now) {
+            run_scheduled_action(&overlay, &item_stream, &action, presenting());
+        }
+
+        // Fire daily reminders, then end peeks and banners that have run their course
+        for event in reminders.poll(&cfg.daily_events, now) {
+            if presenting() {
+                history::record(format!("Reminder (muted while presenting): {}", event.name));
+                continue;
+            }
+            overlay.show_banner(&event.name, cfg.banner_seconds);
+            history::record(format!("Reminder: {}", event.name));
+            let alert = Alert {
+                notice: event.alarm.then(|| {
+                    (
+                        format!("ClockOR alarm ({})", event.time),
+                        event.name.as_str(),
+                    )
+                }),
+                sound: event.sound,
+                hook: HookEvent::Reminder,
+                detail: &event.name,
+            };
+            raise_alert(&overlay, &edge_flash, &cfg, alert, muted());
+            ringing = Some(Ringing::Reminder(event));
+        }
+        if overlay.expire_peek() {
+            // A countdown still at zero rings on after the banner
+            ringing = (countdown_alerted && overlay.countdown_done()).then_some(Ringing::Countdown);
+            if !OVERLAY_VISIBLE.load(Ordering::Relaxed) || idle_hidden || game_bar_hidden {
+                overlay.hide();
+            }
         }
 
         // Process Win32 messages
@@ -281,8 +1342,57 @@ fn main() {
                     break 'main_loop;
                 }
 
-                if msg.message == WM_HOTKEY && msg.wParam.0 == HOTKEY_ID as usize {
-                    toggle_overlay(&overlay);
+                if msg.message == WM_HOTKEY {
+                    let id = msg.wParam.0 as i32;
+                    logging::debug(format!("Hotkey received: {}", hotkey_name(id)));
+                    // msg.time is when the hotkey was posted, on the GetTickCount clock
+                    let queued = GetTickCount().wrapping_sub(msg.time);
+                    let posted = Instant::now()
+                        .checked_sub(Duration::from_millis(queued as u64))
+                        .unwrap_or_else(Instant::now);
+                    // While a reminder or the countdown is ringing, the toggle
+                    // hotkey snoozes it instead
+                    let snooze = id == SNOOZE_HOTKEY_ID || (id == HOTKEY_ID && ringing.is_some());
+                    match id {
+                        _ if snooze => match ringing.take() {
+                            Some(Ringing::Reminder(event)) => {
+                                history::record(format!(
+                                    "Snoozed: {} ({} min)",
+                                    event.name, cfg.snooze_minutes
+                                ));
+                                reminders.snooze(event, now, cfg.snooze_minutes);
+                                overlay.end_peek();
+                            }
+                            Some(Ringing::Countdown) => {
+                                history::record(format!(
+                                    "Snoozed: countdown ({} min)",
+                                    cfg.snooze_minutes
+                                ));
+                                overlay.snooze_countdown(cfg.snooze_minutes);
+                            }
+                            None => {}
+                        },
+                        HOTKEY_ID => {
+                            overlay.time_next_show(posted);
+                            toggle_overlay(&overlay);
+                        }
+                        LOCK_HOTKEY_ID => {
+                            overlay.toggle_click_through();
+                        }
+                        FORMAT_HOTKEY_ID => toggle_time_format(&overlay),
+                        ZOOM_HOTKEY_ID => {
+                            // Reveal a hidden clock for the zoom's duration
+                            if !OVERLAY_VISIBLE.load(Ordering::Relaxed) {
+                                overlay.peek(cfg.zoom_seconds);
+                            }
+                            overlay.toggle_zoom(cfg.zoom_seconds);
+                        }
+                        DEBUG_HOTKEY_ID => {
+                            overlay.toggle_debug();
+                        }
+                        COUNTDOWN_HOTKEY_ID => toggle_countdown(&overlay, &item_countdown),
+                        _ => {}
+                    }
                 }
 
                 let _ = TranslateMessage(&msg);
@@ -290,10 +1400,13 @@ fn main() {
             }
 
             // Wait for messages or 50ms timeout (zero CPU when idle,
-            // wakes immediately on Win32 message, checks atomic flags every 50ms)
-            MsgWaitForMultipleObjects(None, false, 50, QS_ALLINPUT);
+            // wakes immediately on Win32 message, checks atomic flags every 50ms).
+            // With the display off nothing is drawn, so once a second is plenty.
+            let wait_ms = if overlay::display_off() { 1000 } else { 50 };
+            MsgWaitForMultipleObjects(None, false, wait_ms, QS_ALLINPUT);
         }
     }
 
-    unregister_hotkey();
+    save_visibility();
+    unregister_hotkeys();
 }
@@ -0,0 +1,81 @@
+//! The connected monitors, known by the device names Windows gives them
+//! (`\\.\DISPLAY1`), which stay the same while the cabling does.
+
+use windows::Win32::Foundation::{BOOL, HWND, LPARAM, RECT};
+use windows::Win32::Graphics::Gdi::{
+    EnumDisplayMonitors, GetMonitorInfoW, MonitorFromWindow, HDC, HMONITOR, MONITORINFO,
+    MONITORINFOEXW, MONITOR_DEFAULTTOPRIMARY,
+};
+use windows::Win32::UI::WindowsAndMessaging::MONITORINFOF_PRIMARY;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Monitor {
+    pub name: String,
+    /// Left, top, width, height.
+    pub rect: (i32, i32, i32, i32),
+    pub primary: bool,
+}
+
+fn info(monitor: HMONITOR) -> Option<Monitor> {
+    let mut info = MONITORINFOEXW::default();
+    info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+    let ok = unsafe { GetMonitorInfoW(monitor, &mut info as *mut _ as *mut MONITORINFO) };
+    if !ok.as_bool() {
+        return None;
+    }
+    let name_len = info.szDevice.iter().position(|&c| c == 0).unwrap_or(0);
+    let rc = info.monitorInfo.rcMonitor;
+    Some(Monitor {
+        name: String::from_utf16_lossy(&info.szDevice[..name_len]),
+        rect: (rc.left, rc.top, rc.right - rc.left, rc.bottom - rc.top),
+        primary: info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0,
+    })
+}
+
+unsafe extern "system" fn collect(
+    monitor: HMONITOR,
+    _hdc: HDC,
+    _rect: *mut RECT,
+    data: LPARAM,
+) -> BOOL {
+    let out = &mut *(data.0 as *mut Vec<Monitor>);
+    out.extend(info(monitor));
+    true.into()
+}
+
+/// Every connected monitor, primary first.
+pub fn all() -> Vec<Monitor> {
+    let mut monitors: Vec<Monitor> = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(
+            HDC::default(),
+            None,
+            Some(collect),
+            LPARAM(&mut monitors as *mut Vec<Monitor> as isize),
+        );
+    }
+    monitors.sort_by_key(|m| !m.primary);
+    monitors
+}
+
+/// Device name of the monitor `hwnd` is on (the primary one for no window).
+pub fn name_for_window(hwnd: HWND) -> String {
+    let monitor = unsafe { MonitorFromWindow(hwnd, MONITOR_DEFAULTTOPRIMARY) };
+    info(monitor).map(|m| m.name).unwrap_or_default()
+}
+
+/// "DISPLAY2" for `\\.\DISPLAY2`, for showing to people.
+pub fn short_name(name: &str) -> &str {
+    name.trim_start_matches(['\\', '.'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_names_drop_the_device_prefix() {
+        assert_eq!(short_name(r"\\.\DISPLAY2"), "DISPLAY2");
+        assert_eq!(short_name("DISPLAY1"), "DISPLAY1");
+    }
+}
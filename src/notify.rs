@@ -0,0 +1,123 @@
+//! Tray balloon for events worth reporting without blocking on a modal dialog.
+//!
+//! The balloon uses its own short-lived notification icon owned by the overlay
+//! window, so it works before the main tray icon exists. Clicking it is reported
+//! through [`take_clicked`].
+
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM};
+use windows::Win32::UI::Shell::{
+    Shell_NotifyIconW, NIF_ICON, NIF_INFO, NIF_MESSAGE, NIF_TIP, NIIF_INFO, NIIF_WARNING, NIM_ADD,
+    NIM_DELETE, NIM_MODIFY, NIN_BALLOONHIDE, NIN_BALLOONTIMEOUT, NIN_BALLOONUSERCLICK,
+    NOTIFYICONDATAW,
+};
+use windows::Win32::UI::WindowsAndMessaging::{LoadIconW, IDI_INFORMATION, IDI_WARNING, WM_APP};
+
+/// Callback message the balloon icon sends to the overlay window.
+pub const WM_NOTIFY_CALLBACK: u32 = WM_APP + 1;
+
+const BALLOON_UID: u32 = 1;
+
+/// What a balloon is about, so a click can lead to the right place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notice {
+    HotkeyFailed = 1,
+    UpdateAvailable = 2,
+    ElevatedForeground = 3,
+    ExclusiveFullscreen = 4,
+    Alarm = 5,
+}
+
+impl Notice {
+    fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            1 => Some(Notice::HotkeyFailed),
+            2 => Some(Notice::UpdateAvailable),
+            3 => Some(Notice::ElevatedForeground),
+            4 => Some(Notice::ExclusiveFullscreen),
+            5 => Some(Notice::Alarm),
+            _ => None,
+        }
+    }
+}
+
+static SHOWING: AtomicBool = AtomicBool::new(false);
+/// Notice of the balloon currently up (0 = none).
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+/// Notice whose balloon was clicked and not yet handled (0 = none).
+static CLICKED: AtomicU8 = AtomicU8::new(0);
+
+/// Copy `text` into a fixed UTF-16 buffer, truncating and keeping the terminator.
+fn fill<const N: usize>(buf: &mut [u16; N], text: &str) {
+    for (dst, src) in buf.iter_mut().zip(text.encode_utf16().take(N - 1)) {
+        *dst = src;
+    }
+}
+
+fn icon_data(hwnd: HWND) -> NOTIFYICONDATAW {
+    NOTIFYICONDATAW {
+        cbSize: std::mem::size_of::<NOTIFYICONDATAW>() as u32,
+        hWnd: hwnd,
+        uID: BALLOON_UID,
+        ..Default::default()
+    }
+}
+
+/// Show a balloon for `notice`. A balloon that is still up is replaced.
+pub fn show(hwnd: HWND, notice: Notice, title: &str, text: &str) {
+    let (icon, flags) = match notice {
+        Notice::HotkeyFailed | Notice::ElevatedForeground | Notice::ExclusiveFullscreen => {
+            (IDI_WARNING, NIIF_WARNING)
+        }
+        Notice::UpdateAvailable | Notice::Alarm => (IDI_INFORMATION, NIIF_INFO),
+    };
+    let mut data = icon_data(hwnd);
+    data.uFlags = NIF_ICON | NIF_MESSAGE | NIF_TIP | NIF_INFO;
+    data.uCallbackMessage = WM_NOTIFY_CALLBACK;
+    data.hIcon = unsafe { LoadIconW(HINSTANCE::default(), icon) }.unwrap_or_default();
+    data.dwInfoFlags = flags;
+    fill(&mut data.szTip, title);
+    fill(&mut data.szInfoTitle, title);
+    fill(&mut data.szInfo, text);
+    CURRENT.store(notice as u8, Ordering::Relaxed);
+    let message = if SHOWING.swap(true, Ordering::Relaxed) {
+        NIM_MODIFY
+    } else {
+        NIM_ADD
+    };
+    unsafe {
+        let _ = Shell_NotifyIconW(message, &data);
+    }
+}
+
+fn remove(hwnd: HWND) {
+    CURRENT.store(0, Ordering::Relaxed);
+    if SHOWING.swap(false, Ordering::Relaxed) {
+        unsafe {
+            let _ = Shell_NotifyIconW(NIM_DELETE, &icon_data(hwnd));
+        }
+    }
+}
+
+/// Handle `WM_NOTIFY_CALLBACK`: remember clicks and drop the icon once the balloon is gone.
+pub fn handle_callback(hwnd: HWND, lparam: LPARAM) {
+    match lparam.0 as u32 {
+        NIN_BALLOONUSERCLICK => {
+            CLICKED.store(CURRENT.load(Ordering::Relaxed), Ordering::Relaxed);
+            remove(hwnd);
+        }
+        NIN_BALLOONTIMEOUT | NIN_BALLOONHIDE => remove(hwnd),
+        _ => {}
+    }
+}
+
+/// Remove a balloon icon that is still showing (on exit).
+pub fn dismiss(hwnd: HWND) {
+    remove(hwnd);
+}
+
+/// The notice the user clicked, once.
+pub fn take_clicked() -> Option<Notice> {
+    Notice::from_u8(CLICKED.swap(0, Ordering::Relaxed))
+}
@@ -1,46 +1,173 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use windows::core::{w, PCWSTR};
-use windows::Win32::Foundation::{COLORREF, HWND, LPARAM, LRESULT, WPARAM};
+use windows::core::{w, GUID, HSTRING, PCWSTR};
+use windows::Win32::Foundation::{
+    COLORREF, HANDLE, HWND, LPARAM, LRESULT, POINT, RECT, SIZE, WPARAM,
+};
+use windows::Win32::Globalization::{GetDateFormatEx, DATE_LONGDATE};
 use windows::Win32::Graphics::Gdi::{
-    BeginPaint, CreateFontW, CreateSolidBrush, DeleteObject, EndPaint, FillRect, GetMonitorInfoW,
-    InvalidateRect, MonitorFromWindow, SelectObject, SetBkMode, SetTextColor, TextOutW,
-    CLIP_DEFAULT_PRECIS, DEFAULT_CHARSET, DEFAULT_PITCH, FF_SWISS, FW_BOLD, HBRUSH, HGDIOBJ,
-    MONITORINFO, MONITOR_DEFAULTTOPRIMARY, OUT_TT_PRECIS, PAINTSTRUCT, TRANSPARENT,
+    CreateFontW, GetMonitorInfoW, GetTextExtentPoint32W, MonitorFromWindow, SetBkMode,
+    SetTextColor, TextOutW, ANTIALIASED_QUALITY, CLIP_DEFAULT_PRECIS, DEFAULT_CHARSET,
+    DEFAULT_PITCH, FF_SWISS, FONT_QUALITY, HBRUSH, HDC, MONITORINFO, MONITOR_DEFAULTTOPRIMARY,
+    OUT_TT_PRECIS, TRANSPARENT,
 };
 use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::System::Power::{RegisterPowerSettingNotification, POWERBROADCAST_SETTING};
+use windows::Win32::System::SystemInformation::GetTickCount64;
+use windows::Win32::UI::HiDpi::GetDpiForWindow;
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetKeyState, VK_SHIFT};
 use windows::Win32::UI::WindowsAndMessaging::{
-    CreateWindowExW, DefWindowProcW, DestroyWindow, GetClientRect, GetForegroundWindow,
-    GetSystemMetrics, KillTimer, LoadCursorW, PostQuitMessage, RegisterClassW,
-    SetLayeredWindowAttributes, SetTimer, SetWindowPos, ShowWindow, HWND_TOPMOST, IDC_ARROW,
-    LWA_ALPHA, LWA_COLORKEY, SM_CXSCREEN, SM_CYSCREEN, SWP_NOACTIVATE, SW_HIDE, SW_SHOWNOACTIVATE,
-    WM_DESTROY, WM_PAINT, WM_TIMER, WNDCLASSW, WS_EX_LAYERED, WS_EX_TOOLWINDOW, WS_EX_TOPMOST,
-    WS_EX_TRANSPARENT, WS_POPUP,
+    AppendMenuW, CreatePopupMenu, CreateWindowExW, DefWindowProcW, DestroyMenu, DestroyWindow,
+    FindWindowW, GetCursorPos, GetForegroundWindow, GetSystemMetrics, GetWindowLongPtrW,
+    GetWindowRect, IsWindowVisible, KillTimer, LoadCursorW, PostMessageW, PostQuitMessage,
+    RegisterClassW, SetCoalescableTimer, SetForegroundWindow, SetWindowLongPtrW, SetWindowPos,
+    ShowWindow, TrackPopupMenu, DEVICE_NOTIFY_WINDOW_HANDLE, GWL_EXSTYLE, HTCAPTION, HWND_TOPMOST,
+    IDC_ARROW, MA_NOACTIVATE, MF_SEPARATOR, MF_STRING, PBT_POWERSETTINGCHANGE, SM_CXSCREEN,
+    SM_CYSCREEN, SWP_NOACTIVATE, SWP_SHOWWINDOW, SW_HIDE, SW_SHOWNOACTIVATE, TIMERV_NO_COALESCING,
+    TPM_RETURNCMD, TPM_RIGHTBUTTON, WM_APP, WM_DESTROY, WM_DWMCOLORIZATIONCOLORCHANGED,
+    WM_ENDSESSION, WM_ENTERSIZEMOVE, WM_EXITSIZEMOVE, WM_GETOBJECT, WM_MOUSEACTIVATE, WM_MOVING,
+    WM_NCHITTEST, WM_NCRBUTTONUP, WM_POWERBROADCAST, WM_SETTINGCHANGE, WM_SYSCOLORCHANGE, WM_TIMER,
+    WNDCLASSW, WS_EX_LAYERED, WS_EX_TOOLWINDOW, WS_EX_TOPMOST, WS_EX_TRANSPARENT, WS_POPUP,
 };
 
-use crate::config::{Config, Position, TextStyle};
+use crate::config::{
+    ActiveWindowDisplay, Backdrop, Config, CursorReaction, DateDisplay, MonitorMode, Position,
+    TextAlign, TextRenderer, TextStyle, ZOrder,
+};
+use crate::frametime::{self, FrameTimes};
+use crate::gdi::{self, OwnedFont, SelectedObjectGuard};
+use crate::{directwrite, layered, notify, segments, template, theme, uia, zorder};
 
 const TIMER_ID: usize = 1;
+/// Fast timer that polls the cursor position while a cursor reaction is enabled.
+const CURSOR_TIMER_ID: usize = 2;
+/// Short-lived timer that drives the toggle confirmation flash.
+const FLASH_TIMER_ID: usize = 3;
+/// One-shot timer that ends a temporary zoom.
+const ZOOM_TIMER_ID: usize = 4;
+/// Checks that nothing has covered the overlay, for the watchdog z-order.
+const WATCHDOG_TIMER_ID: usize = 5;
+/// Redraws while pressed keys are fading, for the input display.
+const INPUT_TIMER_ID: usize = 6;
+/// How late timers may fire so Windows can serve several with one wakeup, in
+/// milliseconds: the clock tick, the fast polls, and one-shot timeouts.
+const TICK_TOLERANCE_MS: u32 = 30;
+const POLL_TOLERANCE_MS: u32 = 50;
+const ONE_SHOT_TOLERANCE_MS: u32 = 250;
+/// `GUID_CONSOLE_DISPLAY_STATE`: the display turned off, on or dimmed.
+const GUID_CONSOLE_DISPLAY_STATE: GUID = GUID::from_u128(0x6fe69556_704a_47a0_8f24_c28d936fda47);
+/// Posted by `clockor --set` to make the running instance re-read config.toml.
+const WM_RELOAD_CONFIG: u32 = WM_APP + 2;
+/// Number of alpha steps in a flash (alternating bright/faint, ending on normal).
+const FLASH_STEPS: u32 = 6;
+/// Opacity (%) used while the cursor is over a dimmed overlay.
+const DIM_OPACITY: u8 = 15;
 const CLASS_NAME: PCWSTR = w!("ClockOR_Overlay");
-/// Color key for transparent background (RGB 1,0,1 — nearly black, won't match text)
-const COLOR_KEY: COLORREF = COLORREF(0x00010001);
+/// Window class of the extra clocks on `Always` monitors.
+const MONITOR_CLOCK_CLASS_NAME: PCWSTR = w!("ClockOR_MonitorClock");
 
 static OVERLAY_CONFIG: std::sync::OnceLock<Arc<Mutex<Config>>> = std::sync::OnceLock::new();
 
-/// If a COLORREF matches COLOR_KEY (0x00010001), nudge the blue channel to avoid transparency.
-fn guard_color_key(cr: u32) -> u32 {
-    if cr == COLOR_KEY.0 {
-        cr ^ 0x00010000 // flip blue channel bit
-    } else {
-        cr
-    }
+/// A temporary reveal of the overlay, optionally with banner text next to the clock.
+struct Peek {
+    banner: Option<String>,
+    until: Instant,
+}
+
+static PEEK: Mutex<Option<Peek>> = Mutex::new(None);
+
+/// When the manually started stream timer began, if it is running.
+static STREAM_START: Mutex<Option<Instant>> = Mutex::new(None);
+/// When the running countdown reaches zero. It stays (and flashes) until stopped.
+static COUNTDOWN_END: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Whether the cursor is currently over the overlay's home rect.
+static CURSOR_NEAR: AtomicBool = AtomicBool::new(false);
+static CURSOR_TIMER_ON: AtomicBool = AtomicBool::new(false);
+static WATCHDOG_ON: AtomicBool = AtomicBool::new(false);
+static INPUT_TIMER_ON: AtomicBool = AtomicBool::new(false);
+/// The display is off, so the overlay's timers are paused.
+static DISPLAY_OFF: AtomicBool = AtomicBool::new(false);
+
+/// Remaining flash steps; 0 when no flash is in progress.
+static FLASH_REMAINING: AtomicU32 = AtomicU32::new(0);
+
+/// True while the zoom hotkey has the clock drawn at double size.
+static ZOOMED: AtomicBool = AtomicBool::new(false);
+
+/// Size multiplier (percent) for the monitor the overlay is on.
+static MONITOR_SCALE: AtomicU32 = AtomicU32::new(100);
+/// Device name of the monitor the overlay is on.
+static HOME_MONITOR: Mutex<String> = Mutex::new(String::new());
+
+/// The extra clocks on `Always` monitors other than the overlay's, by device
+/// name and window handle.
+static MONITOR_CLOCKS: Mutex<Vec<(String, isize)>> = Mutex::new(Vec::new());
+
+/// True while diagnostics are drawn after the clock (`--debug` or the debug hotkey).
+static DEBUG: AtomicBool = AtomicBool::new(false);
+
+/// Measurements behind the debug line.
+struct DebugStats {
+    /// Frames presented since `second_start`.
+    frames: u32,
+    frames_per_second: u32,
+    second_start: Option<Instant>,
+    /// When the last 1-second tick arrived, and how late it was.
+    last_tick: Option<Instant>,
+    drift_ms: i64,
+    config_loaded: Option<chrono::NaiveTime>,
+    /// 95th percentile of recent paint times.
+    paint_p95: Option<Duration>,
+    /// From the toggle hotkey arriving to the first frame on screen, last time.
+    toggle_latency: Option<Duration>,
+    /// The line itself, rebuilt on every tick.
+    line: String,
 }
 
+static DEBUG_STATS: Mutex<DebugStats> = Mutex::new(DebugStats {
+    frames: 0,
+    frames_per_second: 0,
+    second_start: None,
+    last_tick: None,
+    drift_ms: 0,
+    config_loaded: None,
+    paint_p95: None,
+    toggle_latency: None,
+    line: String::new(),
+});
+
+/// When the hotkey for the show in progress was posted, for the latency log.
+static HOTKEY_AT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Recent paint times of the overlay, for the budget.
+static FRAME_TIMES: Mutex<FrameTimes> = Mutex::new(FrameTimes::new());
+
+const ELLIPSIS: &str = "…";
+
+/// True while the overlay ignores the mouse (the default, for gaming).
+static CLICK_THROUGH: AtomicBool = AtomicBool::new(true);
+/// True while the user is dragging the unlocked overlay; suppresses repositioning.
+static DRAGGING: AtomicBool = AtomicBool::new(false);
+
+/// True while a high-contrast theme is on; widens the outline.
+static HIGH_CONTRAST: AtomicBool = AtomicBool::new(false);
+
+/// Background fill while unlocked, so the whole rect is grabbable (and visibly unlocked).
+const UNLOCKED_BG: COLORREF = COLORREF(0x00303030);
+
+// Context menu command IDs (unlocked mode)
+const MENU_LOCK: usize = 1;
+const MENU_HIDE: usize = 2;
+const MENU_RESET_POSITION: usize = 3;
+
 pub struct Overlay {
     pub hwnd: HWND,
 }
 
-fn get_config() -> Config {
+pub fn get_config() -> Config {
     OVERLAY_CONFIG
         .get()
         .map(|c| c.lock().unwrap().clone())
@@ -51,11 +178,102 @@ pub fn update_config(config: &Config) {
     if let Some(arc) = OVERLAY_CONFIG.get() {
         *arc.lock().unwrap() = config.clone();
     }
+    DEBUG_STATS.lock().unwrap().config_loaded = Some(chrono::Local::now().time());
+}
+
+/// Ask an already running ClockOR to reload config.toml. Does nothing if
+/// none is running.
+pub fn request_reload_running() {
+    unsafe {
+        if let Ok(hwnd) = FindWindowW(CLASS_NAME, PCWSTR::null()) {
+            let _ = PostMessageW(hwnd, WM_RELOAD_CONFIG, WPARAM(0), LPARAM(0));
+        }
+    }
+}
+
+/// The diagnostics line: where the window is, on which monitor at what DPI,
+/// how often it repaints and how long painting takes, how late the clock tick
+/// runs and when config was loaded.
+fn format_debug(
+    rect: (i32, i32, i32, i32),
+    monitor: (i32, i32, i32, i32),
+    dpi: u32,
+    stats: &DebugStats,
+) -> String {
+    let (x, y, w, h) = rect;
+    let (mx, my, mw, mh) = monitor;
+    let loaded = stats
+        .config_loaded
+        .map_or("-".to_string(), |t| t.format("%H:%M:%S").to_string());
+    let paint = stats
+        .paint_p95
+        .map_or("-".to_string(), frametime::format_ms);
+    let toggle = stats
+        .toggle_latency
+        .map_or("-".to_string(), frametime::format_ms);
+    format!(
+        "[win {x},{y} {w}x{h} | mon {mx},{my} {mw}x{mh} | {dpi}dpi ({}%) | {} fps | paint p95 {paint} | toggle {toggle} | drift {:+}ms | cfg {loaded}]",
+        dpi * 100 / 96,
+        stats.frames_per_second,
+        stats.drift_ms,
+    )
+}
+
+/// Measure the 1-second tick and rebuild the debug line.
+fn update_debug(hwnd: HWND) {
+    let now = Instant::now();
+    let mut stats = DEBUG_STATS.lock().unwrap();
+    if let Some(last) = stats.last_tick {
+        stats.drift_ms = now.duration_since(last).as_millis() as i64 - 1000;
+    }
+    stats.last_tick = Some(now);
+    let mut rc = RECT::default();
+    unsafe {
+        let _ = GetWindowRect(hwnd, &mut rc);
+    }
+    let rect = (rc.left, rc.top, rc.right - rc.left, rc.bottom - rc.top);
+    let dpi = unsafe { GetDpiForWindow(hwnd) };
+    stats.paint_p95 = FRAME_TIMES.lock().unwrap().summary().map(|s| s.p95);
+    stats.line = format_debug(rect, monitor_rect_for(hwnd), dpi, &stats);
+}
+
+/// Record how long a frame took to draw, logging it if over budget.
+fn record_paint(config: &Config, started: Instant) {
+    let elapsed = started.elapsed();
+    if FRAME_TIMES.lock().unwrap().record(elapsed) {
+        crate::logging::debug(format!(
+            "Slow frame: {:?} text at {}px took {} (budget {})",
+            config.text_style,
+            config.font_size,
+            frametime::format_ms(elapsed),
+            frametime::format_ms(frametime::BUDGET),
+        ));
+    }
+}
+
+/// Count a presented frame for the debug line's frame rate.
+fn count_frame() {
+    let now = Instant::now();
+    let mut stats = DEBUG_STATS.lock().unwrap();
+    stats.frames += 1;
+    match stats.second_start {
+        Some(start) if now.duration_since(start) < Duration::from_secs(1) => {}
+        _ => {
+            stats.frames_per_second = stats.frames;
+            stats.frames = 0;
+            stats.second_start = Some(now);
+        }
+    }
 }
 
 /// Get the monitor rect (left, top, width, height) for the given window.
 /// Falls back to primary monitor if the window handle is invalid.
-fn monitor_rect_for(hwnd: HWND) -> (i32, i32, i32, i32) {
+pub fn monitor_rect_for(hwnd: HWND) -> (i32, i32, i32, i32) {
+    // Keep the window tests' overlay away from the visible desktop
+    #[cfg(all(test, feature = "win32-tests"))]
+    if let Some(rect) = win32_tests::monitor_override() {
+        return rect;
+    }
     unsafe {
         let hmon = MonitorFromWindow(hwnd, MONITOR_DEFAULTTOPRIMARY);
         let mut info = MONITORINFO {
@@ -78,25 +296,14 @@ fn monitor_rect_for(hwnd: HWND) -> (i32, i32, i32, i32) {
 
 fn calc_window_rect(config: &Config, monitor: (i32, i32, i32, i32)) -> (i32, i32, i32, i32) {
     let (mon_x, mon_y, mon_w, mon_h) = monitor;
-    let font_px = config.font_size as i32;
 
-    // Approximate character width: ~0.6 * font height for proportional font
-    let char_w = (font_px as f32 * 0.6) as i32;
-    let text_chars = match (config.format_24h, config.show_seconds) {
-        (true, true) => 8,   // "HH:MM:SS"
-        (true, false) => 5,  // "HH:MM"
-        (false, true) => 11, // "HH:MM:SS AM"
-        (false, false) => 8, // "HH:MM AM"
-    };
-    let text_w = char_w * text_chars;
-    // Extra width for outline/shadow to prevent clipping
-    let style_pad = match config.text_style {
-        TextStyle::Outline | TextStyle::Shadow => 4,
-        TextStyle::None => 0,
-    };
-    let win_w = text_w + 24 + style_pad;
-    let win_h = font_px + 16;
-    let margin = 10;
+    let lines = fitted_lines(config);
+    let mut win_w = text_box_width(config, &lines) + scale_px(24);
+    let mut win_h = text_height(config, lines.len()) + scale_px(16);
+    if config.vertical {
+        std::mem::swap(&mut win_w, &mut win_h);
+    }
+    let margin = scale_px(10);
 
     let (x, y) = match config.position {
         Position::TopRight => (mon_x + mon_w - win_w - margin, mon_y + margin),
@@ -106,13 +313,459 @@ fn calc_window_rect(config: &Config, monitor: (i32, i32, i32, i32)) -> (i32, i32
             mon_y + mon_h - win_h - margin,
         ),
         Position::BottomLeft => (mon_x + margin, mon_y + mon_h - win_h - margin),
+        // Keep a dragged overlay fully on screen even if the monitor is smaller
+        Position::Custom => (
+            mon_x + config.custom_pos[0].clamp(0, (mon_w - win_w).max(0)),
+            mon_y + config.custom_pos[1].clamp(0, (mon_h - win_h).max(0)),
+        ),
     };
 
     (x, y, win_w, win_h)
 }
 
-fn format_time(config: &Config) -> String {
+/// Width of the area inside the padding that holds `lines`.
+fn text_box_width(config: &Config, lines: &[String]) -> i32 {
+    let text_w = lines
+        .iter()
+        .map(|line| estimate_text_width(line, config.font_size as i32))
+        .max()
+        .unwrap_or(0);
+    // Extra width for outline/shadow to prevent clipping
+    let style_pad = match config.text_style {
+        TextStyle::Outline | TextStyle::Shadow => 4,
+        TextStyle::None => 0,
+    };
+    text_w + style_pad
+}
+
+/// A padding or margin in pixels at the current monitor's scale.
+fn scale_px(px: i32) -> i32 {
+    px * MONITOR_SCALE.load(Ordering::Relaxed) as i32 / 100
+}
+
+/// Height of `lines` lines of text, with the configured spacing between them.
+fn text_height(config: &Config, lines: usize) -> i32 {
+    let lines = lines.max(1) as i32;
+    lines * config.font_size as i32 + (lines - 1) * config.line_spacing as i32
+}
+
+/// Where a line `width` pixels wide starts so it sits `align`ed in a block
+/// `widest` pixels wide starting at `left`.
+fn aligned_x(align: TextAlign, left: i32, width: i32, widest: i32) -> i32 {
+    match align {
+        TextAlign::Left => left,
+        TextAlign::Center => left + (widest - width) / 2,
+        TextAlign::Right => left + widest - width,
+    }
+}
+
+/// Approximate rendered width of `text` in pixels.
+/// ASCII is ~0.6 * font height in a proportional font; CJK, emoji and other wide
+/// glyphs ~1.0. Emoji joiners take no space.
+fn estimate_text_width(text: &str, font_px: i32) -> i32 {
+    let char_w = (font_px as f32 * 0.6) as i32;
+    text.chars()
+        .map(|c| {
+            if crate::emoji::is_joiner(c) {
+                0
+            } else if c.is_ascii() {
+                char_w
+            } else {
+                font_px
+            }
+        })
+        .sum()
+}
+
+/// Cut `text` so it fits in `max_px`, ending with an ellipsis. 0 means no limit.
+fn truncate_to_width(text: &str, font_px: i32, max_px: u32) -> String {
+    if max_px == 0 || estimate_text_width(text, font_px) <= max_px as i32 {
+        return text.to_string();
+    }
+    let budget = max_px as i32 - estimate_text_width(ELLIPSIS, font_px);
+    let mut used = 0;
+    let mut out: String = text
+        .chars()
+        .take_while(|c| {
+            used += estimate_text_width(c.encode_utf8(&mut [0; 4]), font_px);
+            used <= budget
+        })
+        .collect();
+    // Don't leave a dangling separator before the ellipsis
+    out.truncate(out.trim_end().len());
+    out.push_str(ELLIPSIS);
+    out
+}
+
+/// `display_lines`, each limited to the configured maximum width (not while debugging).
+fn fitted_lines(config: &Config) -> Vec<String> {
+    let max_width = if DEBUG.load(Ordering::Relaxed) {
+        0
+    } else {
+        config.max_width
+    };
+    display_lines(config)
+        .iter()
+        .map(|line| truncate_to_width(line, config.font_size as i32, max_width))
+        .collect()
+}
+
+/// The clock and its segments, as set in settings or as a template.
+fn clock_parts(config: &Config) -> Vec<String> {
+    if !config.template.trim().is_empty() {
+        return vec![template::expand(&config.template, |name| {
+            template_value(config, name)
+        })];
+    }
+    // A countdown shown instead of the clock is drawn as its chip
+    let clock = if config.countdown_replaces_clock && COUNTDOWN_END.lock().unwrap().is_some() {
+        String::new()
+    } else {
+        crate::script::output().unwrap_or_else(|| format_time(config))
+    };
+    let mut parts = vec![clock];
+    if let Some(progress) = segments::progress(config.progress, chrono::Local::now().date_naive()) {
+        parts.push(progress);
+    }
+    if config.show_uptime {
+        parts.push(segments::uptime(unsafe { GetTickCount64() }));
+    }
+    if config.show_input_language {
+        parts.extend(crate::inputlang::current());
+    }
+    parts.extend(active_window(config.active_window));
+    if let Some(start) = *STREAM_START.lock().unwrap() {
+        parts.push(segments::stream_elapsed(start.elapsed().as_secs()));
+    }
+    parts.extend(crate::textfile::text());
+    parts.extend(crate::command::text());
+    // With a script, plugin text is only what the script shows
+    if config.script.trim().is_empty() {
+        parts.extend(crate::plugins::texts().into_iter().map(|(_, text)| text));
+    }
+    parts
+}
+
+/// The active window segment, looked up only when it is on.
+fn active_window(kind: ActiveWindowDisplay) -> Option<String> {
+    if kind == ActiveWindowDisplay::Off {
+        return None;
+    }
+    let (image, title) = crate::foreground::current()?;
+    segments::active_window(kind, image.as_deref(), &title)
+}
+
+/// A template token's current value, or `None` when it isn't available.
+fn template_value(config: &Config, name: &str) -> Option<String> {
     let now = chrono::Local::now();
+    if let Some(plugin) = name.strip_prefix("plugin:") {
+        return crate::plugins::texts()
+            .into_iter()
+            .find(|(n, _)| n == plugin)
+            .map(|(_, text)| text);
+    }
+    match name {
+        "time" => Some(format_time(config)),
+        "date" => Some(now.format("%Y-%m-%d").to_string()),
+        "weekday" => Some(now.format("%a").to_string()),
+        "battery" => crate::power::battery().map(|b| format!("{}%", b.percent)),
+        "cpu" => crate::cpu::usage_percent().map(|p| format!("{p}%")),
+        "timer" => STREAM_START
+            .lock()
+            .unwrap()
+            .map(|start| segments::hms(start.elapsed().as_secs())),
+        "countdown" => countdown_left().map(|left| segments::countdown(left.as_secs())),
+        "uptime" => Some(segments::uptime(unsafe { GetTickCount64() })),
+        "progress" => segments::progress(config.progress, now.date_naive()),
+        "window" => active_window(match config.active_window {
+            ActiveWindowDisplay::Off => ActiveWindowDisplay::Process,
+            kind => kind,
+        }),
+        "layout" => crate::inputlang::current(),
+        "custom" => crate::script::output(),
+        "file" => crate::textfile::text(),
+        "command" => crate::command::text(),
+        _ => None,
+    }
+}
+
+/// The lines drawn on the overlay: the clock plus any active segments and
+/// banner, in one row or one per line when `multi_line` is on.
+fn display_lines(config: &Config) -> Vec<String> {
+    let mut parts = clock_parts(config);
+    if let Some(text) = PEEK
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|p| p.banner.as_ref())
+    {
+        parts.push(text.clone());
+    }
+    if DEBUG.load(Ordering::Relaxed) {
+        parts.push(DEBUG_STATS.lock().unwrap().line.clone());
+    }
+    // Last, so the drawing can pick them out and color them
+    for chip in chips(config) {
+        match parts.last_mut() {
+            Some(last) if chip.attached => {
+                last.push(' ');
+                last.push_str(&chip.text);
+            }
+            _ => parts.push(chip.text),
+        }
+    }
+    parts.retain(|part| !part.is_empty());
+    let mut lines = if config.multi_line {
+        parts
+    } else {
+        vec![parts.join("  ")]
+    };
+    // The date and the world clocks go under the clock, in that order
+    let mut under: Vec<String> = date_line(config.date_line).into_iter().collect();
+    under.extend(world_clock_lines(config, chrono::Utc::now()));
+    let at = lines.len().min(1);
+    lines.splice(at..at, under);
+    lines
+}
+
+/// "JST 23:15" for each world clock whose zone is known, at `now`.
+fn world_clock_lines(config: &Config, now: chrono::DateTime<chrono::Utc>) -> Vec<String> {
+    config
+        .world_clocks
+        .iter()
+        .filter_map(|clock| {
+            let there = now.with_timezone(&clock.tz()?);
+            let label = match clock.label.trim() {
+                "" => there.format("%Z").to_string(),
+                label => label.to_string(),
+            };
+            Some(format!("{label} {}", format_time_at(config, &there)))
+        })
+        .collect()
+}
+
+/// Today's date for the line under the clock, or `None` when it is off.
+fn date_line(kind: DateDisplay) -> Option<String> {
+    match kind {
+        DateDisplay::Off => None,
+        DateDisplay::Iso => Some(chrono::Local::now().format("%Y-%m-%d").to_string()),
+        DateDisplay::Localized => localized_date(),
+    }
+}
+
+/// Today in the user's long date format from the region settings.
+fn localized_date() -> Option<String> {
+    let mut buffer = [0u16; 128];
+    // No locale name means the user's; no date means today
+    let len = unsafe {
+        GetDateFormatEx(
+            PCWSTR::null(),
+            DATE_LONGDATE,
+            None,
+            PCWSTR::null(),
+            Some(&mut buffer),
+            PCWSTR::null(),
+        )
+    };
+    // The length includes the terminating null
+    (len > 1).then(|| String::from_utf16_lossy(&buffer[..len as usize - 1]))
+}
+
+/// A warning segment drawn in its own color at the end of the text.
+struct Chip {
+    text: String,
+    /// COLORREF.
+    color: u32,
+    /// Shares a segment with the chip before it, one space apart.
+    attached: bool,
+}
+
+/// The warning chips showing right now, in the order they are drawn.
+fn chips(config: &Config) -> Vec<Chip> {
+    let mut chips = Vec::new();
+    chips.extend(countdown_chip(config));
+    chips.extend(shutdown_chip(config));
+    chips.extend(reminder_chip(config));
+    chips.extend(low_battery_chip(config));
+    chips.extend(input_chips(config));
+    chips
+}
+
+/// Recently pressed keys and buttons as one segment, each fading toward the
+/// background color. With reduced motion each goes at once instead of fading.
+fn input_chips(config: &Config) -> Vec<Chip> {
+    if !config.show_input {
+        return Vec::new();
+    }
+    let calm = theme::reduce_motion(config);
+    crate::inputdisplay::shown()
+        .into_iter()
+        .filter(|&(_, fade)| !calm || fade == 0.0)
+        .enumerate()
+        .map(|(i, (text, fade))| Chip {
+            text,
+            color: faded_colorref(config.text_color, config.background_color, fade),
+            attached: i > 0,
+        })
+        .collect()
+}
+
+/// `from` moved `fade` (0.0–1.0) of the way to `to`, as a COLORREF.
+fn faded_colorref(from: [u8; 3], to: [u8; 3], fade: f32) -> u32 {
+    let channel = |i: usize| {
+        let (a, b) = (from[i] as f32, to[i] as f32);
+        (a + (b - a) * fade.clamp(0.0, 1.0)).round() as u32
+    };
+    channel(0) | channel(1) << 8 | channel(2) << 16
+}
+
+/// Time left on the countdown, rounded up to whole seconds, if one is running.
+fn countdown_left() -> Option<Duration> {
+    let end = (*COUNTDOWN_END.lock().unwrap())?;
+    let left = end.saturating_duration_since(Instant::now());
+    Some(Duration::from_secs(
+        left.as_secs() + u64::from(left.subsec_nanos() > 0),
+    ))
+}
+
+/// The countdown, in the text color while it runs and flashing red at zero.
+fn countdown_chip(config: &Config) -> Option<Chip> {
+    let left = countdown_left()?;
+    let color = if !left.is_zero() {
+        config.text_colorref()
+    } else {
+        alert_colorref(config, bright_beat(config))
+    };
+    Some(Chip {
+        text: segments::countdown(left.as_secs()),
+        color,
+        attached: false,
+    })
+}
+
+/// Countdown to the next reminder once it is within the warning lead time.
+fn reminder_chip(config: &Config) -> Option<Chip> {
+    if config.reminder_warning_minutes == 0 {
+        return None;
+    }
+    let lead = chrono::Duration::minutes(config.reminder_warning_minutes as i64);
+    let now = chrono::Local::now().naive_local();
+    let (event, left) = crate::reminders::upcoming(&config.daily_events, now, lead)?;
+    // Round up, so "1m" shows until it rings
+    let minutes = (left.num_seconds() + 59) / 60;
+    Some(Chip {
+        text: format!("⏰ {minutes}m {}", event.name),
+        color: warning_colorref(config, left.num_seconds(), lead.num_seconds()),
+        attached: false,
+    })
+}
+
+/// A pending shutdown or restart, flashing in the alert color.
+fn shutdown_chip(config: &Config) -> Option<Chip> {
+    if !config.shutdown_warning {
+        return None;
+    }
+    let since = crate::shutdown::pending()?;
+    let text = shutdown_text(since, chrono::Local::now(), config.shutdown_timer_minutes);
+    Some(Chip {
+        text,
+        color: alert_colorref(config, bright_beat(config)),
+        attached: false,
+    })
+}
+
+/// Time left before a shutdown requested at `since`, when the user has set
+/// how long Windows waits. Windows doesn't tell, so with no timer (0) this
+/// counts up from the request instead.
+fn shutdown_text(
+    since: chrono::DateTime<chrono::Local>,
+    now: chrono::DateTime<chrono::Local>,
+    timer_minutes: u32,
+) -> String {
+    if timer_minutes == 0 {
+        let minutes = (now - since).num_minutes().max(0);
+        return format!("⚠ Shutdown requested {minutes}m ago");
+    }
+    let at = since + chrono::Duration::minutes(timer_minutes as i64);
+    let left = (at - now).num_seconds().max(0) as u64;
+    format!("⚠ Shutdown in {}", segments::minutes_seconds(left))
+}
+
+/// The palette's notice, then warning, then alert color as a reminder `left`
+/// seconds away nears, out of `lead`.
+fn warning_colorref(config: &Config, left: i64, lead: i64) -> u32 {
+    let [notice, warning, alert] = config.alert_levels();
+    let level = if left * 2 > lead {
+        notice
+    } else if left * 5 > lead {
+        warning
+    } else {
+        alert
+    };
+    rgb_colorref(level)
+}
+
+/// The battery warning while the charge is at or below the alert threshold.
+fn low_battery_chip(config: &Config) -> Option<Chip> {
+    if config.low_battery_percent == 0 {
+        return None;
+    }
+    let battery = crate::power::battery()
+        .filter(|battery| crate::power::is_low(*battery, config.low_battery_percent))?;
+    Some(Chip {
+        text: format!("🔋 {}%", battery.percent),
+        color: alert_colorref(config, bright_beat(config)),
+        attached: false,
+    })
+}
+
+/// Everything on the overlay as one line.
+fn display_text(config: &Config) -> String {
+    display_lines(config).join("  ")
+}
+
+/// What screen readers get as the overlay's name.
+fn spoken_text() -> String {
+    display_text(&get_config())
+}
+
+/// What a custom script can see this tick.
+fn script_context(config: &Config) -> crate::script::Context {
+    use chrono::{Datelike, Timelike};
+    let now = chrono::Local::now();
+    let battery = crate::power::battery();
+    crate::script::Context {
+        time: format_time(config),
+        hour: now.hour(),
+        minute: now.minute(),
+        second: now.second(),
+        weekday: now.weekday().number_from_monday(),
+        date: now.format("%Y-%m-%d").to_string(),
+        battery: battery.map_or(-1, |b| b.percent as i64),
+        plugged_in: battery.is_none_or(|b| b.plugged_in),
+        uptime_minutes: (unsafe { GetTickCount64() } / 60_000) as i64,
+        stream_minutes: STREAM_START
+            .lock()
+            .unwrap()
+            .map_or(-1, |start| (start.elapsed().as_secs() / 60) as i64),
+        cpu: crate::cpu::usage_percent().map_or(-1, i64::from),
+        plugins: crate::plugins::texts(),
+    }
+}
+
+fn format_time(config: &Config) -> String {
+    format_time_at(config, &chrono::Local::now())
+}
+
+/// `now` in the configured time format.
+fn format_time_at<Tz: chrono::TimeZone>(config: &Config, now: &chrono::DateTime<Tz>) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    // An invalid pattern falls back to the format settings
+    if crate::config::valid_time_format(&config.time_format) {
+        return now.format(&config.time_format).to_string();
+    }
     match (config.format_24h, config.show_seconds) {
         (true, true) => now.format("%H:%M:%S").to_string(),
         (true, false) => now.format("%H:%M").to_string(),
@@ -121,104 +774,965 @@ fn format_time(config: &Config) -> String {
     }
 }
 
-unsafe extern "system" fn wnd_proc(
+fn opacity_alpha(opacity: u8) -> u8 {
+    (opacity as f32 / 100.0 * 255.0) as u8
+}
+
+fn rect_contains(rect: (i32, i32, i32, i32), point: (i32, i32)) -> bool {
+    let (x, y, w, h) = rect;
+    let (px, py) = point;
+    px >= x && px < x + w && py >= y && py < y + h
+}
+
+/// Window rect and alpha, taking the cursor reaction into account.
+fn placement(
+    config: &Config,
+    monitor: (i32, i32, i32, i32),
+    cursor_near: bool,
+) -> ((i32, i32, i32, i32), u8) {
+    let rect = calc_window_rect(config, monitor);
+    let alpha = opacity_alpha(config.opacity);
+    if !cursor_near {
+        return (rect, alpha);
+    }
+    match config.cursor_reaction {
+        CursorReaction::Off => (rect, alpha),
+        CursorReaction::Dim => (rect, opacity_alpha(DIM_OPACITY)),
+        CursorReaction::Dodge => {
+            let dodged = Config {
+                position: config.position.opposite(),
+                ..config.clone()
+            };
+            (calc_window_rect(&dodged, monitor), alpha)
+        }
+    }
+}
+
+/// Alpha override while a toggle flash is running.
+fn flash_alpha(remaining: u32) -> Option<u8> {
+    match remaining {
+        0 => None,
+        n if n % 2 == 0 => Some(255),
+        _ => Some(opacity_alpha(DIM_OPACITY)),
+    }
+}
+
+/// Track whether the cursor is over the overlay's home rect and re-place it on change.
+/// The home rect (not the dodged one) is used so a dodged overlay doesn't bounce back.
+fn check_cursor(hwnd: HWND) {
+    let config = get_config().on_monitor(&HOME_MONITOR.lock().unwrap());
+    let mut near = false;
+    if config.cursor_reaction != CursorReaction::Off {
+        let mut pt = POINT::default();
+        if unsafe { GetCursorPos(&mut pt) }.is_ok() {
+            let home = calc_window_rect(&config, monitor_rect_for(hwnd));
+            near = rect_contains(home, (pt.x, pt.y));
+        }
+    }
+    if CURSOR_NEAR.swap(near, Ordering::Relaxed) != near {
+        refresh(hwnd);
+    }
+}
+
+/// The user's config with the system colors swapped in while high contrast is on.
+fn with_high_contrast(config: Config, colors: theme::HighContrast) -> Config {
+    Config {
+        text_color: colors.text,
+        outline_color: colors.background,
+        background_color: colors.background,
+        text_style: TextStyle::Outline,
+        opacity: 100,
+        backdrop: Backdrop::Flat,
+        ..config
+    }
+}
+
+/// The config at double size, for reading the clock from across the room.
+fn zoomed(config: Config) -> Config {
+    Config {
+        font_size: config.font_size * 2,
+        max_width: config.max_width * 2,
+        ..config
+    }
+}
+
+/// The config at `percent` of its size, for the monitor it's shown on.
+fn scaled(config: Config, percent: u32) -> Config {
+    if percent == 100 {
+        return config;
+    }
+    Config {
+        font_size: config.font_size * percent / 100,
+        max_width: config.max_width * percent / 100,
+        line_spacing: config.line_spacing * percent / 100,
+        ..config
+    }
+}
+
+/// The config with the colors of the color rule in effect at `now`, if any.
+fn with_color_rules(config: Config, now: chrono::NaiveDateTime) -> Config {
+    let Some(rule) = crate::schedule::color_rule(&config.color_rules, now) else {
+        return config;
+    };
+    let (text_color, background) = (rule.text_color, rule.background_color);
+    Config {
+        text_color,
+        background_color: background.unwrap_or(config.background_color),
+        ..config
+    }
+}
+
+/// Pick up the monitor `hwnd` is on and its size multiplier.
+fn update_monitor_scale(hwnd: HWND) {
+    let name = crate::monitors::name_for_window(hwnd);
+    let scale = get_config().scale_for_monitor(&name);
+    MONITOR_SCALE.store(scale, Ordering::Relaxed);
+    *HOME_MONITOR.lock().unwrap() = name;
+}
+
+/// The config as drawn on the overlay's monitor.
+fn drawn_config() -> Config {
+    let monitor = HOME_MONITOR.lock().unwrap().clone();
+    drawn_config_on(&monitor)
+}
+
+/// The config as drawn on `monitor`, following its position and scale, the
+/// zoom and the current high-contrast setting.
+fn drawn_config_on(monitor: &str) -> Config {
+    let mut config = get_config().on_monitor(monitor);
+    let scale = config.scale_for_monitor(monitor);
+    if ZOOMED.load(Ordering::Relaxed) {
+        config = zoomed(config);
+    }
+    config = scaled(config, scale);
+    config = with_color_rules(config, chrono::Local::now().naive_local());
+    let colors = theme::high_contrast();
+    HIGH_CONTRAST.store(colors.is_some(), Ordering::Relaxed);
+    match colors {
+        Some(colors) => with_high_contrast(config, colors),
+        None => config,
+    }
+}
+
+/// Re-apply size, position and opacity from the current config and repaint.
+fn refresh(hwnd: HWND) {
+    update_monitor_scale(hwnd);
+    let config = drawn_config();
+    // Use overlay's own monitor (stays on the monitor where it was shown)
+    let monitor = monitor_rect_for(hwnd);
+    let frosted = crate::backdrop::apply(
+        hwnd,
+        config.backdrop,
+        config.background_color,
+        opacity_alpha(config.background_opacity),
+    );
+    crate::frame::apply(
+        hwnd,
+        config.corner_style,
+        config.window_border.then_some(config.border_color),
+    );
+    // Frames carry the text/background opacity themselves, so the window
+    // alpha only reflects dimming and flashing
+    let base = Config {
+        opacity: 100,
+        ..config.clone()
+    };
+    let ((x, y, w, h), alpha) = placement(&base, monitor, CURSOR_NEAR.load(Ordering::Relaxed));
+    let alpha = flash_alpha(FLASH_REMAINING.load(Ordering::Relaxed)).unwrap_or(alpha);
+
+    // Only poll the cursor quickly while a reaction is configured, and nothing
+    // while the display is off
+    let awake = !DISPLAY_OFF.load(Ordering::Relaxed);
+    let want_cursor_timer = awake && config.cursor_reaction != CursorReaction::Off;
+    if CURSOR_TIMER_ON.swap(want_cursor_timer, Ordering::Relaxed) != want_cursor_timer {
+        unsafe {
+            if want_cursor_timer {
+                start_timer(hwnd, CURSOR_TIMER_ID, 100, POLL_TOLERANCE_MS);
+            } else {
+                let _ = KillTimer(hwnd, CURSOR_TIMER_ID);
+                CURSOR_NEAR.store(false, Ordering::Relaxed);
+            }
+        }
+    }
+
+    crate::inputdisplay::apply(config.show_input);
+    let want_input_timer = awake && config.show_input;
+    if INPUT_TIMER_ON.swap(want_input_timer, Ordering::Relaxed) != want_input_timer {
+        unsafe {
+            if want_input_timer {
+                start_timer(hwnd, INPUT_TIMER_ID, 100, POLL_TOLERANCE_MS);
+            } else {
+                let _ = KillTimer(hwnd, INPUT_TIMER_ID);
+            }
+        }
+    }
+
+    let want_watchdog = awake && config.z_order != ZOrder::Topmost;
+    if WATCHDOG_ON.swap(want_watchdog, Ordering::Relaxed) != want_watchdog {
+        unsafe {
+            if want_watchdog {
+                start_timer(
+                    hwnd,
+                    WATCHDOG_TIMER_ID,
+                    zorder::WATCHDOG_INTERVAL_MS,
+                    POLL_TOLERANCE_MS,
+                );
+            } else {
+                let _ = KillTimer(hwnd, WATCHDOG_TIMER_ID);
+            }
+        }
+    }
+
+    if DEBUG.load(Ordering::Relaxed) {
+        count_frame();
+    }
+    unsafe {
+        if !DRAGGING.load(Ordering::Relaxed) {
+            let _ = SetWindowPos(hwnd, HWND_TOPMOST, x, y, w, h, SWP_NOACTIVATE);
+        }
+    }
+    present_layered(hwnd, &config, alpha, (w, h), frosted);
+    sync_monitor_clocks(hwnd);
+}
+
+/// The rect and device name of the monitor the overlay goes to when shown
+/// over `foreground`, passing over monitors set to `Never`.
+fn home_monitor_rect(config: &Config, foreground: HWND) -> ((i32, i32, i32, i32), String) {
+    let name = crate::monitors::name_for_window(foreground);
+    if config.monitor_mode(&name) != MonitorMode::Never {
+        return (monitor_rect_for(foreground), name);
+    }
+    let monitors = crate::monitors::all();
+    let names: Vec<String> = monitors.iter().map(|m| m.name.clone()).collect();
+    let home = config.home_monitor(&name, &names);
+    match monitors.iter().find(|m| m.name == home) {
+        Some(monitor) => (monitor.rect, monitor.name.clone()),
+        None => (monitor_rect_for(foreground), name),
+    }
+}
+
+/// Give each `Always` monitor other than the overlay's a clock of its own
+/// while the overlay is visible, and redraw them with it. They are drawn like
+/// the overlay, but always click-through and without the hover reaction or a
+/// DWM backdrop.
+fn sync_monitor_clocks(overlay: HWND) {
+    let config = get_config();
+    let visible = unsafe { IsWindowVisible(overlay) }.as_bool();
+    let wanted: Vec<crate::monitors::Monitor> = if visible
+        && config
+            .monitor_overlays
+            .iter()
+            .any(|m| m.mode == MonitorMode::Always)
+    {
+        let home = HOME_MONITOR.lock().unwrap().clone();
+        crate::monitors::all()
+            .into_iter()
+            .filter(|m| {
+                !m.name.eq_ignore_ascii_case(&home)
+                    && config.monitor_mode(&m.name) == MonitorMode::Always
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let mut clocks = MONITOR_CLOCKS.lock().unwrap();
+    clocks.retain(|(name, handle)| {
+        let keep = wanted.iter().any(|m| m.name == *name);
+        if !keep {
+            unsafe {
+                let _ = DestroyWindow(HWND(*handle as _));
+            }
+        }
+        keep
+    });
+    for monitor in &wanted {
+        let hwnd = match clocks.iter().find(|(name, _)| *name == monitor.name) {
+            Some((_, handle)) => HWND(*handle as _),
+            None => {
+                let Some(hwnd) = create_monitor_clock() else {
+                    continue;
+                };
+                clocks.push((monitor.name.clone(), hwnd.0 as isize));
+                hwnd
+            }
+        };
+        draw_monitor_clock(hwnd, monitor);
+    }
+}
+
+fn create_monitor_clock() -> Option<HWND> {
+    unsafe {
+        let hinstance = GetModuleHandleW(None).ok()?;
+        let ex_style = WS_EX_TOPMOST | WS_EX_TRANSPARENT | WS_EX_LAYERED | WS_EX_TOOLWINDOW;
+        CreateWindowExW(
+            ex_style,
+            MONITOR_CLOCK_CLASS_NAME,
+            w!("ClockOR"),
+            WS_POPUP,
+            0,
+            0,
+            0,
+            0,
+            None,
+            None,
+            hinstance,
+            None,
+        )
+        .ok()
+    }
+}
+
+/// Place and draw the extra clock on `monitor`. Margins follow that monitor's
+/// scale for the duration, then the overlay's again.
+fn draw_monitor_clock(hwnd: HWND, monitor: &crate::monitors::Monitor) {
+    let config = drawn_config_on(&monitor.name);
+    let scale = get_config().scale_for_monitor(&monitor.name);
+    let home_scale = MONITOR_SCALE.swap(scale, Ordering::Relaxed);
+    let (x, y, w, h) = calc_window_rect(&config, monitor.rect);
+    let alpha = flash_alpha(FLASH_REMAINING.load(Ordering::Relaxed)).unwrap_or(255);
+    unsafe {
+        let flags = SWP_NOACTIVATE | SWP_SHOWWINDOW;
+        let _ = SetWindowPos(hwnd, HWND_TOPMOST, x, y, w, h, flags);
+    }
+    present_layered(hwnd, &config, alpha, (w, h), false);
+    MONITOR_SCALE.store(home_scale, Ordering::Relaxed);
+}
+
+/// Close every extra clock, e.g. when the overlay goes away.
+fn close_monitor_clocks() {
+    for (_, handle) in MONITOR_CLOCKS.lock().unwrap().drain(..) {
+        unsafe {
+            let _ = DestroyWindow(HWND(handle as _));
+        }
+    }
+}
+
+unsafe extern "system" fn monitor_clock_proc(
     hwnd: HWND,
     msg: u32,
     wparam: WPARAM,
     lparam: LPARAM,
 ) -> LRESULT {
     match msg {
-        WM_PAINT => {
-            let mut ps = PAINTSTRUCT::default();
-            let hdc = BeginPaint(hwnd, &mut ps);
+        WM_MOUSEACTIVATE => LRESULT(MA_NOACTIVATE as isize),
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
 
-            let config = get_config();
+/// Start timer `id` on `hwnd`, letting Windows fire it up to `tolerance_ms`
+/// late to share a wakeup with other timers.
+unsafe fn start_timer(hwnd: HWND, id: usize, ms: u32, tolerance_ms: u32) {
+    SetCoalescableTimer(hwnd, id, ms, None, tolerance_ms);
+}
+
+/// Pause the clock tick and the fast polls while the display is off, and
+/// catch up with a fresh frame when it comes back, so handhelds on battery
+/// aren't woken every second for a clock nobody can see.
+fn set_display_off(hwnd: HWND, off: bool) {
+    if DISPLAY_OFF.swap(off, Ordering::Relaxed) == off {
+        return;
+    }
+    crate::logging::debug(if off {
+        "Display off: overlay timers paused"
+    } else {
+        "Display on: overlay timers resumed"
+    });
+    unsafe {
+        if off {
+            let _ = KillTimer(hwnd, TIMER_ID);
+        } else {
+            start_timer(hwnd, TIMER_ID, 1000, TICK_TOLERANCE_MS);
+        }
+    }
+    refresh(hwnd);
+}
 
-            // Fill entire window with color key (this area becomes transparent)
-            let mut rc = windows::Win32::Foundation::RECT::default();
-            let _ = GetClientRect(hwnd, &mut rc);
-            let key_brush = CreateSolidBrush(COLOR_KEY);
-            let _ = FillRect(hdc, &rc, key_brush);
-            let _ = DeleteObject(key_brush);
+/// Whether the display is off; the main loop then checks in less often.
+pub fn display_off() -> bool {
+    DISPLAY_OFF.load(Ordering::Relaxed)
+}
 
-            // Create font
-            let font = CreateFontW(
-                config.font_size as i32,
-                0,
-                0,
-                0,
-                FW_BOLD.0 as i32,
-                0,
-                0,
-                0,
-                DEFAULT_CHARSET.0 as u32,
-                OUT_TT_PRECIS.0 as u32,
-                CLIP_DEFAULT_PRECIS.0 as u32,
-                5, // CLEARTYPE_QUALITY
-                (DEFAULT_PITCH.0 | FF_SWISS.0) as u32,
-                w!("Segoe UI"),
-            );
-            let old_font = SelectObject(hdc, HGDIOBJ(font.0));
-            SetBkMode(hdc, TRANSPARENT);
-
-            let time_str = format_time(&config);
-            let wide: Vec<u16> = time_str.encode_utf16().collect();
-            let tx = 12;
-            let ty = 8;
-
-            // Resolve colors, guarding against COLOR_KEY collision
-            let text_cr = guard_color_key(config.text_colorref());
-            let outline_cr = guard_color_key(config.outline_colorref());
-
-            match config.text_style {
-                TextStyle::Outline => {
-                    SetTextColor(hdc, COLORREF(outline_cr));
-                    for &(dx, dy) in &[
-                        (-1i32, -1i32),
-                        (0, -1),
-                        (1, -1),
-                        (-1, 0),
-                        (1, 0),
-                        (-1, 1),
-                        (0, 1),
-                        (1, 1),
-                    ] {
-                        let _ = TextOutW(hdc, tx + dx, ty + dy, &wide);
+/// Push a per-pixel alpha frame, so antialiased edges blend with whatever is
+/// behind the overlay. The drawn window size must match `size`.
+/// A `frosted` backdrop is already tinted by DWM, so no panel is drawn over it.
+fn present_layered(hwnd: HWND, config: &Config, window_alpha: u8, size: (i32, i32), frosted: bool) {
+    let (bg, bg_alpha) = if CLICK_THROUGH.load(Ordering::Relaxed) && frosted {
+        (config.background_color, 0)
+    } else if CLICK_THROUGH.load(Ordering::Relaxed) {
+        (
+            config.background_color,
+            opacity_alpha(config.background_opacity),
+        )
+    } else {
+        let c = UNLOCKED_BG.0;
+        ([c as u8, (c >> 8) as u8, (c >> 16) as u8], 255)
+    };
+    let image = crate::bgimage::scaled(&config.background_image, size);
+    let layer = layered::Layer {
+        bg,
+        bg_alpha,
+        image: image.as_deref(),
+        text_alpha: opacity_alpha(config.opacity),
+        window_alpha,
+    };
+    let started = Instant::now();
+    layered::present(hwnd, size, &layer, |hdc| unsafe {
+        draw_text(hdc, config, size)
+    });
+    gdi::assert_released();
+    record_paint(config, started);
+}
+
+/// How near a dragged overlay must come to a screen edge to snap to it, in pixels.
+const SNAP_DISTANCE: i32 = 16;
+
+/// Where an overlay dragged to `rect` (left, top, width, height) settles on
+/// `monitor`: on the nearest grid point, or at the corner margin of an edge
+/// it came within `distance` of, which makes it line up with the corner positions.
+fn snapped(
+    rect: (i32, i32, i32, i32),
+    monitor: (i32, i32, i32, i32),
+    margin: i32,
+    distance: i32,
+    grid: u32,
+    edges: bool,
+) -> (i32, i32) {
+    let axis = |pos: i32, size: i32, extent: i32| {
+        let mut snapped = pos;
+        if grid > 0 {
+            let grid = grid as i32;
+            snapped = (pos + grid / 2).div_euclid(grid) * grid;
+        }
+        if edges {
+            for edge in [margin, extent - size - margin] {
+                if (pos - edge).abs() <= distance {
+                    snapped = edge;
+                }
+            }
+        }
+        snapped
+    };
+    let (x, y, w, h) = rect;
+    let (mon_x, mon_y, mon_w, mon_h) = monitor;
+    (
+        mon_x + axis(x - mon_x, w, mon_w),
+        mon_y + axis(y - mon_y, h, mon_h),
+    )
+}
+
+/// Snap the rect of an overlay being dragged, unless Shift is held.
+fn snap_moving(hwnd: HWND, rect: &mut RECT) {
+    let config = get_config();
+    let free = unsafe { GetKeyState(VK_SHIFT.0 as i32) } < 0;
+    if free || (!config.snap_to_edges && config.snap_grid == 0) {
+        return;
+    }
+    let (w, h) = (rect.right - rect.left, rect.bottom - rect.top);
+    let (x, y) = snapped(
+        (rect.left, rect.top, w, h),
+        monitor_rect_for(hwnd),
+        scale_px(10),
+        scale_px(SNAP_DISTANCE),
+        config.snap_grid,
+        config.snap_to_edges,
+    );
+    *rect = RECT {
+        left: x,
+        top: y,
+        right: x + w,
+        bottom: y + h,
+    };
+}
+
+/// Persist the window's current spot as a custom position after a drag.
+fn save_dragged_position(hwnd: HWND) {
+    let mut rc = RECT::default();
+    if unsafe { GetWindowRect(hwnd, &mut rc) }.is_err() {
+        return;
+    }
+    let (mon_x, mon_y, _, _) = monitor_rect_for(hwnd);
+    let mut config = get_config();
+    let monitor = crate::monitors::name_for_window(hwnd);
+    config.set_custom_pos(&monitor, [rc.left - mon_x, rc.top - mon_y]);
+    update_config(&config);
+    if let Err(e) = config.save() {
+        crate::logging::error(format!("Failed to save config: {e}"));
+    }
+}
+
+/// Right-click menu shown while the overlay is unlocked.
+fn show_context_menu(hwnd: HWND) {
+    unsafe {
+        let Ok(menu) = CreatePopupMenu() else {
+            return;
+        };
+        let _ = AppendMenuW(menu, MF_STRING, MENU_LOCK, w!("Lock (click-through)"));
+        let _ = AppendMenuW(menu, MF_STRING, MENU_RESET_POSITION, w!("Reset Position"));
+        let _ = AppendMenuW(menu, MF_SEPARATOR, 0, PCWSTR::null());
+        let _ = AppendMenuW(menu, MF_STRING, MENU_HIDE, w!("Hide"));
+
+        let mut pt = POINT::default();
+        let _ = GetCursorPos(&mut pt);
+        // Required so the menu closes when the user clicks elsewhere
+        let _ = SetForegroundWindow(hwnd);
+        let cmd = TrackPopupMenu(
+            menu,
+            TPM_RETURNCMD | TPM_RIGHTBUTTON,
+            pt.x,
+            pt.y,
+            0,
+            hwnd,
+            None,
+        );
+        let _ = DestroyMenu(menu);
+
+        match cmd.0 as usize {
+            MENU_LOCK => set_click_through(hwnd, true),
+            MENU_HIDE => crate::request_overlay_hide(),
+            MENU_RESET_POSITION => {
+                let mut config = get_config();
+                config.position = Position::TopRight;
+                // A monitor with a position of its own goes back to the shared one
+                let monitor = crate::monitors::name_for_window(hwnd);
+                if let Some(own) = config
+                    .monitor_overlays
+                    .iter_mut()
+                    .find(|m| m.monitor.eq_ignore_ascii_case(&monitor))
+                {
+                    own.position = None;
+                }
+                update_config(&config);
+                if let Err(e) = config.save() {
+                    crate::logging::error(format!("Failed to save config: {e}"));
+                }
+                refresh(hwnd);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn set_click_through(hwnd: HWND, enabled: bool) {
+    CLICK_THROUGH.store(enabled, Ordering::Relaxed);
+    unsafe {
+        let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+        let style = if enabled {
+            style | WS_EX_TRANSPARENT.0 as isize
+        } else {
+            style & !(WS_EX_TRANSPARENT.0 as isize)
+        };
+        SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style);
+    }
+    refresh(hwnd);
+}
+
+unsafe fn create_font(config: &Config, quality: FONT_QUALITY, face: PCWSTR) -> OwnedFont {
+    let angle = if config.vertical {
+        VERTICAL_ESCAPEMENT
+    } else {
+        0
+    };
+    OwnedFont::new(CreateFontW(
+        config.font_size as i32,
+        0,
+        angle,
+        angle,
+        config.font_weight as i32,
+        0,
+        0,
+        0,
+        DEFAULT_CHARSET.0 as u32,
+        OUT_TT_PRECIS.0 as u32,
+        CLIP_DEFAULT_PRECIS.0 as u32,
+        quality.0 as u32,
+        (DEFAULT_PITCH.0 | FF_SWISS.0) as u32,
+        face,
+    ))
+}
+
+/// Font escapement for vertical text: a quarter turn clockwise, in tenths of a degree.
+const VERTICAL_ESCAPEMENT: i32 = 2700;
+
+/// The text and emoji fonts, with each line already split into runs for them.
+struct TextRuns {
+    text_font: OwnedFont,
+    emoji_font: OwnedFont,
+    lines: Vec<Vec<Run>>,
+    vertical: bool,
+}
+
+/// A stretch of one line drawn with one font.
+struct Run {
+    emoji: bool,
+    /// Own color (COLORREF) for a chip, instead of the text color.
+    color: Option<u32>,
+    text: Vec<u16>,
+}
+
+/// `line` split into runs, with the chips it ends with given their colors.
+/// Chips cut off by the width limit stay in the text color.
+fn line_runs(line: &str, chips: &[Chip]) -> Vec<Run> {
+    // Where each chip sits, found from the end of the line
+    let mut end = line.len();
+    let mut colored: Vec<(usize, usize, u32)> = Vec::new();
+    for chip in chips.iter().rev() {
+        let head = line[..end].trim_end();
+        if let Some(before) = head.strip_suffix(chip.text.as_str()) {
+            colored.push((before.len(), head.len(), chip.color));
+            end = before.len();
+        }
+    }
+    let mut pieces: Vec<(&str, Option<u32>)> = Vec::new();
+    let mut at = 0;
+    for &(start, stop, color) in colored.iter().rev() {
+        pieces.push((&line[at..start], None));
+        pieces.push((&line[start..stop], Some(color)));
+        at = stop;
+    }
+    pieces.push((&line[at..], None));
+    pieces
+        .into_iter()
+        .flat_map(|(text, color)| {
+            crate::emoji::runs(text)
+                .into_iter()
+                .map(move |(emoji, run)| Run {
+                    emoji,
+                    color,
+                    text: run.encode_utf16().collect(),
+                })
+        })
+        .collect()
+}
+
+impl TextRuns {
+    /// `chips` are colored where the lines end with them.
+    unsafe fn new(
+        lines: &[String],
+        config: &Config,
+        quality: FONT_QUALITY,
+        chips: &[Chip],
+    ) -> Self {
+        // GDI has no width axis; condensed faces draw at normal width
+        let family = HSTRING::from(config.font_family.name());
+        Self {
+            text_font: create_font(config, quality, PCWSTR(family.as_ptr())),
+            emoji_font: create_font(config, quality, w!("Segoe UI Emoji")),
+            lines: lines.iter().map(|line| line_runs(line, chips)).collect(),
+            vertical: config.vertical,
+        }
+    }
+
+    fn font(&self, emoji: bool) -> &OwnedFont {
+        if emoji {
+            &self.emoji_font
+        } else {
+            &self.text_font
+        }
+    }
+
+    /// Drawn width of line `index`.
+    unsafe fn width(&self, hdc: HDC, index: usize) -> i32 {
+        let mut width = 0;
+        for run in &self.lines[index] {
+            let _font = SelectedObjectGuard::select(hdc, self.font(run.emoji));
+            let mut size = SIZE::default();
+            let _ = GetTextExtentPoint32W(hdc, &run.text, &mut size);
+            width += size.cx;
+        }
+        width
+    }
+
+    /// Draw line `index` from `(x, y)` in the current text color (chips in
+    /// their own when `chip_colors`), down the window instead of across for
+    /// vertical text.
+    unsafe fn draw(&self, hdc: HDC, index: usize, (x, y): (i32, i32), chip_colors: bool) {
+        let (mut x, mut y) = (x, y);
+        for run in &self.lines[index] {
+            let _font = SelectedObjectGuard::select(hdc, self.font(run.emoji));
+            match run.color.filter(|_| chip_colors) {
+                Some(color) => {
+                    let normal = SetTextColor(hdc, COLORREF(color));
+                    let _ = TextOutW(hdc, x, y, &run.text);
+                    SetTextColor(hdc, normal);
+                }
+                None => {
+                    let _ = TextOutW(hdc, x, y, &run.text);
+                }
+            }
+            let mut size = SIZE::default();
+            let _ = GetTextExtentPoint32W(hdc, &run.text, &mut size);
+            if self.vertical {
+                y += size.cx;
+            } else {
+                x += size.cx;
+            }
+        }
+    }
+
+    /// Draw every line at its origin, shifted by `(dx, dy)`.
+    unsafe fn draw_all(&self, hdc: HDC, origins: &[(i32, i32)], (dx, dy): (i32, i32)) {
+        for (index, (x, y)) in origins.iter().enumerate() {
+            self.draw(hdc, index, (x + dx, y + dy), false);
+        }
+    }
+
+    /// Draw every line at its origin in the text color, the chips in theirs.
+    unsafe fn draw_main(&self, hdc: HDC, origins: &[(i32, i32)]) {
+        for (index, &origin) in origins.iter().enumerate() {
+            self.draw(hdc, index, origin, true);
+        }
+    }
+}
+
+/// Where each line of `lines`, `widths` wide, starts drawing.
+fn line_origins(config: &Config, lines: &[String], widths: &[i32]) -> Vec<(i32, i32)> {
+    let ty = scale_px(8);
+    let widest = widths.iter().copied().max().unwrap_or(0);
+    // The box is sized from an estimate, so the text rarely fills it exactly
+    let box_w = text_box_width(config, lines).max(widest);
+    let tx = aligned_x(config.text_align, scale_px(12), widest, box_w);
+    let step = config.font_size as i32 + config.line_spacing as i32;
+    // Vertical text is laid out as if horizontal, then turned a quarter
+    // clockwise: the lines run down the window and stack right to left.
+    let turned_height = text_height(config, lines.len()) + scale_px(16);
+    widths
+        .iter()
+        .enumerate()
+        .map(|(i, &width)| {
+            let x = aligned_x(config.alignment_for_line(i), tx, width, widest);
+            let y = ty + i as i32 * step;
+            if config.vertical {
+                (turned_height - y, x)
+            } else {
+                (x, y)
+            }
+        })
+        .collect()
+}
+
+/// Draw the clock text (with outline/shadow) onto `hdc`, whose drawable
+/// area is `size`. Grayscale antialiasing, since ClearType's colored edges
+/// can't be told apart from coverage in a per-pixel frame.
+unsafe fn draw_text(hdc: HDC, config: &Config, size: (i32, i32)) {
+    let lines = fitted_lines(config);
+    let chips = chips(config);
+    if config.text_renderer == TextRenderer::DirectWrite
+        && draw_text_directwrite(hdc, config, &lines, &chips, size)
+    {
+        return;
+    }
+    SetBkMode(hdc, TRANSPARENT);
+    let text = TextRuns::new(&lines, config, ANTIALIASED_QUALITY, &chips);
+    let widths: Vec<i32> = (0..text.lines.len()).map(|i| text.width(hdc, i)).collect();
+    let origins = line_origins(config, &lines, &widths);
+
+    SetTextColor(hdc, COLORREF(config.outline_colorref()));
+    for offset in config
+        .text_style
+        .offsets(HIGH_CONTRAST.load(Ordering::Relaxed))
+    {
+        text.draw_all(hdc, &origins, offset);
+    }
+    SetTextColor(hdc, COLORREF(config.text_colorref()));
+    text.draw_main(hdc, &origins);
+}
+
+/// Draw the clock text with DirectWrite. Returns false if it couldn't, for
+/// GDI to draw it instead.
+fn draw_text_directwrite(
+    hdc: HDC,
+    config: &Config,
+    lines: &[String],
+    chips: &[Chip],
+    size: (i32, i32),
+) -> bool {
+    // DirectWrite falls back to an emoji font by itself, so each line is one run
+    let dw_lines: Vec<directwrite::Line> = lines
+        .iter()
+        .map(|line| {
+            let mut line_text = Vec::new();
+            let mut colors = Vec::new();
+            for run in line_runs(line, chips) {
+                if let Some(color) = run.color {
+                    let start = line_text.len() as u32;
+                    colors.push((start, run.text.len() as u32, color));
+                }
+                line_text.extend(run.text);
+            }
+            directwrite::Line {
+                text: line_text,
+                colors,
+            }
+        })
+        .collect();
+    let font = directwrite::Font {
+        family: config.font_family.name(),
+        px: config.font_size,
+        weight: config.font_weight,
+        width: config.font_width,
+        subpixel: config.subpixel_text,
+    };
+    let Some(text) = directwrite::Text::new(&dw_lines, &font) else {
+        return false;
+    };
+    let widths: Vec<i32> = (0..dw_lines.len()).map(|i| text.width(i)).collect();
+    let origins = line_origins(config, lines, &widths);
+    let outline_cr = config.outline_colorref();
+    let mut passes: Vec<directwrite::Pass> = config
+        .text_style
+        .offsets(HIGH_CONTRAST.load(Ordering::Relaxed))
+        .into_iter()
+        .map(|offset| directwrite::Pass {
+            offset,
+            color: outline_cr,
+            line_colors: false,
+        })
+        .collect();
+    passes.push(directwrite::Pass {
+        offset: (0, 0),
+        color: config.text_colorref(),
+        line_colors: true,
+    });
+    directwrite::draw(hdc, size, &text, &origins, config.vertical, &passes)
+}
+
+/// The palette's alert color, or a darker shade of it for the off beat of a pulse.
+fn alert_colorref(config: &Config, bright: bool) -> u32 {
+    let [_, _, alert] = config.alert_levels();
+    if bright {
+        rgb_colorref(alert)
+    } else {
+        rgb_colorref(alert.map(|c| (c as u32 * 5 / 8) as u8))
+    }
+}
+
+/// Whether a pulsing alert is on its bright beat this second. Always, when
+/// motion is reduced, so the alert holds a steady color.
+fn bright_beat(config: &Config) -> bool {
+    theme::reduce_motion(config) || chrono::Local::now().timestamp() % 2 == 0
+}
+
+/// [R,G,B] as a COLORREF (0x00BBGGRR).
+fn rgb_colorref([r, g, b]: [u8; 3]) -> u32 {
+    r as u32 | (g as u32) << 8 | (b as u32) << 16
+}
+
+unsafe extern "system" fn wnd_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_TIMER => {
+            match wparam.0 {
+                CURSOR_TIMER_ID => check_cursor(hwnd),
+                WATCHDOG_TIMER_ID => zorder::reassert(hwnd),
+                INPUT_TIMER_ID => {
+                    if crate::inputdisplay::needs_redraw() {
+                        refresh(hwnd);
+                    }
+                }
+                FLASH_TIMER_ID => {
+                    let remaining = FLASH_REMAINING.load(Ordering::Relaxed).saturating_sub(1);
+                    FLASH_REMAINING.store(remaining, Ordering::Relaxed);
+                    if remaining == 0 {
+                        let _ = KillTimer(hwnd, FLASH_TIMER_ID);
                     }
-                    SetTextColor(hdc, COLORREF(text_cr));
-                    let _ = TextOutW(hdc, tx, ty, &wide);
+                    refresh(hwnd);
                 }
-                TextStyle::Shadow => {
-                    SetTextColor(hdc, COLORREF(outline_cr));
-                    let _ = TextOutW(hdc, tx + 2, ty + 2, &wide);
-                    SetTextColor(hdc, COLORREF(text_cr));
-                    let _ = TextOutW(hdc, tx, ty, &wide);
+                ZOOM_TIMER_ID => {
+                    let _ = KillTimer(hwnd, ZOOM_TIMER_ID);
+                    ZOOMED.store(false, Ordering::Relaxed);
+                    refresh(hwnd);
                 }
-                TextStyle::None => {
-                    SetTextColor(hdc, COLORREF(text_cr));
-                    let _ = TextOutW(hdc, tx, ty, &wide);
+                _ => {
+                    let config = get_config();
+                    crate::plugins::sync(&config);
+                    crate::cpu::sample();
+                    if config.show_input_language || config.template.contains("{layout") {
+                        crate::inputlang::sample();
+                    }
+                    crate::textfile::update(&config.text_file);
+                    crate::command::update(&config.command, config.command_interval_seconds);
+                    crate::script::update(&config.script, &script_context(&config));
+                    if DEBUG.load(Ordering::Relaxed) {
+                        update_debug(hwnd);
+                    }
+                    refresh(hwnd);
                 }
             }
-
-            SelectObject(hdc, old_font);
-            let _ = DeleteObject(font);
-
-            let _ = EndPaint(hwnd, &ps);
             LRESULT(0)
         }
-        WM_TIMER => {
-            let config = get_config();
-            // Use overlay's own monitor (stays on the monitor where it was shown)
-            let monitor = monitor_rect_for(hwnd);
-            let (x, y, w, h) = calc_window_rect(&config, monitor);
-            let alpha = (config.opacity as f32 / 100.0 * 255.0) as u8;
-            let _ = SetLayeredWindowAttributes(hwnd, COLOR_KEY, alpha, LWA_COLORKEY | LWA_ALPHA);
-            let _ = SetWindowPos(hwnd, HWND_TOPMOST, x, y, w, h, SWP_NOACTIVATE);
-            let _ = InvalidateRect(hwnd, None, true);
+        // Treat the whole window as a caption so a plain drag moves it
+        WM_NCHITTEST if !CLICK_THROUGH.load(Ordering::Relaxed) => LRESULT(HTCAPTION as isize),
+        WM_MOUSEACTIVATE => LRESULT(MA_NOACTIVATE as isize),
+        WM_NCRBUTTONUP => {
+            show_context_menu(hwnd);
+            LRESULT(0)
+        }
+        WM_ENTERSIZEMOVE => {
+            DRAGGING.store(true, Ordering::Relaxed);
+            LRESULT(0)
+        }
+        WM_MOVING => {
+            snap_moving(hwnd, &mut *(lparam.0 as *mut RECT));
+            LRESULT(1)
+        }
+        WM_EXITSIZEMOVE => {
+            DRAGGING.store(false, Ordering::Relaxed);
+            save_dragged_position(hwnd);
+            LRESULT(0)
+        }
+        WM_SETTINGCHANGE | WM_SYSCOLORCHANGE | WM_DWMCOLORIZATIONCOLORCHANGED => {
+            // Theme switches arrive as "ImmersiveColorSet", high contrast also
+            // changes the system colors. Redraw now instead of on the next tick.
+            crate::request_theme_refresh();
+            refresh(hwnd);
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_POWERBROADCAST if wparam.0 as u32 == PBT_POWERSETTINGCHANGE => {
+            let setting = &*(lparam.0 as *const POWERBROADCAST_SETTING);
+            // Data is 0 for off, 1 for on and 2 for dimmed
+            if setting.PowerSetting == GUID_CONSOLE_DISPLAY_STATE {
+                set_display_off(hwnd, setting.Data[0] == 0);
+            }
+            LRESULT(1)
+        }
+        WM_ENDSESSION => {
+            // Windows may end the process without a WM_QUIT after this
+            if wparam.0 != 0 {
+                crate::save_visibility();
+            }
+            LRESULT(0)
+        }
+        WM_GETOBJECT => uia::handle_get_object(hwnd, wparam, lparam, spoken_text)
+            .unwrap_or_else(|| DefWindowProcW(hwnd, msg, wparam, lparam)),
+        notify::WM_NOTIFY_CALLBACK => {
+            notify::handle_callback(hwnd, lparam);
+            LRESULT(0)
+        }
+        WM_RELOAD_CONFIG => {
+            crate::logging::debug("Config reloaded by --set");
+            let previous = get_config();
+            let config = Config::load();
+            crate::autostart::apply(&config, &previous);
+            update_config(&config);
+            crate::request_hotkey_reregister();
+            refresh(hwnd);
             LRESULT(0)
         }
         WM_DESTROY => {
+            notify::dismiss(hwnd);
+            uia::disconnect(hwnd);
             let _ = KillTimer(hwnd, TIMER_ID);
+            let _ = KillTimer(hwnd, CURSOR_TIMER_ID);
+            let _ = KillTimer(hwnd, FLASH_TIMER_ID);
+            let _ = KillTimer(hwnd, ZOOM_TIMER_ID);
+            let _ = KillTimer(hwnd, WATCHDOG_TIMER_ID);
+            let _ = KillTimer(hwnd, INPUT_TIMER_ID);
+            close_monitor_clocks();
+            crate::plugins::shutdown();
             PostQuitMessage(0);
             LRESULT(0)
         }
@@ -227,8 +1741,10 @@ unsafe extern "system" fn wnd_proc(
 }
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
     use super::*;
+    use crate::config::AlertPalette;
 
     const PRIMARY: (i32, i32, i32, i32) = (0, 0, 1920, 1080);
     const OFFSET: (i32, i32, i32, i32) = (1920, 0, 2560, 1440);
@@ -363,21 +1879,639 @@ mod tests {
         assert!(s.ends_with("AM") || s.ends_with("PM"));
     }
 
-    // --- guard_color_key ---
+    #[test]
+    fn custom_time_format_overrides_the_settings() {
+        let mut cfg = test_config();
+        cfg.format_24h = false;
+        cfg.time_format = "%H.%M.%S.%3f".to_string();
+        let s = format_time(&cfg);
+        // "HH.MM.SS.mmm" — 12 chars
+        assert_eq!(s.len(), 12);
+        assert!(!s.ends_with('M'));
+
+        cfg.time_format = "%H:%Q".to_string();
+        assert!(format_time(&cfg).ends_with('M'));
+    }
+
+    // --- estimate_text_width ---
+
+    #[test]
+    fn text_width_ascii() {
+        // 22px font -> 13px per ASCII char
+        assert_eq!(estimate_text_width("12:34", 22), 13 * 5);
+    }
+
+    #[test]
+    fn text_width_wide_chars() {
+        // Full-width characters count as a whole font height each
+        assert_eq!(estimate_text_width("服薬", 22), 22 * 2);
+        assert!(estimate_text_width("服薬", 22) > estimate_text_width("ab", 22));
+    }
+
+    #[test]
+    fn text_width_emoji_sequences() {
+        // A ZWJ sequence is still drawn as one glyph
+        assert_eq!(estimate_text_width("👩\u{200D}💻", 22), 22 * 2);
+        assert_eq!(estimate_text_width("❤\u{FE0F}", 22), 22);
+    }
+
+    // --- display_text segments ---
+
+    #[test]
+    fn progress_widens_window() {
+        let plain = test_config();
+        let (_, _, w_plain, _) = calc_window_rect(&plain, PRIMARY);
+
+        let mut with_progress = test_config();
+        with_progress.progress = crate::config::ProgressDisplay::Year;
+        let (_, _, w_progress, _) = calc_window_rect(&with_progress, PRIMARY);
+
+        assert!(w_progress > w_plain);
+    }
+
+    #[test]
+    fn color_rule_recolors_text_and_panel() {
+        use crate::config::ColorRule;
+        let night = chrono::NaiveDate::from_ymd_opt(2026, 3, 7)
+            .unwrap()
+            .and_hms_opt(1, 0, 0)
+            .unwrap();
+        let cfg = Config {
+            color_rules: vec![ColorRule {
+                background_color: Some([10, 0, 0]),
+                ..ColorRule::default()
+            }],
+            ..test_config()
+        };
+        let drawn = with_color_rules(cfg.clone(), night);
+        assert_eq!(drawn.text_color, [255, 64, 64]);
+        assert_eq!(drawn.background_color, [10, 0, 0]);
+        let noon = night + chrono::Duration::hours(11);
+        assert_eq!(with_color_rules(cfg.clone(), noon), cfg);
+    }
+
+    #[test]
+    fn monitor_scale_grows_text() {
+        let cfg = Config {
+            max_width: 300,
+            ..test_config()
+        };
+        let big = scaled(cfg.clone(), 200);
+        assert_eq!(big.font_size, cfg.font_size * 2);
+        assert_eq!(big.max_width, 600);
+        assert_eq!(scaled(cfg.clone(), 100), cfg);
+    }
+
+    #[test]
+    fn multi_line_stacks_segments() {
+        let mut cfg = test_config();
+        cfg.progress = crate::config::ProgressDisplay::Year;
+        let (_, _, w_row, h_row) = calc_window_rect(&cfg, PRIMARY);
+        assert_eq!(display_lines(&cfg).len(), 1);
+
+        cfg.multi_line = true;
+        cfg.line_spacing = 4;
+        assert_eq!(display_lines(&cfg).len(), 2);
+        let (_, _, w_stack, h_stack) = calc_window_rect(&cfg, PRIMARY);
+        assert_eq!(h_stack, h_row + cfg.font_size as i32 + 4);
+        assert!(w_stack < w_row);
+        // Still read out as one line
+        assert!(!display_text(&cfg).contains('\n'));
+    }
+
+    #[test]
+    fn date_goes_under_the_clock() {
+        let mut cfg = test_config();
+        cfg.progress = crate::config::ProgressDisplay::Year;
+        cfg.date_line = DateDisplay::Iso;
+        let lines = display_lines(&cfg);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains('%'));
+        let date = chrono::NaiveDate::parse_from_str(&lines[1], "%Y-%m-%d");
+        assert!(date.is_ok());
+
+        cfg.multi_line = true;
+        let lines = display_lines(&cfg);
+        assert_eq!(lines.len(), 3);
+        assert!(chrono::NaiveDate::parse_from_str(&lines[1], "%Y-%m-%d").is_ok());
+        assert!(lines[2].contains('%'));
+    }
+
+    #[test]
+    fn world_clocks_in_their_zones() {
+        use crate::config::WorldClock;
+        let mut cfg = test_config();
+        cfg.format_24h = true;
+        cfg.show_seconds = false;
+        cfg.world_clocks = vec![
+            WorldClock {
+                label: String::new(),
+                zone: "Asia/Tokyo".to_string(),
+            },
+            WorldClock {
+                label: "NY".to_string(),
+                zone: "America/New_York".to_string(),
+            },
+            WorldClock {
+                label: "?".to_string(),
+                zone: "Mars/Olympus".to_string(),
+            },
+        ];
+        let now = chrono::DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        assert_eq!(world_clock_lines(&cfg, now), vec!["JST 09:00", "NY 19:00"]);
+
+        // Under the clock and the date
+        cfg.date_line = DateDisplay::Iso;
+        let lines = display_lines(&cfg);
+        assert_eq!(lines.len(), 4);
+        assert!(chrono::NaiveDate::parse_from_str(&lines[1], "%Y-%m-%d").is_ok());
+        assert!(lines[2].starts_with("JST "));
+        assert!(lines[3].starts_with("NY "));
+    }
+
+    #[test]
+    fn lines_align_within_the_widest() {
+        assert_eq!(aligned_x(TextAlign::Left, 12, 50, 100), 12);
+        assert_eq!(aligned_x(TextAlign::Center, 12, 50, 100), 37);
+        assert_eq!(aligned_x(TextAlign::Right, 12, 50, 100), 62);
+    }
+
+    // --- snapping ---
+
+    #[test]
+    fn drags_snap_to_edges_at_the_corner_margin() {
+        let monitor = (1920, 0, 1920, 1080);
+        // Near the top-right corner: both axes snap
+        assert_eq!(
+            snapped((3720, 22, 100, 40), monitor, 10, 16, 0, true),
+            (3730, 10)
+        );
+        // Far from every edge: left alone
+        assert_eq!(
+            snapped((2500, 500, 100, 40), monitor, 10, 16, 0, true),
+            (2500, 500)
+        );
+        assert_eq!(
+            snapped((3720, 22, 100, 40), monitor, 10, 16, 0, false),
+            (3720, 22)
+        );
+    }
+
+    #[test]
+    fn drags_snap_to_the_grid_away_from_edges() {
+        let monitor = (0, 0, 1920, 1080);
+        assert_eq!(
+            snapped((514, 493, 100, 40), monitor, 10, 16, 20, true),
+            (520, 500)
+        );
+        // An edge wins over the grid
+        assert_eq!(
+            snapped((14, 493, 100, 40), monitor, 10, 16, 20, true),
+            (10, 500)
+        );
+        // Left of the monitor origin
+        assert_eq!(
+            snapped(
+                (-1034, 0, 100, 40),
+                (-1920, 0, 1920, 1080),
+                10,
+                16,
+                50,
+                false
+            ),
+            (-1020, 0)
+        );
+    }
+
+    // --- cursor reaction ---
+
+    #[test]
+    fn rect_contains_edges() {
+        let rect = (100, 100, 50, 20);
+        assert!(rect_contains(rect, (100, 100)));
+        assert!(rect_contains(rect, (149, 119)));
+        assert!(!rect_contains(rect, (150, 110)));
+        assert!(!rect_contains(rect, (120, 99)));
+    }
+
+    #[test]
+    fn placement_ignores_cursor_when_off() {
+        let cfg = test_config();
+        assert_eq!(
+            placement(&cfg, PRIMARY, true),
+            placement(&cfg, PRIMARY, false)
+        );
+    }
+
+    #[test]
+    fn placement_dim_lowers_alpha() {
+        let mut cfg = test_config();
+        cfg.cursor_reaction = CursorReaction::Dim;
+        let (rect_far, alpha_far) = placement(&cfg, PRIMARY, false);
+        let (rect_near, alpha_near) = placement(&cfg, PRIMARY, true);
+        assert_eq!(rect_far, rect_near);
+        assert!(alpha_near < alpha_far);
+    }
+
+    #[test]
+    fn placement_dodge_moves_to_opposite_corner() {
+        let mut cfg = test_config(); // TopRight
+        cfg.cursor_reaction = CursorReaction::Dodge;
+        let ((x, y, _, h), _) = placement(&cfg, PRIMARY, true);
+        assert_eq!(x, 10);
+        assert_eq!(y, 1080 - h - 10);
+    }
+
+    // --- toggle flash ---
+
+    #[test]
+    fn flash_alpha_alternates_and_ends() {
+        assert_eq!(flash_alpha(0), None);
+        assert_eq!(flash_alpha(FLASH_STEPS), Some(255));
+        assert_eq!(
+            flash_alpha(FLASH_STEPS - 1),
+            Some(opacity_alpha(DIM_OPACITY))
+        );
+    }
+
+    // --- custom position ---
+
+    #[test]
+    fn custom_position_offsets_from_monitor() {
+        let mut cfg = test_config();
+        cfg.position = Position::Custom;
+        cfg.custom_pos = [300, 200];
+        let (x, y, _, _) = calc_window_rect(&cfg, OFFSET);
+        assert_eq!(x, 1920 + 300);
+        assert_eq!(y, 200);
+    }
+
+    #[test]
+    fn custom_position_clamped_on_screen() {
+        let mut cfg = test_config();
+        cfg.position = Position::Custom;
+        cfg.custom_pos = [5000, -50];
+        let (x, y, w, _) = calc_window_rect(&cfg, PRIMARY);
+        assert_eq!(x, 1920 - w);
+        assert_eq!(y, 0);
+    }
+
+    // --- max width ---
+
+    #[test]
+    fn truncate_leaves_short_text_alone() {
+        assert_eq!(truncate_to_width("12:34", 22, 0), "12:34");
+        assert_eq!(truncate_to_width("12:34", 22, 500), "12:34");
+    }
+
+    #[test]
+    fn truncate_adds_ellipsis_within_limit() {
+        let long = "12:34  Take medication with water";
+        let cut = truncate_to_width(long, 22, 200);
+        assert!(cut.ends_with(ELLIPSIS));
+        assert!(cut.starts_with("12:34"));
+        assert!(estimate_text_width(&cut, 22) <= 200);
+    }
+
+    #[test]
+    fn truncate_drops_trailing_space() {
+        // "12:34" fits but the budget ends inside the separator
+        let cut = truncate_to_width("12:34  abcdef", 22, 13 * 6 + 22);
+        assert_eq!(cut, "12:34…");
+    }
+
+    #[test]
+    fn max_width_caps_window() {
+        let mut cfg = test_config();
+        cfg.progress = crate::config::ProgressDisplay::Year;
+        let (_, _, wide, _) = calc_window_rect(&cfg, PRIMARY);
+        cfg.max_width = 100;
+        let (_, _, capped, _) = calc_window_rect(&cfg, PRIMARY);
+        assert!(capped < wide);
+        assert!(capped <= 100 + 24 + 4);
+    }
+
+    #[test]
+    fn high_contrast_overrides_colors() {
+        let mut cfg = test_config();
+        cfg.text_style = TextStyle::Shadow;
+        cfg.opacity = 40;
+        cfg.font_size = 30;
+        let colors = theme::HighContrast {
+            text: [255, 255, 0],
+            background: [0, 0, 0],
+        };
+        let hc = with_high_contrast(cfg.clone(), colors);
+        assert_eq!(hc.text_color, [255, 255, 0]);
+        assert_eq!(hc.outline_color, [0, 0, 0]);
+        assert_eq!(hc.background_color, [0, 0, 0]);
+        assert_eq!(hc.text_style, TextStyle::Outline);
+        assert_eq!(hc.opacity, 100);
+        assert_eq!(hc.backdrop, Backdrop::Flat);
+        // Layout settings are the user's
+        assert_eq!(hc.font_size, 30);
+        assert_eq!(hc.position, cfg.position);
+    }
+
+    #[test]
+    fn debug_line_reports_geometry_and_timing() {
+        let stats = DebugStats {
+            frames: 0,
+            frames_per_second: 2,
+            second_start: None,
+            last_tick: None,
+            drift_ms: 15,
+            config_loaded: chrono::NaiveTime::from_hms_opt(9, 5, 0),
+            paint_p95: Some(Duration::from_micros(420)),
+            toggle_latency: Some(Duration::from_millis(18)),
+            line: String::new(),
+        };
+        let line = format_debug((1700, 10, 210, 38), (0, 0, 1920, 1080), 144, &stats);
+        assert_eq!(
+            line,
+            "[win 1700,10 210x38 | mon 0,0 1920x1080 | 144dpi (150%) | 2 fps | paint p95 0.4ms | toggle 18.0ms | drift +15ms | cfg 09:05:00]"
+        );
+    }
+
+    #[test]
+    fn zoom_doubles_size_and_keeps_unlimited_width() {
+        let mut cfg = test_config();
+        cfg.font_size = 30;
+        let big = zoomed(cfg.clone());
+        assert_eq!(big.font_size, 60);
+        assert_eq!(big.max_width, 0);
+        let monitor = (0, 0, 1920, 1080);
+        assert!(calc_window_rect(&big, monitor).3 > calc_window_rect(&cfg, monitor).3);
+        cfg.max_width = 200;
+        assert_eq!(zoomed(cfg).max_width, 400);
+    }
+
+    fn chip(text: &str, color: u32) -> Chip {
+        Chip {
+            text: text.to_string(),
+            color,
+            attached: false,
+        }
+    }
+
+    #[test]
+    fn attached_chips_share_a_segment() {
+        let chips = [
+            chip("🔋 9%", 2),
+            chip("Ctrl+C", 3),
+            Chip {
+                attached: true,
+                ..chip("LMB", 4)
+            },
+        ];
+        let runs = line_runs("12:00  🔋 9%  Ctrl+C LMB", &chips);
+        assert_eq!(
+            colored(&runs)[4..],
+            [
+                (Some(3), "Ctrl+C".to_string()),
+                (None, " ".to_string()),
+                (Some(4), "LMB".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn inputs_fade_toward_the_background() {
+        assert_eq!(faded_colorref([255, 255, 255], [0, 0, 0], 0.0), 0x00FF_FFFF);
+        assert_eq!(faded_colorref([255, 255, 255], [0, 0, 0], 1.0), 0);
+        assert_eq!(
+            faded_colorref([200, 100, 0], [0, 100, 200], 0.5),
+            0x0064_6464
+        );
+    }
+
+    fn colored(runs: &[Run]) -> Vec<(Option<u32>, String)> {
+        runs.iter()
+            .map(|r| (r.color, String::from_utf16_lossy(&r.text)))
+            .collect()
+    }
+
+    #[test]
+    fn chip_runs_get_their_colors() {
+        let chips = [chip("⏰ 5m Standup", 1), chip("🔋 9%", 2)];
+        let runs = line_runs("12:00  ⏰ 5m Standup  🔋 9%", &chips);
+        assert_eq!(
+            colored(&runs),
+            [
+                (None, "12:00  ".to_string()),
+                (Some(1), "⏰".to_string()),
+                (Some(1), " 5m Standup".to_string()),
+                (None, "  ".to_string()),
+                (Some(2), "🔋".to_string()),
+                (Some(2), " 9%".to_string()),
+            ]
+        );
+        // One chip per line when multi-line
+        assert_eq!(
+            colored(&line_runs("⏰ 5m Standup", &chips))[1],
+            (Some(1), " 5m Standup".to_string())
+        );
+        // Cut short by max width: nothing is colored
+        assert!(line_runs("12:00  🔋…", &chips)
+            .iter()
+            .all(|r| r.color.is_none()));
+    }
+
+    #[test]
+    fn reminder_warning_escalates() {
+        let lead = 15 * 60;
+        let config = Config::default();
+        assert_eq!(warning_colorref(&config, 14 * 60, lead), 0x0000D2FF);
+        assert_eq!(warning_colorref(&config, 5 * 60, lead), 0x00008CFF);
+        assert_eq!(warning_colorref(&config, 60, lead), 0x004040FF);
+    }
+
+    #[test]
+    fn shutdown_counts_down_when_the_timer_is_known() {
+        let since = chrono::Local::now();
+        let later = |secs| since + chrono::Duration::seconds(secs);
+        assert_eq!(
+            shutdown_text(since, later(190), 0),
+            "⚠ Shutdown requested 3m ago"
+        );
+        assert_eq!(shutdown_text(since, later(1), 10), "⚠ Shutdown in 9:59");
+        assert_eq!(shutdown_text(since, later(0), 90), "⚠ Shutdown in 1:30:00");
+        // Still pending after the timer ran out
+        assert_eq!(shutdown_text(since, later(700), 10), "⚠ Shutdown in 0:00");
+    }
+
+    #[test]
+    fn alert_colors_follow_the_palette() {
+        let mut config = Config {
+            alert_palette: AlertPalette::Custom,
+            alert_colors: [[0, 0, 255], [0, 255, 0], [255, 0, 0]],
+            ..Default::default()
+        };
+        assert_eq!(warning_colorref(&config, 60, 15 * 60), 0x000000FF);
+        assert_eq!(warning_colorref(&config, 14 * 60, 15 * 60), 0x00FF0000);
+        assert_eq!(alert_colorref(&config, true), 0x000000FF);
+        assert_eq!(alert_colorref(&config, false), 0x0000009F);
+        config.alert_palette = AlertPalette::ColorblindSafe;
+        assert_eq!(alert_colorref(&config, true), 0x00005ED5);
+    }
+
+    #[test]
+    fn text_box_leaves_room_for_outline() {
+        let lines = vec!["12:00".to_string()];
+        let plain = Config {
+            text_style: TextStyle::None,
+            ..test_config()
+        };
+        let outlined = Config {
+            text_style: TextStyle::Outline,
+            ..test_config()
+        };
+        let width = text_box_width(&plain, &lines);
+        assert_eq!(width, estimate_text_width("12:00", plain.font_size as i32));
+        assert_eq!(text_box_width(&outlined, &lines), width + 4);
+    }
 
     #[test]
-    fn guard_color_key_passes_normal_colors() {
-        assert_eq!(guard_color_key(0x00FFFFFF), 0x00FFFFFF); // white
-        assert_eq!(guard_color_key(0x00000000), 0x00000000); // black
-        assert_eq!(guard_color_key(0x000000FF), 0x000000FF); // red
+    fn vertical_text_turns_the_window() {
+        let cfg = test_config();
+        let (_, _, w, h) = calc_window_rect(&cfg, PRIMARY);
+        let turned = Config {
+            vertical: true,
+            ..cfg
+        };
+        // Still hugs the top-right corner, now as a tall strip
+        let (x, _, vw, vh) = calc_window_rect(&turned, PRIMARY);
+        assert_eq!((vw, vh), (h, w));
+        assert!(vh > vw);
+        assert_eq!(x + vw, 1920 - 10);
+    }
+}
+
+/// Drives the real window through show/hide, config updates and timer ticks
+/// and checks what Windows reports back. Run with
+/// `cargo test --features win32-tests win32` on a desktop session.
+#[cfg(all(test, feature = "win32-tests"))]
+mod win32_tests {
+    use super::*;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        DispatchMessageW, GetLayeredWindowAttributes, IsWindowVisible, PeekMessageW,
+        TranslateMessage, LAYERED_WINDOW_ATTRIBUTES_FLAGS, MSG, PM_REMOVE,
+    };
+
+    /// Where the overlay's "monitor" is during these tests: far off the desktop.
+    const OFFSCREEN_MONITOR: (i32, i32, i32, i32) = (-20000, -20000, 1920, 1080);
+
+    /// Stands in for the real lookup so the window never appears on screen.
+    pub fn monitor_override() -> Option<(i32, i32, i32, i32)> {
+        Some(OFFSCREEN_MONITOR)
+    }
+
+    /// Run this thread's messages (timers, paints) for `duration`.
+    fn pump_for(duration: Duration) {
+        let end = Instant::now() + duration;
+        let mut msg = MSG::default();
+        while Instant::now() < end {
+            unsafe {
+                while PeekMessageW(&mut msg, HWND::default(), 0, 0, PM_REMOVE).as_bool() {
+                    let _ = TranslateMessage(&msg);
+                    DispatchMessageW(&msg);
+                }
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    fn window_rect(hwnd: HWND) -> (i32, i32, i32, i32) {
+        let mut rc = RECT::default();
+        unsafe { GetWindowRect(hwnd, &mut rc) }.unwrap();
+        (rc.left, rc.top, rc.right - rc.left, rc.bottom - rc.top)
+    }
+
+    fn ex_style(hwnd: HWND) -> u32 {
+        unsafe { GetWindowLongPtrW(hwnd, GWL_EXSTYLE) as u32 }
+    }
+
+    /// Color key, alpha and flags, or `None` for a per-pixel (UpdateLayeredWindow) window.
+    fn layered_attributes(hwnd: HWND) -> Option<(COLORREF, u8, LAYERED_WINDOW_ATTRIBUTES_FLAGS)> {
+        let mut key = COLORREF(0);
+        let mut alpha = 0u8;
+        let mut flags = LAYERED_WINDOW_ATTRIBUTES_FLAGS(0);
+        unsafe {
+            GetLayeredWindowAttributes(hwnd, Some(&mut key), Some(&mut alpha), Some(&mut flags))
+        }
+        .ok()
+        .map(|()| (key, alpha, flags))
+    }
+
+    fn visible(hwnd: HWND) -> bool {
+        unsafe { IsWindowVisible(hwnd) }.as_bool()
     }
 
+    // One test, since the overlay's state is process-wide
     #[test]
-    fn guard_color_key_nudges_matching_color() {
-        // COLOR_KEY = 0x00010001, should be nudged
-        assert_ne!(guard_color_key(0x00010001), 0x00010001);
-        // Result should differ only slightly
-        assert_eq!(guard_color_key(0x00010001), 0x00000001);
+    fn win32_overlay_window_cycle() {
+        let config = Config::default();
+        let overlay = Overlay::new(&config);
+        let hwnd = overlay.hwnd;
+        assert!(!visible(hwnd), "starts hidden");
+        let style = ex_style(hwnd);
+        for flag in [
+            WS_EX_TOPMOST,
+            WS_EX_TRANSPARENT,
+            WS_EX_LAYERED,
+            WS_EX_TOOLWINDOW,
+        ] {
+            assert_ne!(style & flag.0, 0, "missing {flag:?}");
+        }
+
+        // Shown: placed on its monitor, drawn with per-pixel alpha, which has
+        // no layered attributes
+        overlay.show();
+        assert!(visible(hwnd));
+        let shown = window_rect(hwnd);
+        assert_eq!(shown, calc_window_rect(&drawn_config(), OFFSCREEN_MONITOR));
+        assert!(layered_attributes(hwnd).is_none());
+
+        // Config update: moves and resizes on refresh
+        update_config(&Config {
+            position: Position::BottomLeft,
+            font_size: 40,
+            ..config.clone()
+        });
+        overlay.refresh();
+        let moved = window_rect(hwnd);
+        assert_eq!(moved, calc_window_rect(&drawn_config(), OFFSCREEN_MONITOR));
+        assert_ne!(moved, shown);
+
+        // A visible panel stays per-pixel
+        update_config(&Config {
+            background_opacity: 50,
+            ..config.clone()
+        });
+        overlay.refresh();
+        assert!(layered_attributes(hwnd).is_none());
+        update_config(&config);
+        overlay.refresh();
+
+        // Unlocking drops click-through
+        assert!(!overlay.toggle_click_through());
+        assert_eq!(ex_style(hwnd) & WS_EX_TRANSPARENT.0, 0);
+        assert!(overlay.toggle_click_through());
+        assert_ne!(ex_style(hwnd) & WS_EX_TRANSPARENT.0, 0);
+
+        // The 1-second timer keeps ticking: the debug line is rebuilt on each tick
+        overlay.toggle_debug();
+        DEBUG_STATS.lock().unwrap().line.clear();
+        pump_for(Duration::from_millis(1500));
+        assert!(DEBUG_STATS.lock().unwrap().line.starts_with("[win -2"));
+        overlay.toggle_debug();
+        gdi::assert_released();
+
+        overlay.hide();
+        assert!(!visible(hwnd));
+        overlay.destroy();
     }
 }
 
@@ -399,6 +2533,12 @@ impl Overlay {
                 ..Default::default()
             };
             RegisterClassW(&wc);
+            RegisterClassW(&WNDCLASSW {
+                lpfnWndProc: Some(monitor_clock_proc),
+                hInstance: hinstance_win,
+                lpszClassName: MONITOR_CLOCK_CLASS_NAME,
+                ..Default::default()
+            });
 
             // Initial position on primary monitor (overlay starts hidden)
             let monitor = monitor_rect_for(HWND::default());
@@ -406,49 +2546,246 @@ impl Overlay {
 
             let ex_style = WS_EX_TOPMOST | WS_EX_TRANSPARENT | WS_EX_LAYERED | WS_EX_TOOLWINDOW;
 
-            let hwnd = CreateWindowExW(
-                ex_style,
-                CLASS_NAME,
-                w!("ClockOR"),
-                WS_POPUP,
-                x,
-                y,
-                w,
-                h,
-                None,
-                None,
-                hinstance_win,
-                None,
-            )
-            .unwrap();
-
-            let alpha = (config.opacity as f32 / 100.0 * 255.0) as u8;
-            let _ = SetLayeredWindowAttributes(hwnd, COLOR_KEY, alpha, LWA_COLORKEY | LWA_ALPHA);
+            let banded = if config.z_order == ZOrder::Band {
+                zorder::create_in_band(
+                    ex_style,
+                    CLASS_NAME,
+                    w!("ClockOR"),
+                    WS_POPUP,
+                    (x, y, w, h),
+                    hinstance_win,
+                )
+            } else {
+                None
+            };
+            let hwnd = banded.unwrap_or_else(|| {
+                CreateWindowExW(
+                    ex_style,
+                    CLASS_NAME,
+                    w!("ClockOR"),
+                    WS_POPUP,
+                    x,
+                    y,
+                    w,
+                    h,
+                    None,
+                    None,
+                    hinstance_win,
+                    None,
+                )
+                .unwrap()
+            });
 
-            SetTimer(hwnd, TIMER_ID, 1000, None);
+            start_timer(hwnd, TIMER_ID, 1000, TICK_TOLERANCE_MS);
+            // Stop ticking while the screen is off; see set_display_off()
+            if let Err(e) = RegisterPowerSettingNotification(
+                HANDLE(hwnd.0),
+                &GUID_CONSOLE_DISPLAY_STATE,
+                DEVICE_NOTIFY_WINDOW_HANDLE,
+            ) {
+                crate::logging::debug(format!("Display power notifications unavailable: {e}"));
+            }
 
             Overlay { hwnd }
         }
     }
 
     pub fn show(&self) {
+        let hotkey_at = HOTKEY_AT.lock().unwrap().take();
+        let started = Instant::now();
         unsafe {
             let config = get_config();
             // Position on the foreground window's monitor (likely the game)
-            let monitor = monitor_rect_for(GetForegroundWindow());
-            let (x, y, w, h) = calc_window_rect(&config, monitor);
-            let alpha = (config.opacity as f32 / 100.0 * 255.0) as u8;
-            let _ =
-                SetLayeredWindowAttributes(self.hwnd, COLOR_KEY, alpha, LWA_COLORKEY | LWA_ALPHA);
+            let (monitor, name) = home_monitor_rect(&config, GetForegroundWindow());
+            let (x, y, w, h) = calc_window_rect(&config.on_monitor(&name), monitor);
             let _ = SetWindowPos(self.hwnd, HWND_TOPMOST, x, y, w, h, SWP_NOACTIVATE);
+            // Now on the right monitor; apply cursor reaction and opacity from there
+            refresh(self.hwnd);
             let _ = ShowWindow(self.hwnd, SW_SHOWNOACTIVATE);
         }
+        sync_monitor_clocks(self.hwnd);
+        let Some(hotkey_at) = hotkey_at else {
+            return;
+        };
+        let done = Instant::now();
+        let latency = done.duration_since(hotkey_at);
+        crate::logging::debug(format!(
+            "Toggle latency {}: {} queued, {} to show and paint",
+            frametime::format_ms(latency),
+            frametime::format_ms(started.saturating_duration_since(hotkey_at)),
+            frametime::format_ms(done.duration_since(started)),
+        ));
+        DEBUG_STATS.lock().unwrap().toggle_latency = Some(latency);
+    }
+
+    /// Time the next `show` from `posted`, when the toggle hotkey was posted.
+    pub fn time_next_show(&self, posted: Instant) {
+        *HOTKEY_AT.lock().unwrap() = Some(posted);
+    }
+
+    /// Re-apply the current config immediately instead of on the next tick.
+    pub fn refresh(&self) {
+        refresh(self.hwnd);
+    }
+
+    /// Switch between click-through (locked) and draggable (unlocked) modes.
+    /// Returns true if the overlay is now click-through.
+    pub fn toggle_click_through(&self) -> bool {
+        let enabled = !CLICK_THROUGH.load(Ordering::Relaxed);
+        set_click_through(self.hwnd, enabled);
+        enabled
+    }
+
+    /// Pulse the overlay's opacity a few times so the user notices where it appeared.
+    pub fn flash(&self) {
+        FLASH_REMAINING.store(FLASH_STEPS, Ordering::Relaxed);
+        unsafe {
+            start_timer(self.hwnd, FLASH_TIMER_ID, 120, TIMERV_NO_COALESCING);
+        }
+        refresh(self.hwnd);
+    }
+
+    /// Turn the diagnostics line on or off. Returns true if it is now on.
+    pub fn toggle_debug(&self) -> bool {
+        let on = !DEBUG.fetch_xor(true, Ordering::Relaxed);
+        if on {
+            update_debug(self.hwnd);
+        }
+        refresh(self.hwnd);
+        on
+    }
+
+    /// Draw the clock at double size for `seconds`, or end a zoom in progress early.
+    pub fn toggle_zoom(&self, seconds: u32) {
+        let zoom = !ZOOMED.load(Ordering::Relaxed);
+        ZOOMED.store(zoom, Ordering::Relaxed);
+        unsafe {
+            if zoom {
+                start_timer(
+                    self.hwnd,
+                    ZOOM_TIMER_ID,
+                    seconds * 1000,
+                    ONE_SHOT_TOLERANCE_MS,
+                );
+            } else {
+                let _ = KillTimer(self.hwnd, ZOOM_TIMER_ID);
+            }
+        }
+        refresh(self.hwnd);
+    }
+
+    /// Show `text` next to the clock for `seconds`, revealing the overlay if it is hidden.
+    pub fn show_banner(&self, text: &str, seconds: u32) {
+        self.start_peek(Some(text.to_string()), seconds);
+        uia::announce();
+    }
+
+    /// Reveal the overlay for `seconds` without changing the user's visibility choice.
+    pub fn peek(&self, seconds: u32) {
+        self.start_peek(None, seconds);
+    }
+
+    fn start_peek(&self, banner: Option<String>, seconds: u32) {
+        let until = Instant::now() + Duration::from_secs(seconds as u64);
+        let mut peek = PEEK.lock().unwrap();
+        // Overlapping peeks extend each other and keep the existing banner text
+        *peek = Some(match peek.take() {
+            Some(old) => Peek {
+                banner: banner.or(old.banner),
+                until: until.max(old.until),
+            },
+            None => Peek { banner, until },
+        });
+        drop(peek);
+        self.show();
+        refresh(self.hwnd);
+    }
+
+    /// Cut the current peek short; it is cleared on the next `expire_peek`.
+    pub fn end_peek(&self) {
+        if let Some(peek) = PEEK.lock().unwrap().as_mut() {
+            peek.until = Instant::now();
+        }
+    }
+
+    /// Clear the peek once it has expired. Returns true if one was cleared.
+    pub fn expire_peek(&self) -> bool {
+        let mut peek = PEEK.lock().unwrap();
+        match peek.as_ref() {
+            Some(p) if Instant::now() >= p.until => {
+                *peek = None;
+                drop(peek);
+                refresh(self.hwnd);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Start the stream timer, or stop it if it is already running.
+    /// Returns true if the timer is now running.
+    pub fn toggle_stream_timer(&self) -> bool {
+        let mut start = STREAM_START.lock().unwrap();
+        *start = match *start {
+            Some(_) => None,
+            None => Some(Instant::now()),
+        };
+        let running = start.is_some();
+        drop(start);
+        refresh(self.hwnd);
+        running
+    }
+
+    /// Start the countdown for the configured length, or stop it if one is
+    /// running (or done). Returns true if it is now running.
+    pub fn toggle_countdown(&self) -> bool {
+        let mut end = COUNTDOWN_END.lock().unwrap();
+        *end = match *end {
+            Some(_) => None,
+            None => {
+                let config = get_config();
+                let length = config.countdown_length(chrono::Local::now().naive_local());
+                Some(Instant::now() + length.to_std().unwrap_or_default())
+            }
+        };
+        let running = end.is_some();
+        drop(end);
+        refresh(self.hwnd);
+        running
+    }
+
+    /// Ring a finished countdown again in `minutes`.
+    pub fn snooze_countdown(&self, minutes: u32) {
+        let end = Instant::now() + Duration::from_secs(minutes as u64 * 60);
+        *COUNTDOWN_END.lock().unwrap() = Some(end);
+        refresh(self.hwnd);
+    }
+
+    /// Whether the countdown has reached zero and is waiting to be stopped.
+    pub fn countdown_done(&self) -> bool {
+        countdown_left().is_some_and(|left| left.is_zero())
+    }
+
+    /// Minutes left on the countdown, rounded up, while it is still running.
+    pub fn countdown_minutes_left(&self) -> Option<u64> {
+        countdown_left()
+            .filter(|left| !left.is_zero())
+            .map(|left| left.as_secs().div_ceil(60))
+    }
+
+    /// Whole minutes the stream timer has been running, if it is.
+    pub fn stream_minutes(&self) -> Option<u64> {
+        STREAM_START
+            .lock()
+            .unwrap()
+            .map(|start| start.elapsed().as_secs() / 60)
     }
 
     pub fn hide(&self) {
         unsafe {
             let _ = ShowWindow(self.hwnd, SW_HIDE);
         }
+        sync_monitor_clocks(self.hwnd);
     }
 
     pub fn destroy(&self) {
@@ -0,0 +1,89 @@
+//! How long the current fullscreen session has run, for the "you've been
+//! playing a while" reveal. Alt-tabbing out for a moment doesn't end the
+//! session; being out of fullscreen for `BREAK` does.
+
+use std::time::{Duration, Instant};
+
+/// Time out of fullscreen that counts as a break and starts the count again.
+const BREAK: Duration = Duration::from_secs(120);
+
+#[derive(Default)]
+pub struct PlayTracker {
+    /// When the session started, if one is running.
+    started: Option<Instant>,
+    /// When the fullscreen app last went to the background.
+    left_at: Option<Instant>,
+    /// The reveal already happened for this session.
+    nudged: bool,
+}
+
+impl PlayTracker {
+    /// Feed whether a fullscreen app is in front. Returns true once per
+    /// session, when it has run for `limit`.
+    pub fn poll(&mut self, fullscreen: bool, now: Instant, limit: Duration) -> bool {
+        if !fullscreen {
+            self.left_at.get_or_insert(now);
+        }
+        if self
+            .left_at
+            .is_some_and(|left| now.duration_since(left) >= BREAK)
+        {
+            self.started = None;
+            self.nudged = false;
+        }
+        if !fullscreen {
+            return false;
+        }
+        self.left_at = None;
+        let started = *self.started.get_or_insert(now);
+        if self.nudged || now.duration_since(started) < limit {
+            return false;
+        }
+        self.nudged = true;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMIT: Duration = Duration::from_secs(90 * 60);
+
+    fn mins(m: u64) -> Duration {
+        Duration::from_secs(m * 60)
+    }
+
+    #[test]
+    fn fires_once_after_the_limit() {
+        let t0 = Instant::now();
+        let mut play = PlayTracker::default();
+        assert!(!play.poll(true, t0, LIMIT));
+        assert!(!play.poll(true, t0 + mins(89), LIMIT));
+        assert!(play.poll(true, t0 + mins(90), LIMIT));
+        assert!(!play.poll(true, t0 + mins(91), LIMIT));
+    }
+
+    #[test]
+    fn a_quick_alt_tab_keeps_the_session() {
+        let t0 = Instant::now();
+        let mut play = PlayTracker::default();
+        play.poll(true, t0, LIMIT);
+        play.poll(true, t0 + mins(60), LIMIT);
+        assert!(!play.poll(false, t0 + mins(61), LIMIT));
+        play.poll(true, t0 + mins(62), LIMIT);
+        assert!(play.poll(true, t0 + mins(90), LIMIT));
+    }
+
+    #[test]
+    fn a_break_starts_over() {
+        let t0 = Instant::now();
+        let mut play = PlayTracker::default();
+        play.poll(true, t0, LIMIT);
+        play.poll(true, t0 + mins(80), LIMIT);
+        play.poll(false, t0 + mins(82), LIMIT);
+        play.poll(false, t0 + mins(85), LIMIT);
+        assert!(!play.poll(true, t0 + mins(90), LIMIT));
+        assert!(play.poll(true, t0 + mins(180), LIMIT));
+    }
+}
@@ -0,0 +1,507 @@
+//! Native plugins: DLLs in the `plugins` folder next to clockor.exe that add
+//! overlay data sources (hardware sensors, counters, …) through the C
+//! interface in `include/clockor_plugin.h`.
+//!
+//! Nothing is loaded unless `plugins` is on, and each DLL can be turned off by
+//! file name. Every plugin runs on a thread of its own, so one that hangs only
+//! stops its own text, and one whose reads keep failing is unloaded. A plugin
+//! is still native code inside ClockOR: a crash in it takes ClockOR down.
+
+use std::ffi::{c_char, CStr};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use windows::core::{s, PCWSTR};
+use windows::Win32::Foundation::{FreeLibrary, HMODULE};
+use windows::Win32::System::LibraryLoader::{GetProcAddress, LoadLibraryW};
+
+use crate::config::Config;
+
+/// The `abi_version` this build understands.
+pub const ABI_VERSION: u32 = 1;
+/// Bytes a plugin may write per read.
+const READ_BUFFER: usize = 256;
+/// Failed reads in a row before a plugin is unloaded.
+const MAX_FAILURES: u32 = 5;
+/// A read taking longer than this shows the plugin as not responding.
+const HANG_AFTER: Duration = Duration::from_secs(5);
+
+/// `ClockorPlugin` from the header.
+#[repr(C)]
+struct VTable {
+    abi_version: u32,
+    name: *const c_char,
+    interval_ms: u32,
+    init: Option<unsafe extern "C" fn() -> i32>,
+    read: Option<unsafe extern "C" fn(*mut c_char, u32) -> i32>,
+    shutdown: Option<unsafe extern "C" fn()>,
+}
+
+type EntryPoint = unsafe extern "C" fn() -> *const VTable;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum State {
+    Running,
+    NotResponding,
+    Failed(String),
+    /// Turned off in settings, or plugins are off altogether.
+    Off,
+}
+
+impl State {
+    pub fn label(&self) -> String {
+        match self {
+            State::Running => "running".to_string(),
+            State::NotResponding => "not responding".to_string(),
+            State::Failed(e) => format!("failed: {e}"),
+            State::Off => "off".to_string(),
+        }
+    }
+}
+
+/// A DLL in the plugins folder, as listed in settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginInfo {
+    pub file: String,
+    /// The name the plugin gave, or the file name until it has loaded.
+    pub name: String,
+    pub state: State,
+}
+
+/// What a plugin thread reports back.
+struct Shared {
+    name: Mutex<String>,
+    text: Mutex<Option<String>>,
+    failed: Mutex<Option<String>>,
+    /// When the read in progress started.
+    busy_since: Mutex<Option<Instant>>,
+    stop: AtomicBool,
+    finished: AtomicBool,
+}
+
+impl Shared {
+    fn new(file: &str) -> Shared {
+        Shared {
+            name: Mutex::new(file.to_string()),
+            text: Mutex::new(None),
+            failed: Mutex::new(None),
+            busy_since: Mutex::new(None),
+            stop: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+        }
+    }
+
+    fn state(&self) -> State {
+        if let Some(e) = self.failed.lock().unwrap().clone() {
+            return State::Failed(e);
+        }
+        match *self.busy_since.lock().unwrap() {
+            Some(since) if since.elapsed() > HANG_AFTER => State::NotResponding,
+            _ => State::Running,
+        }
+    }
+}
+
+struct Slot {
+    file: String,
+    shared: Arc<Shared>,
+}
+
+/// What decides which plugins should run. The folder is only read again
+/// when this changes.
+#[derive(PartialEq)]
+struct Scan {
+    plugins: bool,
+    disabled: Vec<String>,
+    /// The plugins folder's last change, which moves when a DLL is added,
+    /// removed or renamed.
+    modified: Option<SystemTime>,
+}
+
+impl Scan {
+    fn of(config: &Config) -> Scan {
+        let modified = config
+            .plugins
+            .then(|| std::fs::metadata(folder()).and_then(|m| m.modified()).ok())
+            .flatten();
+        Scan {
+            plugins: config.plugins,
+            disabled: config.disabled_plugins.clone(),
+            modified,
+        }
+    }
+}
+
+struct Plugins {
+    slots: Vec<Slot>,
+    /// Turned off, but still inside a call into the DLL.
+    stopping: Vec<Slot>,
+    /// The files that should run, as of `scan`.
+    wanted: Vec<String>,
+    scan: Option<Scan>,
+}
+
+static PLUGINS: Mutex<Plugins> = Mutex::new(Plugins {
+    slots: Vec::new(),
+    stopping: Vec::new(),
+    wanted: Vec::new(),
+    scan: None,
+});
+
+pub fn folder() -> PathBuf {
+    crate::config::app_dir().join("plugins")
+}
+
+fn is_plugin_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("dll"))
+}
+
+/// DLL file names in the plugins folder, sorted so segments keep their order.
+fn plugin_files() -> Vec<String> {
+    let mut files: Vec<String> = std::fs::read_dir(folder())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_plugin_file(path))
+        .filter_map(|path| Some(path.file_name()?.to_string_lossy().into_owned()))
+        .collect();
+    files.sort_by_key(|f| f.to_lowercase());
+    files
+}
+
+fn is_disabled(config: &Config, file: &str) -> bool {
+    config
+        .disabled_plugins
+        .iter()
+        .any(|d| d.eq_ignore_ascii_case(file))
+}
+
+/// Load newly added or re-enabled plugins and stop the ones turned off.
+/// Called on every clock tick; the folder is only read when it or the
+/// plugin settings changed.
+pub fn sync(config: &Config) {
+    let mut plugins = PLUGINS.lock().unwrap();
+    let scan = Scan::of(config);
+    if plugins.scan.as_ref() != Some(&scan) {
+        plugins.wanted = if config.plugins {
+            plugin_files()
+                .into_iter()
+                .filter(|f| !is_disabled(config, f))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        plugins.scan = Some(scan);
+    }
+    for file in reconcile(&mut plugins) {
+        plugins.slots.push(start(file));
+    }
+}
+
+/// Ask the plugins no longer wanted to stop, and return the wanted files
+/// that need a thread. A file whose old thread hasn't exited yet waits for
+/// it, so the same DLL never runs on two threads.
+fn reconcile(plugins: &mut Plugins) -> Vec<String> {
+    plugins
+        .stopping
+        .retain(|slot| !slot.shared.finished.load(Ordering::Relaxed));
+    let (keep, stop): (Vec<Slot>, Vec<Slot>) = std::mem::take(&mut plugins.slots)
+        .into_iter()
+        .partition(|slot| plugins.wanted.contains(&slot.file));
+    for slot in &stop {
+        slot.shared.stop.store(true, Ordering::Relaxed);
+    }
+    plugins.slots = keep;
+    plugins.stopping.extend(stop);
+    plugins
+        .wanted
+        .iter()
+        .filter(|file| {
+            !plugins
+                .slots
+                .iter()
+                .chain(&plugins.stopping)
+                .any(|slot| &slot.file == *file)
+        })
+        .cloned()
+        .collect()
+}
+
+fn start(file: String) -> Slot {
+    let shared = Arc::new(Shared::new(&file));
+    let path = folder().join(&file);
+    let thread_shared = Arc::clone(&shared);
+    let thread_file = file.clone();
+    std::thread::spawn(move || {
+        let result = unsafe { run(&path, &thread_shared) };
+        *thread_shared.text.lock().unwrap() = None;
+        match result {
+            Ok(()) => crate::logging::info(format!("Plugin unloaded: {thread_file}")),
+            Err(e) => {
+                crate::logging::error(format!("Plugin {thread_file}: {e}"));
+                *thread_shared.failed.lock().unwrap() = Some(e);
+            }
+        }
+        thread_shared.finished.store(true, Ordering::Relaxed);
+    });
+    Slot { file, shared }
+}
+
+/// Frees the DLL when the plugin thread is done with it.
+struct Library(HMODULE);
+
+impl Drop for Library {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = FreeLibrary(self.0);
+        }
+    }
+}
+
+fn check_abi(version: u32) -> Result<(), String> {
+    if version == ABI_VERSION {
+        Ok(())
+    } else {
+        Err(format!(
+            "built for plugin ABI {version}, this ClockOR supports {ABI_VERSION}"
+        ))
+    }
+}
+
+/// The plugin's requested interval, kept to a sane range.
+fn interval(ms: u32) -> Duration {
+    let ms = if ms == 0 { 1000 } else { ms.clamp(250, 60_000) };
+    Duration::from_millis(ms as u64)
+}
+
+/// Turn what `read` wrote into one overlay segment (`None` = show nothing).
+fn text_from_buffer(buf: &[u8], written: i32) -> Result<Option<String>, String> {
+    let len = usize::try_from(written).map_err(|_| format!("read returned {written}"))?;
+    if len > buf.len() {
+        return Err(format!("read claimed {len} bytes of {}", buf.len()));
+    }
+    let text = String::from_utf8_lossy(&buf[..len]);
+    let text = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("  ");
+    Ok((!text.is_empty()).then_some(text))
+}
+
+/// Sleep for `duration`, waking early when asked to stop.
+fn sleep_unless_stopped(duration: Duration, stop: &AtomicBool) {
+    let until = Instant::now() + duration;
+    while !stop.load(Ordering::Relaxed) {
+        let left = until.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            break;
+        }
+        std::thread::sleep(left.min(Duration::from_millis(100)));
+    }
+}
+
+/// Load the DLL and poll it until stopped or it keeps failing.
+unsafe fn run(path: &Path, shared: &Shared) -> Result<(), String> {
+    let wide: Vec<u16> = path
+        .as_os_str()
+        .to_string_lossy()
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    let library =
+        Library(LoadLibraryW(PCWSTR(wide.as_ptr())).map_err(|e| format!("could not load ({e})"))?);
+    let entry = GetProcAddress(library.0, s!("clockor_plugin"))
+        .ok_or("not a ClockOR plugin (no clockor_plugin export)")?;
+    let entry: EntryPoint = std::mem::transmute(entry);
+    let table = entry().as_ref().ok_or("clockor_plugin returned null")?;
+    check_abi(table.abi_version)?;
+    let read = table.read.ok_or("no read function")?;
+    if !table.name.is_null() {
+        *shared.name.lock().unwrap() = CStr::from_ptr(table.name).to_string_lossy().into_owned();
+    }
+    if let Some(init) = table.init {
+        let code = init();
+        if code != 0 {
+            return Err(format!("init failed ({code})"));
+        }
+    }
+    crate::logging::info(format!(
+        "Plugin loaded: {} ({})",
+        shared.name.lock().unwrap(),
+        path.display()
+    ));
+
+    let interval = interval(table.interval_ms);
+    let mut buf = [0u8; READ_BUFFER];
+    let mut failures = 0;
+    let outcome = loop {
+        if shared.stop.load(Ordering::Relaxed) {
+            break Ok(());
+        }
+        *shared.busy_since.lock().unwrap() = Some(Instant::now());
+        let written = read(buf.as_mut_ptr().cast(), buf.len() as u32);
+        *shared.busy_since.lock().unwrap() = None;
+        match text_from_buffer(&buf, written) {
+            Ok(text) => {
+                failures = 0;
+                *shared.text.lock().unwrap() = text;
+            }
+            Err(e) => {
+                failures += 1;
+                *shared.text.lock().unwrap() = None;
+                if failures >= MAX_FAILURES {
+                    break Err(format!("{e} ({MAX_FAILURES} times in a row)"));
+                }
+            }
+        }
+        sleep_unless_stopped(interval, &shared.stop);
+    };
+    if let Some(shutdown) = table.shutdown {
+        shutdown();
+    }
+    outcome
+}
+
+/// `(name, text)` for each plugin with something to show, in folder order.
+pub fn texts() -> Vec<(String, String)> {
+    PLUGINS
+        .lock()
+        .unwrap()
+        .slots
+        .iter()
+        .filter_map(|slot| {
+            let text = slot.shared.text.lock().unwrap().clone()?;
+            Some((slot.shared.name.lock().unwrap().clone(), text))
+        })
+        .collect()
+}
+
+/// Every DLL in the plugins folder with its state, for settings.
+pub fn list() -> Vec<PluginInfo> {
+    let plugins = PLUGINS.lock().unwrap();
+    plugin_files()
+        .into_iter()
+        .map(
+            |file| match plugins.slots.iter().find(|slot| slot.file == file) {
+                Some(slot) => PluginInfo {
+                    name: slot.shared.name.lock().unwrap().clone(),
+                    state: slot.shared.state(),
+                    file,
+                },
+                None => PluginInfo {
+                    name: file.clone(),
+                    state: State::Off,
+                    file,
+                },
+            },
+        )
+        .collect()
+}
+
+/// Stop every plugin, giving them a moment to run their shutdown.
+pub fn shutdown() {
+    let slots: Vec<Slot> = {
+        let mut plugins = PLUGINS.lock().unwrap();
+        let stopping = std::mem::take(&mut plugins.stopping);
+        std::mem::take(&mut plugins.slots)
+            .into_iter()
+            .chain(stopping)
+            .collect()
+    };
+    for slot in &slots {
+        slot.shared.stop.store(true, Ordering::Relaxed);
+    }
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while Instant::now() < deadline
+        && slots
+            .iter()
+            .any(|slot| !slot.shared.finished.load(Ordering::Relaxed))
+    {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_dlls_are_plugins() {
+        assert!(is_plugin_file(Path::new("sensors.dll")));
+        assert!(is_plugin_file(Path::new("Sensors.DLL")));
+        assert!(!is_plugin_file(Path::new("readme.txt")));
+        assert!(!is_plugin_file(Path::new("dll")));
+    }
+
+    #[test]
+    fn abi_must_match() {
+        assert!(check_abi(ABI_VERSION).is_ok());
+        assert!(check_abi(ABI_VERSION + 1).is_err());
+        assert!(check_abi(0).is_err());
+    }
+
+    #[test]
+    fn interval_defaults_and_clamps() {
+        assert_eq!(interval(0), Duration::from_secs(1));
+        assert_eq!(interval(10), Duration::from_millis(250));
+        assert_eq!(interval(2000), Duration::from_secs(2));
+        assert_eq!(interval(u32::MAX), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn reads_text_from_buffer() {
+        let mut buf = [0u8; 16];
+        buf[..9].copy_from_slice(b"CPU 61\xc2\xb0C");
+        assert_eq!(text_from_buffer(&buf, 9), Ok(Some("CPU 61°C".to_string())));
+        buf[..9].copy_from_slice(b"a\r\n\r\nb   ");
+        assert_eq!(text_from_buffer(&buf, 9), Ok(Some("a  b".to_string())));
+        assert_eq!(text_from_buffer(&buf, 0), Ok(None));
+    }
+
+    #[test]
+    fn bad_reads_are_errors() {
+        let buf = [0u8; 16];
+        assert!(text_from_buffer(&buf, -1).is_err());
+        assert!(text_from_buffer(&buf, 17).is_err());
+    }
+
+    #[test]
+    fn restarts_only_after_the_old_thread_exits() {
+        let slot = |file: &str| Slot {
+            file: file.to_string(),
+            shared: Arc::new(Shared::new(file)),
+        };
+        let mut plugins = Plugins {
+            slots: vec![slot("a.dll")],
+            stopping: Vec::new(),
+            wanted: Vec::new(),
+            scan: None,
+        };
+        // Turned off: asked to stop, still in a read
+        assert!(reconcile(&mut plugins).is_empty());
+        let old = Arc::clone(&plugins.stopping[0].shared);
+        assert!(old.stop.load(Ordering::Relaxed));
+        // Turned back on before the read returned
+        plugins.wanted = vec!["a.dll".to_string(), "b.dll".to_string()];
+        assert_eq!(reconcile(&mut plugins), ["b.dll"]);
+        old.finished.store(true, Ordering::Relaxed);
+        assert_eq!(reconcile(&mut plugins), ["a.dll", "b.dll"]);
+        assert!(plugins.stopping.is_empty());
+    }
+
+    #[test]
+    fn disabled_match_ignores_case() {
+        let config = Config {
+            disabled_plugins: vec!["Sensors.dll".to_string()],
+            ..Default::default()
+        };
+        assert!(is_disabled(&config, "sensors.dll"));
+        assert!(!is_disabled(&config, "other.dll"));
+    }
+}
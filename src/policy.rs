@@ -0,0 +1,279 @@
+//! Administrator-locked settings from `HKLM\SOFTWARE\Policies\ClockOR`.
+//!
+//! Each registry value named after a config.toml key overrides that key
+//! (DWORD for numbers and booleans, REG_SZ for text and choices such as
+//! `position = "bottom-left"`). `disable_network` (DWORD 1) additionally turns
+//! off every feature that talks to the network. Policies are read once at startup.
+
+use std::sync::OnceLock;
+
+use windows::core::{w, PWSTR};
+use windows::Win32::System::Registry::{
+    RegCloseKey, RegEnumValueW, RegOpenKeyExW, HKEY, HKEY_LOCAL_MACHINE, KEY_READ, REG_DWORD,
+    REG_SZ, REG_VALUE_TYPE,
+};
+
+use crate::config::Config;
+
+/// Meta-policy that disables network features.
+const DISABLE_NETWORK: &str = "disable_network";
+
+/// Config keys that only matter for network features.
+const NETWORK_KEYS: &[&str] = &["check_updates", "usage_stats"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolicyValue {
+    Number(u32),
+    Text(String),
+}
+
+#[derive(Debug, Default)]
+pub struct Policies {
+    values: Vec<(String, PolicyValue)>,
+    network_disabled: bool,
+}
+
+/// Convert a policy value to the TOML type the config already uses for that key.
+fn coerce(existing: &toml::Value, value: &PolicyValue) -> Option<toml::Value> {
+    match (existing, value) {
+        (toml::Value::Boolean(_), PolicyValue::Number(n)) => Some(toml::Value::Boolean(*n != 0)),
+        (toml::Value::Boolean(_), PolicyValue::Text(t)) => {
+            t.trim().parse().ok().map(toml::Value::Boolean)
+        }
+        (toml::Value::Integer(_), PolicyValue::Number(n)) => Some(toml::Value::Integer(*n as i64)),
+        (toml::Value::Integer(_), PolicyValue::Text(t)) => {
+            t.trim().parse().ok().map(toml::Value::Integer)
+        }
+        (toml::Value::String(_), PolicyValue::Text(t)) => Some(toml::Value::String(t.clone())),
+        _ => None,
+    }
+}
+
+impl Policies {
+    pub fn from_values(values: Vec<(String, PolicyValue)>) -> Self {
+        let network_disabled = values
+            .iter()
+            .any(|(name, value)| name == DISABLE_NETWORK && *value != PolicyValue::Number(0));
+        Self {
+            values,
+            network_disabled,
+        }
+    }
+
+    /// Whether the config key is set by policy and can't be changed by the user.
+    pub fn is_locked(&self, key: &str) -> bool {
+        (self.network_disabled && NETWORK_KEYS.contains(&key))
+            || self.values.iter().any(|(name, _)| name == key)
+    }
+
+    /// Names of the policy values that are set, as found in the registry.
+    pub fn locked_keys(&self) -> Vec<&str> {
+        self.values.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    pub fn network_allowed(&self) -> bool {
+        !self.network_disabled
+    }
+
+    /// Override `config` with the policy values. Values of the wrong type or out of
+    /// the key's allowed choices are ignored.
+    pub fn apply(&self, config: &mut Config) {
+        let Ok(mut table) = toml::Table::try_from(&*config) else {
+            return;
+        };
+        for (name, value) in &self.values {
+            let Some(new) = table.get(name).and_then(|old| coerce(old, value)) else {
+                continue;
+            };
+            let mut candidate = table.clone();
+            candidate.insert(name.clone(), new);
+            if candidate.clone().try_into::<Config>().is_ok() {
+                table = candidate;
+            }
+        }
+        if let Ok(mut overridden) = table.try_into::<Config>() {
+            if self.network_disabled {
+                overridden.check_updates = false;
+                overridden.usage_stats = false;
+            }
+            overridden.sanitize();
+            *config = overridden;
+        }
+    }
+
+    /// `config` with every locked key back at its value in `user` (the config
+    /// as saved, before policies), so saving doesn't overwrite the user's own
+    /// values and removing a policy brings them back.
+    pub fn unapply(&self, config: &Config, user: &Config) -> Config {
+        let (Ok(mut table), Ok(user)) =
+            (toml::Table::try_from(config), toml::Table::try_from(user))
+        else {
+            return config.clone();
+        };
+        for (name, value) in user {
+            if self.is_locked(&name) {
+                table.insert(name, value);
+            }
+        }
+        table.try_into().unwrap_or_else(|_| config.clone())
+    }
+}
+
+/// Read every value under the policy key. A missing key means no policies.
+fn read_registry() -> Vec<(String, PolicyValue)> {
+    let mut values = Vec::new();
+    unsafe {
+        let mut key = HKEY::default();
+        if RegOpenKeyExW(
+            HKEY_LOCAL_MACHINE,
+            w!("SOFTWARE\\Policies\\ClockOR"),
+            0,
+            KEY_READ,
+            &mut key,
+        )
+        .is_err()
+        {
+            return values;
+        }
+        for index in 0.. {
+            let mut name = [0u16; 256];
+            let mut name_len = name.len() as u32;
+            let mut kind = REG_VALUE_TYPE::default();
+            let mut data = [0u8; 1024];
+            let mut data_len = data.len() as u32;
+            let status = RegEnumValueW(
+                key,
+                index,
+                PWSTR(name.as_mut_ptr()),
+                &mut name_len,
+                None,
+                Some(&mut kind.0),
+                Some(data.as_mut_ptr()),
+                Some(&mut data_len),
+            );
+            if status.is_err() {
+                break;
+            }
+            let name = String::from_utf16_lossy(&name[..name_len as usize]).to_ascii_lowercase();
+            let data = &data[..data_len as usize];
+            let value = match kind {
+                REG_DWORD if data.len() >= 4 => {
+                    PolicyValue::Number(u32::from_le_bytes([data[0], data[1], data[2], data[3]]))
+                }
+                REG_SZ => {
+                    let wide: Vec<u16> = data
+                        .chunks_exact(2)
+                        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                        .take_while(|&c| c != 0)
+                        .collect();
+                    PolicyValue::Text(String::from_utf16_lossy(&wide))
+                }
+                _ => continue,
+            };
+            values.push((name, value));
+        }
+        let _ = RegCloseKey(key);
+    }
+    values
+}
+
+/// Policies in effect for this run.
+pub fn current() -> &'static Policies {
+    static POLICIES: OnceLock<Policies> = OnceLock::new();
+    POLICIES.get_or_init(|| Policies::from_values(read_registry()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Position;
+
+    fn policies(values: &[(&str, PolicyValue)]) -> Policies {
+        Policies::from_values(
+            values
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn overrides_matching_keys() {
+        let p = policies(&[
+            ("format_24h", PolicyValue::Number(1)),
+            ("font_size", PolicyValue::Number(30)),
+            ("position", PolicyValue::Text("bottom-left".into())),
+        ]);
+        let mut cfg = Config {
+            format_24h: false,
+            ..Default::default()
+        };
+        p.apply(&mut cfg);
+        assert!(cfg.format_24h);
+        assert_eq!(cfg.font_size, 30);
+        assert_eq!(cfg.position, Position::BottomLeft);
+        assert!(p.is_locked("format_24h"));
+        assert!(!p.is_locked("show_seconds"));
+    }
+
+    #[test]
+    fn ignores_invalid_values() {
+        let p = policies(&[
+            ("position", PolicyValue::Text("middle".into())),
+            ("format_24h", PolicyValue::Text("maybe".into())),
+            ("no_such_key", PolicyValue::Number(1)),
+        ]);
+        let mut cfg = Config::default();
+        p.apply(&mut cfg);
+        assert_eq!(cfg, Config::default());
+    }
+
+    #[test]
+    fn clamps_policy_values() {
+        let p = policies(&[("font_size", PolicyValue::Number(500))]);
+        let mut cfg = Config::default();
+        p.apply(&mut cfg);
+        assert_eq!(cfg.font_size, 60);
+    }
+
+    #[test]
+    fn disable_network_turns_off_update_checks() {
+        let p = policies(&[(DISABLE_NETWORK, PolicyValue::Number(1))]);
+        let mut cfg = Config {
+            check_updates: true,
+            usage_stats: true,
+            ..Default::default()
+        };
+        p.apply(&mut cfg);
+        assert!(!cfg.check_updates);
+        assert!(!cfg.usage_stats);
+        assert!(p.is_locked("check_updates"));
+        assert!(p.is_locked("usage_stats"));
+        assert!(!p.network_allowed());
+
+        let p = policies(&[(DISABLE_NETWORK, PolicyValue::Number(0))]);
+        assert!(p.network_allowed());
+        assert!(!p.is_locked("check_updates"));
+    }
+
+    #[test]
+    fn saving_keeps_the_users_locked_values() {
+        let p = policies(&[
+            ("format_24h", PolicyValue::Number(1)),
+            (DISABLE_NETWORK, PolicyValue::Number(1)),
+        ]);
+        let user = Config {
+            format_24h: false,
+            check_updates: true,
+            ..Default::default()
+        };
+        let mut cfg = user.clone();
+        p.apply(&mut cfg);
+        cfg.show_seconds = true;
+        let saved = p.unapply(&cfg, &user);
+        assert!(!saved.format_24h);
+        assert!(saved.check_updates);
+        // Unlocked edits are kept
+        assert!(saved.show_seconds);
+    }
+}
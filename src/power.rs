@@ -0,0 +1,82 @@
+//! Battery state, for scripts and battery-aware features.
+
+use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+/// `BatteryFlag` bit for "no system battery".
+const NO_BATTERY: u8 = 128;
+/// `BatteryLifePercent` when the charge is unknown.
+const UNKNOWN_PERCENT: u8 = 255;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Battery {
+    /// Remaining charge, 0–100.
+    pub percent: u8,
+    /// Running on AC power.
+    pub plugged_in: bool,
+}
+
+fn from_status(status: &SYSTEM_POWER_STATUS) -> Option<Battery> {
+    if status.BatteryFlag & NO_BATTERY != 0 || status.BatteryLifePercent == UNKNOWN_PERCENT {
+        return None;
+    }
+    Some(Battery {
+        percent: status.BatteryLifePercent.min(100),
+        plugged_in: status.ACLineStatus == 1,
+    })
+}
+
+/// The battery, or `None` on a desktop or when Windows doesn't know the charge.
+pub fn battery() -> Option<Battery> {
+    let mut status = SYSTEM_POWER_STATUS::default();
+    unsafe { GetSystemPowerStatus(&mut status) }.ok()?;
+    from_status(&status)
+}
+
+/// Running on a battery charged to `threshold` percent or less (0 = never).
+pub fn is_low(battery: Battery, threshold: u8) -> bool {
+    threshold > 0 && !battery.plugged_in && battery.percent <= threshold
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(ac: u8, flag: u8, percent: u8) -> SYSTEM_POWER_STATUS {
+        SYSTEM_POWER_STATUS {
+            ACLineStatus: ac,
+            BatteryFlag: flag,
+            BatteryLifePercent: percent,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reads_laptop_battery() {
+        assert_eq!(
+            from_status(&status(0, 1, 64)),
+            Some(Battery {
+                percent: 64,
+                plugged_in: false
+            })
+        );
+        assert!(from_status(&status(1, 8, 80)).unwrap().plugged_in);
+    }
+
+    #[test]
+    fn low_only_on_battery_power() {
+        let battery = |percent, plugged_in| Battery {
+            percent,
+            plugged_in,
+        };
+        assert!(is_low(battery(15, false), 15));
+        assert!(!is_low(battery(16, false), 15));
+        assert!(!is_low(battery(5, true), 15));
+        assert!(!is_low(battery(5, false), 0));
+    }
+
+    #[test]
+    fn desktop_and_unknown_have_no_battery() {
+        assert_eq!(from_status(&status(1, NO_BATTERY, 255)), None);
+        assert_eq!(from_status(&status(1, 0, UNKNOWN_PERCENT)), None);
+    }
+}
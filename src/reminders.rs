@@ -0,0 +1,205 @@
+use chrono::{Duration, NaiveDateTime};
+
+use crate::config::DailyEvent;
+
+/// Gaps longer than this (sleep, hibernate, clock changes) are skipped
+/// instead of replaying every reminder that was missed in between.
+pub const MAX_CATCH_UP_MINUTES: i64 = 5;
+
+/// Tracks which daily events have already fired between polls, plus snoozed repeats.
+pub struct ReminderTracker {
+    last_check: NaiveDateTime,
+    snoozed: Vec<(DailyEvent, NaiveDateTime)>,
+}
+
+impl ReminderTracker {
+    pub fn new(now: NaiveDateTime) -> Self {
+        Self {
+            last_check: now,
+            snoozed: Vec::new(),
+        }
+    }
+
+    /// Fire `event` again `minutes` after `now`.
+    pub fn snooze(&mut self, event: DailyEvent, now: NaiveDateTime, minutes: u32) {
+        self.snoozed
+            .push((event, now + Duration::minutes(minutes as i64)));
+    }
+
+    /// Return the events whose time of day fell in `(last_check, now]`,
+    /// followed by any snoozed events that are now due.
+    pub fn poll(&mut self, events: &[DailyEvent], now: NaiveDateTime) -> Vec<DailyEvent> {
+        let catch_up = Duration::minutes(MAX_CATCH_UP_MINUTES);
+        let mut due = Vec::new();
+        self.snoozed.retain(|(event, at)| {
+            if *at > now {
+                return true;
+            }
+            // Like scheduled events, a snooze that ended long ago (sleep) is dropped
+            if now - *at <= catch_up {
+                due.push(event.clone());
+            }
+            false
+        });
+
+        let last = std::mem::replace(&mut self.last_check, now);
+        if now <= last || now - last > catch_up {
+            return due;
+        }
+
+        let mut fired: Vec<DailyEvent> = events
+            .iter()
+            .filter(|ev| {
+                let Some(time) = ev.parsed_time() else {
+                    return false;
+                };
+                // Check both days so an event at 00:00 fires when polling across midnight
+                [last.date(), now.date()].iter().any(|day| {
+                    let at = day.and_time(time);
+                    last < at && at <= now
+                })
+            })
+            .cloned()
+            .collect();
+        fired.append(&mut due);
+        fired
+    }
+}
+
+/// The next reminder due within `lead` of `now`, and how long until it rings.
+pub fn upcoming(
+    events: &[DailyEvent],
+    now: NaiveDateTime,
+    lead: Duration,
+) -> Option<(&DailyEvent, Duration)> {
+    events
+        .iter()
+        .filter_map(|ev| {
+            let time = ev.parsed_time()?;
+            let today = now.date().and_time(time);
+            let at = if today > now {
+                today
+            } else {
+                now.date().succ_opt()?.and_time(time)
+            };
+            Some((ev, at - now))
+        })
+        .filter(|(_, left)| *left <= lead)
+        .min_by_key(|(_, left)| *left)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(h: u32, m: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, 1)
+            .unwrap()
+            .and_hms_opt(h, m, s)
+            .unwrap()
+    }
+
+    fn event(name: &str, time: &str) -> DailyEvent {
+        DailyEvent {
+            name: name.to_string(),
+            time: time.to_string(),
+            sound: false,
+            alarm: false,
+        }
+    }
+
+    #[test]
+    fn upcoming_picks_the_nearest_within_lead() {
+        let events = [event("Lunch", "12:00"), event("Standup", "09:30")];
+        let (ev, left) = upcoming(&events, at(9, 20, 0), Duration::minutes(15)).unwrap();
+        assert_eq!(ev.name, "Standup");
+        assert_eq!(left, Duration::minutes(10));
+        assert!(upcoming(&events, at(9, 0, 0), Duration::minutes(15)).is_none());
+        // Once it has rung, it's tomorrow's
+        assert!(upcoming(&events, at(9, 30, 0), Duration::minutes(15)).is_none());
+    }
+
+    #[test]
+    fn upcoming_across_midnight() {
+        let events = [event("Sleep", "00:05")];
+        let (_, left) = upcoming(&events, at(23, 58, 0), Duration::minutes(10)).unwrap();
+        assert_eq!(left, Duration::minutes(7));
+    }
+
+    #[test]
+    fn fires_when_time_is_crossed() {
+        let events = [event("Medication", "08:00")];
+        let mut tracker = ReminderTracker::new(at(7, 59, 59));
+        let fired = tracker.poll(&events, at(8, 0, 0));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].name, "Medication");
+    }
+
+    #[test]
+    fn fires_only_once() {
+        let events = [event("Medication", "08:00")];
+        let mut tracker = ReminderTracker::new(at(7, 59, 59));
+        assert_eq!(tracker.poll(&events, at(8, 0, 0)).len(), 1);
+        assert!(tracker.poll(&events, at(8, 0, 1)).is_empty());
+    }
+
+    #[test]
+    fn does_not_fire_before_time() {
+        let events = [event("Medication", "08:00")];
+        let mut tracker = ReminderTracker::new(at(7, 58, 0));
+        assert!(tracker.poll(&events, at(7, 59, 59)).is_empty());
+    }
+
+    #[test]
+    fn fires_across_midnight() {
+        let events = [event("Midnight", "00:00")];
+        let mut tracker = ReminderTracker::new(at(23, 59, 59));
+        let next_day = at(0, 0, 0) + Duration::days(1);
+        assert_eq!(tracker.poll(&events, next_day).len(), 1);
+    }
+
+    #[test]
+    fn skips_long_gaps() {
+        let events = [event("Lunch", "12:00")];
+        let mut tracker = ReminderTracker::new(at(9, 0, 0));
+        assert!(tracker.poll(&events, at(13, 0, 0)).is_empty());
+    }
+
+    #[test]
+    fn ignores_backwards_clock() {
+        let events = [event("Lunch", "12:00")];
+        let mut tracker = ReminderTracker::new(at(12, 0, 30));
+        assert!(tracker.poll(&events, at(11, 59, 0)).is_empty());
+    }
+
+    #[test]
+    fn snoozed_event_fires_again() {
+        let events = [event("Stretch", "10:00")];
+        let mut tracker = ReminderTracker::new(at(9, 59, 59));
+        let fired = tracker.poll(&events, at(10, 0, 0));
+        tracker.snooze(fired[0].clone(), at(10, 0, 5), 5);
+
+        assert!(tracker.poll(&events, at(10, 4, 0)).is_empty());
+        let again = tracker.poll(&events, at(10, 5, 5));
+        assert_eq!(again.len(), 1);
+        assert_eq!(again[0].name, "Stretch");
+        assert!(tracker.poll(&events, at(10, 5, 6)).is_empty());
+    }
+
+    #[test]
+    fn stale_snooze_dropped() {
+        let events = [event("Stretch", "10:00")];
+        let mut tracker = ReminderTracker::new(at(10, 0, 0));
+        tracker.snooze(event("Stretch", "10:00"), at(10, 0, 0), 5);
+        // Woke from sleep an hour later
+        assert!(tracker.poll(&events, at(11, 0, 0)).is_empty());
+    }
+
+    #[test]
+    fn ignores_invalid_times() {
+        let events = [event("Broken", "noon")];
+        let mut tracker = ReminderTracker::new(at(11, 59, 0));
+        assert!(tracker.poll(&events, at(12, 0, 0)).is_empty());
+    }
+}
@@ -0,0 +1,43 @@
+//! Controller rumble for reminders, through XInput.
+//!
+//! With game audio up a reminder sound is easy to miss, but a pulse in the
+//! hands isn't. Every connected Xbox-compatible controller is pulsed; XInput
+//! quietly ignores slots with nothing plugged in.
+
+use std::time::Duration;
+
+use windows::Win32::UI::Input::XboxController::{
+    XInputSetState, XINPUT_VIBRATION, XUSER_MAX_COUNT,
+};
+
+/// Motor strength for each pulse (of 65535).
+const STRENGTH: u16 = 40_000;
+const PULSE: Duration = Duration::from_millis(250);
+const GAP: Duration = Duration::from_millis(150);
+const PULSES: u32 = 2;
+
+fn set_all(speed: u16) {
+    let vibration = XINPUT_VIBRATION {
+        wLeftMotorSpeed: speed,
+        wRightMotorSpeed: speed,
+    };
+    for user in 0..XUSER_MAX_COUNT {
+        unsafe {
+            XInputSetState(user, &vibration);
+        }
+    }
+}
+
+/// Pulse the rumble motors of every connected controller, without blocking the caller.
+pub fn pulse() {
+    std::thread::spawn(|| {
+        for i in 0..PULSES {
+            if i > 0 {
+                std::thread::sleep(GAP);
+            }
+            set_all(STRENGTH);
+            std::thread::sleep(PULSE);
+            set_all(0);
+        }
+    });
+}
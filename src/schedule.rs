@@ -0,0 +1,175 @@
+use chrono::{Datelike, Duration, NaiveDateTime, Timelike, Weekday};
+
+use crate::config::{ColorRule, ScheduleDays, ScheduleRange};
+
+pub fn day_matches(days: ScheduleDays, day: Weekday) -> bool {
+    let weekend = matches!(day, Weekday::Sat | Weekday::Sun);
+    match days {
+        ScheduleDays::EveryDay => true,
+        ScheduleDays::Weekdays => !weekend,
+        ScheduleDays::Weekends => weekend,
+    }
+}
+
+fn range_contains(range: &ScheduleRange, now: NaiveDateTime) -> bool {
+    let Some((start, end)) = range.parsed() else {
+        return false;
+    };
+    let t = now.time();
+    let today = now.weekday();
+    if start <= end {
+        day_matches(range.days, today) && start <= t && t < end
+    } else {
+        // Overnight range: the part after midnight belongs to the previous day
+        (t >= start && day_matches(range.days, today))
+            || (t < end && day_matches(range.days, today.pred()))
+    }
+}
+
+/// True if `now` falls inside any of the configured ranges.
+pub fn is_active(ranges: &[ScheduleRange], now: NaiveDateTime) -> bool {
+    ranges.iter().any(|r| range_contains(r, now))
+}
+
+/// The first color rule in effect at `now`.
+pub fn color_rule(rules: &[ColorRule], now: NaiveDateTime) -> Option<&ColorRule> {
+    rules.iter().find(|rule| range_contains(&rule.range(), now))
+}
+
+/// True if a multiple of `interval_minutes` past midnight fell in `(last, now]`.
+/// An interval of 60 fires at the top of every hour.
+pub fn interval_crossed(last: NaiveDateTime, now: NaiveDateTime, interval_minutes: u32) -> bool {
+    if interval_minutes == 0 || now <= last {
+        return false;
+    }
+    let minutes = now.hour() * 60 + now.minute();
+    let boundary = now.date().and_hms_opt(0, 0, 0).unwrap()
+        + Duration::minutes((minutes / interval_minutes * interval_minutes) as i64);
+    last < boundary && boundary <= now
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    // 2026-03-06 is a Friday
+    fn fri(h: u32, m: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, 6)
+            .unwrap()
+            .and_hms_opt(h, m, 0)
+            .unwrap()
+    }
+
+    fn sat(h: u32, m: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, 7)
+            .unwrap()
+            .and_hms_opt(h, m, 0)
+            .unwrap()
+    }
+
+    fn range(start: &str, end: &str, days: ScheduleDays) -> ScheduleRange {
+        ScheduleRange {
+            start: start.to_string(),
+            end: end.to_string(),
+            days,
+        }
+    }
+
+    #[test]
+    fn same_day_range() {
+        let r = [range("09:00", "17:00", ScheduleDays::EveryDay)];
+        assert!(!is_active(&r, fri(8, 59)));
+        assert!(is_active(&r, fri(9, 0)));
+        assert!(is_active(&r, fri(16, 59)));
+        assert!(!is_active(&r, fri(17, 0)));
+    }
+
+    #[test]
+    fn first_matching_color_rule_wins() {
+        let rule = |start: &str, end: &str, color| ColorRule {
+            start: start.to_string(),
+            end: end.to_string(),
+            text_color: color,
+            ..ColorRule::default()
+        };
+        let rules = [
+            rule("00:00", "06:00", [255, 0, 0]),
+            rule("09:00", "17:00", [0, 0, 255]),
+            rule("00:00", "23:59", [0, 255, 0]),
+        ];
+        assert_eq!(
+            color_rule(&rules, sat(1, 0)).unwrap().text_color,
+            [255, 0, 0]
+        );
+        assert_eq!(
+            color_rule(&rules, fri(10, 0)).unwrap().text_color,
+            [0, 0, 255]
+        );
+        assert_eq!(
+            color_rule(&rules, fri(20, 0)).unwrap().text_color,
+            [0, 255, 0]
+        );
+        assert!(color_rule(&rules[..2], fri(20, 0)).is_none());
+    }
+
+    #[test]
+    fn overnight_range() {
+        let r = [range("21:00", "01:00", ScheduleDays::EveryDay)];
+        assert!(!is_active(&r, fri(20, 59)));
+        assert!(is_active(&r, fri(23, 30)));
+        assert!(is_active(&r, sat(0, 30)));
+        assert!(!is_active(&r, sat(1, 0)));
+    }
+
+    #[test]
+    fn weekdays_overnight_belongs_to_start_day() {
+        let r = [range("21:00", "01:00", ScheduleDays::Weekdays)];
+        // Friday night counts, including the hour after midnight on Saturday
+        assert!(is_active(&r, fri(22, 0)));
+        assert!(is_active(&r, sat(0, 30)));
+        // Saturday night does not
+        assert!(!is_active(&r, sat(22, 0)));
+    }
+
+    #[test]
+    fn weekends_only() {
+        let r = [range("10:00", "12:00", ScheduleDays::Weekends)];
+        assert!(!is_active(&r, fri(11, 0)));
+        assert!(is_active(&r, sat(11, 0)));
+    }
+
+    #[test]
+    fn invalid_and_empty_ranges() {
+        assert!(!is_active(&[], fri(12, 0)));
+        let r = [range("noon", "13:00", ScheduleDays::EveryDay)];
+        assert!(!is_active(&r, fri(12, 30)));
+        let r = [range("12:00", "12:00", ScheduleDays::EveryDay)];
+        assert!(!is_active(&r, fri(12, 0)));
+    }
+
+    #[test]
+    fn interval_top_of_hour() {
+        assert!(interval_crossed(fri(9, 59), fri(10, 0), 60));
+        assert!(!interval_crossed(fri(10, 0), fri(10, 1), 60));
+        assert!(!interval_crossed(fri(10, 30), fri(10, 31), 60));
+    }
+
+    #[test]
+    fn interval_every_fifteen_minutes() {
+        assert!(interval_crossed(fri(10, 14), fri(10, 15), 15));
+        assert!(!interval_crossed(fri(10, 15), fri(10, 16), 15));
+        assert!(interval_crossed(fri(10, 44), fri(10, 45), 15));
+    }
+
+    #[test]
+    fn interval_disabled_or_backwards() {
+        assert!(!interval_crossed(fri(9, 59), fri(10, 0), 0));
+        assert!(!interval_crossed(fri(10, 0), fri(9, 59), 60));
+    }
+
+    #[test]
+    fn interval_midnight() {
+        assert!(interval_crossed(fri(23, 59), sat(0, 0), 60));
+    }
+}
@@ -0,0 +1,217 @@
+//! Custom overlay text from a Rhai script.
+//!
+//! When `script` names a file, it runs on every clock tick and the value it
+//! returns replaces the clock at the start of the overlay line (segments,
+//! banners and the debug line still follow). The time, battery and session
+//! values in [`Context`] are available as variables. The file is reloaded when
+//! it changes; a script that fails or runs too long falls back to the normal
+//! clock and the error goes to the log.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use rhai::{Dynamic, Engine, Scope, AST};
+
+/// Rhai operations one run may take, so a runaway loop can't freeze the overlay.
+const MAX_OPERATIONS: u64 = 200_000;
+
+/// Values a script can read, refreshed on every tick.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Context {
+    /// The clock as ClockOR would draw it, e.g. "21:05".
+    pub time: String,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    /// 1 = Monday … 7 = Sunday.
+    pub weekday: u32,
+    /// "2026-03-01".
+    pub date: String,
+    /// Battery percent, or -1 without a battery.
+    pub battery: i64,
+    pub plugged_in: bool,
+    pub uptime_minutes: i64,
+    /// Minutes on the stream timer, or -1 when it isn't running.
+    pub stream_minutes: i64,
+    /// CPU usage percent, or -1 until it has been measured.
+    pub cpu: i64,
+    /// `(name, text)` from each running plugin.
+    pub plugins: Vec<(String, String)>,
+}
+
+impl Context {
+    fn scope(&self) -> Scope<'static> {
+        let mut scope = Scope::new();
+        scope.push_constant("time", self.time.clone());
+        scope.push_constant("hour", self.hour as i64);
+        scope.push_constant("minute", self.minute as i64);
+        scope.push_constant("second", self.second as i64);
+        scope.push_constant("weekday", self.weekday as i64);
+        scope.push_constant("date", self.date.clone());
+        scope.push_constant("battery", self.battery);
+        scope.push_constant("plugged_in", self.plugged_in);
+        scope.push_constant("uptime_minutes", self.uptime_minutes);
+        scope.push_constant("stream_minutes", self.stream_minutes);
+        scope.push_constant("cpu", self.cpu);
+        let plugins: rhai::Map = self
+            .plugins
+            .iter()
+            .map(|(name, text)| (name.into(), text.clone().into()))
+            .collect();
+        scope.push_constant("plugins", plugins);
+        scope
+    }
+}
+
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(32);
+    engine.set_max_string_size(1024);
+    engine.on_print(|text| crate::logging::debug(format!("Script: {text}")));
+    engine.on_debug(|text, _, _| crate::logging::debug(format!("Script: {text}")));
+    engine
+}
+
+/// Run a compiled script and turn its value into one overlay line.
+fn evaluate(engine: &Engine, ast: &AST, context: &Context) -> Result<String, String> {
+    let value = engine
+        .eval_ast_with_scope::<Dynamic>(&mut context.scope(), ast)
+        .map_err(|e| e.to_string())?;
+    let text = value.to_string();
+    Ok(text.lines().map(str::trim).collect::<Vec<_>>().join("  "))
+}
+
+/// The loaded script and what is needed to notice it changed.
+struct Host {
+    engine: Engine,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    ast: Result<AST, String>,
+}
+
+impl Host {
+    fn load(path: PathBuf) -> Self {
+        let engine = engine();
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        let ast = std::fs::read_to_string(&path)
+            .map_err(|e| format!("{}: {e}", path.display()))
+            .and_then(|source| engine.compile(source).map_err(|e| e.to_string()));
+        Self {
+            engine,
+            path,
+            modified,
+            ast,
+        }
+    }
+
+    fn is_stale(&self, path: &PathBuf) -> bool {
+        *path != self.path
+            || std::fs::metadata(path).and_then(|m| m.modified()).ok() != self.modified
+    }
+
+    fn run(&self, context: &Context) -> Result<String, String> {
+        let ast = self.ast.as_ref().map_err(Clone::clone)?;
+        evaluate(&self.engine, ast, context)
+    }
+}
+
+static HOST: Mutex<Option<Host>> = Mutex::new(None);
+/// What the script returned on the last tick; `None` shows the normal clock.
+static OUTPUT: Mutex<Option<String>> = Mutex::new(None);
+/// The last error, logged once rather than on every tick.
+static LAST_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+/// Run the configured script for this tick. An empty `script` turns it off.
+pub fn update(script: &str, context: &Context) {
+    if script.trim().is_empty() {
+        *HOST.lock().unwrap() = None;
+        *OUTPUT.lock().unwrap() = None;
+        *LAST_ERROR.lock().unwrap() = None;
+        return;
+    }
+    let path = crate::config::user_path(script);
+    let mut host = HOST.lock().unwrap();
+    if host.as_ref().is_none_or(|h| h.is_stale(&path)) {
+        *host = Some(Host::load(path));
+    }
+    let result = host.as_ref().map(|h| h.run(context)).unwrap();
+    drop(host);
+    let mut last_error = LAST_ERROR.lock().unwrap();
+    match result {
+        Ok(text) => {
+            *OUTPUT.lock().unwrap() = Some(text);
+            *last_error = None;
+        }
+        Err(e) => {
+            *OUTPUT.lock().unwrap() = None;
+            if last_error.as_deref() != Some(e.as_str()) {
+                crate::logging::error(format!("Script: {e}"));
+                *last_error = Some(e);
+            }
+        }
+    }
+}
+
+/// The script's text for this tick, if a script is set and succeeded.
+pub fn output() -> Option<String> {
+    OUTPUT.lock().unwrap().clone()
+}
+
+/// Why the script isn't showing, for settings.
+pub fn last_error() -> Option<String> {
+    LAST_ERROR.lock().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(source: &str, context: &Context) -> Result<String, String> {
+        let engine = engine();
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+        evaluate(&engine, &ast, context)
+    }
+
+    fn context() -> Context {
+        Context {
+            time: "21:05".into(),
+            hour: 21,
+            minute: 5,
+            battery: 42,
+            stream_minutes: -1,
+            plugins: vec![("Sensors".into(), "CPU 61°C".into())],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn script_sees_context_variables() {
+        let text = run(r#"`${time} | ${battery}%`"#, &context()).unwrap();
+        assert_eq!(text, "21:05 | 42%");
+        let text = run(
+            r#"if stream_minutes < 0 { "offline" } else { "live" }"#,
+            &context(),
+        );
+        assert_eq!(text.unwrap(), "offline");
+        let text = run(
+            r#"plugins["Sensors"] + " " + (plugins["Missing"] ?? "-")"#,
+            &context(),
+        );
+        assert_eq!(text.unwrap(), "CPU 61°C -");
+    }
+
+    #[test]
+    fn numbers_and_lines_become_one_line() {
+        assert_eq!(run("hour * 60 + minute", &context()).unwrap(), "1265");
+        assert_eq!(run(r#""a\n b""#, &context()).unwrap(), "a  b");
+    }
+
+    #[test]
+    fn errors_and_runaway_loops_are_reported() {
+        assert!(run("let x = ;", &context()).is_err());
+        assert!(run("undefined_variable", &context()).is_err());
+        assert!(run("loop {}", &context()).is_err());
+    }
+}
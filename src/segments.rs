@@ -0,0 +1,198 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::config::{ActiveWindowDisplay, ProgressDisplay};
+
+/// Longest the active window segment gets, in characters.
+const ACTIVE_WINDOW_CHARS: usize = 32;
+
+/// Number of days in the month containing `date`.
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (y, m) = (date.year(), date.month());
+    let next = if m == 12 {
+        NaiveDate::from_ymd_opt(y + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(y, m + 1, 1)
+    };
+    next.and_then(|n| n.pred_opt())
+        .map(|last| last.day())
+        .unwrap_or(30)
+}
+
+/// Whole days elapsed in the year or month, e.g. "61% of 2025" or "45% of March".
+/// The value only changes at midnight, so it is effectively refreshed daily.
+pub fn progress(kind: ProgressDisplay, date: NaiveDate) -> Option<String> {
+    let (elapsed, total, label) = match kind {
+        ProgressDisplay::Off => return None,
+        ProgressDisplay::Year => {
+            let total = if date.leap_year() { 366 } else { 365 };
+            (date.ordinal0(), total, date.year().to_string())
+        }
+        ProgressDisplay::Month => (
+            date.day0(),
+            days_in_month(date),
+            date.format("%B").to_string(),
+        ),
+    };
+    let percent = elapsed * 100 / total;
+    Some(format!("{percent}% of {label}"))
+}
+
+/// Compact machine uptime from a millisecond tick count, e.g. "up 6h 12m".
+pub fn uptime(ms: u64) -> String {
+    let minutes = ms / 60_000;
+    let (d, h, m) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    match (d, h) {
+        (0, 0) => format!("up {m}m"),
+        (0, _) => format!("up {h}h {m}m"),
+        _ => format!("up {d}d {h}h"),
+    }
+}
+
+/// The focused program ("chrome.exe") or window title, cut with an ellipsis
+/// when long. `None` when off or when there is nothing to name.
+pub fn active_window(
+    kind: ActiveWindowDisplay,
+    image: Option<&str>,
+    title: &str,
+) -> Option<String> {
+    let text = match kind {
+        ActiveWindowDisplay::Off => return None,
+        ActiveWindowDisplay::Process => image?.rsplit(['\\', '/']).next()?,
+        ActiveWindowDisplay::Title => title.trim(),
+    };
+    if text.is_empty() {
+        return None;
+    }
+    if text.chars().count() <= ACTIVE_WINDOW_CHARS {
+        return Some(text.to_string());
+    }
+    let cut: String = text.chars().take(ACTIVE_WINDOW_CHARS - 1).collect();
+    Some(format!("{}…", cut.trim_end()))
+}
+
+/// Stream duration as "LIVE H:MM:SS".
+pub fn stream_elapsed(secs: u64) -> String {
+    format!("LIVE {}", hms(secs))
+}
+
+/// Time left on the countdown as "⏳ M:SS", or "⏳ H:MM:SS" from an hour.
+pub fn countdown(secs: u64) -> String {
+    format!("⏳ {}", minutes_seconds(secs))
+}
+
+/// A duration as "M:SS", or "H:MM:SS" from an hour.
+pub fn minutes_seconds(secs: u64) -> String {
+    if secs >= 3600 {
+        hms(secs)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// A duration as "H:MM:SS".
+pub fn hms(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    format!("{h}:{m:02}:{s:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn progress_off() {
+        assert!(progress(ProgressDisplay::Off, date(2025, 8, 12)).is_none());
+    }
+
+    #[test]
+    fn year_progress() {
+        assert_eq!(
+            progress(ProgressDisplay::Year, date(2025, 1, 1)).unwrap(),
+            "0% of 2025"
+        );
+        // Day 224 of 365 -> 223 full days elapsed
+        assert_eq!(
+            progress(ProgressDisplay::Year, date(2025, 8, 12)).unwrap(),
+            "61% of 2025"
+        );
+        assert_eq!(
+            progress(ProgressDisplay::Year, date(2024, 12, 31)).unwrap(),
+            "99% of 2024"
+        );
+    }
+
+    #[test]
+    fn month_progress() {
+        assert_eq!(
+            progress(ProgressDisplay::Month, date(2025, 3, 1)).unwrap(),
+            "0% of March"
+        );
+        assert_eq!(
+            progress(ProgressDisplay::Month, date(2025, 2, 15)).unwrap(),
+            "50% of February"
+        );
+    }
+
+    #[test]
+    fn month_lengths() {
+        assert_eq!(days_in_month(date(2024, 2, 10)), 29);
+        assert_eq!(days_in_month(date(2025, 2, 10)), 28);
+        assert_eq!(days_in_month(date(2025, 12, 31)), 31);
+        assert_eq!(days_in_month(date(2025, 4, 1)), 30);
+    }
+
+    #[test]
+    fn uptime_minutes_only() {
+        assert_eq!(uptime(0), "up 0m");
+        assert_eq!(uptime(12 * 60_000 + 59_999), "up 12m");
+    }
+
+    #[test]
+    fn uptime_hours_and_minutes() {
+        assert_eq!(uptime((6 * 60 + 12) * 60_000), "up 6h 12m");
+    }
+
+    #[test]
+    fn uptime_days() {
+        assert_eq!(uptime((26 * 60 + 5) * 60_000), "up 1d 2h");
+    }
+
+    #[test]
+    fn stream_elapsed_format() {
+        assert_eq!(stream_elapsed(0), "LIVE 0:00:00");
+        assert_eq!(stream_elapsed(3723), "LIVE 1:02:03");
+        assert_eq!(stream_elapsed(11 * 3600 + 59), "LIVE 11:00:59");
+    }
+
+    #[test]
+    fn countdown_format() {
+        assert_eq!(countdown(0), "⏳ 0:00");
+        assert_eq!(countdown(599), "⏳ 9:59");
+        assert_eq!(countdown(3599), "⏳ 59:59");
+        assert_eq!(countdown(3723), "⏳ 1:02:03");
+    }
+
+    #[test]
+    fn active_window_names() {
+        let image = Some(r"C:\Program Files\Game\game.exe");
+        let kind = ActiveWindowDisplay::Process;
+        assert_eq!(active_window(kind, image, "Game").unwrap(), "game.exe");
+        assert_eq!(active_window(kind, None, "Game"), None);
+        let kind = ActiveWindowDisplay::Title;
+        assert_eq!(active_window(kind, image, " Game ").unwrap(), "Game");
+        assert_eq!(active_window(kind, image, ""), None);
+        assert_eq!(active_window(ActiveWindowDisplay::Off, image, "Game"), None);
+    }
+
+    #[test]
+    fn long_titles_are_cut() {
+        let title = "README.md - Notepad and a great deal more text";
+        let shown = active_window(ActiveWindowDisplay::Title, None, title).unwrap();
+        assert_eq!(shown.chars().count(), ACTIVE_WINDOW_CHARS);
+        assert!(shown.ends_with('…'));
+    }
+}
@@ -1,6 +1,556 @@
+use std::time::{Duration, Instant};
+
 use eframe::egui;
 
-use crate::config::{Config, Position, TextStyle, KEY_OPTIONS, MODIFIER_OPTIONS};
+use crate::config::{
+    parse_hhmm, ActionKind, ActiveWindowDisplay, AlertPalette, AutostartMethod, Backdrop,
+    ColorRule, Config, CornerStyle, CursorReaction, DailyEvent, DateDisplay, DoubleTapKey,
+    FontFamily, Hook, HookEvent, HotCorner, MonitorMode, MonitorOverlay, MonitorScale, Position,
+    ProgressDisplay, ScheduleDays, ScheduleRange, ScheduledAction, ScreenEdge, SettingsSection,
+    TextAlign, TextRenderer, TextStyle, TrayAction, WorldClock, ZOrder, KEY_OPTIONS,
+    MODIFIER_OPTIONS,
+};
+use crate::sound::{Cue, OutputDevice};
+
+/// How often unapplied edits are written to the draft file.
+const DRAFT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Searchable settings: section, English label, Japanese keywords, and the
+/// config keys the control edits (for policy locks).
+const SEARCH_INDEX: &[(SettingsSection, &str, &str, &[&str])] = &[
+    (
+        SettingsSection::Display,
+        "Position",
+        "表示位置 角 カスタム",
+        &["position", "custom_pos"],
+    ),
+    (
+        SettingsSection::Display,
+        "Snapping",
+        "スナップ 吸着 グリッド 端 ドラッグ 整列",
+        &["snap_to_edges", "snap_grid"],
+    ),
+    (
+        SettingsSection::Display,
+        "Monitor scale",
+        "モニター ディスプレイ 倍率 拡大 テレビ 大きさ",
+        &["monitor_scales"],
+    ),
+    (
+        SettingsSection::Display,
+        "Monitor clocks",
+        "モニター ディスプレイ 複数 テレビ 個別 位置 マルチ",
+        &["monitor_overlays"],
+    ),
+    (
+        SettingsSection::Display,
+        "On Hover",
+        "マウス カーソル 薄く 逃がす",
+        &["cursor_reaction"],
+    ),
+    (
+        SettingsSection::Display,
+        "Stay on top",
+        "最前面 重なり 隠れる ゲーム 前面 z-order",
+        &["z_order"],
+    ),
+    (
+        SettingsSection::Display,
+        "Time Format",
+        "時刻 形式 24時間 12時間",
+        &["format_24h"],
+    ),
+    (
+        SettingsSection::Display,
+        "Show seconds",
+        "秒",
+        &["show_seconds"],
+    ),
+    (
+        SettingsSection::Display,
+        "Custom format",
+        "時刻 形式 書式 フォーマット カスタム strftime ミリ秒 曜日",
+        &["time_format"],
+    ),
+    (
+        SettingsSection::Display,
+        "Progress",
+        "経過率 今年 今月",
+        &["progress"],
+    ),
+    (
+        SettingsSection::Display,
+        "Date line",
+        "日付 年月日 カレンダー 2行",
+        &["date_line"],
+    ),
+    (
+        SettingsSection::Display,
+        "World clocks",
+        "世界時計 タイムゾーン 時差 海外 UTC JST PST",
+        &["world_clocks"],
+    ),
+    (
+        SettingsSection::Display,
+        "Show uptime",
+        "稼働時間 起動",
+        &["show_uptime"],
+    ),
+    (
+        SettingsSection::Display,
+        "Show input",
+        "キー入力 マウス 入力表示 配信 ストリーマー キーボード",
+        &["show_input"],
+    ),
+    (
+        SettingsSection::Display,
+        "Active window",
+        "アクティブ ウィンドウ タイトル プロセス フォーカス 配信",
+        &["active_window"],
+    ),
+    (
+        SettingsSection::Display,
+        "Input language",
+        "入力言語 キーボード レイアウト IME 日本語 英語 かな",
+        &["show_input_language"],
+    ),
+    (
+        SettingsSection::Display,
+        "Script",
+        "スクリプト Rhai カスタム 表示 電池",
+        &["script"],
+    ),
+    (
+        SettingsSection::Display,
+        "Template",
+        "テンプレート 書式 トークン 日付 電池 CPU 組み合わせ",
+        &["template"],
+    ),
+    (
+        SettingsSection::Display,
+        "Text file",
+        "テキストファイル 外部 連携 再生中 曲名",
+        &["text_file"],
+    ),
+    (
+        SettingsSection::Display,
+        "Command",
+        "コマンド 出力 シェル 株価 ビルド 状態",
+        &["command", "command_interval_seconds"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Font Size",
+        "フォント 文字 サイズ 大きさ",
+        &["font_size"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Font Weight",
+        "フォント 太さ 太字 細字 ウェイト",
+        &["font_weight"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Font",
+        "フォント 書体 Segoe UI Bahnschrift 可変フォント",
+        &["font_family"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Font Width",
+        "フォント 幅 字幅 長体 コンデンス 可変フォント",
+        &["font_width"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Max Width",
+        "最大幅 省略",
+        &["max_width"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Alignment",
+        "揃え 左寄せ 中央 右寄せ 配置",
+        &["text_align"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Multi-line",
+        "複数行 改行 段 縦に並べる 行間 揃え 中央",
+        &["multi_line", "line_spacing", "line_alignment"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Vertical",
+        "縦書き 縦 回転 90度 端 ウルトラワイド",
+        &["vertical"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Text Style",
+        "縁取り 影 装飾",
+        &["text_style"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Text Rendering",
+        "描画 レンダリング アンチエイリアス にじみ ぼやけ DirectWrite GDI 絵文字",
+        &["text_renderer"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Sub-pixel Text",
+        "サブピクセル 文字 位置 字間 くっきり にじみ DirectWrite",
+        &["subpixel_text"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Text Color",
+        "文字色 色",
+        &["text_color"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Outline/Shadow Color",
+        "縁取り 影 色",
+        &["outline_color"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Color rules",
+        "色 ルール 時間帯 深夜 赤 仕事 自動 パレット",
+        &["color_rules"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Alert colors",
+        "警告 色 色覚 色弱 色盲 カラーユニバーサル 赤 黄 バッテリー リマインダー カウントダウン",
+        &["alert_palette", "alert_colors"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Text Opacity",
+        "透明度 不透明度 文字",
+        &["opacity"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Background Opacity",
+        "背景 パネル 透明度 不透明度 色",
+        &["background_opacity", "background_color"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Background image",
+        "背景 画像 ロゴ PNG プレート",
+        &["background_image"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Backdrop",
+        "背景 すりガラス ぼかし アクリル 透明 ブラー",
+        &["backdrop"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Corners",
+        "角 丸い 角丸 Windows 11",
+        &["corner_style"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Border",
+        "枠 枠線 ボーダー 色 Windows 11",
+        &["window_border", "border_color"],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Theme file",
+        "テーマ スキン 共有 読み込み 書き出し エクスポート",
+        &[],
+    ),
+    (
+        SettingsSection::Appearance,
+        "Preset code",
+        "プリセット コード 共有 コピー 貼り付け Discord テーマ",
+        &[],
+    ),
+    (
+        SettingsSection::Reminders,
+        "Reminders",
+        "リマインダー 通知 時刻 音",
+        &["daily_events"],
+    ),
+    (
+        SettingsSection::Reminders,
+        "Banner seconds",
+        "バナー 秒数",
+        &["banner_seconds"],
+    ),
+    (
+        SettingsSection::Reminders,
+        "Snooze",
+        "スヌーズ",
+        &["snooze_minutes"],
+    ),
+    (
+        SettingsSection::Reminders,
+        "Warn before",
+        "事前 予告 カウントダウン 会議 ミーティング 色 近づく",
+        &["reminder_warning_minutes"],
+    ),
+    (
+        SettingsSection::Reminders,
+        "Countdown",
+        "カウントダウン タイマー 残り時間 キッチンタイマー 締め切り 目標時刻",
+        &[
+            "countdown_minutes",
+            "countdown_until",
+            "countdown_replaces_clock",
+        ],
+    ),
+    (
+        SettingsSection::Reminders,
+        "Edge flash",
+        "画面端 点滅 光る 無音 聴覚 視覚的 通知",
+        &["edge_flash", "edge_flash_color"],
+    ),
+    (
+        SettingsSection::Reminders,
+        "Controller rumble",
+        "コントローラー 振動 バイブレーション ゲームパッド Xbox",
+        &["controller_rumble"],
+    ),
+    (
+        SettingsSection::Sound,
+        "Tick every minute",
+        "音 チック 毎分 音量",
+        &["tick_sound", "tick_volume"],
+    ),
+    (
+        SettingsSection::Sound,
+        "Chime on the hour",
+        "音 時報 チャイム 毎正時 音量",
+        &["hourly_chime", "chime_volume"],
+    ),
+    (
+        SettingsSection::Sound,
+        "Mute in fullscreen",
+        "ミュート 全画面 フルスクリーン ゲーム 音",
+        &["mute_in_fullscreen"],
+    ),
+    (
+        SettingsSection::Sound,
+        "Output device",
+        "出力 デバイス スピーカー ヘッドセット 再生",
+        &["audio_device"],
+    ),
+    (
+        SettingsSection::Automation,
+        "Schedule",
+        "スケジュール 時間帯 自動表示 平日 週末",
+        &["visibility_schedule"],
+    ),
+    (
+        SettingsSection::Automation,
+        "Scheduled actions",
+        "予約 指定時刻 自動 タイマー開始 テーマ切り替え Webhook",
+        &["scheduled_actions"],
+    ),
+    (
+        SettingsSection::Automation,
+        "Hide when idle",
+        "離席 アイドル 隠す 焼き付き",
+        &["idle_hide_minutes"],
+    ),
+    (
+        SettingsSection::Automation,
+        "Peek every",
+        "毎正時 定期 自動表示 秒数",
+        &["peek_interval_minutes", "peek_seconds"],
+    ),
+    (
+        SettingsSection::Automation,
+        "Play reminder",
+        "プレイ時間 長時間 連続 全画面 休憩 自動表示",
+        &["play_reveal_minutes"],
+    ),
+    (
+        SettingsSection::Automation,
+        "Low battery alert",
+        "電池 バッテリー 残量 低下 警告 ノートパソコン 充電",
+        &["low_battery_percent"],
+    ),
+    (
+        SettingsSection::Automation,
+        "Shutdown warning",
+        "シャットダウン 再起動 予約 Windows Update 更新 警告",
+        &["shutdown_warning"],
+    ),
+    (
+        SettingsSection::Automation,
+        "Shutdown timer",
+        "シャットダウン 再起動 残り時間 カウントダウン タイマー /t",
+        &["shutdown_timer_minutes"],
+    ),
+    (
+        SettingsSection::Automation,
+        "Hide under Game Bar",
+        "ゲームバー Xbox Win+G 重なり",
+        &["hide_under_game_bar"],
+    ),
+    (
+        SettingsSection::Automation,
+        "Quiet while presenting",
+        "プレゼンテーション 発表 ミュート 通知 抑制",
+        &["quiet_when_presenting"],
+    ),
+    (
+        SettingsSection::Tray,
+        "Left click",
+        "トレイ クリック 左クリック",
+        &["tray_left_click"],
+    ),
+    (
+        SettingsSection::Tray,
+        "Double click",
+        "トレイ ダブルクリック",
+        &["tray_double_click"],
+    ),
+    (
+        SettingsSection::Tray,
+        "Middle click",
+        "トレイ 中クリック ホイール",
+        &["tray_middle_click"],
+    ),
+    (
+        SettingsSection::Tray,
+        "Confirm quit",
+        "終了 確認 誤操作",
+        &["confirm_quit"],
+    ),
+    (
+        SettingsSection::System,
+        "Hotkey",
+        "ホットキー キー 表示 非表示 切り替え",
+        &["hotkey"],
+    ),
+    (
+        SettingsSection::System,
+        "Double-tap",
+        "ダブルタップ 修飾キー 2回",
+        &["double_tap"],
+    ),
+    (
+        SettingsSection::System,
+        "Hot corner",
+        "ホットコーナー 角 マウス カーソル 一時表示 キー不要",
+        &["hot_corner"],
+    ),
+    (
+        SettingsSection::System,
+        "Lock Hotkey",
+        "ロック クリック透過 ドラッグ 移動",
+        &["lock_hotkey"],
+    ),
+    (
+        SettingsSection::System,
+        "Format Hotkey",
+        "24時間 12時間 形式 切り替え",
+        &["format_hotkey"],
+    ),
+    (
+        SettingsSection::System,
+        "Snooze Hotkey",
+        "スヌーズ",
+        &["snooze_hotkey"],
+    ),
+    (
+        SettingsSection::System,
+        "Zoom Hotkey",
+        "拡大 ズーム 大きく 遠く 一時的",
+        &["zoom_hotkey", "zoom_seconds"],
+    ),
+    (
+        SettingsSection::System,
+        "Countdown Hotkey",
+        "カウントダウン タイマー 開始 停止",
+        &["countdown_hotkey"],
+    ),
+    (
+        SettingsSection::System,
+        "Flash on toggle",
+        "点滅 フラッシュ",
+        &["toggle_flash"],
+    ),
+    (
+        SettingsSection::System,
+        "Reduce motion",
+        "動き アニメーション 点滅 抑制 視覚 酔い",
+        &["reduce_motion"],
+    ),
+    (
+        SettingsSection::System,
+        "Start with Windows",
+        "自動起動 スタートアップ",
+        &["start_with_windows"],
+    ),
+    (
+        SettingsSection::System,
+        "Autostart method",
+        "自動起動 スタートアップ ショートカット レジストリ タスクスケジューラ",
+        &["autostart_method"],
+    ),
+    (
+        SettingsSection::System,
+        "Startup delay",
+        "自動起動 遅延 待機 ログイン 起動時",
+        &["startup_delay_seconds", "wait_for_shell"],
+    ),
+    (
+        SettingsSection::System,
+        "Start visible",
+        "起動時 表示 最初から 前回 状態 記憶",
+        &["start_visible", "remember_visibility"],
+    ),
+    (
+        SettingsSection::System,
+        "Run as administrator",
+        "管理者 昇格 UAC 権限",
+        &["run_elevated"],
+    ),
+    (
+        SettingsSection::System,
+        "Check for updates",
+        "更新 アップデート 新しいバージョン",
+        &["check_updates"],
+    ),
+    (
+        SettingsSection::System,
+        "Send usage statistics",
+        "利用状況 統計 テレメトリ 匿名 送信",
+        &["usage_stats"],
+    ),
+    (
+        SettingsSection::System,
+        "Plugins",
+        "プラグイン 拡張 DLL センサー",
+        &["plugins", "disabled_plugins"],
+    ),
+    (
+        SettingsSection::System,
+        "Event hooks",
+        "フック Webhook イベント 自動化 照明 連携 コマンド",
+        &["hooks"],
+    ),
+    (
+        SettingsSection::System,
+        "Collect Diagnostics",
+        "診断 不具合 報告 バグ ログ サポート",
+        &[],
+    ),
+];
 
 struct SettingsApp {
     config: Config,
@@ -8,20 +558,84 @@ struct SettingsApp {
     selected_mod: usize,
     selected_key: usize,
     applied: bool,
+    /// Search text; controls whose label or keywords don't contain it are hidden.
+    filter: String,
+    /// Section to bring into view on the first frame.
+    scroll_to: Option<SettingsSection>,
+    /// Playback devices, listed when the Sound section is first drawn.
+    audio_devices: Option<Vec<OutputDevice>>,
+    /// Where the last diagnostics file went, or why it failed.
+    diagnostics_result: Option<String>,
+    /// Name for "Export Theme".
+    theme_name: String,
+    /// What the last theme load or export did.
+    theme_result: Option<String>,
+    /// A preset code pasted to be loaded.
+    preset_code: String,
+    /// Edits from a settings window that crashed, offered until restored or discarded.
+    recovered: Option<Config>,
+    /// The settings as of the last draft check, and when that was.
+    drafted: Config,
+    drafted_at: Instant,
 }
 
 impl SettingsApp {
-    fn new(config: Config) -> Self {
-        let (mod_idx, key_idx) = Self::find_hotkey_indices(&config.hotkey);
+    fn new(config: Config, scroll_to: Option<SettingsSection>) -> Self {
+        let (mod_idx, key_idx) = Self::toggle_hotkey_indices(&config);
+        let recovered = Config::load_draft().filter(|draft| *draft != config);
+        if recovered.is_none() {
+            Config::discard_draft();
+        }
         Self {
+            recovered,
+            drafted: config.clone(),
+            drafted_at: Instant::now(),
             saved_config: config.clone(),
             config,
             selected_mod: mod_idx,
             selected_key: key_idx,
             applied: false,
+            filter: String::new(),
+            scroll_to,
+            audio_devices: None,
+            diagnostics_result: None,
+            theme_name: String::new(),
+            theme_result: None,
+            preset_code: String::new(),
         }
     }
 
+    /// Take the look from a theme file; policy-locked keys keep their values.
+    fn load_theme(&mut self, path: &std::path::Path) {
+        self.theme_result = Some(match crate::themefile::Theme::load(path) {
+            Ok(theme) => {
+                let name = crate::themefile::display_name(path);
+                self.apply_theme(theme, &name)
+            }
+            Err(e) => format!("Could not load {}: {e}", path.display()),
+        });
+    }
+
+    /// Take the look from a pasted preset code.
+    fn load_preset_code(&mut self) {
+        self.theme_result = Some(
+            match crate::themefile::Theme::from_code(&self.preset_code) {
+                Ok(theme) => {
+                    self.preset_code.clear();
+                    self.apply_theme(theme, "preset")
+                }
+                Err(e) => format!("Could not load the code: {e}"),
+            },
+        );
+    }
+
+    fn apply_theme(&mut self, theme: crate::themefile::Theme, fallback_name: &str) -> String {
+        theme.apply(&mut self.config);
+        crate::policy::current().apply(&mut self.config);
+        let name = theme.name.as_deref().unwrap_or(fallback_name);
+        format!("Loaded \"{name}\" (Apply to keep it)")
+    }
+
     fn find_hotkey_indices(hotkey: &str) -> (usize, usize) {
         let parts: Vec<&str> = hotkey.split('+').map(str::trim).collect();
         let key_name = parts.last().unwrap_or(&"F12");
@@ -43,6 +657,20 @@ impl SettingsApp {
         (mod_idx, key_idx)
     }
 
+    /// Picker positions for the toggle hotkey, or for Ctrl+F12 when it can't be parsed.
+    fn toggle_hotkey_indices(config: &Config) -> (usize, usize) {
+        let (modifiers, vk) = config.parsed_hotkey();
+        let mod_idx = MODIFIER_OPTIONS
+            .iter()
+            .position(|(_, m)| *m == modifiers)
+            .unwrap_or(0);
+        let key_idx = KEY_OPTIONS
+            .iter()
+            .position(|(_, k)| *k == vk)
+            .unwrap_or(KEY_OPTIONS.len() - 1);
+        (mod_idx, key_idx)
+    }
+
     fn build_hotkey_string(&self) -> String {
         let mod_name = MODIFIER_OPTIONS[self.selected_mod].0;
         let key_name = KEY_OPTIONS[self.selected_key].0;
@@ -58,179 +686,2369 @@ impl SettingsApp {
     fn has_unsaved_changes(&self) -> bool {
         self.current_config() != self.saved_config
     }
-}
-
-impl eframe::App for SettingsApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("ClockOR Settings");
-            ui.add_space(8.0);
 
-            // === Display Section ===
-            ui.strong("Display");
-            ui.add_space(4.0);
-
-            // Position
-            ui.horizontal(|ui| {
-                ui.label("Position:")
-                    .on_hover_text("画面のどの角に時計を表示するか");
-                ui.radio_value(&mut self.config.position, Position::TopLeft, "Top-Left");
-                ui.radio_value(&mut self.config.position, Position::TopRight, "Top-Right");
-                ui.radio_value(
-                    &mut self.config.position,
-                    Position::BottomLeft,
-                    "Bottom-Left",
-                );
-                ui.radio_value(
-                    &mut self.config.position,
-                    Position::BottomRight,
-                    "Bottom-Right",
-                );
-            });
-            ui.add_space(4.0);
+    /// Write unapplied edits to the draft file every few seconds, or remove
+    /// it once there are none. A recovered draft is left alone until the user
+    /// decides what to do with it.
+    fn update_draft(&mut self, ctx: &egui::Context) {
+        if self.recovered.is_some() {
+            return;
+        }
+        let current = self.current_config();
+        if current == self.drafted {
+            return;
+        }
+        if self.drafted_at.elapsed() < DRAFT_INTERVAL {
+            // Come back even if nothing else happens in the window
+            ctx.request_repaint_after(DRAFT_INTERVAL);
+            return;
+        }
+        if current == self.saved_config {
+            Config::discard_draft();
+        } else if let Err(e) = current.save_draft() {
+            crate::logging::error(format!("Failed to save settings draft: {e}"));
+        }
+        self.drafted = current;
+        self.drafted_at = Instant::now();
+    }
 
-            // Format
+    /// Offer the edits a crashed settings window left behind.
+    fn recovery_banner(&mut self, ui: &mut egui::Ui) {
+        if self.recovered.is_none() {
+            return;
+        }
+        ui.group(|ui| {
+            ui.label("Unapplied changes from last time were recovered.")
+                .on_hover_text("前回設定画面が正常に閉じられなかったときの未適用の変更");
             ui.horizontal(|ui| {
-                ui.label("Time Format:");
-                ui.radio_value(&mut self.config.format_24h, true, "24-hour");
-                ui.radio_value(&mut self.config.format_24h, false, "12-hour");
+                if ui.button("Restore").clicked() {
+                    if let Some(draft) = self.recovered.take() {
+                        self.config = draft;
+                        self.enforce_policies();
+                        self.applied = false;
+                    }
+                }
+                if ui.button("Discard").clicked() {
+                    self.recovered = None;
+                    Config::discard_draft();
+                }
             });
-            ui.add_space(4.0);
+        });
+        ui.add_space(8.0);
+    }
 
-            // Seconds
-            ui.checkbox(&mut self.config.show_seconds, "Show seconds");
+    fn search_hit(&self, section: SettingsSection, label: &str, keywords: &str) -> bool {
+        let needle = self.filter.trim().to_lowercase();
+        needle.is_empty()
+            || format!("{section:?}").to_lowercase().contains(&needle)
+            || label.to_lowercase().contains(&needle)
+            || keywords.to_lowercase().contains(&needle)
+    }
 
-            ui.add_space(8.0);
-            ui.separator();
-            ui.add_space(4.0);
+    /// Whether the control with this `SEARCH_INDEX` label matches the filter.
+    fn shown(&self, label: &str) -> bool {
+        SEARCH_INDEX
+            .iter()
+            .find(|(_, l, _, _)| *l == label)
+            .is_none_or(|(section, l, keywords, _)| self.search_hit(*section, l, keywords))
+    }
 
-            // === Appearance Section ===
-            ui.strong("Appearance");
-            ui.add_space(4.0);
+    /// Whether an administrator policy fixes any value the control edits.
+    fn locked(&self, label: &str) -> bool {
+        let policies = crate::policy::current();
+        SEARCH_INDEX
+            .iter()
+            .find(|(_, l, _, _)| *l == label)
+            .is_some_and(|(_, _, _, keys)| keys.iter().any(|key| policies.is_locked(key)))
+    }
 
-            // Font size
-            ui.horizontal(|ui| {
-                ui.label("Font Size:")
-                    .on_hover_text("時計テキストのピクセル高さ");
-                let mut font_size_f = self.config.font_size as f32;
-                ui.add(
-                    egui::Slider::new(&mut font_size_f, 10.0..=60.0)
-                        .text("px")
-                        .integer(),
-                );
-                self.config.font_size = font_size_f as u32;
-            });
-            ui.add_space(4.0);
+    /// Put policy-locked values back after a reset.
+    fn enforce_policies(&mut self) {
+        crate::policy::current().apply(&mut self.config);
+        let (mod_idx, key_idx) = Self::toggle_hotkey_indices(&self.config);
+        self.selected_mod = mod_idx;
+        self.selected_key = key_idx;
+    }
 
-            // Text style
-            ui.horizontal(|ui| {
-                ui.label("Text Style:")
-                    .on_hover_text("None=装飾なし Outline=縁取り Shadow=影");
-                ui.radio_value(&mut self.config.text_style, TextStyle::None, "None");
-                ui.radio_value(&mut self.config.text_style, TextStyle::Outline, "Outline");
-                ui.radio_value(&mut self.config.text_style, TextStyle::Shadow, "Shadow");
-            });
-            ui.add_space(4.0);
+    fn section_shown(&self, section: SettingsSection) -> bool {
+        SEARCH_INDEX
+            .iter()
+            .any(|(s, l, keywords, _)| *s == section && self.search_hit(*s, l, keywords))
+    }
 
-            // Text Color
-            ui.horizontal(|ui| {
-                ui.label("Text Color:");
-                ui.color_edit_button_srgb(&mut self.config.text_color);
-            });
-            ui.add_space(4.0);
-
-            // Outline/Shadow Color (only when text_style != None)
-            if self.config.text_style != TextStyle::None {
-                ui.horizontal(|ui| {
-                    let label = match self.config.text_style {
-                        TextStyle::Outline => "Outline Color:",
-                        TextStyle::Shadow => "Shadow Color:",
-                        TextStyle::None => unreachable!(),
-                    };
-                    ui.label(label);
-                    ui.color_edit_button_srgb(&mut self.config.outline_color);
-                });
-                ui.add_space(4.0);
+    /// Section title with a small button that resets just that section.
+    fn section_header(&mut self, ui: &mut egui::Ui, title: &str, section: SettingsSection) {
+        ui.horizontal(|ui| {
+            let heading = ui.strong(title);
+            if self.scroll_to == Some(section) {
+                heading.scroll_to_me(Some(egui::Align::TOP));
+                self.scroll_to = None;
+            }
+            let reset = a11y_name(
+                ui.small_button("↺"),
+                egui::WidgetType::Button,
+                &format!("Reset {title} section"),
+            );
+            if reset
+                .on_hover_text("このセクションだけ初期値に戻す")
+                .clicked()
+            {
+                self.config.reset_section(section);
+                self.enforce_policies();
             }
+        });
+    }
+}
 
-            // Opacity
-            let mut opacity_f = self.config.opacity as f32;
-            ui.add(
-                egui::Slider::new(&mut opacity_f, 25.0..=100.0)
-                    .text("Opacity %")
-                    .integer(),
-            )
-            .on_hover_text("時計オーバーレイの透明度");
-            self.config.opacity = opacity_f as u8;
-
-            ui.add_space(8.0);
-            ui.separator();
-            ui.add_space(4.0);
-
-            // === System Section ===
-            ui.strong("System");
-            ui.add_space(4.0);
-
-            // Hotkey
-            ui.horizontal(|ui| {
-                ui.label("Hotkey:")
-                    .on_hover_text("時計の表示/非表示を切り替えるキー");
-
-                let current_mod = MODIFIER_OPTIONS[self.selected_mod].0;
-                egui::ComboBox::from_id_salt("modifier")
-                    .selected_text(current_mod)
-                    .show_ui(ui, |ui| {
-                        for (i, (name, _)) in MODIFIER_OPTIONS.iter().enumerate() {
-                            ui.selectable_value(&mut self.selected_mod, i, *name);
-                        }
-                    });
+/// Explain why a control is greyed out.
+fn policy_note(ui: &mut egui::Ui, locked: bool) {
+    if locked {
+        ui.label(
+            egui::RichText::new("🔒 Set by your administrator")
+                .small()
+                .weak(),
+        );
+    }
+}
 
-                ui.label("+");
+/// Give a widget without a visible label a name for screen readers.
+fn a11y_name(response: egui::Response, typ: egui::WidgetType, name: &str) -> egui::Response {
+    response.widget_info(|| egui::WidgetInfo::labeled(typ, true, name));
+    response
+}
 
-                let current_key = KEY_OPTIONS[self.selected_key].0;
-                egui::ComboBox::from_id_salt("key")
-                    .selected_text(current_key)
-                    .show_ui(ui, |ui| {
-                        for (i, (name, _)) in KEY_OPTIONS.iter().enumerate() {
-                            ui.selectable_value(&mut self.selected_key, i, *name);
-                        }
-                    });
-            });
-            ui.add_space(4.0);
+/// Modifier + key pickers for an optional binding; choosing "None" clears it.
+/// `name` is the row label, used for widget IDs and accessible names.
+fn optional_hotkey_picker(ui: &mut egui::Ui, name: &str, binding: &mut String) {
+    let bound = !binding.is_empty();
+    let (mut mod_idx, key_idx) = SettingsApp::find_hotkey_indices(binding);
+    let mut key_idx = bound.then_some(key_idx);
 
-            // Auto start
-            ui.checkbox(&mut self.config.start_with_windows, "Start with Windows");
-            ui.add_space(12.0);
+    let modifier = egui::ComboBox::from_id_salt((name, "modifier"))
+        .selected_text(MODIFIER_OPTIONS[mod_idx].0)
+        .show_ui(ui, |ui| {
+            for (i, (option, _)) in MODIFIER_OPTIONS.iter().enumerate() {
+                ui.selectable_value(&mut mod_idx, i, *option);
+            }
+        });
+    a11y_name(
+        modifier.response,
+        egui::WidgetType::ComboBox,
+        &format!("{name} modifier"),
+    );
 
+    ui.label("+");
+
+    let key = egui::ComboBox::from_id_salt((name, "key"))
+        .selected_text(key_idx.map_or("None", |k| KEY_OPTIONS[k].0))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut key_idx, None, "None");
+            for (i, (option, _)) in KEY_OPTIONS.iter().enumerate() {
+                ui.selectable_value(&mut key_idx, Some(i), *option);
+            }
+        });
+    a11y_name(
+        key.response,
+        egui::WidgetType::ComboBox,
+        &format!("{name} key"),
+    );
+
+    *binding = match key_idx {
+        Some(k) => format!("{}+{}", MODIFIER_OPTIONS[mod_idx].0, KEY_OPTIONS[k].0),
+        None => String::new(),
+    };
+}
+
+impl eframe::App for SettingsApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.update_draft(ctx);
+        // A theme file dropped on the window is loaded
+        let dropped: Vec<std::path::PathBuf> = ctx.input(|i| {
+            i.raw
+                .dropped_files
+                .iter()
+                .filter_map(|f| f.path.clone())
+                .collect()
+        });
+        if let Some(path) = dropped.iter().find(|p| crate::themefile::is_theme_file(p)) {
+            self.load_theme(path);
+        }
+        egui::TopBottomPanel::bottom("actions").show(ctx, |ui| {
+            ui.add_space(6.0);
             // Apply + Reset buttons + status
             ui.horizontal(|ui| {
                 if ui.button("Apply").clicked() {
                     self.config.hotkey = self.build_hotkey_string();
                     if let Err(e) = self.config.save() {
-                        eprintln!("Failed to save config: {e}");
+                        crate::logging::error(format!("Failed to save config: {e}"));
                     }
+                    crate::logging::debug("Settings applied");
                     crate::overlay::update_config(&self.config);
-                    crate::apply_autostart(&self.config);
+                    crate::autostart::apply(&self.config, &self.saved_config);
                     crate::request_hotkey_reregister();
                     self.saved_config = self.config.clone();
                     self.applied = true;
+                    Config::discard_draft();
+                    self.drafted = self.saved_config.clone();
                 }
-                if ui.button("Reset to Defaults").clicked() {
-                    self.config = Config::default();
-                    let (mod_idx, key_idx) = Self::find_hotkey_indices(&self.config.hotkey);
+                let revert =
+                    ui.add_enabled(self.has_unsaved_changes(), egui::Button::new("Revert"));
+                if revert
+                    .on_hover_text("未保存の変更を破棄して最後に保存した設定に戻す")
+                    .clicked()
+                {
+                    self.config = self.saved_config.clone();
+                    let (mod_idx, key_idx) = Self::toggle_hotkey_indices(&self.config);
                     self.selected_mod = mod_idx;
                     self.selected_key = key_idx;
+                }
+                if ui.button("Reset to Defaults").clicked() {
+                    self.config = Config::machine_defaults();
+                    self.enforce_policies();
                     self.applied = false;
                 }
                 if self.applied && !self.has_unsaved_changes() {
                     ui.label("Settings saved!");
                 }
             });
+            ui.add_space(6.0);
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.heading("ClockOR Settings");
+                ui.add_space(4.0);
+                let search = ui.add(
+                    egui::TextEdit::singleline(&mut self.filter)
+                        .hint_text("🔍 Search (e.g. opacity / 透明度)")
+                        .desired_width(f32::INFINITY),
+                );
+                a11y_name(search, egui::WidgetType::TextEdit, "Search settings");
+                self.recovery_banner(ui);
+                if !SettingsSection::ALL
+                    .iter()
+                    .any(|section| self.section_shown(*section))
+                {
+                    ui.label("No matching settings");
+                }
+                ui.add_space(8.0);
+
+                // === Display Section ===
+                if self.section_shown(SettingsSection::Display) {
+                    self.section_header(ui, "Display", SettingsSection::Display);
+                    ui.add_space(4.0);
+
+                    // Position
+                    if self.shown("Position") {
+                        let locked = self.locked("Position");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Position:")
+                                    .on_hover_text("画面のどの角に時計を表示するか");
+                                ui.radio_value(&mut self.config.position, Position::TopLeft, "Top-Left");
+                                ui.radio_value(&mut self.config.position, Position::TopRight, "Top-Right");
+                                ui.radio_value(
+                                    &mut self.config.position,
+                                    Position::BottomLeft,
+                                    "Bottom-Left",
+                                );
+                                ui.radio_value(
+                                    &mut self.config.position,
+                                    Position::BottomRight,
+                                    "Bottom-Right",
+                                );
+                                ui.radio_value(&mut self.config.position, Position::Custom, "Custom")
+                                    .on_hover_text("ロック解除中にドラッグした位置");
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Drag snapping
+                    if self.shown("Snapping") {
+                        let locked = self.locked("Snapping");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut self.config.snap_to_edges, "Snap to edges")
+                                    .on_hover_text("ドラッグ中に画面の端や角へ吸着させる（Shift を押している間は吸着しない）");
+                                ui.add(
+                                    egui::Slider::new(&mut self.config.snap_grid, 0..=200)
+                                        .suffix(" px")
+                                        .text("Grid"),
+                                )
+                                .on_hover_text("ドラッグした位置をこの間隔のグリッドにそろえる（0 = グリッドなし）");
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Per-monitor size
+                    if self.shown("Monitor scale") {
+                        let locked = self.locked("Monitor scale");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.label("Monitor scale:")
+                                .on_hover_text("モニターごとの文字と余白の倍率。テレビでは大きく、手元のモニターでは小さく");
+                            for monitor in crate::monitors::all() {
+                                let mut scale = self.config.scale_for_monitor(&monitor.name);
+                                let label = format!(
+                                    "{} ({}×{}{})",
+                                    crate::monitors::short_name(&monitor.name),
+                                    monitor.rect.2,
+                                    monitor.rect.3,
+                                    if monitor.primary { ", primary" } else { "" }
+                                );
+                                let slider = ui.add(
+                                    egui::Slider::new(&mut scale, 50..=400)
+                                        .suffix("%")
+                                        .text(label.as_str()),
+                                );
+                                if a11y_name(slider, egui::WidgetType::Slider, &label).changed() {
+                                    let existing = self
+                                        .config
+                                        .monitor_scales
+                                        .iter_mut()
+                                        .find(|m| m.monitor.eq_ignore_ascii_case(&monitor.name));
+                                    match existing {
+                                        Some(entry) => entry.scale = scale,
+                                        None => self.config.monitor_scales.push(MonitorScale {
+                                            monitor: monitor.name.clone(),
+                                            scale,
+                                        }),
+                                    }
+                                }
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Per-monitor clocks
+                    if self.shown("Monitor clocks") {
+                        let locked = self.locked("Monitor clocks");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.label("Monitor clocks:")
+                                .on_hover_text("モニターごとの時計（Follow=前面のウィンドウのモニターで表示 Always=いつもそのモニターにも表示 Never=表示しない）");
+                            for monitor in crate::monitors::all() {
+                                let name = crate::monitors::short_name(&monitor.name).to_string();
+                                let before = self
+                                    .config
+                                    .monitor_overlay(&monitor.name)
+                                    .cloned()
+                                    .unwrap_or_else(|| MonitorOverlay {
+                                        monitor: monitor.name.clone(),
+                                        ..Default::default()
+                                    });
+                                let mut entry = before.clone();
+                                ui.horizontal(|ui| {
+                                    ui.label(&name);
+                                    let mode = egui::ComboBox::from_id_salt(("monitor_mode", &monitor.name))
+                                        .selected_text(format!("{:?}", entry.mode))
+                                        .show_ui(ui, |ui| {
+                                            for choice in [MonitorMode::Follow, MonitorMode::Always, MonitorMode::Never] {
+                                                ui.selectable_value(&mut entry.mode, choice, format!("{choice:?}"));
+                                            }
+                                        });
+                                    a11y_name(mode.response, egui::WidgetType::ComboBox, &format!("{name} clock"));
+                                    let position = egui::ComboBox::from_id_salt(("monitor_position", &monitor.name))
+                                        .selected_text(entry.position.map_or("Same position".to_string(), |p| format!("{p:?}")))
+                                        .show_ui(ui, |ui| {
+                                            ui.selectable_value(&mut entry.position, None, "Same position");
+                                            for choice in [
+                                                Position::TopRight,
+                                                Position::TopLeft,
+                                                Position::BottomRight,
+                                                Position::BottomLeft,
+                                                Position::Custom,
+                                            ] {
+                                                ui.selectable_value(&mut entry.position, Some(choice), format!("{choice:?}"));
+                                            }
+                                        });
+                                    a11y_name(position.response, egui::WidgetType::ComboBox, &format!("{name} position"))
+                                        .on_hover_text("このモニターでの表示位置（Custom はロック解除中にドラッグした位置）");
+                                });
+                                if entry != before {
+                                    let existing = self
+                                        .config
+                                        .monitor_overlays
+                                        .iter_mut()
+                                        .find(|m| m.monitor.eq_ignore_ascii_case(&monitor.name));
+                                    match existing {
+                                        Some(own) => *own = entry,
+                                        None => self.config.monitor_overlays.push(entry),
+                                    }
+                                }
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Cursor reaction
+                    if self.shown("On Hover") {
+                        let locked = self.locked("On Hover");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("On Hover:")
+                                    .on_hover_text("マウスカーソルが時計に重なったときの動作（Dim=薄くする Dodge=反対の角へ移動）");
+                                ui.radio_value(&mut self.config.cursor_reaction, CursorReaction::Off, "Nothing");
+                                ui.radio_value(&mut self.config.cursor_reaction, CursorReaction::Dim, "Dim");
+                                ui.radio_value(&mut self.config.cursor_reaction, CursorReaction::Dodge, "Dodge");
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Z-order
+                    if self.shown("Stay on top") {
+                        let locked = self.locked("Stay on top");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Stay on top:")
+                                    .on_hover_text("ゲームの画面に時計が隠れるときに強めにする");
+                                ui.radio_value(&mut self.config.z_order, ZOrder::Topmost, "Topmost")
+                                    .on_hover_text("通常の最前面表示");
+                                ui.radio_value(&mut self.config.z_order, ZOrder::Watchdog, "Watchdog")
+                                    .on_hover_text("1 秒に 4 回確認し、ほかのウィンドウに隠されたら前面に戻す");
+                                ui.radio_value(&mut self.config.z_order, ZOrder::Band, "Band")
+                                    .on_hover_text("アクセシビリティツールと同じ層に表示する（署名済みの UIAccess 版のみ。使えなければ Watchdog と同じ）。ClockOR の再起動後に有効");
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Format
+                    if self.shown("Time Format") {
+                        let locked = self.locked("Time Format");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Time Format:");
+                                ui.radio_value(&mut self.config.format_24h, true, "24-hour");
+                                ui.radio_value(&mut self.config.format_24h, false, "12-hour");
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Seconds
+                    if self.shown("Show seconds") {
+                        let locked = self.locked("Show seconds");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.show_seconds, "Show seconds");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // strftime pattern
+                    if self.shown("Custom format") {
+                        let locked = self.locked("Custom format");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Custom format:")
+                                    .on_hover_text("chrono（strftime）形式の書式。例: %H:%M:%S %a。設定すると上の 24/12 時間と秒の設定より優先される（空欄 = 使わない）");
+                                let field = ui.add(
+                                    egui::TextEdit::singleline(&mut self.config.time_format)
+                                        .desired_width(140.0)
+                                        .hint_text("%H:%M:%S"),
+                                );
+                                a11y_name(field, egui::WidgetType::TextEdit, "Custom time format");
+                            });
+                            let pattern = &self.config.time_format;
+                            if crate::config::valid_time_format(pattern) {
+                                ui.small(format!("Preview: {}", chrono::Local::now().format(pattern)));
+                            } else if !pattern.trim().is_empty() {
+                                ui.small("Invalid format; the settings above are used");
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Year/month progress
+                    if self.shown("Progress") {
+                        let locked = self.locked("Progress");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Progress:")
+                                    .on_hover_text("今年・今月がどれだけ経過したかを時刻の横に表示");
+                                ui.radio_value(&mut self.config.progress, ProgressDisplay::Off, "Off");
+                                ui.radio_value(&mut self.config.progress, ProgressDisplay::Year, "Year");
+                                ui.radio_value(&mut self.config.progress, ProgressDisplay::Month, "Month");
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Date under the clock
+                    if self.shown("Date line") {
+                        let locked = self.locked("Date line");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Date line:")
+                                    .on_hover_text("今日の日付を時計の下の行に表示（Localized = Windows の地域設定の長い日付形式）");
+                                ui.radio_value(&mut self.config.date_line, DateDisplay::Off, "Off");
+                                ui.radio_value(&mut self.config.date_line, DateDisplay::Iso, "YYYY-MM-DD");
+                                ui.radio_value(&mut self.config.date_line, DateDisplay::Localized, "Localized");
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Other time zones
+                    if self.shown("World clocks") {
+                        let locked = self.locked("World clocks");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.label("World clocks:")
+                                .on_hover_text("ほかのタイムゾーンの時刻を時計の下に 1 行ずつ表示する。ラベルが空ならタイムゾーンの略称（JST など）");
+                            let mut remove = None;
+                            for (i, clock) in self.config.world_clocks.iter_mut().enumerate() {
+                                ui.horizontal(|ui| {
+                                    let label = ui.add(
+                                        egui::TextEdit::singleline(&mut clock.label)
+                                            .desired_width(60.0)
+                                            .hint_text("Label"),
+                                    );
+                                    a11y_name(label, egui::WidgetType::TextEdit, "World clock label");
+                                    let zone = ui.add(
+                                        egui::TextEdit::singleline(&mut clock.zone)
+                                            .desired_width(160.0)
+                                            .hint_text("Asia/Tokyo"),
+                                    );
+                                    a11y_name(zone, egui::WidgetType::TextEdit, "World clock time zone");
+                                    let delete = a11y_name(
+                                        ui.small_button("🗑"),
+                                        egui::WidgetType::Button,
+                                        "Remove world clock",
+                                    );
+                                    if delete.clicked() {
+                                        remove = Some(i);
+                                    }
+                                    match clock.tz() {
+                                        Some(tz) => {
+                                            let now = chrono::Utc::now().with_timezone(&tz);
+                                            ui.weak(now.format("%H:%M %Z").to_string());
+                                        }
+                                        None => {
+                                            ui.colored_label(egui::Color32::RED, "!").on_hover_text(
+                                                "不明なタイムゾーン。Asia/Tokyo、America/Los_Angeles、UTC のような名前で入力してください",
+                                            );
+                                        }
+                                    }
+                                });
+                            }
+                            if let Some(i) = remove {
+                                self.config.world_clocks.remove(i);
+                            }
+                            if ui.button("Add World Clock").clicked() {
+                                self.config.world_clocks.push(WorldClock::default());
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Uptime
+                    if self.shown("Show uptime") {
+                        let locked = self.locked("Show uptime");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.show_uptime, "Show uptime")
+                                .on_hover_text("PC の起動からの経過時間を表示（例: up 6h 12m）");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Input display
+                    if self.shown("Show input") {
+                        let locked = self.locked("Show input");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.show_input, "Show input")
+                                .on_hover_text("押したキーとマウスボタンを末尾に表示し、数秒で薄れて消える（配信向け）。パスワードの入力も表示されるので注意");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Active window
+                    if self.shown("Active window") {
+                        let locked = self.locked("Active window");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Active window:")
+                                    .on_hover_text("操作中のプログラム名かウィンドウタイトルを表示（長いものは 32 文字で省略）。配信中に何がフォーカスされているかの確認に");
+                                ui.radio_value(&mut self.config.active_window, ActiveWindowDisplay::Off, "Off");
+                                ui.radio_value(&mut self.config.active_window, ActiveWindowDisplay::Process, "Program");
+                                ui.radio_value(&mut self.config.active_window, ActiveWindowDisplay::Title, "Title");
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Input language
+                    if self.shown("Input language") {
+                        let locked = self.locked("Input language");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.show_input_language, "Show input language")
+                                .on_hover_text("操作中のウィンドウの入力言語を表示（例: EN、JP あ）。日本語・韓国語・中国語では IME のオン/オフも表示");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Script
+                    if self.shown("Script") {
+                        let locked = self.locked("Script");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Script:");
+                                let script = ui.add(
+                                    egui::TextEdit::singleline(&mut self.config.script)
+                                        .desired_width(180.0)
+                                        .hint_text("clock.rhai"),
+                                );
+                                a11y_name(script, egui::WidgetType::TextEdit, "Script")
+                                    .on_hover_text("時計の代わりに Rhai スクリプトの戻り値を表示（毎秒実行）。相対パスは設定フォルダー基準。空欄でオフ");
+                            });
+                            if let Some(error) = crate::script::last_error() {
+                                ui.small(format!("⚠ {error}"));
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Template
+                    if self.shown("Template") {
+                        let locked = self.locked("Template");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Template:");
+                                let template = ui.add(
+                                    egui::TextEdit::singleline(&mut self.config.template)
+                                        .desired_width(220.0)
+                                        .hint_text("{time}  {battery|AC}  {cpu}"),
+                                );
+                                a11y_name(template, egui::WidgetType::TextEdit, "Template")
+                                    .on_hover_text("時計と項目の並びをトークンで指定する。値がないトークンは | の後の文字になるか消える。空欄なら上の設定どおり");
+                            });
+                            egui::CollapsingHeader::new("Tokens")
+                                .id_salt("template_tokens")
+                                .show(ui, |ui| {
+                                    egui::Grid::new("template_token_grid").show(ui, |ui| {
+                                        for (token, meaning) in crate::template::TOKENS {
+                                            ui.monospace(format!("{{{token}}}"));
+                                            ui.label(*meaning);
+                                            ui.end_row();
+                                        }
+                                    });
+                                });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Text file
+                    if self.shown("Text file") {
+                        let locked = self.locked("Text file");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Text file:");
+                                let file = ui.add(
+                                    egui::TextEdit::singleline(&mut self.config.text_file)
+                                        .desired_width(180.0)
+                                        .hint_text("nowplaying.txt"),
+                                );
+                                a11y_name(file, egui::WidgetType::TextEdit, "Text file")
+                                    .on_hover_text("ほかのツールが書き出すファイルの 1 行目を表示し、変更されたら読み直す（曲名スクリプトやマクロツールとの連携用）。相対パスは設定フォルダー基準。空欄でオフ");
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Command
+                    if self.shown("Command") {
+                        let locked = self.locked("Command");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Command:");
+                                let command = ui.add(
+                                    egui::TextEdit::singleline(&mut self.config.command)
+                                        .desired_width(180.0)
+                                        .hint_text("curl -s https://…"),
+                                );
+                                a11y_name(command, egui::WidgetType::TextEdit, "Command")
+                                    .on_hover_text("コマンドを一定間隔でウィンドウなしで実行し、標準出力を表示する（cmd /C で実行、10 秒で打ち切り）。空欄でオフ");
+                            });
+                            ui.add(
+                                egui::Slider::new(&mut self.config.command_interval_seconds, 5..=3600)
+                                    .text("Run every (s)")
+                                    .logarithmic(true),
+                            );
+                            if let Some(error) = crate::command::last_error() {
+                                ui.small(format!("⚠ {error}"));
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    ui.add_space(8.0);
+                    ui.separator();
+                    ui.add_space(4.0);
+                }
+
+                // === Appearance Section ===
+                if self.section_shown(SettingsSection::Appearance) {
+                    self.section_header(ui, "Appearance", SettingsSection::Appearance);
+                    ui.add_space(4.0);
+
+                    // Font size
+                    if self.shown("Font Size") {
+                        let locked = self.locked("Font Size");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                let label = ui
+                                    .label("Font Size:")
+                                    .on_hover_text("時計テキストのピクセル高さ");
+                                let mut font_size_f = self.config.font_size as f32;
+                                ui.add(
+                                    egui::Slider::new(&mut font_size_f, 10.0..=60.0)
+                                        .text("px")
+                                        .integer(),
+                                )
+                                .labelled_by(label.id);
+                                self.config.font_size = font_size_f as u32;
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Font weight
+                    if self.shown("Font Weight") {
+                        let locked = self.locked("Font Weight");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                let label = ui
+                                    .label("Font Weight:")
+                                    .on_hover_text("文字の太さ（400=標準 700=太字）。小さいサイズでは細めにすると読みやすいことがある");
+                                ui.add(
+                                    egui::Slider::new(&mut self.config.font_weight, 100..=900)
+                                        .step_by(100.0),
+                                )
+                                .labelled_by(label.id);
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Font family
+                    if self.shown("Font") {
+                        let locked = self.locked("Font");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Font:").on_hover_text(
+                                    "時計の文字のフォント。Bahnschrift は Windows 10 以降に入っている可変フォントで、字幅（Font Width）も変えられる",
+                                );
+                                ui.radio_value(&mut self.config.font_family, FontFamily::SegoeUi, "Segoe UI");
+                                ui.radio_value(
+                                    &mut self.config.font_family,
+                                    FontFamily::Bahnschrift,
+                                    "Bahnschrift",
+                                );
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Font width, for families that have narrower faces
+                    if self.config.font_family.has_widths() && self.shown("Font Width") {
+                        let locked = self.locked("Font Width");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Font Width:").on_hover_text(
+                                    "字幅。狭くすると同じ大きさでも横に短くなり、画面の端に置いても邪魔になりにくい（DirectWrite 描画時のみ。GDI では標準の幅）",
+                                );
+                                ui.radio_value(&mut self.config.font_width, 3, "Condensed");
+                                ui.radio_value(&mut self.config.font_width, 4, "Semi-condensed");
+                                ui.radio_value(&mut self.config.font_width, 5, "Normal");
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Max width
+                    if self.shown("Max Width") {
+                        let locked = self.locked("Max Width");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                let label = ui.label("Max Width:").on_hover_text(
+                                    "これより長いテキストは「…」で省略する（0=制限なし）",
+                                );
+                                ui.add(
+                                    egui::Slider::new(&mut self.config.max_width, 0..=1920)
+                                        .text("px")
+                                        .step_by(10.0),
+                                )
+                                .labelled_by(label.id);
+                                // Same floor as the config loader so the preview matches
+                                if self.config.max_width > 0 && self.config.max_width < 100 {
+                                    self.config.max_width = 100;
+                                }
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Alignment in the box
+                    if self.shown("Alignment") {
+                        let locked = self.locked("Alignment");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Alignment:")
+                                    .on_hover_text("枠が文字より広いとき（背景パネル・複数行）に文字を寄せる位置");
+                                ui.radio_value(&mut self.config.text_align, TextAlign::Left, "Left");
+                                ui.radio_value(&mut self.config.text_align, TextAlign::Center, "Center");
+                                ui.radio_value(&mut self.config.text_align, TextAlign::Right, "Right");
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Multi-line layout
+                    if self.shown("Multi-line") {
+                        let locked = self.locked("Multi-line");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.multi_line, "Multi-line")
+                                .on_hover_text("時刻・タイマー・各表示項目を 1 行ずつ縦に並べる");
+                            if self.config.multi_line {
+                                ui.add(
+                                    egui::Slider::new(&mut self.config.line_spacing, 0..=40)
+                                        .text("Line spacing (px)"),
+                                )
+                                .on_hover_text("行と行の間の余白");
+                                ui.horizontal(|ui| {
+                                    for (i, name) in ["Line 1", "Line 2", "Line 3+"].into_iter().enumerate() {
+                                        let mut align = self.config.alignment_for_line(i);
+                                        let combo = egui::ComboBox::from_id_salt(("line_align", i))
+                                            .selected_text(format!("{name}: {align:?}"))
+                                            .show_ui(ui, |ui| {
+                                                for choice in [TextAlign::Left, TextAlign::Center, TextAlign::Right] {
+                                                    ui.selectable_value(&mut align, choice, format!("{choice:?}"));
+                                                }
+                                            });
+                                        a11y_name(combo.response, egui::WidgetType::ComboBox, &format!("{name} alignment"))
+                                            .on_hover_text("その行の揃え（3 行目以降は Line 3+ に従う）");
+                                        if align != self.config.alignment_for_line(i) {
+                                            let mut lines: Vec<TextAlign> =
+                                                (0..3).map(|l| self.config.alignment_for_line(l)).collect();
+                                            lines[i] = align;
+                                            self.config.line_alignment = lines;
+                                        }
+                                    }
+                                });
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Vertical text
+                    if self.shown("Vertical") {
+                        let locked = self.locked("Vertical");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.vertical, "Vertical")
+                                .on_hover_text("文字を 90° 回して上から下へ表示する。画面の左右の端に沿わせたいときに");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Text style
+                    if self.shown("Text Style") {
+                        let locked = self.locked("Text Style");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Text Style:")
+                                    .on_hover_text("None=装飾なし Outline=縁取り Shadow=影");
+                                ui.radio_value(&mut self.config.text_style, TextStyle::None, "None");
+                                ui.radio_value(&mut self.config.text_style, TextStyle::Outline, "Outline");
+                                ui.radio_value(&mut self.config.text_style, TextStyle::Shadow, "Shadow");
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Text renderer
+                    if self.shown("Text Rendering") {
+                        let locked = self.locked("Text Rendering");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Text Rendering:").on_hover_text(
+                                    "DirectWrite=なめらかな文字・カラー絵文字・足りない文字の自動補完 GDI=従来の描画。文字がにじむ・欠けるときに",
+                                );
+                                ui.radio_value(
+                                    &mut self.config.text_renderer,
+                                    TextRenderer::DirectWrite,
+                                    "DirectWrite",
+                                );
+                                ui.radio_value(&mut self.config.text_renderer, TextRenderer::Gdi, "GDI");
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Sub-pixel positioning
+                    if self.shown("Sub-pixel Text") {
+                        let locked = self.locked("Sub-pixel Text");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.subpixel_text, "Sub-pixel Text")
+                                .on_hover_text("文字を 1 ピクセル未満の単位で並べ、字間をなめらかにそろえる。オフにするとピクセル単位にそろえ、小さいサイズで輪郭がくっきりする（DirectWrite 描画時のみ）");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Text Color
+                    if self.shown("Text Color") {
+                        let locked = self.locked("Text Color");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                let label = ui.label("Text Color:");
+                                ui.color_edit_button_srgb(&mut self.config.text_color)
+                                    .labelled_by(label.id);
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Outline/Shadow Color (only when text_style != None)
+                    if self.shown("Outline/Shadow Color")
+                        && self.config.text_style != TextStyle::None
+                    {
+                        let locked = self.locked("Outline/Shadow Color");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                let label = match self.config.text_style {
+                                    TextStyle::Outline => "Outline Color:",
+                                    TextStyle::Shadow => "Shadow Color:",
+                                    TextStyle::None => unreachable!(),
+                                };
+                                let label = ui.label(label);
+                                ui.color_edit_button_srgb(&mut self.config.outline_color)
+                                    .labelled_by(label.id);
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Colors by time of day
+                    if self.shown("Color rules") {
+                        let locked = self.locked("Color rules");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.label("Color rules:")
+                                .on_hover_text("時間帯ごとに文字色（と背景パネルの色）を変える。上にあるルールが優先（終了時刻が開始より前なら日付をまたぐ）");
+                            let mut remove = None;
+                            for (i, rule) in self.config.color_rules.iter_mut().enumerate() {
+                                ui.horizontal(|ui| {
+                                    let start = ui.add(
+                                        egui::TextEdit::singleline(&mut rule.start)
+                                            .desired_width(44.0)
+                                            .hint_text("HH:MM"),
+                                    );
+                                    a11y_name(start, egui::WidgetType::TextEdit, "Rule start");
+                                    ui.label("–");
+                                    let end = ui.add(
+                                        egui::TextEdit::singleline(&mut rule.end)
+                                            .desired_width(44.0)
+                                            .hint_text("HH:MM"),
+                                    );
+                                    a11y_name(end, egui::WidgetType::TextEdit, "Rule end");
+                                    let days = egui::ComboBox::from_id_salt(("color_rule_days", i))
+                                        .selected_text(match rule.days {
+                                            ScheduleDays::EveryDay => "Every day",
+                                            ScheduleDays::Weekdays => "Weekdays",
+                                            ScheduleDays::Weekends => "Weekends",
+                                        })
+                                        .show_ui(ui, |ui| {
+                                            ui.selectable_value(&mut rule.days, ScheduleDays::EveryDay, "Every day");
+                                            ui.selectable_value(&mut rule.days, ScheduleDays::Weekdays, "Weekdays");
+                                            ui.selectable_value(&mut rule.days, ScheduleDays::Weekends, "Weekends");
+                                        });
+                                    a11y_name(days.response, egui::WidgetType::ComboBox, "Rule days");
+                                    let text = ui.color_edit_button_srgb(&mut rule.text_color);
+                                    a11y_name(text, egui::WidgetType::ColorButton, "Rule text color")
+                                        .on_hover_text("文字色");
+                                    let mut panel = rule.background_color.is_some();
+                                    if ui.checkbox(&mut panel, "Panel").on_hover_text("背景パネルの色も変える").changed() {
+                                        rule.background_color = panel.then_some([0, 0, 0]);
+                                    }
+                                    if let Some(color) = rule.background_color.as_mut() {
+                                        let bg = ui.color_edit_button_srgb(color);
+                                        a11y_name(bg, egui::WidgetType::ColorButton, "Rule panel color");
+                                    }
+                                    let delete = a11y_name(
+                                        ui.small_button("🗑"),
+                                        egui::WidgetType::Button,
+                                        "Remove color rule",
+                                    );
+                                    if delete.clicked() {
+                                        remove = Some(i);
+                                    }
+                                    if rule.range().parsed().is_none() {
+                                        ui.colored_label(egui::Color32::RED, "!")
+                                            .on_hover_text("時刻は HH:MM（24時間）形式で入力してください");
+                                    }
+                                });
+                            }
+                            if let Some(i) = remove {
+                                self.config.color_rules.remove(i);
+                            }
+                            if ui.button("Add Color Rule").clicked() {
+                                self.config.color_rules.push(ColorRule::default());
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Warning chip colors
+                    if self.shown("Alert colors") {
+                        let locked = self.locked("Alert colors");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Alert colors:").on_hover_text(
+                                    "リマインダー・バッテリー・カウントダウンなどの警告の色（予告→注意→警告）。Colorblind-safe は色覚の違いがあっても見分けやすい配色",
+                                );
+                                ui.radio_value(
+                                    &mut self.config.alert_palette,
+                                    AlertPalette::Standard,
+                                    "Standard",
+                                );
+                                ui.radio_value(
+                                    &mut self.config.alert_palette,
+                                    AlertPalette::ColorblindSafe,
+                                    "Colorblind-safe",
+                                );
+                                ui.radio_value(
+                                    &mut self.config.alert_palette,
+                                    AlertPalette::Custom,
+                                    "Custom",
+                                );
+                            });
+                            if self.config.alert_palette == AlertPalette::Custom {
+                                ui.horizontal(|ui| {
+                                    for (color, name) in self
+                                        .config
+                                        .alert_colors
+                                        .iter_mut()
+                                        .zip(["Notice", "Warning", "Alert"])
+                                    {
+                                        let label = ui.label(format!("{name}:"));
+                                        ui.color_edit_button_srgb(color).labelled_by(label.id);
+                                    }
+                                });
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Text opacity
+                    if self.shown("Text Opacity") {
+                        let locked = self.locked("Text Opacity");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            let mut opacity_f = self.config.opacity as f32;
+                            ui.add(
+                                egui::Slider::new(&mut opacity_f, 25.0..=100.0)
+                                    .text("Text Opacity %")
+                                    .integer(),
+                            )
+                            .on_hover_text("時計の文字の不透明度");
+                            self.config.opacity = opacity_f as u8;
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Background panel
+                    if self.shown("Background Opacity") {
+                        let locked = self.locked("Background Opacity");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.add(
+                                egui::Slider::new(&mut self.config.background_opacity, 0..=100)
+                                    .text("Background Opacity %"),
+                            )
+                            .on_hover_text("文字の背後に敷くパネルの不透明度（0=パネルなし）");
+                            if self.config.background_opacity > 0 {
+                                ui.horizontal(|ui| {
+                                    let label = ui.label("Background Color:");
+                                    ui.color_edit_button_srgb(&mut self.config.background_color)
+                                        .labelled_by(label.id);
+                                });
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Background image
+                    if self.shown("Background image") {
+                        let locked = self.locked("Background image");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Background image:");
+                                let image = ui.add(
+                                    egui::TextEdit::singleline(&mut self.config.background_image)
+                                        .desired_width(180.0)
+                                        .hint_text("logo.png"),
+                                );
+                                a11y_name(image, egui::WidgetType::TextEdit, "Background image")
+                                    .on_hover_text("文字の後ろに PNG（透過可）を表示枠いっぱいに引き伸ばして描く（チームロゴや下地など）。相対パスは設定フォルダー基準。空欄でなし");
+                            });
+                            if !self.config.background_image.trim().is_empty() {
+                                if let Some(error) = crate::bgimage::last_error() {
+                                    ui.small(format!("⚠ {error}"));
+                                }
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Backdrop
+                    if self.shown("Backdrop") {
+                        let locked = self.locked("Backdrop");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Backdrop:")
+                                    .on_hover_text("Flat=単色の背景 Blur=後ろをぼかしたすりガラス Acrylic=Windows のアクリル素材。Background Color と Background Opacity で色味を付ける。使えない環境では単色になる");
+                                ui.radio_value(&mut self.config.backdrop, Backdrop::Flat, "Flat");
+                                ui.radio_value(&mut self.config.backdrop, Backdrop::Blur, "Blur");
+                                ui.radio_value(&mut self.config.backdrop, Backdrop::Acrylic, "Acrylic");
+                            });
+                            if self.config.backdrop != Backdrop::Flat
+                                && !crate::theme::transparency_enabled()
+                            {
+                                ui.small("Transparency effects are off in Windows, so a flat panel is drawn.");
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Windows 11 corners
+                    if self.shown("Corners") {
+                        let locked = self.locked("Corners");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Corners:")
+                                    .on_hover_text("Windows 11 での表示枠の角の形（System=Windows に任せる）。Windows 10 以前では変わらない");
+                                ui.radio_value(&mut self.config.corner_style, CornerStyle::System, "System");
+                                ui.radio_value(&mut self.config.corner_style, CornerStyle::Square, "Square");
+                                ui.radio_value(&mut self.config.corner_style, CornerStyle::Round, "Round");
+                                ui.radio_value(&mut self.config.corner_style, CornerStyle::RoundSmall, "Small");
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Windows 11 border
+                    if self.shown("Border") {
+                        let locked = self.locked("Border");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut self.config.window_border, "Border")
+                                    .on_hover_text("Windows 11 で表示枠の周りに細い線を引く。Windows 10 以前では表示されない");
+                                if self.config.window_border {
+                                    let color = ui.color_edit_button_srgb(&mut self.config.border_color);
+                                    a11y_name(color, egui::WidgetType::ColorButton, "Border color");
+                                }
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Theme files
+                    if self.shown("Theme file") {
+                        let mut load = None;
+                        ui.horizontal(|ui| {
+                            let themes = crate::themefile::installed();
+                            let combo = egui::ComboBox::from_id_salt("load_theme")
+                                .selected_text("Load Theme…")
+                                .show_ui(ui, |ui| {
+                                    if themes.is_empty() {
+                                        ui.label("No themes installed");
+                                    }
+                                    for path in &themes {
+                                        if ui
+                                            .selectable_label(false, crate::themefile::display_name(path))
+                                            .clicked()
+                                        {
+                                            load = Some(path.clone());
+                                        }
+                                    }
+                                });
+                            a11y_name(combo.response, egui::WidgetType::ComboBox, "Load theme")
+                                .on_hover_text("themes フォルダーの .clockor-theme.toml から見た目（色・フォント・スタイル・背景・位置）だけを読み込む。ファイルをこのウィンドウにドロップしても読み込める");
+                            if ui.button("Open Themes Folder").clicked() {
+                                let folder = crate::themefile::folder();
+                                let _ = std::fs::create_dir_all(&folder);
+                                let _ = std::process::Command::new("explorer").arg(&folder).spawn();
+                            }
+                        });
+                        if let Some(path) = load {
+                            self.load_theme(&path);
+                        }
+                        ui.horizontal(|ui| {
+                            let name = ui.add(
+                                egui::TextEdit::singleline(&mut self.theme_name)
+                                    .desired_width(140.0)
+                                    .hint_text("Theme name"),
+                            );
+                            a11y_name(name, egui::WidgetType::TextEdit, "Theme name");
+                            if ui
+                                .button("Export Theme")
+                                .on_hover_text("今の見た目を themes フォルダーに .clockor-theme.toml として保存する（ホットキーやパスは含まない）")
+                                .clicked()
+                            {
+                                self.theme_result = Some(
+                                    match crate::themefile::export(&self.config, &self.theme_name) {
+                                        Ok(path) => {
+                                            let _ = std::process::Command::new("explorer")
+                                                .arg(format!("/select,{}", path.display()))
+                                                .spawn();
+                                            format!("Saved {}", path.display())
+                                        }
+                                        Err(e) => format!("Could not save: {e}"),
+                                    },
+                                );
+                            }
+                        });
+                        if let Some(result) = &self.theme_result {
+                            ui.small(result);
+                        }
+                        ui.add_space(4.0);
+                    }
+
+                    // Preset codes
+                    if self.shown("Preset code") {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .button("Copy Preset Code")
+                                .on_hover_text("今の見た目を 1 行のコードとしてクリップボードにコピーする。Discord などに貼って共有できる（ホットキーやパスは含まない）")
+                                .clicked()
+                            {
+                                let theme = crate::themefile::Theme::from_config(&self.config, &self.theme_name);
+                                ui.output_mut(|o| o.copied_text = theme.to_code());
+                                self.theme_result = Some("Preset code copied".to_string());
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            let code = ui.add(
+                                egui::TextEdit::singleline(&mut self.preset_code)
+                                    .desired_width(200.0)
+                                    .hint_text("Paste a preset code"),
+                            );
+                            a11y_name(code, egui::WidgetType::TextEdit, "Preset code");
+                            let load = ui.add_enabled(
+                                !self.preset_code.trim().is_empty(),
+                                egui::Button::new("Paste Preset Code"),
+                            );
+                            if load
+                                .on_hover_text("貼り付けたコードの見た目を読み込む（Apply で確定）")
+                                .clicked()
+                            {
+                                self.load_preset_code();
+                            }
+                        });
+                        ui.add_space(4.0);
+                    }
+
+                    ui.add_space(8.0);
+                    ui.separator();
+                    ui.add_space(4.0);
+                }
+
+                // === Reminders Section ===
+                if self.section_shown(SettingsSection::Reminders) {
+                    self.section_header(ui, "Reminders", SettingsSection::Reminders);
+                    ui.add_space(4.0);
+
+                    // Reminder list
+                    if self.shown("Reminders") {
+                        let locked = self.locked("Reminders");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            let mut remove = None;
+                            for (i, event) in self.config.daily_events.iter_mut().enumerate() {
+                                ui.horizontal(|ui| {
+                                    let time = ui.add(
+                                        egui::TextEdit::singleline(&mut event.time)
+                                            .desired_width(44.0)
+                                            .hint_text("HH:MM"),
+                                    );
+                                    a11y_name(time, egui::WidgetType::TextEdit, "Reminder time");
+                                    let name = ui.add(
+                                        egui::TextEdit::singleline(&mut event.name).desired_width(150.0),
+                                    );
+                                    a11y_name(name, egui::WidgetType::TextEdit, "Reminder name");
+                                    ui.checkbox(&mut event.sound, "Sound");
+                                    ui.checkbox(&mut event.alarm, "Alarm").on_hover_text(
+                                        "Windows の通知を出し、オーバーレイを点滅させる（非表示中でも）",
+                                    );
+                                    let delete = a11y_name(
+                                        ui.small_button("🗑"),
+                                        egui::WidgetType::Button,
+                                        "Remove reminder",
+                                    );
+                                    if delete.clicked() {
+                                        remove = Some(i);
+                                    }
+                                    if event.parsed_time().is_none() {
+                                        ui.colored_label(egui::Color32::RED, "!")
+                                            .on_hover_text("時刻は HH:MM（24時間）形式で入力してください");
+                                    }
+                                });
+                            }
+                            if let Some(i) = remove {
+                                self.config.daily_events.remove(i);
+                            }
+                            if ui.button("Add Reminder").clicked() {
+                                self.config.daily_events.push(DailyEvent::default());
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Banner duration
+                    if self.shown("Banner seconds") {
+                        let locked = self.locked("Banner seconds");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            let mut banner_f = self.config.banner_seconds as f32;
+                            ui.add(
+                                egui::Slider::new(&mut banner_f, 3.0..=60.0)
+                                    .text("Banner seconds")
+                                    .integer(),
+                            )
+                            .on_hover_text("リマインダーのバナーを表示する秒数");
+                            self.config.banner_seconds = banner_f as u32;
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Snooze delay
+                    if self.shown("Snooze") {
+                        let locked = self.locked("Snooze");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.add(
+                                egui::Slider::new(&mut self.config.snooze_minutes, 1..=60)
+                                    .text("Snooze (min)"),
+                            )
+                            .on_hover_text("スヌーズしたリマインダーやカウントダウンを再通知するまでの分数");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Countdown before a reminder
+                    if self.shown("Warn before") {
+                        let locked = self.locked("Warn before");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.add(
+                                egui::Slider::new(&mut self.config.reminder_warning_minutes, 0..=120)
+                                    .text("Warn before (min)"),
+                            )
+                            .on_hover_text("次のリマインダーのこの分数前から、時計の横に残り時間を表示する。近づくにつれ黄→橙→赤に変わる（0=無効）");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Countdown timer
+                    if self.shown("Countdown") {
+                        let locked = self.locked("Countdown");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.add(
+                                egui::Slider::new(&mut self.config.countdown_minutes, 1..=1440)
+                                    .logarithmic(true)
+                                    .text("Countdown (min)"),
+                            )
+                            .on_hover_text("トレイメニューの Start Countdown かホットキーで始めるカウントダウンの長さ。0 になると時計を表示して点滅する");
+                            ui.horizontal(|ui| {
+                                let label = ui.label("Until:");
+                                ui.add(
+                                        egui::TextEdit::singleline(&mut self.config.countdown_until)
+                                            .desired_width(44.0)
+                                            .hint_text("HH:MM"),
+                                    )
+                                    .labelled_by(label.id)
+                                    .on_hover_text("長さの代わりにこの時刻まで数える（過ぎていれば翌日のその時刻。空=長さを使う）");
+                                let until_text = self.config.countdown_until.trim();
+                                if !until_text.is_empty() && parse_hhmm(until_text).is_none() {
+                                    ui.colored_label(egui::Color32::RED, "!")
+                                        .on_hover_text("時刻は HH:MM（24時間）形式で入力してください");
+                                }
+                            });
+                            ui.checkbox(&mut self.config.countdown_replaces_clock, "Instead of the clock")
+                                .on_hover_text("カウントダウン中は時計の代わりに残り時間だけを表示する");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Silent alert band
+                    if self.shown("Edge flash") {
+                        let locked = self.locked("Edge flash");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                let label = ui.label("Edge flash:").on_hover_text(
+                                    "リマインダーの時刻とカウントダウン終了時に画面の端を色の帯で点滅させる（音が聞こえなくても気付けるように）",
+                                );
+                                let combo = egui::ComboBox::from_id_salt(label.id)
+                                    .selected_text(format!("{:?}", self.config.edge_flash))
+                                    .show_ui(ui, |ui| {
+                                        for edge in [
+                                            ScreenEdge::Off,
+                                            ScreenEdge::Top,
+                                            ScreenEdge::Bottom,
+                                            ScreenEdge::Left,
+                                            ScreenEdge::Right,
+                                        ] {
+                                            ui.selectable_value(
+                                                &mut self.config.edge_flash,
+                                                edge,
+                                                format!("{edge:?}"),
+                                            );
+                                        }
+                                    });
+                                combo.response.labelled_by(label.id);
+                                if self.config.edge_flash != ScreenEdge::Off {
+                                    let color = a11y_name(
+                                        ui.color_edit_button_srgb(&mut self.config.edge_flash_color),
+                                        egui::WidgetType::ColorButton,
+                                        "Edge flash color",
+                                    );
+                                    color.on_hover_text("帯の色");
+                                }
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    if self.shown("Controller rumble") {
+                        let locked = self.locked("Controller rumble");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.controller_rumble, "Controller rumble")
+                                .on_hover_text("リマインダーの時刻とカウントダウン終了時に、接続中の Xbox 互換コントローラーを短く2回振動させる");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    ui.add_space(8.0);
+                    ui.separator();
+                    ui.add_space(4.0);
+                }
+
+                // === Sound Section ===
+                if self.section_shown(SettingsSection::Sound) {
+                    self.section_header(ui, "Sound", SettingsSection::Sound);
+                    ui.add_space(4.0);
+
+                    let cues = [
+                        ("Tick every minute", Cue::Tick),
+                        ("Chime on the hour", Cue::Chime),
+                    ];
+                    for (label, cue) in cues {
+                        if !self.shown(label) {
+                            continue;
+                        }
+                        let locked = self.locked(label);
+                        let (enabled, volume, hint) = match cue {
+                            Cue::Tick => (
+                                &mut self.config.tick_sound,
+                                &mut self.config.tick_volume,
+                                "毎分0秒に小さなチック音を鳴らす",
+                            ),
+                            Cue::Chime => (
+                                &mut self.config.hourly_chime,
+                                &mut self.config.chime_volume,
+                                "毎正時にチャイムを鳴らす",
+                            ),
+                        };
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(enabled, label).on_hover_text(hint);
+                            if *enabled {
+                                ui.horizontal(|ui| {
+                                    ui.add(egui::Slider::new(volume, 0..=100).text("Volume"))
+                                        .on_hover_text("音量（他の音とは別に設定）");
+                                    let preview = a11y_name(
+                                        ui.small_button("▶"),
+                                        egui::WidgetType::Button,
+                                        &format!("Preview {label}"),
+                                    );
+                                    if preview.on_hover_text("試聴").clicked() {
+                                        crate::sound::play_cue(cue, *volume, &self.config.audio_device);
+                                    }
+                                });
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    if self.shown("Mute in fullscreen") {
+                        let locked = self.locked("Mute in fullscreen");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.mute_in_fullscreen, "Mute in fullscreen")
+                                .on_hover_text("全画面のゲームやアプリが前面にある間は、チック音・時報・リマインダーの通知音を鳴らさない");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    if self.shown("Output device") {
+                        let locked = self.locked("Output device");
+                        let devices = self
+                            .audio_devices
+                            .get_or_insert_with(crate::sound::output_devices);
+                        let current = if self.config.audio_device.is_empty() {
+                            "Windows default".to_string()
+                        } else {
+                            devices
+                                .iter()
+                                .find(|d| d.id == self.config.audio_device)
+                                .map_or("Not connected".to_string(), |d| d.name.clone())
+                        };
+                        let mut refresh = false;
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                let label = ui.label("Output device:");
+                                egui::ComboBox::from_id_salt(label.id)
+                                    .selected_text(current)
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(
+                                            &mut self.config.audio_device,
+                                            String::new(),
+                                            "Windows default",
+                                        );
+                                        for device in devices.iter() {
+                                            ui.selectable_value(
+                                                &mut self.config.audio_device,
+                                                device.id.clone(),
+                                                &device.name,
+                                            );
+                                        }
+                                    })
+                                    .response
+                                    .labelled_by(label.id)
+                                    .on_hover_text("ClockOR の音を鳴らす出力デバイス（未接続のときは Windows の既定のデバイス）");
+                                let rescan = a11y_name(
+                                    ui.small_button("⟳"),
+                                    egui::WidgetType::Button,
+                                    "Refresh output devices",
+                                );
+                                refresh = rescan.on_hover_text("デバイス一覧を更新").clicked();
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                        if refresh {
+                            self.audio_devices = None;
+                        }
+                    }
+
+                    ui.add_space(8.0);
+                    ui.separator();
+                    ui.add_space(4.0);
+                }
+
+                // === Automation Section ===
+                if self.section_shown(SettingsSection::Automation) {
+                    self.section_header(ui, "Automation", SettingsSection::Automation);
+                    ui.add_space(4.0);
+
+                    // Schedule
+                    if self.shown("Schedule") {
+                        let locked = self.locked("Schedule");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.label("Schedule:")
+                                .on_hover_text("指定した時間帯になると自動で時計を表示（終了時刻が開始より前なら日付をまたぐ）");
+                            let mut remove = None;
+                            for (i, range) in self.config.visibility_schedule.iter_mut().enumerate() {
+                                ui.horizontal(|ui| {
+                                    let start = ui.add(
+                                        egui::TextEdit::singleline(&mut range.start)
+                                            .desired_width(44.0)
+                                            .hint_text("HH:MM"),
+                                    );
+                                    a11y_name(start, egui::WidgetType::TextEdit, "Range start");
+                                    ui.label("–");
+                                    let end = ui.add(
+                                        egui::TextEdit::singleline(&mut range.end)
+                                            .desired_width(44.0)
+                                            .hint_text("HH:MM"),
+                                    );
+                                    a11y_name(end, egui::WidgetType::TextEdit, "Range end");
+                                    let days = egui::ComboBox::from_id_salt(("schedule_days", i))
+                                        .selected_text(match range.days {
+                                            ScheduleDays::EveryDay => "Every day",
+                                            ScheduleDays::Weekdays => "Weekdays",
+                                            ScheduleDays::Weekends => "Weekends",
+                                        })
+                                        .show_ui(ui, |ui| {
+                                            ui.selectable_value(
+                                                &mut range.days,
+                                                ScheduleDays::EveryDay,
+                                                "Every day",
+                                            );
+                                            ui.selectable_value(
+                                                &mut range.days,
+                                                ScheduleDays::Weekdays,
+                                                "Weekdays",
+                                            );
+                                            ui.selectable_value(
+                                                &mut range.days,
+                                                ScheduleDays::Weekends,
+                                                "Weekends",
+                                            );
+                                        });
+                                    a11y_name(days.response, egui::WidgetType::ComboBox, "Range days");
+                                    let delete = a11y_name(
+                                        ui.small_button("🗑"),
+                                        egui::WidgetType::Button,
+                                        "Remove time range",
+                                    );
+                                    if delete.clicked() {
+                                        remove = Some(i);
+                                    }
+                                    if range.parsed().is_none() {
+                                        ui.colored_label(egui::Color32::RED, "!")
+                                            .on_hover_text("時刻は HH:MM（24時間）形式で入力してください");
+                                    }
+                                });
+                            }
+                            if let Some(i) = remove {
+                                self.config.visibility_schedule.remove(i);
+                            }
+                            if ui.button("Add Time Range").clicked() {
+                                self.config
+                                    .visibility_schedule
+                                    .push(ScheduleRange::default());
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Scheduled actions
+                    if self.shown("Scheduled actions") {
+                        let locked = self.locked("Scheduled actions");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.label("Scheduled actions:")
+                                .on_hover_text("指定した時刻に表示・非表示、配信タイマーの開始・停止、テーマの切り替え、フック（URL への POST かコマンド）を実行");
+                            let mut remove = None;
+                            for (i, action) in self.config.scheduled_actions.iter_mut().enumerate() {
+                                ui.horizontal(|ui| {
+                                    let time = ui.add(
+                                        egui::TextEdit::singleline(&mut action.time)
+                                            .desired_width(44.0)
+                                            .hint_text("HH:MM"),
+                                    );
+                                    a11y_name(time, egui::WidgetType::TextEdit, "Action time");
+                                    let days_label = |days| match days {
+                                        ScheduleDays::EveryDay => "Every day",
+                                        ScheduleDays::Weekdays => "Weekdays",
+                                        ScheduleDays::Weekends => "Weekends",
+                                    };
+                                    let days = egui::ComboBox::from_id_salt(("action_days", i))
+                                        .selected_text(days_label(action.days))
+                                        .show_ui(ui, |ui| {
+                                            for choice in [
+                                                ScheduleDays::EveryDay,
+                                                ScheduleDays::Weekdays,
+                                                ScheduleDays::Weekends,
+                                            ] {
+                                                ui.selectable_value(&mut action.days, choice, days_label(choice));
+                                            }
+                                        });
+                                    a11y_name(days.response, egui::WidgetType::ComboBox, "Action days");
+                                    let kind = egui::ComboBox::from_id_salt(("action_kind", i))
+                                        .selected_text(action.action.label())
+                                        .show_ui(ui, |ui| {
+                                            for choice in ActionKind::ALL {
+                                                ui.selectable_value(&mut action.action, choice, choice.label());
+                                            }
+                                        });
+                                    a11y_name(kind.response, egui::WidgetType::ComboBox, "Action");
+                                    if action.action.has_target() {
+                                        let hint = if action.action == ActionKind::ApplyTheme {
+                                            "Theme name"
+                                        } else {
+                                            "https://… or command"
+                                        };
+                                        let target = ui.add(
+                                            egui::TextEdit::singleline(&mut action.target)
+                                                .desired_width(160.0)
+                                                .hint_text(hint),
+                                        );
+                                        a11y_name(target, egui::WidgetType::TextEdit, "Action target");
+                                    }
+                                    let delete = a11y_name(
+                                        ui.small_button("🗑"),
+                                        egui::WidgetType::Button,
+                                        "Remove action",
+                                    );
+                                    if delete.clicked() {
+                                        remove = Some(i);
+                                    }
+                                    if action.parsed_time().is_none() {
+                                        ui.colored_label(egui::Color32::RED, "!")
+                                            .on_hover_text("時刻は HH:MM（24時間）形式で入力してください");
+                                    }
+                                });
+                            }
+                            if let Some(i) = remove {
+                                self.config.scheduled_actions.remove(i);
+                            }
+                            if ui.button("Add Action").clicked() {
+                                self.config
+                                    .scheduled_actions
+                                    .push(ScheduledAction::default());
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Idle hide
+                    if self.shown("Hide when idle") {
+                        let locked = self.locked("Hide when idle");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            let mut idle_f = self.config.idle_hide_minutes as f32;
+                            ui.add(
+                                egui::Slider::new(&mut idle_f, 0.0..=120.0)
+                                    .text("Hide when idle (min)")
+                                    .integer(),
+                            )
+                            .on_hover_text("指定した分数だけ操作がないと時計を隠し、操作すると再表示（0=無効）");
+                            self.config.idle_hide_minutes = idle_f as u32;
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Periodic peek
+                    if self.shown("Peek every") {
+                        let locked = self.locked("Peek every");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            let mut peek_interval_f = self.config.peek_interval_minutes as f32;
+                            ui.add(
+                                egui::Slider::new(&mut peek_interval_f, 0.0..=120.0)
+                                    .text("Peek every (min)")
+                                    .integer(),
+                            )
+                            .on_hover_text("指定した間隔（60=毎正時）で時計を数秒だけ表示（0=無効）");
+                            self.config.peek_interval_minutes = peek_interval_f as u32;
+                            if self.config.peek_interval_minutes > 0 {
+                                let mut peek_seconds_f = self.config.peek_seconds as f32;
+                                ui.add(
+                                    egui::Slider::new(&mut peek_seconds_f, 3.0..=60.0)
+                                        .text("Peek seconds")
+                                        .integer(),
+                                );
+                                self.config.peek_seconds = peek_seconds_f as u32;
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Reveal after a long fullscreen session
+                    if self.shown("Play reminder") {
+                        let locked = self.locked("Play reminder");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            let mut minutes_f = self.config.play_reveal_minutes as f32;
+                            ui.add(
+                                egui::Slider::new(&mut minutes_f, 0.0..=600.0)
+                                    .text("Show after playing (min)")
+                                    .integer(),
+                            )
+                            .on_hover_text("全画面のゲームをこの分数続けて遊んだら時計を表示する。ホットキーで消せる（0=無効）");
+                            self.config.play_reveal_minutes = minutes_f as u32;
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Low battery alert
+                    if self.shown("Low battery alert") {
+                        let locked = self.locked("Low battery alert");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            let mut percent_f = self.config.low_battery_percent as f32;
+                            ui.add(
+                                egui::Slider::new(&mut percent_f, 0.0..=50.0)
+                                    .text("Low battery alert (%)")
+                                    .integer(),
+                            )
+                            .on_hover_text("電池駆動中に残量がこの値以下になったら時計を表示し、赤く点滅する電池残量を添える（0=無効）");
+                            self.config.low_battery_percent = percent_f as u8;
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    if self.shown("Shutdown warning") {
+                        let locked = self.locked("Shutdown warning");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.shutdown_warning, "Shutdown warning")
+                                .on_hover_text("shutdown /t や更新プログラムでシャットダウン・再起動が予約されたら時計を表示し、赤く点滅する「⚠ Shutdown requested」を添える");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    if self.shown("Shutdown timer") {
+                        let locked = self.locked("Shutdown timer");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            let mut minutes_f = self.config.shutdown_timer_minutes as f32;
+                            ui.add(
+                                egui::Slider::new(&mut minutes_f, 0.0..=240.0)
+                                    .text("Shutdown timer (min)")
+                                    .integer(),
+                            )
+                            .on_hover_text("予約からシャットダウンまでの時間（会社の更新ポリシーの shutdown /t など）。Windows からは残り時間を読めないため、分かっていれば指定すると「⚠ Shutdown in 9:59」と残り時間を表示する（0=予約からの経過時間を表示）");
+                            self.config.shutdown_timer_minutes = minutes_f as u32;
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    if self.shown("Hide under Game Bar") {
+                        let locked = self.locked("Hide under Game Bar");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.hide_under_game_bar, "Hide under Game Bar")
+                                .on_hover_text("Xbox Game Bar（Win+G）を開いている間は時計を隠し、ウィジェットと重ならないようにする");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    if self.shown("Quiet while presenting") {
+                        let locked = self.locked("Quiet while presenting");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.quiet_when_presenting, "Quiet while presenting")
+                                .on_hover_text("Windows のプレゼンテーション設定がオンの間は、スケジュール・定期表示・リマインダーのバナーと通知音を出さない");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    ui.add_space(8.0);
+                    ui.separator();
+                    ui.add_space(4.0);
+                }
+
+                // === Tray Section ===
+                if self.section_shown(SettingsSection::Tray) {
+                    self.section_header(ui, "Tray", SettingsSection::Tray);
+                    ui.add_space(4.0);
+
+                    let labels = ["Left click", "Double click", "Middle click"];
+                    let shown = labels.map(|l| self.shown(l));
+                    let locked = labels.map(|l| self.locked(l));
+                    let actions = [
+                        &mut self.config.tray_left_click,
+                        &mut self.config.tray_double_click,
+                        &mut self.config.tray_middle_click,
+                    ];
+                    for (i, action) in actions.into_iter().enumerate() {
+                        if !shown[i] {
+                            continue;
+                        }
+                        ui.add_enabled_ui(!locked[i], |ui| {
+                            ui.horizontal(|ui| {
+                                let label = ui.label(format!("{}:", labels[i]));
+                                let combo = egui::ComboBox::from_id_salt(label.id)
+                                    .selected_text(action.label())
+                                    .show_ui(ui, |ui| {
+                                        for choice in TrayAction::ALL {
+                                            ui.selectable_value(action, choice, choice.label());
+                                        }
+                                    });
+                                combo.response.labelled_by(label.id);
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked[i]);
+                    }
+
+                    if self.shown("Confirm quit") {
+                        let locked = self.locked("Confirm quit");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.confirm_quit, "Confirm quit")
+                                .on_hover_text("トレイメニューの Quit で終了前に確認する（ゲーム中の誤操作防止）");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    ui.add_space(8.0);
+                    ui.separator();
+                    ui.add_space(4.0);
+                }
+
+                // === System Section ===
+                if self.section_shown(SettingsSection::System) {
+                    self.section_header(ui, "System", SettingsSection::System);
+                    ui.add_space(4.0);
+
+                    // Hotkey
+                    if self.shown("Hotkey") {
+                        let locked = self.locked("Hotkey");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Hotkey:")
+                                    .on_hover_text("時計の表示/非表示を切り替えるキー");
+
+                                let current_mod = MODIFIER_OPTIONS[self.selected_mod].0;
+                                let modifier = egui::ComboBox::from_id_salt("modifier")
+                                    .selected_text(current_mod)
+                                    .show_ui(ui, |ui| {
+                                        for (i, (name, _)) in MODIFIER_OPTIONS.iter().enumerate() {
+                                            ui.selectable_value(&mut self.selected_mod, i, *name);
+                                        }
+                                    });
+                                a11y_name(
+                                    modifier.response,
+                                    egui::WidgetType::ComboBox,
+                                    "Hotkey modifier",
+                                );
+
+                                ui.label("+");
+
+                                let current_key = KEY_OPTIONS[self.selected_key].0;
+                                let key = egui::ComboBox::from_id_salt("key")
+                                    .selected_text(current_key)
+                                    .show_ui(ui, |ui| {
+                                        for (i, (name, _)) in KEY_OPTIONS.iter().enumerate() {
+                                            ui.selectable_value(&mut self.selected_key, i, *name);
+                                        }
+                                    });
+                                a11y_name(key.response, egui::WidgetType::ComboBox, "Hotkey key");
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Double-tap gesture
+                    if self.shown("Double-tap") {
+                        let locked = self.locked("Double-tap");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                let label = ui.label("Double-tap:")
+                                    .on_hover_text("修飾キーを素早く2回押して表示/非表示を切り替える（300ms 以内）");
+                                let combo = egui::ComboBox::from_id_salt("double_tap")
+                                    .selected_text(format!("{:?}", self.config.double_tap))
+                                    .show_ui(ui, |ui| {
+                                        for key in [
+                                            DoubleTapKey::Off,
+                                            DoubleTapKey::Ctrl,
+                                            DoubleTapKey::Shift,
+                                            DoubleTapKey::Alt,
+                                        ] {
+                                            ui.selectable_value(
+                                                &mut self.config.double_tap,
+                                                key,
+                                                format!("{key:?}"),
+                                            );
+                                        }
+                                    });
+                                combo.response.labelled_by(label.id);
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Hot corner
+                    if self.shown("Hot corner") {
+                        let locked = self.locked("Hot corner");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                let label = ui.label("Hot corner:")
+                                    .on_hover_text("マウスカーソルを画面のこの角に押し当てると、Peek seconds の間だけ時計を表示する");
+                                let combo = egui::ComboBox::from_id_salt("hot_corner")
+                                    .selected_text(format!("{:?}", self.config.hot_corner))
+                                    .show_ui(ui, |ui| {
+                                        for corner in HotCorner::ALL {
+                                            ui.selectable_value(
+                                                &mut self.config.hot_corner,
+                                                corner,
+                                                format!("{corner:?}"),
+                                            );
+                                        }
+                                    });
+                                combo.response.labelled_by(label.id);
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Lock hotkey
+                    if self.shown("Lock Hotkey") {
+                        let locked = self.locked("Lock Hotkey");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Lock Hotkey:").on_hover_text(
+                                    "クリック透過を切り替えるキー（解除中はドラッグで移動、右クリックでメニュー）",
+                                );
+                                optional_hotkey_picker(ui, "Lock Hotkey", &mut self.config.lock_hotkey);
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // 12/24h format hotkey
+                    if self.shown("Format Hotkey") {
+                        let locked = self.locked("Format Hotkey");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Format Hotkey:")
+                                    .on_hover_text("24時間/12時間表示をその場で切り替えるキー（設定も保存）");
+                                optional_hotkey_picker(ui, "Format Hotkey", &mut self.config.format_hotkey);
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Snooze hotkey
+                    if self.shown("Snooze Hotkey") {
+                        let locked = self.locked("Snooze Hotkey");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Snooze Hotkey:").on_hover_text(
+                                    "表示中のリマインダーや 0 になったカウントダウンをスヌーズするキー（その間は Hotkey でもスヌーズ）",
+                                );
+                                optional_hotkey_picker(ui, "Snooze Hotkey", &mut self.config.snooze_hotkey);
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Temporary zoom hotkey
+                    if self.shown("Zoom Hotkey") {
+                        let locked = self.locked("Zoom Hotkey");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Zoom Hotkey:").on_hover_text(
+                                    "数秒間だけ時計を2倍の大きさで表示するキー（もう一度押すと元に戻る。設定は変わらない）",
+                                );
+                                optional_hotkey_picker(ui, "Zoom Hotkey", &mut self.config.zoom_hotkey);
+                            });
+                            if !self.config.zoom_hotkey.is_empty() {
+                                let mut zoom_f = self.config.zoom_seconds as f32;
+                                ui.add(
+                                    egui::Slider::new(&mut zoom_f, 2.0..=30.0)
+                                        .text("Zoom seconds")
+                                        .integer(),
+                                )
+                                .on_hover_text("拡大表示を続ける秒数");
+                                self.config.zoom_seconds = zoom_f as u32;
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Countdown hotkey
+                    if self.shown("Countdown Hotkey") {
+                        let locked = self.locked("Countdown Hotkey");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Countdown Hotkey:")
+                                    .on_hover_text("カウントダウンを開始・停止するキー（0 になって点滅しているときは止める）");
+                                optional_hotkey_picker(ui, "Countdown Hotkey", &mut self.config.countdown_hotkey);
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Toggle flash
+                    if self.shown("Flash on toggle") {
+                        let locked = self.locked("Flash on toggle");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.toggle_flash, "Flash on toggle")
+                                .on_hover_text("表示したときに時計を数回点滅させ、どこに出たか分かるようにする");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    if self.shown("Reduce motion") {
+                        let locked = self.locked("Reduce motion");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.reduce_motion, "Reduce motion")
+                                .on_hover_text("点滅やアニメーションをすべて止める（Windows の「アニメーション効果」がオフのときも同様）");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    // Auto start
+                    if self.shown("Start with Windows") {
+                        let locked = self.locked("Start with Windows");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.start_with_windows, "Start with Windows");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    if self.shown("Autostart method") {
+                        let locked = self.locked("Autostart method");
+                        let enabled = !locked && self.config.start_with_windows;
+                        ui.add_enabled_ui(enabled, |ui| {
+                            ui.horizontal(|ui| {
+                                let label = ui.label("Autostart method:");
+                                let method = &mut self.config.autostart_method;
+                                let combo = egui::ComboBox::from_id_salt(label.id)
+                                    .selected_text(method.label())
+                                    .show_ui(ui, |ui| {
+                                        for choice in AutostartMethod::ALL {
+                                            ui.selectable_value(method, choice, choice.label());
+                                        }
+                                    });
+                                combo.response.labelled_by(label.id).on_hover_text(
+                                    "セキュリティソフトがレジストリの自動起動登録を警告する場合は Startup folder を選択",
+                                );
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    if self.shown("Startup delay") {
+                        let locked = self.locked("Startup delay");
+                        let enabled = !locked && self.config.start_with_windows;
+                        ui.add_enabled_ui(enabled, |ui| {
+                            let mut delay_f = self.config.startup_delay_seconds as f32;
+                            ui.add(
+                                egui::Slider::new(&mut delay_f, 0.0..=300.0)
+                                    .text("Startup delay (s)")
+                                    .integer(),
+                            )
+                            .on_hover_text("自動起動時、ホットキー登録とオーバーレイ作成までこの秒数だけ待つ（0=待たない）");
+                            self.config.startup_delay_seconds = delay_f as u32;
+                            ui.checkbox(&mut self.config.wait_for_shell, "Wait for the taskbar")
+                                .on_hover_text("自動起動時、タスクバーが応答するようになるまで待ってから開始する（最大2分）");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    if self.shown("Start visible") {
+                        let locked = self.locked("Start visible");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.start_visible, "Start visible")
+                                .on_hover_text("起動したらすぐに時計を表示する（オフならホットキーを押すまで非表示）");
+                            ui.checkbox(&mut self.config.remember_visibility, "Remember last state")
+                                .on_hover_text("終了・サインアウト時の表示/非表示の状態を次の起動で復元する（記録がまだなければ Start visible に従う）");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    if self.shown("Run as administrator") {
+                        let locked = self.locked("Run as administrator");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.run_elevated, "Run as administrator")
+                                .on_hover_text("管理者として実行中のゲームでもホットキーが効くよう、起動時に UAC で昇格する（次回起動から有効）。自動起動を Scheduled task にするとログイン時の UAC 確認が不要");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    if self.shown("Check for updates") {
+                        let locked = self.locked("Check for updates");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.check_updates, "Check for updates")
+                                .on_hover_text("起動時に GitHub の新しいリリースを確認する（1日1回まで）");
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    if self.shown("Send usage statistics") {
+                        let locked = self.locked("Send usage statistics");
+                        let active = crate::telemetry::active();
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.add_enabled(
+                                active,
+                                egui::Checkbox::new(&mut self.config.usage_stats, "Send usage statistics"),
+                            )
+                            .on_hover_text("どの機能を使っているか（オン/オフ・表示スタイル・バージョン）を匿名で送信し、開発の優先順位付けに役立てる（週1回まで）。ホットキー・リマインダー名・色などは含まない")
+                            .on_disabled_hover_text("このビルドには送信先サーバーが設定されていないため、まだ使えない");
+                            if !active {
+                                ui.label(
+                                    egui::RichText::new("Not active yet: this build has no collection server")
+                                        .small()
+                                        .weak(),
+                                );
+                            }
+                            egui::CollapsingHeader::new("Preview what is sent")
+                                .id_salt("usage_stats_preview")
+                                .show(ui, |ui| {
+                                    ui.monospace(crate::telemetry::preview(&self.config));
+                                });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    if self.shown("Plugins") {
+                        let locked = self.locked("Plugins");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.checkbox(&mut self.config.plugins, "Load plugins")
+                                .on_hover_text("plugins フォルダーの DLL を読み込み、センサー値などを表示に追加する。プラグインは ClockOR 内で動くため、信頼できるものだけを入れること");
+                            ui.indent("plugin_list", |ui| {
+                                let plugins = crate::plugins::list();
+                                if plugins.is_empty() {
+                                    ui.small("No plugins installed");
+                                }
+                                for plugin in plugins {
+                                    let mut enabled = !self
+                                        .config
+                                        .disabled_plugins
+                                        .iter()
+                                        .any(|d| d.eq_ignore_ascii_case(&plugin.file));
+                                    let label = if plugin.name == plugin.file {
+                                        plugin.file.clone()
+                                    } else {
+                                        format!("{} ({})", plugin.name, plugin.file)
+                                    };
+                                    ui.horizontal(|ui| {
+                                        if ui.checkbox(&mut enabled, label).changed() {
+                                            self.config
+                                                .disabled_plugins
+                                                .retain(|d| !d.eq_ignore_ascii_case(&plugin.file));
+                                            if !enabled {
+                                                self.config.disabled_plugins.push(plugin.file.clone());
+                                            }
+                                        }
+                                        ui.small(plugin.state.label());
+                                    });
+                                }
+                                if ui.button("Open Plugins Folder").clicked() {
+                                    let folder = crate::plugins::folder();
+                                    let _ = std::fs::create_dir_all(&folder);
+                                    let _ = std::process::Command::new("explorer").arg(&folder).spawn();
+                                }
+                            });
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    if self.shown("Event hooks") {
+                        let locked = self.locked("Event hooks");
+                        ui.add_enabled_ui(!locked, |ui| {
+                            ui.label("Event hooks:")
+                                .on_hover_text("イベントが起きたときに URL へ JSON を POST するか、コマンドを実行する（スマート照明や記録の自動化用）。コマンドには環境変数 CLOCKOR_EVENT / CLOCKOR_DETAIL が渡される");
+                            let mut remove = None;
+                            for (i, hook) in self.config.hooks.iter_mut().enumerate() {
+                                ui.horizontal(|ui| {
+                                    let event = egui::ComboBox::from_id_salt(("hook_event", i))
+                                        .selected_text(hook.event.label())
+                                        .show_ui(ui, |ui| {
+                                            for choice in HookEvent::ALL {
+                                                ui.selectable_value(&mut hook.event, choice, choice.label());
+                                            }
+                                        });
+                                    a11y_name(event.response, egui::WidgetType::ComboBox, "Hook event");
+                                    if hook.event == HookEvent::PcOnHours {
+                                        ui.add(egui::DragValue::new(&mut hook.hours).range(1..=24).suffix(" h"));
+                                    }
+                                    let action = ui.add(
+                                        egui::TextEdit::singleline(&mut hook.action)
+                                            .desired_width(200.0)
+                                            .hint_text("https://… or command"),
+                                    );
+                                    a11y_name(action, egui::WidgetType::TextEdit, "Hook action");
+                                    let delete = a11y_name(
+                                        ui.small_button("🗑"),
+                                        egui::WidgetType::Button,
+                                        "Remove hook",
+                                    );
+                                    if delete.clicked() {
+                                        remove = Some(i);
+                                    }
+                                });
+                            }
+                            if let Some(i) = remove {
+                                self.config.hooks.remove(i);
+                            }
+                            if ui.button("Add Hook").clicked() {
+                                self.config.hooks.push(Hook::default());
+                            }
+                            ui.add_space(4.0);
+                        });
+                        policy_note(ui, locked);
+                    }
+
+                    if self.shown("Collect Diagnostics") {
+                        if ui
+                            .button("Collect Diagnostics")
+                            .on_hover_text("不具合報告に添付する診断ファイル（個人的な文字を除いた設定・履歴・モニター構成・DPI・バージョン）をデスクトップに zip で保存")
+                            .clicked()
+                        {
+                            self.diagnostics_result =
+                                Some(match crate::diagnostics::collect(&self.config) {
+                                    Ok(path) => format!("Saved {}", path.display()),
+                                    Err(e) => format!("Could not save: {e}"),
+                                });
+                        }
+                        if let Some(result) = &self.diagnostics_result {
+                            ui.small(result);
+                        }
+                        ui.add_space(4.0);
+                    }
+                }
+            });
         });
     }
 }
 
-pub fn open_settings(config: Config) {
+pub fn open_settings(config: Config, scroll_to: Option<SettingsSection>) {
     // Generate icon for settings window
     let icon_rgba = crate::generate_icon_rgba(32);
     let icon_data = egui::IconData {
@@ -250,6 +3068,20 @@ pub fn open_settings(config: Config) {
     let _ = eframe::run_native(
         "ClockOR Settings",
         options,
-        Box::new(|_cc| Ok(Box::new(SettingsApp::new(config)))),
+        Box::new(|cc| {
+            // Make keyboard focus obvious: focused widgets use the "active" visuals.
+            // Both palettes get it, as the window follows light/dark switches live.
+            let reduce_motion = crate::theme::reduce_motion(&config);
+            cc.egui_ctx.all_styles_mut(|style| {
+                let color = style.visuals.selection.stroke.color;
+                style.visuals.widgets.active.bg_stroke = egui::Stroke::new(2.0, color);
+                if reduce_motion {
+                    style.animation_time = 0.0;
+                }
+            });
+            Ok(Box::new(SettingsApp::new(config, scroll_to)))
+        }),
     );
+    // Closed normally, so unapplied edits were given up on purpose
+    Config::discard_draft();
 }
@@ -0,0 +1,31 @@
+//! What the shell reports about the foreground app, via `SHQueryUserNotificationState`.
+//!
+//! A game in exclusive (Direct3D) fullscreen owns the display, so no layered
+//! window can be drawn over it: the overlay is "shown" but never visible.
+//! Presentation mode is how the user asks for no interruptions while presenting;
+//! plain fullscreen apps report `Busy`, which includes the games ClockOR is for.
+
+use windows::Win32::UI::Shell::{
+    SHQueryUserNotificationState, QUNS_BUSY, QUNS_PRESENTATION_MODE, QUNS_RUNNING_D3D_FULL_SCREEN,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellState {
+    /// Nothing in particular, or a state we don't act on.
+    Normal,
+    /// A fullscreen app that isn't exclusive (borderless game, video player).
+    Busy,
+    /// A Direct3D app in exclusive fullscreen.
+    ExclusiveFullscreen,
+    /// Windows presentation settings are on.
+    Presenting,
+}
+
+pub fn current() -> ShellState {
+    match unsafe { SHQueryUserNotificationState() } {
+        Ok(QUNS_RUNNING_D3D_FULL_SCREEN) => ShellState::ExclusiveFullscreen,
+        Ok(QUNS_PRESENTATION_MODE) => ShellState::Presenting,
+        Ok(QUNS_BUSY) => ShellState::Busy,
+        _ => ShellState::Normal,
+    }
+}
@@ -0,0 +1,128 @@
+//! Shutdowns and restarts Windows has been asked to carry out, e.g. by
+//! `shutdown /r /t 600` or an update restart. Each request is logged to the
+//! System log (event 1074 from User32) and each `shutdown /a` too (1075), but
+//! Windows offers no way to read back how long the timer still has to run, so
+//! the overlay counts down from the request by the user's Shutdown timer, or
+//! up from it when none is set.
+
+use std::sync::Mutex;
+
+use chrono::{DateTime, Local, Utc};
+use windows::core::{w, HSTRING};
+use windows::Win32::System::EventLog::{
+    EvtClose, EvtNext, EvtQuery, EvtQueryChannelPath, EvtQueryReverseDirection, EvtRender,
+    EvtRenderEventXml, EVT_HANDLE,
+};
+
+/// A shutdown or restart was requested.
+const INITIATED: u32 = 1074;
+/// The request was cancelled with `shutdown /a`.
+const ABORTED: u32 = 1075;
+
+/// How often the System log is read, in seconds.
+pub const POLL_SECONDS: u64 = 10;
+
+/// When the pending request was made, as of the last poll.
+static PENDING: Mutex<Option<DateTime<Local>>> = Mutex::new(None);
+
+/// When the pending shutdown or restart was requested, or `None`.
+pub fn pending() -> Option<DateTime<Local>> {
+    *PENDING.lock().unwrap()
+}
+
+/// Read the System log for requests made since `since` and remember the
+/// outcome for `pending`. Only requests after `since` count: with fast
+/// startup the last session's shutdown is logged after the boot time.
+pub fn poll(since: DateTime<Local>) -> Option<DateTime<Local>> {
+    let found = latest_event(since)
+        .and_then(|xml| parse(&xml))
+        .and_then(|(id, at)| (id == INITIATED).then(|| at.with_timezone(&Local)));
+    *PENDING.lock().unwrap() = found;
+    found
+}
+
+/// The newest request or cancellation since `since`, as event XML.
+fn latest_event(since: DateTime<Local>) -> Option<String> {
+    let age_ms = (Local::now() - since).num_milliseconds().max(0);
+    let query = HSTRING::from(format!(
+        "*[System[Provider[@Name='User32'] and (EventID={INITIATED} or EventID={ABORTED}) \
+         and TimeCreated[timediff(@SystemTime) <= {age_ms}]]]"
+    ));
+    unsafe {
+        let flags = EvtQueryChannelPath.0 | EvtQueryReverseDirection.0;
+        let results = EvtQuery(EVT_HANDLE::default(), w!("System"), &query, flags).ok()?;
+        let mut events = [0isize; 1];
+        let mut returned = 0;
+        let xml = match EvtNext(results, &mut events, 1000, 0, &mut returned) {
+            Ok(()) if returned == 1 => {
+                let event = EVT_HANDLE(events[0]);
+                let xml = render(event);
+                let _ = EvtClose(event);
+                xml
+            }
+            _ => None,
+        };
+        let _ = EvtClose(results);
+        xml
+    }
+}
+
+unsafe fn render(event: EVT_HANDLE) -> Option<String> {
+    let (mut used, mut count) = (0u32, 0u32);
+    // The first call fails and reports the size needed
+    let _ = EvtRender(
+        EVT_HANDLE::default(),
+        event,
+        EvtRenderEventXml.0,
+        0,
+        None,
+        &mut used,
+        &mut count,
+    );
+    let mut buffer = vec![0u16; used as usize / 2 + 1];
+    EvtRender(
+        EVT_HANDLE::default(),
+        event,
+        EvtRenderEventXml.0,
+        (buffer.len() * 2) as u32,
+        Some(buffer.as_mut_ptr().cast()),
+        &mut used,
+        &mut count,
+    )
+    .ok()?;
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    Some(String::from_utf16_lossy(&buffer[..len]))
+}
+
+/// Event ID and time from an event's XML.
+fn parse(xml: &str) -> Option<(u32, DateTime<Utc>)> {
+    let id = xml.split("<EventID").nth(1)?.split("</EventID>").next()?;
+    let id = id.rsplit('>').next()?.trim().parse().ok()?;
+    let time = xml.split("SystemTime=").nth(1)?;
+    let quote = time.chars().next()?;
+    let time = time[1..].split(quote).next()?;
+    let at = DateTime::parse_from_rfc3339(time).ok()?.with_timezone(&Utc);
+    Some((id, at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_id_and_time() {
+        let xml = "<Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'><System>\
+            <Provider Name='User32'/><EventID Qualifiers='32768'>1074</EventID>\
+            <TimeCreated SystemTime='2026-10-16T12:34:56.1234567Z'/></System></Event>";
+        let (id, at) = parse(xml).unwrap();
+        assert_eq!(id, INITIATED);
+        assert_eq!(at.to_rfc3339(), "2026-10-16T12:34:56.123456700+00:00");
+    }
+
+    #[test]
+    fn double_quotes_and_plain_ids() {
+        let xml = r#"<EventID>1075</EventID><TimeCreated SystemTime="2026-01-02T03:04:05Z"/>"#;
+        assert_eq!(parse(xml).map(|(id, _)| id), Some(ABORTED));
+        assert_eq!(parse("<Event/>"), None);
+    }
+}
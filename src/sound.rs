@@ -0,0 +1,259 @@
+//! Sounds: the system notification sound for reminders, and synthesized cues
+//! (minute tick, hourly chime) played at ClockOR's own volume.
+//!
+//! Sounds go to the Windows default output unless an output device is chosen
+//! in settings, in which case they are rendered there through WASAPI. The
+//! system notification sound can only follow the default device, so reminders
+//! on a chosen device use the built-in chime instead.
+
+use std::time::Duration;
+
+use windows::core::{w, HSTRING, PCWSTR};
+use windows::Win32::Devices::FunctionDiscovery::PKEY_Device_FriendlyName;
+use windows::Win32::Foundation::HMODULE;
+use windows::Win32::Media::Audio::{
+    eRender, IAudioClient, IAudioRenderClient, IMMDeviceEnumerator, MMDeviceEnumerator, PlaySoundW,
+    AUDCLNT_SHAREMODE_SHARED, AUDCLNT_STREAMFLAGS_AUTOCONVERTPCM,
+    AUDCLNT_STREAMFLAGS_SRC_DEFAULT_QUALITY, DEVICE_STATE_ACTIVE, SND_ALIAS, SND_ASYNC, SND_MEMORY,
+    SND_NODEFAULT, WAVEFORMATEX, WAVE_FORMAT_PCM,
+};
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoTaskMemFree, CoUninitialize, CLSCTX_ALL,
+    COINIT_MULTITHREADED, STGM_READ,
+};
+
+const SAMPLE_RATE: u32 = 22_050;
+
+/// A synthesized sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cue {
+    /// Soft click at the turn of each minute.
+    Tick,
+    /// Two-tone bell on the hour.
+    Chime,
+}
+
+/// Mono 16-bit samples for `cue` at `volume` percent.
+pub fn samples(cue: Cue, volume: u8) -> Vec<i16> {
+    let (seconds, decay, partials): (f32, f32, &[(f32, f32)]) = match cue {
+        Cue::Tick => (0.03, 0.004, &[(1800.0, 0.5)]),
+        Cue::Chime => (1.2, 0.35, &[(880.0, 0.4), (1320.0, 0.2)]),
+    };
+    let gain = f32::from(volume.min(100)) / 100.0;
+    let count = (seconds * SAMPLE_RATE as f32) as usize;
+    (0..count)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            // 2 ms fade-in so the start doesn't click
+            let envelope = (t / 0.002).min(1.0) * (-t / decay).exp();
+            let wave: f32 = partials
+                .iter()
+                .map(|(freq, amp)| amp * (std::f32::consts::TAU * freq * t).sin())
+                .sum();
+            (wave * envelope * gain * f32::from(i16::MAX)) as i16
+        })
+        .collect()
+}
+
+/// Wrap samples in a RIFF/WAVE header.
+fn wav(samples: &[i16]) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut out = Vec::with_capacity(44 + data_len as usize);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    out.extend_from_slice(&1u16.to_le_bytes()); // mono
+    out.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    out.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    out.extend_from_slice(&2u16.to_le_bytes());
+    out.extend_from_slice(&16u16.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    for s in samples {
+        out.extend_from_slice(&s.to_le_bytes());
+    }
+    out
+}
+
+/// An active audio output endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputDevice {
+    /// Endpoint ID, stable across restarts; this is what the config stores.
+    pub id: String,
+    pub name: String,
+}
+
+/// Run `f` with COM initialized on this thread, balancing the call only if
+/// this one succeeded.
+fn with_com<T>(f: impl FnOnce() -> windows::core::Result<T>) -> windows::core::Result<T> {
+    unsafe {
+        let initialized = CoInitializeEx(None, COINIT_MULTITHREADED).is_ok();
+        let result = f();
+        if initialized {
+            CoUninitialize();
+        }
+        result
+    }
+}
+
+fn enumerator() -> windows::core::Result<IMMDeviceEnumerator> {
+    unsafe { CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL) }
+}
+
+/// Active playback devices, in the order Windows lists them.
+pub fn output_devices() -> Vec<OutputDevice> {
+    with_com(|| unsafe {
+        let devices = enumerator()?.EnumAudioEndpoints(eRender, DEVICE_STATE_ACTIVE)?;
+        let mut out = Vec::new();
+        for i in 0..devices.GetCount()? {
+            let device = devices.Item(i)?;
+            let raw_id = device.GetId()?;
+            let id = raw_id.to_string();
+            CoTaskMemFree(Some(raw_id.0 as _));
+            let name = device
+                .OpenPropertyStore(STGM_READ)?
+                .GetValue(&PKEY_Device_FriendlyName)?
+                .to_string();
+            if let Ok(id) = id {
+                out.push(OutputDevice { id, name });
+            }
+        }
+        Ok(out)
+    })
+    .unwrap_or_default()
+}
+
+/// Render `samples` on the endpoint `device_id` in shared mode, returning once
+/// they have played.
+fn play_on_device(device_id: &str, samples: &[i16]) -> windows::core::Result<()> {
+    with_com(|| unsafe {
+        let device = enumerator()?.GetDevice(&HSTRING::from(device_id))?;
+        let client: IAudioClient = device.Activate(CLSCTX_ALL, None)?;
+        let format = WAVEFORMATEX {
+            wFormatTag: WAVE_FORMAT_PCM as u16,
+            nChannels: 1,
+            nSamplesPerSec: SAMPLE_RATE,
+            nAvgBytesPerSec: SAMPLE_RATE * 2,
+            nBlockAlign: 2,
+            wBitsPerSample: 16,
+            cbSize: 0,
+        };
+        // 200 ms buffer; the device mix format usually differs, so let the
+        // audio engine convert
+        client.Initialize(
+            AUDCLNT_SHAREMODE_SHARED,
+            AUDCLNT_STREAMFLAGS_AUTOCONVERTPCM | AUDCLNT_STREAMFLAGS_SRC_DEFAULT_QUALITY,
+            2_000_000,
+            0,
+            &format,
+            None,
+        )?;
+        let buffer_frames = client.GetBufferSize()?;
+        let render: IAudioRenderClient = client.GetService()?;
+        let mut rest = samples;
+        let mut started = false;
+        loop {
+            let padding = client.GetCurrentPadding()?;
+            if rest.is_empty() {
+                if padding == 0 {
+                    break;
+                }
+            } else {
+                let frames = (buffer_frames - padding).min(rest.len() as u32);
+                if frames > 0 {
+                    let data = render.GetBuffer(frames)?;
+                    std::ptr::copy_nonoverlapping(rest.as_ptr(), data.cast(), frames as usize);
+                    render.ReleaseBuffer(frames, 0)?;
+                    rest = &rest[frames as usize..];
+                }
+                if !started {
+                    client.Start()?;
+                    started = true;
+                }
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        client.Stop()
+    })
+}
+
+/// Play on `device_id`, or on the default device if it is empty or gone.
+/// Blocks until done.
+fn play_samples(device_id: &str, samples: &[i16]) {
+    if !device_id.is_empty() && play_on_device(device_id, samples).is_ok() {
+        return;
+    }
+    let wav = wav(samples);
+    unsafe {
+        let _ = PlaySoundW(
+            PCWSTR(wav.as_ptr().cast()),
+            HMODULE::default(),
+            SND_MEMORY | SND_NODEFAULT,
+        );
+    }
+}
+
+/// Reminder sound without blocking the caller: the system notification sound,
+/// or the built-in chime at `volume` when an output device is chosen.
+pub fn play_chime(device_id: &str, volume: u8) {
+    if device_id.is_empty() {
+        unsafe {
+            let _ = PlaySoundW(
+                w!("SystemNotification"),
+                HMODULE::default(),
+                SND_ALIAS | SND_ASYNC | SND_NODEFAULT,
+            );
+        }
+    } else {
+        play_cue(Cue::Chime, volume, device_id);
+    }
+}
+
+/// Play a synthesized cue on its own thread, which also keeps the buffer alive.
+pub fn play_cue(cue: Cue, volume: u8, device_id: &str) {
+    if volume == 0 {
+        return;
+    }
+    let samples = samples(cue, volume);
+    let device_id = device_id.to_string();
+    std::thread::spawn(move || play_samples(&device_id, &samples));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volume_scales_samples() {
+        let peak = |v| {
+            samples(Cue::Chime, v)
+                .iter()
+                .map(|s| s.unsigned_abs())
+                .max()
+                .unwrap()
+        };
+        assert_eq!(peak(0), 0);
+        assert!(peak(50) < peak(100));
+        assert_eq!(peak(200), peak(100));
+    }
+
+    #[test]
+    fn tick_is_shorter_than_chime() {
+        assert!(samples(Cue::Tick, 100).len() < samples(Cue::Chime, 100).len());
+    }
+
+    #[test]
+    fn wav_header_matches_data() {
+        let data = samples(Cue::Tick, 50);
+        let bytes = wav(&data);
+        assert_eq!(&bytes[..4], b"RIFF");
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(bytes.len(), 44 + data.len() * 2);
+        assert_eq!(
+            u32::from_le_bytes(bytes[40..44].try_into().unwrap()) as usize,
+            data.len() * 2
+        );
+    }
+}
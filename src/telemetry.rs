@@ -0,0 +1,257 @@
+//! Opt-in anonymous usage statistics: which features are turned on, so
+//! development can go where people actually are.
+//!
+//! Nothing is sent unless "Send usage statistics" is checked, network features
+//! aren't disabled by policy, and the build has a collection endpoint; builds
+//! without one grey the option out as not yet active. The
+//! report holds on/off flags, style choices and version numbers only — no
+//! hotkeys, reminder names, colors, paths or identifiers — and settings shows
+//! exactly the JSON that would be sent.
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::config::{
+    ActiveWindowDisplay, AlertPalette, Backdrop, Config, DateDisplay, DoubleTapKey, FontFamily,
+    HotCorner, ProgressDisplay, ScreenEdge, TextRenderer, ZOrder,
+};
+
+/// Where reports are posted (an https URL), set when the release workflow
+/// builds. Without it reports are only shown in the settings preview.
+const ENDPOINT: Option<&str> = option_env!("CLOCKOR_USAGE_STATS_URL");
+
+/// Minimum time between two reports.
+const SEND_INTERVAL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+#[derive(Debug, Serialize)]
+struct Report {
+    version: &'static str,
+    windows_build: String,
+    position: String,
+    text_style: String,
+    font_size: u32,
+    format_24h: bool,
+    show_seconds: bool,
+    background_panel: bool,
+    /// Names of the optional features that are turned on.
+    features: Vec<&'static str>,
+}
+
+/// Optional features that are on, by stable names.
+fn enabled_features(config: &Config) -> Vec<&'static str> {
+    let flags = [
+        ("progress", config.progress != ProgressDisplay::Off),
+        ("uptime", config.show_uptime),
+        ("input_display", config.show_input),
+        (
+            "active_window",
+            config.active_window != ActiveWindowDisplay::Off,
+        ),
+        ("input_language", config.show_input_language),
+        ("script", !config.script.is_empty()),
+        ("template", !config.template.is_empty()),
+        ("background_image", !config.background_image.is_empty()),
+        ("backdrop", config.backdrop != Backdrop::Flat),
+        ("multi_line", config.multi_line),
+        ("vertical", config.vertical),
+        ("color_rules", !config.color_rules.is_empty()),
+        ("z_order", config.z_order != ZOrder::Topmost),
+        ("snap_off", !config.snap_to_edges),
+        ("snap_grid", config.snap_grid > 0),
+        ("monitor_scales", !config.monitor_scales.is_empty()),
+        ("time_format", !config.time_format.is_empty()),
+        ("date_line", config.date_line != DateDisplay::Off),
+        ("world_clocks", !config.world_clocks.is_empty()),
+        ("gdi_text", config.text_renderer == TextRenderer::Gdi),
+        ("font_family", config.font_family != FontFamily::SegoeUi),
+        ("font_width", config.font_width != 5),
+        ("subpixel_text_off", !config.subpixel_text),
+        (
+            "alert_palette",
+            config.alert_palette != AlertPalette::Standard,
+        ),
+        ("monitor_overlays", !config.monitor_overlays.is_empty()),
+        ("window_border", config.window_border),
+        ("text_file", !config.text_file.is_empty()),
+        ("command", !config.command.is_empty()),
+        ("plugins", config.plugins),
+        ("hooks", !config.hooks.is_empty()),
+        ("reminders", !config.daily_events.is_empty()),
+        ("alarms", config.daily_events.iter().any(|e| e.alarm)),
+        ("reminder_warning", config.reminder_warning_minutes > 0),
+        ("scheduled_actions", !config.scheduled_actions.is_empty()),
+        (
+            "visibility_schedule",
+            !config.visibility_schedule.is_empty(),
+        ),
+        ("idle_hide", config.idle_hide_minutes > 0),
+        ("auto_peek", config.peek_interval_minutes > 0),
+        ("hot_corner", config.hot_corner != HotCorner::Off),
+        ("play_reveal", config.play_reveal_minutes > 0),
+        ("low_battery", config.low_battery_percent > 0),
+        ("shutdown_warning_off", !config.shutdown_warning),
+        ("shutdown_timer", config.shutdown_timer_minutes > 0),
+        ("start_visible", config.start_visible),
+        ("remember_visibility", config.remember_visibility),
+        ("hide_under_game_bar", config.hide_under_game_bar),
+        ("quiet_when_presenting", config.quiet_when_presenting),
+        ("tick_sound", config.tick_sound),
+        ("hourly_chime", config.hourly_chime),
+        ("edge_flash", config.edge_flash != ScreenEdge::Off),
+        ("controller_rumble", config.controller_rumble),
+        ("reduce_motion", config.reduce_motion),
+        ("lock_hotkey", !config.lock_hotkey.is_empty()),
+        ("format_hotkey", !config.format_hotkey.is_empty()),
+        ("snooze_hotkey", !config.snooze_hotkey.is_empty()),
+        ("zoom_hotkey", !config.zoom_hotkey.is_empty()),
+        ("countdown_hotkey", !config.countdown_hotkey.is_empty()),
+        ("double_tap", config.double_tap != DoubleTapKey::Off),
+        ("start_with_windows", config.start_with_windows),
+        ("run_elevated", config.run_elevated),
+    ];
+    flags
+        .into_iter()
+        .filter_map(|(name, on)| on.then_some(name))
+        .collect()
+}
+
+/// A choice as its config-file spelling, e.g. "bottom-left".
+fn choice<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn report_for(config: &Config, windows_build: String) -> Report {
+    Report {
+        version: crate::update::CURRENT_VERSION,
+        windows_build,
+        position: choice(&config.position),
+        text_style: choice(&config.text_style),
+        font_size: config.font_size,
+        format_24h: config.format_24h,
+        show_seconds: config.show_seconds,
+        background_panel: config.background_opacity > 0,
+        features: enabled_features(config),
+    }
+}
+
+/// The exact JSON a report for `config` would contain, for the settings preview.
+pub fn preview(config: &Config) -> String {
+    let report = report_for(config, crate::diagnostics::windows_build());
+    serde_json::to_string_pretty(&report).unwrap_or_default()
+}
+
+/// Remembers when the last report went out, next to the config file.
+fn stamp_path() -> PathBuf {
+    crate::config::app_dir().join("usage_stats_sent")
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn send_due(last: u64, now: u64) -> bool {
+    now < last || now - last >= SEND_INTERVAL.as_secs()
+}
+
+/// `url` if it is usable as the endpoint; an unset workflow variable builds
+/// in as an empty string.
+fn https_url(url: Option<&str>) -> Option<&str> {
+    url.filter(|url| url.starts_with("https://") && url.len() > "https://".len())
+}
+
+/// Whether this build can send reports at all.
+pub fn active() -> bool {
+    https_url(ENDPOINT).is_some()
+}
+
+/// Send a report on a background thread if the user opted in and the last
+/// one is over a week old.
+pub fn spawn_send(config: &Config) {
+    let Some(endpoint) = https_url(ENDPOINT) else {
+        return;
+    };
+    if !config.usage_stats || !crate::policy::current().network_allowed() {
+        return;
+    }
+    let last = std::fs::read_to_string(stamp_path())
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(0);
+    let now = unix_now();
+    if !send_due(last, now) {
+        return;
+    }
+    let json = preview(config);
+    std::thread::spawn(move || match crate::update::post_json(endpoint, &json) {
+        Ok(()) => {
+            let _ = std::fs::write(stamp_path(), now.to_string());
+        }
+        Err(e) => crate::logging::error(format!("Usage statistics not sent: {e}")),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DailyEvent, Position, TextStyle};
+
+    #[test]
+    fn default_config_reports_no_features() {
+        let report = report_for(&Config::default(), "22631".into());
+        assert!(report.features.is_empty());
+        assert_eq!(report.position, "top-right");
+        assert_eq!(report.text_style, "outline");
+    }
+
+    #[test]
+    fn report_has_flags_but_no_personal_text() {
+        let config = Config {
+            position: Position::BottomLeft,
+            text_style: TextStyle::Shadow,
+            daily_events: vec![DailyEvent {
+                name: "Call Mum".to_string(),
+                ..Default::default()
+            }],
+            zoom_hotkey: "Ctrl+Alt+Z".to_string(),
+            hourly_chime: true,
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&report_for(&config, "22631".into())).unwrap();
+        assert!(json.contains("\"bottom-left\""));
+        assert!(json.contains("\"shadow\""));
+        assert!(json.contains("\"reminders\""));
+        assert!(json.contains("\"zoom_hotkey\""));
+        assert!(json.contains("\"hourly_chime\""));
+        assert!(!json.contains("Call Mum"));
+        assert!(!json.contains("Ctrl+Alt+Z"));
+    }
+
+    #[test]
+    fn endpoint_must_be_https() {
+        assert_eq!(
+            https_url(Some("https://stats.example.org/v1")),
+            Some("https://stats.example.org/v1")
+        );
+        assert_eq!(https_url(Some("")), None);
+        assert_eq!(https_url(Some("https://")), None);
+        assert_eq!(https_url(Some("http://stats.example.org")), None);
+        assert_eq!(https_url(None), None);
+    }
+
+    #[test]
+    fn sends_at_most_weekly() {
+        let week = SEND_INTERVAL.as_secs();
+        assert!(send_due(0, 1_000_000));
+        assert!(!send_due(1_000_000, 1_000_000 + week - 1));
+        assert!(send_due(1_000_000, 1_000_000 + week));
+        // Clock went backwards
+        assert!(send_due(1_000_000, 10));
+    }
+}
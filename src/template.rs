@@ -0,0 +1,131 @@
+//! Overlay text templates: one format field such as
+//! `{time}  {battery|AC}  {cpu}` that composes the whole overlay line.
+//!
+//! A token whose value isn't available right now (no battery, stream timer
+//! stopped, no script) becomes its fallback after `|`, or nothing, and the
+//! spaces around it are tidied up. Unknown tokens are left as typed so a typo
+//! stays visible. `{{` and `}}` write literal braces.
+
+/// Tokens a template can use, with what they show, for settings.
+pub const TOKENS: &[(&str, &str)] = &[
+    ("time", "時計（12/24時間・秒の設定どおり）"),
+    ("date", "日付（2026-03-01）"),
+    ("weekday", "曜日（Mon）"),
+    ("battery", "電池残量（64%）。電池がなければなし"),
+    ("cpu", "CPU 使用率（23%）"),
+    ("timer", "配信タイマー（1:02:03）。停止中はなし"),
+    (
+        "countdown",
+        "カウントダウンの残り時間（⏳ 4:59）。停止中はなし",
+    ),
+    ("uptime", "稼働時間（up 6h 12m）"),
+    ("progress", "今年・今月の経過率（Progress の設定に従う）"),
+    ("layout", "入力言語と IME の状態（JP あ）"),
+    (
+        "window",
+        "操作中のウィンドウ（Active window の設定に従い、オフならプログラム名）",
+    ),
+    ("custom", "スクリプトの戻り値。スクリプトがなければなし"),
+    ("file", "Text file の 1 行目。ファイルがなければなし"),
+    ("command", "Command の出力。失敗中はなし"),
+    (
+        "plugin:名前",
+        "プラグインの表示。読み込まれていなければなし",
+    ),
+];
+
+/// Whether `name` is a token, available or not.
+pub fn is_known(name: &str) -> bool {
+    name.starts_with("plugin:") || TOKENS.iter().any(|(token, _)| *token == name)
+}
+
+/// Expand `template`, asking `value` for each known token.
+pub fn expand(template: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        let tail = &rest[i..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        let token = tail
+            .starts_with('{')
+            .then(|| tail.find('}'))
+            .flatten()
+            .map(|end| &tail[1..end]);
+        match token {
+            Some(token) => {
+                let (name, fallback) = token.split_once('|').unwrap_or((token, ""));
+                if is_known(name.trim()) {
+                    out.push_str(&value(name.trim()).unwrap_or_else(|| fallback.to_string()));
+                } else {
+                    out.push_str(&tail[..token.len() + 2]);
+                }
+                rest = &tail[token.len() + 2..];
+            }
+            None => {
+                out.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    tidy(&out)
+}
+
+/// Drop the gaps an empty token leaves: trim the ends and keep no run of
+/// spaces longer than the template's widest separator (two spaces).
+fn tidy(text: &str) -> String {
+    let mut out = String::new();
+    let mut spaces = 0;
+    for c in text.trim().chars() {
+        if c == ' ' {
+            spaces += 1;
+            if spaces <= 2 {
+                out.push(c);
+            }
+        } else {
+            spaces = 0;
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(name: &str) -> Option<String> {
+        match name {
+            "time" => Some("21:05".to_string()),
+            "cpu" => Some("23%".to_string()),
+            "plugin:Sensors" => Some("GPU 70°C".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expands_tokens() {
+        assert_eq!(expand("{time}  CPU {cpu}", values), "21:05  CPU 23%");
+        assert_eq!(expand("{plugin:Sensors}", values), "GPU 70°C");
+    }
+
+    #[test]
+    fn unavailable_tokens_use_fallback_or_vanish() {
+        assert_eq!(expand("{time}  {battery}  {cpu}", values), "21:05  23%");
+        assert_eq!(expand("{battery|AC} {time}", values), "AC 21:05");
+        assert_eq!(expand("{timer}", values), "");
+    }
+
+    #[test]
+    fn unknown_tokens_and_braces_stay_literal() {
+        assert_eq!(expand("{tiem}", values), "{tiem}");
+        assert_eq!(expand("{{time}}", values), "{time}");
+        assert_eq!(expand("50% {", values), "50% {");
+        assert_eq!(expand("a } b", values), "a } b");
+    }
+}
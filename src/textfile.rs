@@ -0,0 +1,121 @@
+//! Text from a file another tool writes (now-playing scripts, macro tools):
+//! the first line of `text_file` is shown as a segment and re-read whenever
+//! the file changes.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Longest line shown, in characters.
+const MAX_CHARS: usize = 200;
+
+struct Watched {
+    path: PathBuf,
+    /// Modification time and size when last read.
+    stamp: Option<(SystemTime, u64)>,
+    line: Option<String>,
+}
+
+static WATCHED: Mutex<Option<Watched>> = Mutex::new(None);
+
+/// The first non-empty line, decoding UTF-8 and UTF-16 (as PowerShell's
+/// `Out-File` writes) and cut to `MAX_CHARS`.
+fn first_line(bytes: &[u8]) -> Option<String> {
+    let text = if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        let units: Vec<u16> = rest
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        let units: Vec<u16> = rest
+            .chunks_exact(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        let rest = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+        String::from_utf8_lossy(rest).into_owned()
+    };
+    let line = text.lines().map(str::trim).find(|l| !l.is_empty())?;
+    Some(line.chars().take(MAX_CHARS).collect())
+}
+
+fn stamp(path: &PathBuf) -> Option<(SystemTime, u64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// Re-read the file if it changed since the last tick. An empty `text_file`
+/// turns the segment off.
+pub fn update(text_file: &str) {
+    let mut watched = WATCHED.lock().unwrap();
+    if text_file.trim().is_empty() {
+        *watched = None;
+        return;
+    }
+    let path = crate::config::user_path(text_file);
+    if watched.as_ref().is_none_or(|w| w.path != path) {
+        *watched = Some(Watched {
+            path: path.clone(),
+            stamp: None,
+            line: None,
+        });
+    }
+    let watched = watched.as_mut().expect("set above");
+    let now = stamp(&path);
+    if now == watched.stamp {
+        return;
+    }
+    match std::fs::read(&path) {
+        Ok(bytes) => {
+            watched.line = first_line(&bytes);
+            watched.stamp = now;
+        }
+        // Gone: show nothing. Locked mid-write: keep the old line and retry next tick.
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            watched.line = None;
+            watched.stamp = None;
+        }
+        Err(_) => {}
+    }
+}
+
+/// The line to show, if the file exists and has one.
+pub fn text() -> Option<String> {
+    WATCHED.lock().unwrap().as_ref()?.line.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_non_empty_line() {
+        assert_eq!(
+            first_line(b"\r\n  Now playing: Song  \r\nnext").as_deref(),
+            Some("Now playing: Song")
+        );
+        assert_eq!(first_line(b"\xEF\xBB\xBFbom").as_deref(), Some("bom"));
+        assert_eq!(first_line(b" \n\n"), None);
+    }
+
+    #[test]
+    fn decodes_utf16_files() {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend("曲名\r\n".encode_utf16().flat_map(u16::to_le_bytes));
+        assert_eq!(first_line(&bytes).as_deref(), Some("曲名"));
+        let mut bytes = vec![0xFE, 0xFF];
+        bytes.extend("BE".encode_utf16().flat_map(u16::to_be_bytes));
+        assert_eq!(first_line(&bytes).as_deref(), Some("BE"));
+    }
+
+    #[test]
+    fn long_lines_are_cut() {
+        let long = "あ".repeat(MAX_CHARS + 50);
+        assert_eq!(
+            first_line(long.as_bytes()).unwrap().chars().count(),
+            MAX_CHARS
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! Reads the Windows light/dark theme, high-contrast and animation settings.
+
+use windows::core::w;
+use windows::Win32::Graphics::Gdi::{GetSysColor, COLOR_WINDOW, COLOR_WINDOWTEXT};
+use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+use windows::Win32::UI::Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTW};
+use windows::Win32::UI::WindowsAndMessaging::{
+    SystemParametersInfoW, SPI_GETCLIENTAREAANIMATION, SPI_GETHIGHCONTRAST,
+    SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+};
+
+/// Read a DWORD under the current user's theme personalization key.
+fn personalize_dword(name: windows::core::PCWSTR) -> Option<u32> {
+    let mut value = 0u32;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            w!("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize"),
+            name,
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut value as *mut u32 as *mut _),
+            Some(&mut size),
+        )
+    };
+    status.is_ok().then_some(value)
+}
+
+/// Whether the taskbar (and so the tray) uses the light theme. Older Windows
+/// versions without the setting have a dark taskbar.
+pub fn taskbar_is_light() -> bool {
+    personalize_dword(w!("SystemUsesLightTheme")) == Some(1)
+}
+
+/// Whether "Transparency effects" is on in Windows personalization settings.
+pub fn transparency_enabled() -> bool {
+    personalize_dword(w!("EnableTransparency")) != Some(0)
+}
+
+/// Text and background colors of the active high-contrast theme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HighContrast {
+    pub text: [u8; 3],
+    pub background: [u8; 3],
+}
+
+fn sys_color(index: windows::Win32::Graphics::Gdi::SYS_COLOR_INDEX) -> [u8; 3] {
+    let cr = unsafe { GetSysColor(index) };
+    [cr as u8, (cr >> 8) as u8, (cr >> 16) as u8]
+}
+
+/// The high-contrast colors, or `None` when high contrast is off.
+pub fn high_contrast() -> Option<HighContrast> {
+    let mut hc = HIGHCONTRASTW {
+        cbSize: std::mem::size_of::<HIGHCONTRASTW>() as u32,
+        ..Default::default()
+    };
+    unsafe {
+        SystemParametersInfoW(
+            SPI_GETHIGHCONTRAST,
+            hc.cbSize,
+            Some(&mut hc as *mut _ as *mut _),
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+        .ok()?;
+    }
+    (hc.dwFlags & HCF_HIGHCONTRASTON == HCF_HIGHCONTRASTON).then(|| HighContrast {
+        text: sys_color(COLOR_WINDOWTEXT),
+        background: sys_color(COLOR_WINDOW),
+    })
+}
+
+/// Whether "Animation effects" is on in Windows accessibility settings.
+fn animations_enabled() -> bool {
+    let mut enabled = windows::Win32::Foundation::BOOL(1);
+    unsafe {
+        let _ = SystemParametersInfoW(
+            SPI_GETCLIENTAREAANIMATION,
+            0,
+            Some(&mut enabled as *mut _ as *mut _),
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        );
+    }
+    enabled.as_bool()
+}
+
+/// No flashing or animation: asked for in settings or in Windows.
+pub fn reduce_motion(config: &crate::config::Config) -> bool {
+    config.reduce_motion || !animations_enabled()
+}
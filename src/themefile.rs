@@ -0,0 +1,337 @@
+//! `.clockor-theme.toml` files: a shareable look (colors, font, style,
+//! background, position) kept apart from behavior, so sharing a theme never
+//! shares hotkeys, paths or reminders.
+//!
+//! Every key is optional; loading a theme changes only what it sets, and keys
+//! that aren't part of a theme are ignored. Themes live in the `themes` folder
+//! next to clockor.exe. A theme also fits in a one-line preset code, for
+//! pasting in chat where files are a bother.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Backdrop, Config, CornerStyle, FontFamily, Position, TextAlign, TextStyle};
+
+pub const EXTENSION: &str = ".clockor-theme.toml";
+
+/// Starts every preset code and names its format, so it can change later.
+const CODE_PREFIX: &str = "CLOCKOR1-";
+/// URL-safe base64, which survives chat apps and URLs unescaped.
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    pub position: Option<Position>,
+    pub font_size: Option<u32>,
+    pub font_weight: Option<u32>,
+    pub font_family: Option<FontFamily>,
+    pub font_width: Option<u32>,
+    pub max_width: Option<u32>,
+    pub text_align: Option<TextAlign>,
+    pub multi_line: Option<bool>,
+    pub line_spacing: Option<u32>,
+    pub line_alignment: Option<Vec<TextAlign>>,
+    pub vertical: Option<bool>,
+    pub text_style: Option<TextStyle>,
+    pub text_color: Option<[u8; 3]>,
+    pub outline_color: Option<[u8; 3]>,
+    pub opacity: Option<u8>,
+    pub background_opacity: Option<u8>,
+    pub background_color: Option<[u8; 3]>,
+    pub backdrop: Option<Backdrop>,
+    pub corner_style: Option<CornerStyle>,
+    pub window_border: Option<bool>,
+    pub border_color: Option<[u8; 3]>,
+}
+
+impl Theme {
+    /// The look of `config`, ready to export.
+    pub fn from_config(config: &Config, name: &str) -> Self {
+        Self {
+            name: (!name.trim().is_empty()).then(|| name.trim().to_string()),
+            author: None,
+            position: Some(config.position),
+            font_size: Some(config.font_size),
+            font_weight: Some(config.font_weight),
+            font_family: Some(config.font_family),
+            font_width: Some(config.font_width),
+            max_width: Some(config.max_width),
+            text_align: Some(config.text_align),
+            multi_line: Some(config.multi_line),
+            line_spacing: Some(config.line_spacing),
+            line_alignment: Some(config.line_alignment.clone()),
+            vertical: Some(config.vertical),
+            text_style: Some(config.text_style),
+            text_color: Some(config.text_color),
+            outline_color: Some(config.outline_color),
+            opacity: Some(config.opacity),
+            background_opacity: Some(config.background_opacity),
+            background_color: Some(config.background_color),
+            backdrop: Some(config.backdrop),
+            corner_style: Some(config.corner_style),
+            window_border: Some(config.window_border),
+            border_color: Some(config.border_color),
+        }
+    }
+
+    /// Apply the keys this theme sets, clamped like any config value.
+    pub fn apply(&self, config: &mut Config) {
+        fn set<T: Clone>(target: &mut T, value: &Option<T>) {
+            if let Some(value) = value {
+                *target = value.clone();
+            }
+        }
+        set(&mut config.position, &self.position);
+        set(&mut config.font_size, &self.font_size);
+        set(&mut config.font_weight, &self.font_weight);
+        set(&mut config.font_family, &self.font_family);
+        set(&mut config.font_width, &self.font_width);
+        set(&mut config.max_width, &self.max_width);
+        set(&mut config.text_align, &self.text_align);
+        set(&mut config.multi_line, &self.multi_line);
+        set(&mut config.line_spacing, &self.line_spacing);
+        set(&mut config.line_alignment, &self.line_alignment);
+        set(&mut config.vertical, &self.vertical);
+        set(&mut config.text_style, &self.text_style);
+        set(&mut config.text_color, &self.text_color);
+        set(&mut config.outline_color, &self.outline_color);
+        set(&mut config.opacity, &self.opacity);
+        set(&mut config.background_opacity, &self.background_opacity);
+        set(&mut config.background_color, &self.background_color);
+        set(&mut config.backdrop, &self.backdrop);
+        set(&mut config.corner_style, &self.corner_style);
+        set(&mut config.window_border, &self.window_border);
+        set(&mut config.border_color, &self.border_color);
+        config.sanitize();
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::parse(&text)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// The theme as a preset code: compact JSON in base64 behind `CODE_PREFIX`.
+    pub fn to_code(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        format!("{CODE_PREFIX}{}", base64_encode(json.as_bytes()))
+    }
+
+    /// Read a preset code, ignoring any message text around it.
+    pub fn from_code(code: &str) -> Result<Self, String> {
+        let start = code.find(CODE_PREFIX).ok_or("not a ClockOR preset code")?;
+        let body = &code[start + CODE_PREFIX.len()..];
+        let body = body.split_whitespace().next().unwrap_or("");
+        let json = base64_decode(body).ok_or("the preset code is damaged")?;
+        serde_json::from_slice(&json).map_err(|_| "the preset code is damaged".to_string())
+    }
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        // Three bytes make four characters; a short last chunk makes one more than its bytes
+        for i in 0..=chunk.len() {
+            out.push(BASE64[(n >> (18 - 6 * i) & 63) as usize] as char);
+        }
+    }
+    out
+}
+
+/// Decode base64, with or without padding; `None` on other characters.
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut acc, mut bits) = (0u32, 0);
+    for c in text.bytes().filter(|&c| c != b'=') {
+        let value = BASE64.iter().position(|&b| b == c)? as u32;
+        acc = (acc << 6 | value) & 0xFFFF;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+pub fn folder() -> PathBuf {
+    crate::config::app_dir().join("themes")
+}
+
+pub fn is_theme_file(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|n| n.to_string_lossy().to_lowercase().ends_with(EXTENSION))
+}
+
+/// Theme files in the themes folder, sorted by name.
+pub fn installed() -> Vec<PathBuf> {
+    let mut themes: Vec<PathBuf> = std::fs::read_dir(folder())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_theme_file(path))
+        .collect();
+    themes.sort();
+    themes
+}
+
+/// How a theme file is listed: its file name without the extension.
+pub fn display_name(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    name[..name.len() - EXTENSION.len().min(name.len())].to_string()
+}
+
+/// A file name for `name`, with characters Windows rejects replaced.
+fn file_name(name: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_control() || r#"<>:"/\|?*"#.contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let stem = if stem.is_empty() { "My theme" } else { &stem };
+    format!("{stem}{EXTENSION}")
+}
+
+/// The file for a theme referred to by name ("Neon") or path.
+pub fn resolve(name: &str) -> PathBuf {
+    let name = name.trim();
+    if is_theme_file(Path::new(name)) {
+        crate::config::user_path(name)
+    } else {
+        folder().join(file_name(name))
+    }
+}
+
+/// Save the look of `config` as `name` in the themes folder.
+pub fn export(config: &Config, name: &str) -> std::io::Result<PathBuf> {
+    let dir = folder();
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(file_name(name));
+    std::fs::write(&path, Theme::from_config(config, name).to_toml())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_the_look() {
+        let config = Config {
+            position: Position::BottomLeft,
+            text_style: TextStyle::Shadow,
+            text_color: [10, 20, 30],
+            font_size: 36,
+            background_opacity: 40,
+            ..Default::default()
+        };
+        let theme = Theme::parse(&Theme::from_config(&config, "Dusk").to_toml()).unwrap();
+        assert_eq!(theme.name.as_deref(), Some("Dusk"));
+        let mut other = Config::default();
+        theme.apply(&mut other);
+        assert_eq!(other.position, Position::BottomLeft);
+        assert_eq!(other.text_style, TextStyle::Shadow);
+        assert_eq!(other.text_color, [10, 20, 30]);
+        assert_eq!(other.font_size, 36);
+        assert_eq!(other.background_opacity, 40);
+    }
+
+    #[test]
+    fn partial_theme_changes_only_its_keys() {
+        let theme = Theme::parse("text_color = [255, 0, 0]").unwrap();
+        let mut config = Config {
+            font_size: 30,
+            ..Default::default()
+        };
+        theme.apply(&mut config);
+        assert_eq!(config.text_color, [255, 0, 0]);
+        assert_eq!(config.font_size, 30);
+    }
+
+    #[test]
+    fn behavior_keys_are_ignored_and_values_clamped() {
+        let theme = Theme::parse(
+            "hotkey = \"Alt+F4\"\nscript = \"evil.rhai\"\nfont_size = 500\nopacity = 0",
+        )
+        .unwrap();
+        let mut config = Config::default();
+        theme.apply(&mut config);
+        assert_eq!(config.hotkey, Config::default().hotkey);
+        assert!(config.script.is_empty());
+        assert_eq!(config.font_size, 60);
+        assert_eq!(config.opacity, 25);
+    }
+
+    #[test]
+    fn base64_matches_reference() {
+        assert_eq!(base64_encode(b"f"), "Zg");
+        assert_eq!(base64_encode(b"fo"), "Zm8");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(&[0xFB, 0xFF]), "-_8");
+        assert_eq!(base64_decode("Zm9vYg==").unwrap(), b"foob");
+        assert_eq!(base64_decode("Zm9v!"), None);
+    }
+
+    #[test]
+    fn preset_codes_round_trip() {
+        let config = Config {
+            text_color: [250, 120, 0],
+            font_size: 48,
+            vertical: true,
+            ..Default::default()
+        };
+        let code = Theme::from_config(&config, "Sunset").to_code();
+        assert!(code.starts_with("CLOCKOR1-"));
+        assert!(!code.contains(char::is_whitespace));
+
+        // As pasted from a chat message
+        let pasted = format!("try mine: {code}\nthanks");
+        let theme = Theme::from_code(&pasted).unwrap();
+        assert_eq!(theme.name.as_deref(), Some("Sunset"));
+        let mut other = Config::default();
+        theme.apply(&mut other);
+        assert_eq!(other.text_color, [250, 120, 0]);
+        assert_eq!(other.font_size, 48);
+        assert!(other.vertical);
+
+        assert!(Theme::from_code("hello").is_err());
+        assert!(Theme::from_code(&code[..code.len() - 10]).is_err());
+    }
+
+    #[test]
+    fn theme_file_names() {
+        assert!(is_theme_file(Path::new("Neon.clockor-theme.toml")));
+        assert!(!is_theme_file(Path::new("config.toml")));
+        assert_eq!(file_name("a/b: c"), "a_b_ c.clockor-theme.toml");
+        assert_eq!(file_name("  "), "My theme.clockor-theme.toml");
+        assert_eq!(display_name(Path::new("x/Neon.clockor-theme.toml")), "Neon");
+        assert_eq!(resolve(" Neon "), folder().join("Neon.clockor-theme.toml"));
+    }
+}
@@ -0,0 +1,101 @@
+//! UI Automation provider for the overlay window.
+//!
+//! The overlay is a layered, click-through window with no child controls, so
+//! accessibility tools see nothing in it. This answers `WM_GETOBJECT` with a
+//! single text element whose name is the line currently drawn, letting screen
+//! readers read the time. Reminder banners are announced as live-region changes.
+
+use std::cell::RefCell;
+
+use windows::core::{implement, Error, IUnknown, Result, BSTR, VARIANT};
+use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::UI::Accessibility::{
+    IRawElementProviderSimple, IRawElementProviderSimple_Impl, Polite, ProviderOptions,
+    ProviderOptions_ServerSideProvider, UIA_ControlTypePropertyId, UIA_LiveRegionChangedEventId,
+    UIA_LiveSettingPropertyId, UIA_NamePropertyId, UIA_TextControlTypeId, UiaClientsAreListening,
+    UiaHostProviderFromHwnd, UiaRaiseAutomationEvent, UiaReturnRawElementProvider, UiaRootObjectId,
+    UIA_PATTERN_ID, UIA_PROPERTY_ID,
+};
+
+#[implement(IRawElementProviderSimple)]
+struct OverlayProvider {
+    hwnd: HWND,
+    /// Produces the text currently on the overlay.
+    text: fn() -> String,
+}
+
+impl IRawElementProviderSimple_Impl for OverlayProvider_Impl {
+    fn ProviderOptions(&self) -> Result<ProviderOptions> {
+        Ok(ProviderOptions_ServerSideProvider)
+    }
+
+    fn GetPatternProvider(&self, _pattern: UIA_PATTERN_ID) -> Result<IUnknown> {
+        // No patterns: an empty error is S_OK with a null provider
+        Err(Error::empty())
+    }
+
+    fn GetPropertyValue(&self, property: UIA_PROPERTY_ID) -> Result<VARIANT> {
+        let value = if property == UIA_NamePropertyId {
+            VARIANT::from(BSTR::from((self.text)()))
+        } else if property == UIA_ControlTypePropertyId {
+            VARIANT::from(UIA_TextControlTypeId.0)
+        } else if property == UIA_LiveSettingPropertyId {
+            VARIANT::from(Polite.0)
+        } else {
+            VARIANT::default()
+        };
+        Ok(value)
+    }
+
+    fn HostRawElementProvider(&self) -> Result<IRawElementProviderSimple> {
+        unsafe { UiaHostProviderFromHwnd(self.hwnd) }
+    }
+}
+
+thread_local! {
+    /// Provider for the overlay window, created on first request.
+    static PROVIDER: RefCell<Option<IRawElementProviderSimple>> = const { RefCell::new(None) };
+}
+
+fn provider(hwnd: HWND, text: fn() -> String) -> IRawElementProviderSimple {
+    PROVIDER.with_borrow_mut(|slot| {
+        slot.get_or_insert_with(|| OverlayProvider { hwnd, text }.into())
+            .clone()
+    })
+}
+
+/// Handle `WM_GETOBJECT`. `None` means the message isn't for UI Automation and
+/// should go to `DefWindowProc`.
+pub fn handle_get_object(
+    hwnd: HWND,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    text: fn() -> String,
+) -> Option<LRESULT> {
+    if lparam.0 as i32 != UiaRootObjectId {
+        return None;
+    }
+    let provider = provider(hwnd, text);
+    Some(unsafe { UiaReturnRawElementProvider(hwnd, wparam, lparam, &provider) })
+}
+
+/// Tell listening screen readers that the text changed in a way worth reading out.
+pub fn announce() {
+    PROVIDER.with_borrow(|slot| {
+        if let Some(provider) = slot {
+            unsafe {
+                if UiaClientsAreListening().as_bool() {
+                    let _ = UiaRaiseAutomationEvent(provider, UIA_LiveRegionChangedEventId);
+                }
+            }
+        }
+    });
+}
+
+/// Release UI Automation's references to the provider (on `WM_DESTROY`).
+pub fn disconnect(hwnd: HWND) {
+    unsafe {
+        UiaReturnRawElementProvider(hwnd, WPARAM(0), LPARAM(0), None);
+    }
+    PROVIDER.with_borrow_mut(|slot| *slot = None);
+}
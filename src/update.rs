@@ -0,0 +1,475 @@
+//! Opt-in check for a newer release on GitHub, and in-place self-update.
+//!
+//! The check runs on a background thread at most once a day; the main loop picks
+//! up the result with [`take_available`]. HTTP goes through WinHTTP so no TLS
+//! stack has to be bundled.
+//!
+//! Installing downloads the release executable next to the running one and
+//! checks its minisign signature against the public key built into this
+//! executable, so a release asset swapped by someone else is refused. Then it
+//! swaps the files: Windows lets a running executable be renamed, so the old
+//! binary moves aside to `*.old` (deleted on the next start) and the new one
+//! takes its name. Builds made without a key only open the release page.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use minisign_verify::{PublicKey, Signature};
+use serde::Deserialize;
+use windows::core::{HSTRING, PCWSTR};
+use windows::Win32::Networking::WinHttp::{
+    WinHttpCloseHandle, WinHttpConnect, WinHttpOpen, WinHttpOpenRequest, WinHttpQueryDataAvailable,
+    WinHttpQueryHeaders, WinHttpReadData, WinHttpReceiveResponse, WinHttpSendRequest,
+    INTERNET_DEFAULT_HTTPS_PORT, WINHTTP_ACCESS_TYPE_AUTOMATIC_PROXY, WINHTTP_FLAG_SECURE,
+    WINHTTP_QUERY_FLAG_NUMBER, WINHTTP_QUERY_STATUS_CODE,
+};
+
+/// Version of this build, from Cargo metadata.
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/imonoonoko/ClockOR/releases/latest";
+
+/// The release asset to install, as named by the release workflow.
+const EXE_ASSET: &str = "clockor.exe";
+
+/// Minisign public key (base64) that release executables are signed with,
+/// set when the release workflow builds.
+const PUBLIC_KEY: Option<&str> = option_env!("CLOCKOR_UPDATE_PUBLIC_KEY");
+
+/// The update key, if this build has one. An unset workflow variable builds
+/// in as an empty string.
+fn public_key() -> Option<&'static str> {
+    PUBLIC_KEY.filter(|key| !key.trim().is_empty())
+}
+
+/// Minimum time between two checks.
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A release newer than the running build.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    /// Version without the leading "v".
+    pub version: String,
+    /// Release page to open for the download.
+    pub url: String,
+    /// Executable and signature downloads, when the release publishes both
+    /// and this build can check the signature.
+    pub install: Option<Install>,
+}
+
+/// Download URLs needed to install a release.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Install {
+    pub exe_url: String,
+    pub signature_url: String,
+}
+
+#[derive(Deserialize)]
+struct ReleaseJson {
+    tag_name: String,
+    html_url: String,
+    #[serde(default)]
+    assets: Vec<AssetJson>,
+}
+
+#[derive(Deserialize)]
+struct AssetJson {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Find the executable asset and its signature ("<exe>.minisig"), by exact name.
+fn install_assets(assets: &[AssetJson]) -> Option<Install> {
+    let url = |name: &str| {
+        assets
+            .iter()
+            .find(|a| a.name == name)
+            .map(|a| a.browser_download_url.clone())
+    };
+    Some(Install {
+        exe_url: url(EXE_ASSET)?,
+        signature_url: url(&format!("{EXE_ASSET}.minisig"))?,
+    })
+}
+
+/// Parse "v1.2.3" / "1.2.3" (pre-release suffixes ignored) into comparable parts.
+fn parse_version(text: &str) -> Option<(u32, u32, u32)> {
+    let core = text.trim().trim_start_matches('v');
+    let core = core.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u32>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
+
+/// Whether `latest` is strictly newer than `current`. Unparsable versions never are.
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+/// Pick the release out of a GitHub "latest release" response if it beats `current`.
+fn newer_release(json: &str, current: &str) -> Option<Release> {
+    let release: ReleaseJson = serde_json::from_str(json).ok()?;
+    is_newer(&release.tag_name, current).then(|| Release {
+        version: release.tag_name.trim_start_matches('v').to_string(),
+        install: public_key().and(install_assets(&release.assets)),
+        url: release.html_url,
+    })
+}
+
+/// Check a minisign `signature` (the `.minisig` file's text) of `data` against
+/// the base64 `public_key`. Only prehashed signatures, minisign's default, count.
+fn verify_signature(public_key: &str, data: &[u8], signature: &str) -> Result<(), String> {
+    let key = PublicKey::from_base64(public_key.trim()).map_err(|e| format!("bad key: {e}"))?;
+    let signature =
+        Signature::decode(signature.trim()).map_err(|e| format!("bad signature: {e}"))?;
+    key.verify(data, &signature, false)
+        .map_err(|e| format!("signature check failed: {e}"))
+}
+
+/// Whether enough time has passed since `last` (Unix seconds) to check again.
+fn check_due(last: u64, now: u64) -> bool {
+    now < last || now - last >= CHECK_INTERVAL.as_secs()
+}
+
+/// Remembers when the last check ran, next to the config file.
+fn stamp_path() -> PathBuf {
+    crate::config::app_dir().join("update_check")
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Closes a WinHTTP handle on drop.
+struct Handle(*mut core::ffi::c_void);
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        if !self.0.is_null() {
+            unsafe {
+                let _ = WinHttpCloseHandle(self.0);
+            }
+        }
+    }
+}
+
+/// GET an https URL and return the body of a 200 response. Redirects are followed.
+fn fetch(url: &str) -> Result<Vec<u8>, String> {
+    request("GET", url, None)
+}
+
+/// POST a JSON body to an https URL. Any 2xx response counts as delivered.
+pub fn post_json(url: &str, json: &str) -> Result<(), String> {
+    request("POST", url, Some(json)).map(|_| ())
+}
+
+/// Send an https request, with an optional JSON body, and return the response body.
+fn request(method: &str, url: &str, json: Option<&str>) -> Result<Vec<u8>, String> {
+    let rest = url
+        .strip_prefix("https://")
+        .ok_or_else(|| format!("not an https URL: {url}"))?;
+    let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let agent = format!("ClockOR/{CURRENT_VERSION}");
+    unsafe {
+        let session = Handle(WinHttpOpen(
+            &HSTRING::from(agent),
+            WINHTTP_ACCESS_TYPE_AUTOMATIC_PROXY,
+            PCWSTR::null(),
+            PCWSTR::null(),
+            0,
+        ));
+        if session.0.is_null() {
+            return Err("WinHttpOpen failed".into());
+        }
+        let connection = Handle(WinHttpConnect(
+            session.0,
+            &HSTRING::from(host),
+            INTERNET_DEFAULT_HTTPS_PORT,
+            0,
+        ));
+        if connection.0.is_null() {
+            return Err(format!("cannot connect to {host}"));
+        }
+        let request = Handle(WinHttpOpenRequest(
+            connection.0,
+            &HSTRING::from(method),
+            &HSTRING::from(path),
+            PCWSTR::null(),
+            PCWSTR::null(),
+            std::ptr::null(),
+            WINHTTP_FLAG_SECURE,
+        ));
+        if request.0.is_null() {
+            return Err("WinHttpOpenRequest failed".into());
+        }
+        match json {
+            Some(body) => {
+                let headers: Vec<u16> = "Content-Type: application/json\r\n"
+                    .encode_utf16()
+                    .collect();
+                let len = body.len() as u32;
+                WinHttpSendRequest(
+                    request.0,
+                    Some(&headers),
+                    Some(body.as_ptr().cast()),
+                    len,
+                    len,
+                    0,
+                )
+            }
+            None => WinHttpSendRequest(request.0, None, None, 0, 0, 0),
+        }
+        .map_err(|e| e.to_string())?;
+        WinHttpReceiveResponse(request.0, std::ptr::null_mut()).map_err(|e| e.to_string())?;
+
+        let mut status = 0u32;
+        let mut len = std::mem::size_of::<u32>() as u32;
+        WinHttpQueryHeaders(
+            request.0,
+            WINHTTP_QUERY_STATUS_CODE | WINHTTP_QUERY_FLAG_NUMBER,
+            PCWSTR::null(),
+            Some(&mut status as *mut u32 as *mut _),
+            &mut len,
+            std::ptr::null_mut(),
+        )
+        .map_err(|e| e.to_string())?;
+        let ok = match method {
+            "GET" => status == 200,
+            _ => (200..300).contains(&status),
+        };
+        if !ok {
+            return Err(format!("HTTP {status}"));
+        }
+
+        let mut body = Vec::new();
+        loop {
+            let mut available = 0u32;
+            WinHttpQueryDataAvailable(request.0, &mut available).map_err(|e| e.to_string())?;
+            if available == 0 {
+                break;
+            }
+            let start = body.len();
+            body.resize(start + available as usize, 0);
+            let mut read = 0u32;
+            WinHttpReadData(
+                request.0,
+                body[start..].as_mut_ptr().cast(),
+                available,
+                &mut read,
+            )
+            .map_err(|e| e.to_string())?;
+            body.truncate(start + read as usize);
+        }
+        Ok(body)
+    }
+}
+
+static AVAILABLE: Mutex<Option<Release>> = Mutex::new(None);
+
+/// Start a background check unless one ran within the last day
+/// or network features are disabled by policy.
+pub fn spawn_check() {
+    if !crate::policy::current().network_allowed() {
+        return;
+    }
+    let last = std::fs::read_to_string(stamp_path())
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(0);
+    let now = unix_now();
+    if !check_due(last, now) {
+        return;
+    }
+    std::thread::spawn(move || {
+        let _ = std::fs::write(stamp_path(), now.to_string());
+        match fetch(LATEST_RELEASE_URL) {
+            Ok(body) => {
+                let json = String::from_utf8_lossy(&body);
+                if let Some(release) = newer_release(&json, CURRENT_VERSION) {
+                    *AVAILABLE.lock().unwrap() = Some(release);
+                }
+            }
+            Err(e) => crate::logging::error(format!("Update check failed: {e}")),
+        }
+    });
+}
+
+/// A newer release found by the background check, returned once.
+pub fn take_available() -> Option<Release> {
+    AVAILABLE.lock().unwrap().take()
+}
+
+/// Where the running executable goes while its replacement takes its place.
+fn old_path(exe: &Path) -> PathBuf {
+    exe.with_extension("exe.old")
+}
+
+/// Download `install` and check its signature, leaving it beside `exe` as `*.new`.
+fn download_verified(install: &Install, exe: &Path) -> Result<PathBuf, String> {
+    let public_key = public_key().ok_or("this build has no update key")?;
+    let signature = fetch(&install.signature_url)?;
+    let binary = fetch(&install.exe_url)?;
+    verify_signature(public_key, &binary, &String::from_utf8_lossy(&signature))?;
+    let staged = exe.with_extension("exe.new");
+    std::fs::write(&staged, &binary).map_err(|e| e.to_string())?;
+    Ok(staged)
+}
+
+/// Move the running executable aside and put `staged` in its place.
+fn swap_executable(exe: &Path, staged: &Path) -> Result<(), String> {
+    let old = old_path(exe);
+    let _ = std::fs::remove_file(&old);
+    std::fs::rename(exe, &old).map_err(|e| e.to_string())?;
+    if let Err(e) = std::fs::rename(staged, exe) {
+        // Put the original back so the app still starts
+        let _ = std::fs::rename(&old, exe);
+        return Err(e.to_string());
+    }
+    Ok(())
+}
+
+static INSTALLED: Mutex<Option<Result<(), String>>> = Mutex::new(None);
+
+/// Download, verify and swap in `install` on a background thread.
+/// The outcome is picked up with [`take_install_result`].
+pub fn spawn_install(install: Install) {
+    std::thread::spawn(move || {
+        let result = std::env::current_exe()
+            .map_err(|e| e.to_string())
+            .and_then(|exe| {
+                let staged = download_verified(&install, &exe)?;
+                swap_executable(&exe, &staged).inspect_err(|_| {
+                    let _ = std::fs::remove_file(&staged);
+                })
+            });
+        *INSTALLED.lock().unwrap() = Some(result);
+    });
+}
+
+/// Outcome of [`spawn_install`], returned once. `Ok` means the new executable is
+/// in place and the app should [`relaunch`].
+pub fn take_install_result() -> Option<Result<(), String>> {
+    INSTALLED.lock().unwrap().take()
+}
+
+/// Command-line flag telling the new process to wait for the old one to exit.
+pub const RELAUNCH_FLAG: &str = "--updated";
+
+/// Start the (new) executable again; the caller should exit right after.
+pub fn relaunch() -> bool {
+    std::env::current_exe()
+        .and_then(|exe| std::process::Command::new(exe).arg(RELAUNCH_FLAG).spawn())
+        .is_ok()
+}
+
+/// Delete the executable left behind by the previous update, if any.
+pub fn remove_old_executable() {
+    if let Ok(exe) = std::env::current_exe() {
+        let _ = std::fs::remove_file(old_path(&exe));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_versions() {
+        assert!(is_newer("v0.3.0", "0.2.0"));
+        assert!(is_newer("0.2.1", "0.2.0"));
+        assert!(is_newer("v1.0", "0.9.9"));
+        assert!(!is_newer("v0.2.0", "0.2.0"));
+        assert!(!is_newer("v0.1.9", "0.2.0"));
+        assert!(!is_newer("nightly", "0.2.0"));
+    }
+
+    #[test]
+    fn ignores_prerelease_suffix() {
+        assert_eq!(parse_version("v0.3.0-beta.1"), Some((0, 3, 0)));
+        assert_eq!(parse_version("1.2.3+build"), Some((1, 2, 3)));
+    }
+
+    #[test]
+    fn parses_github_response() {
+        let json = r#"{"tag_name":"v9.0.0","html_url":"https://github.com/imonoonoko/ClockOR/releases/tag/v9.0.0","assets":[]}"#;
+        assert_eq!(
+            newer_release(json, "0.2.0"),
+            Some(Release {
+                version: "9.0.0".into(),
+                url: "https://github.com/imonoonoko/ClockOR/releases/tag/v9.0.0".into(),
+                install: None,
+            })
+        );
+        assert_eq!(newer_release(json, "9.0.0"), None);
+        assert_eq!(newer_release("not json", "0.2.0"), None);
+    }
+
+    #[test]
+    fn throttles_to_once_a_day() {
+        assert!(check_due(0, 1_000_000));
+        assert!(!check_due(1_000_000, 1_000_000 + 3600));
+        assert!(check_due(1_000_000, 1_000_000 + 86_400));
+        // A clock that went backwards doesn't block checks forever
+        assert!(check_due(2_000_000, 1_000_000));
+    }
+
+    fn asset(name: &str) -> AssetJson {
+        AssetJson {
+            name: name.into(),
+            browser_download_url: format!("https://example.com/{name}"),
+        }
+    }
+
+    #[test]
+    fn finds_install_assets() {
+        let assets = [
+            asset("clockor.zip"),
+            asset("clockor.exe"),
+            asset("clockor.exe.minisig"),
+        ];
+        let install = install_assets(&assets).unwrap();
+        assert_eq!(install.exe_url, "https://example.com/clockor.exe");
+        assert_eq!(
+            install.signature_url,
+            "https://example.com/clockor.exe.minisig"
+        );
+
+        // Only the release executable itself, by its exact name
+        let assets = [
+            asset("setup.exe"),
+            asset("setup.exe.minisig"),
+            asset("ClockOR.exe"),
+            asset("ClockOR.exe.minisig"),
+        ];
+        assert_eq!(install_assets(&assets), None);
+
+        // Never install without something to verify against
+        assert_eq!(install_assets(&[asset("clockor.exe")]), None);
+    }
+
+    // A prehashed signature of b"test" from the minisign-verify test vectors
+    const TEST_KEY: &str = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+    const TEST_SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=
+trusted comment: timestamp:1556193335\tfile:test
+y/rUw2y8/hOUYjZU71eHp/Wo1KZ40fGy2VJEDl34XMJM+TX48Ss/17u3IvIfbVR1FkZZSNCisQbuQY+bHwhEBg==
+";
+
+    #[test]
+    fn verifies_signatures() {
+        assert_eq!(verify_signature(TEST_KEY, b"test", TEST_SIGNATURE), Ok(()));
+        assert!(verify_signature(TEST_KEY, b"Test", TEST_SIGNATURE).is_err());
+        assert!(verify_signature(TEST_KEY, b"test", "not a signature").is_err());
+        // Signed with a different key
+        let other = "RWTAPRW2qy9FjsBiMVE7LxNtf7GXn0XE0L9FoSTrSDCd5gb0qPvBfM3S";
+        assert!(verify_signature(other, b"test", TEST_SIGNATURE).is_err());
+    }
+}
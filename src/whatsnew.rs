@@ -0,0 +1,167 @@
+//! "What's new" window shown on the first start after the version changes.
+//!
+//! The notes come from the CHANGELOG bundled into the executable. The last
+//! version whose notes were dismissed is remembered in a small file next to the
+//! config; unticking "Don't show again" keeps the window coming back.
+
+use std::path::PathBuf;
+
+use eframe::egui;
+
+use crate::update::CURRENT_VERSION;
+
+const CHANGELOG: &str = include_str!("../CHANGELOG.md");
+
+/// The body of the `## [version]` section, falling back to `[Unreleased]`.
+pub fn release_notes<'a>(changelog: &'a str, version: &str) -> Option<&'a str> {
+    let section = |name: &str| {
+        let heading = format!("## [{name}]");
+        let start = changelog.find(&heading)?;
+        let body = &changelog[start..];
+        let body = &body[body.find('\n').unwrap_or(body.len())..];
+        let end = body.find("\n## ").unwrap_or(body.len());
+        let body = body[..end].trim();
+        (!body.is_empty()).then_some(body)
+    };
+    section(version).or_else(|| section("Unreleased"))
+}
+
+fn stamp_path() -> PathBuf {
+    crate::config::app_dir().join("seen_version")
+}
+
+/// Whether to show the notes: the version changed since they were last dismissed.
+/// A fresh install (no config yet) has nothing new to show.
+fn should_show(seen: Option<&str>, has_config: bool) -> bool {
+    match seen {
+        Some(seen) => seen.trim() != CURRENT_VERSION,
+        None => has_config,
+    }
+}
+
+struct WhatsNewApp {
+    notes: &'static str,
+    dont_show: bool,
+    /// Stamp contents before this window, restored if "Don't show again" is unticked.
+    previous: Option<String>,
+}
+
+impl WhatsNewApp {
+    fn remember(&self) {
+        let path = stamp_path();
+        let _ = match (self.dont_show, &self.previous) {
+            (true, _) => std::fs::write(path, CURRENT_VERSION),
+            (false, Some(previous)) => std::fs::write(path, previous),
+            (false, None) => std::fs::remove_file(path),
+        };
+    }
+}
+
+impl eframe::App for WhatsNewApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::TopBottomPanel::bottom("actions").show(ctx, |ui| {
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                if ui
+                    .checkbox(&mut self.dont_show, "Don't show again for this version")
+                    .changed()
+                {
+                    self.remember();
+                }
+                if ui.button("Close").clicked() {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+            });
+            ui.add_space(6.0);
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading(format!("What's new in ClockOR {CURRENT_VERSION}"));
+            ui.add_space(4.0);
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for line in self.notes.lines().map(str::trim) {
+                    if let Some(heading) = line.strip_prefix("### ") {
+                        ui.add_space(4.0);
+                        ui.strong(heading);
+                    } else if let Some(item) = line.strip_prefix("- ") {
+                        ui.horizontal_wrapped(|ui| {
+                            ui.label("•");
+                            ui.label(item);
+                        });
+                    }
+                }
+            });
+        });
+    }
+}
+
+/// Show the notes for this version if they haven't been dismissed yet.
+/// Blocks until the window is closed, like the settings window.
+pub fn show_if_updated() {
+    let previous = std::fs::read_to_string(stamp_path()).ok();
+    let has_config = crate::config::app_dir().join("config.toml").exists();
+    if !should_show(previous.as_deref(), has_config) {
+        return;
+    }
+    let Some(notes) = release_notes(CHANGELOG, CURRENT_VERSION) else {
+        return;
+    };
+
+    let app = WhatsNewApp {
+        notes,
+        dont_show: true,
+        previous,
+    };
+    app.remember();
+
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([420.0, 360.0])
+            .with_always_on_top(),
+        ..Default::default()
+    };
+    let _ = eframe::run_native(
+        "What's new in ClockOR",
+        options,
+        Box::new(|_cc| Ok(Box::new(app))),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "# Changelog\n\n## [Unreleased]\n\n### Added\n\n- Next thing\n\n\
+                          ## [0.2.0] - 2026-03-01\n\n### Added\n\n- Thing\n\n\
+                          ## [0.1.0] - 2026-02-17\n\n- First\n";
+
+    #[test]
+    fn picks_version_section() {
+        assert_eq!(release_notes(SAMPLE, "0.2.0"), Some("### Added\n\n- Thing"));
+        assert_eq!(release_notes(SAMPLE, "0.1.0"), Some("- First"));
+    }
+
+    #[test]
+    fn falls_back_to_unreleased() {
+        assert_eq!(
+            release_notes(SAMPLE, "0.3.0"),
+            Some("### Added\n\n- Next thing")
+        );
+        assert_eq!(release_notes("# Changelog\n", "0.3.0"), None);
+    }
+
+    #[test]
+    fn bundled_changelog_has_notes() {
+        assert!(release_notes(CHANGELOG, CURRENT_VERSION).is_some());
+    }
+
+    #[test]
+    fn shows_once_per_version() {
+        assert!(should_show(Some("0.0.1"), true));
+        assert!(!should_show(Some(CURRENT_VERSION), true));
+        assert!(!should_show(Some(&format!("{CURRENT_VERSION}\n")), true));
+        // Fresh install vs. upgrade from a build that didn't track versions
+        assert!(!should_show(None, false));
+        assert!(should_show(None, true));
+    }
+}
@@ -0,0 +1,105 @@
+//! Keeping the overlay above other windows. Plain topmost is enough for most
+//! games, but some raise their own topmost windows again and again; the
+//! watchdog answers those. Band placement uses the undocumented
+//! `CreateWindowInBand`, which Windows only grants to signed UIAccess
+//! processes, so everywhere else it falls back to an ordinary window.
+
+use std::ffi::c_void;
+
+use windows::core::{s, w, PCWSTR};
+use windows::Win32::Foundation::{HINSTANCE, HWND};
+use windows::Win32::System::LibraryLoader::{GetModuleHandleW, GetProcAddress};
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetWindow, IsWindowVisible, SetWindowPos, GW_HWNDPREV, HMENU, HWND_NOTOPMOST, HWND_TOPMOST,
+    SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE, WINDOW_EX_STYLE, WINDOW_STYLE,
+};
+
+/// How often the watchdog checks the z-order, in milliseconds.
+pub const WATCHDOG_INTERVAL_MS: u32 = 250;
+
+/// The band accessibility tools (magnifier, on-screen keyboard) live in,
+/// above every ordinary topmost window.
+const ZBID_UIACCESS: u32 = 2;
+
+type CreateWindowInBand = unsafe extern "system" fn(
+    u32,
+    PCWSTR,
+    PCWSTR,
+    u32,
+    i32,
+    i32,
+    i32,
+    i32,
+    HWND,
+    HMENU,
+    HINSTANCE,
+    *const c_void,
+    u32,
+) -> HWND;
+
+/// Create the window in the accessibility band, or `None` where the call is
+/// missing or refused (the caller then creates an ordinary window).
+pub unsafe fn create_in_band(
+    ex_style: WINDOW_EX_STYLE,
+    class: PCWSTR,
+    title: PCWSTR,
+    style: WINDOW_STYLE,
+    (x, y, w, h): (i32, i32, i32, i32),
+    hinstance: HINSTANCE,
+) -> Option<HWND> {
+    let user32 = GetModuleHandleW(w!("user32.dll")).ok()?;
+    let proc = GetProcAddress(user32, s!("CreateWindowInBand"))?;
+    let create =
+        std::mem::transmute::<unsafe extern "system" fn() -> isize, CreateWindowInBand>(proc);
+    let hwnd = create(
+        ex_style.0,
+        class,
+        title,
+        style.0,
+        x,
+        y,
+        w,
+        h,
+        HWND::default(),
+        HMENU::default(),
+        hinstance,
+        std::ptr::null(),
+        ZBID_UIACCESS,
+    );
+    if hwnd.is_invalid() {
+        crate::logging::info(format!(
+            "Band placement isn't allowed here ({}); using a topmost window",
+            windows::core::Error::from_win32().message()
+        ));
+        return None;
+    }
+    Some(hwnd)
+}
+
+/// Whether a visible window sits above `hwnd`.
+unsafe fn covered(hwnd: HWND) -> bool {
+    let mut above = GetWindow(hwnd, GW_HWNDPREV);
+    while let Ok(window) = above {
+        if window.is_invalid() {
+            break;
+        }
+        if IsWindowVisible(window).as_bool() {
+            return true;
+        }
+        above = GetWindow(window, GW_HWNDPREV);
+    }
+    false
+}
+
+/// Put `hwnd` back in front if another window got above it. Leaving the
+/// topmost group and rejoining it moves the window to the front of that group.
+pub fn reassert(hwnd: HWND) {
+    unsafe {
+        if !covered(hwnd) {
+            return;
+        }
+        let flags = SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE;
+        let _ = SetWindowPos(hwnd, HWND_NOTOPMOST, 0, 0, 0, 0, flags);
+        let _ = SetWindowPos(hwnd, HWND_TOPMOST, 0, 0, 0, 0, flags);
+    }
+}